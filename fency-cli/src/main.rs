@@ -0,0 +1,285 @@
+use fency_core::utils::compression;
+use fency_core::utils::draw::to_figurine_san;
+use fency_core::utils::explorer::{OpeningKey, OpeningReport};
+use fency_core::utils::export::{ply_records_from, COLUMNS, OPTIONAL_COLUMNS};
+use fency_core::utils::filter::GameFilter;
+use fency_core::utils::notation::NotationConfig;
+use fency_core::utils::pgn::{PgnGame, PgnReader};
+use fency_core::utils::recovery::truncate_at_error;
+use fency_core::utils::report::GameReport;
+use fency_core::utils::stats::EloStats;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process::exit;
+
+const USAGE: &str = "\
+Usage: fency-pgn [OPTIONS] [FILE ...]
+
+Replay the games of PGN files (or stdin, if no file is given) and write one row per ply. Input
+compressed with zstd, gzip or bzip2 (e.g. a Lichess .pgn.zst dump) is decompressed on the fly.
+
+Options:
+  -f, --format <fen|jsonl|csv|report|stats|openings|openings-json>
+                                Output format, where 'report' lints the games and writes one JSON
+                                object per game, 'stats' writes a CSV summary per rating bucket
+                                without replaying the games and 'openings' writes the games and
+                                scores per opening as CSV ('openings-json' as JSON) [default: fen]
+      --bucket-width <n>        Width of the rating buckets of 'stats' [default: 200]
+      --opening-plies <n>       Group 'openings' by the first n plies instead of the ECO tag
+      --min-elo <n>             Only read games where both players are rated at least n
+      --max-elo <n>             Only read games where both players are rated at most n
+      --time-control <a,b,...>  Only read games with one of the TimeControl tags, e.g. '180+2'
+  -c, --columns <a,b,...>       Columns for jsonl/csv output, optionally including 'mobility'
+                                (number of legal moves), 'clock' and 'time_spent' (seconds, from
+                                %clk comments) and 'position' (UCI command with the moves so far)
+                                [default: all but optional ones]
+  -v, --variant <name>          Chess variant, only 'standard' is supported [default: standard]
+  -b, --batch-size <n>          Number of games to process before flushing output [default: 1000]
+  -n, --notation <lang>         Language of the piece letters, e.g. 'de' for Sf3 [default: en]
+      --figurine                Write SAN in figurine notation, e.g. ♘f3 instead of Nf3
+      --lenient                 Truncate games at their first illegal move and report it on stderr,
+                                instead of aborting
+  -h, --help                    Print this help
+";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Fen,
+    Jsonl,
+    Csv,
+    Report,
+    Stats,
+    Openings,
+    OpeningsJson,
+}
+
+struct Args {
+    format: Format,
+    columns: Vec<String>,
+    batch_size: usize,
+    notation: NotationConfig,
+    figurine: bool,
+    lenient: bool,
+    bucket_width: u16,
+    opening_key: OpeningKey,
+    filter: GameFilter,
+    files: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        format: Format::Fen,
+        columns: COLUMNS.iter().map(|c| c.to_string()).collect(),
+        batch_size: 1000,
+        notation: NotationConfig::english(),
+        figurine: false,
+        lenient: false,
+        bucket_width: 200,
+        opening_key: OpeningKey::Eco,
+        filter: GameFilter::default(),
+        files: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                exit(0);
+            }
+            "-f" | "--format" => {
+                parsed.format = match value(&arg)?.as_str() {
+                    "fen" => Format::Fen,
+                    "jsonl" => Format::Jsonl,
+                    "csv" => Format::Csv,
+                    "report" => Format::Report,
+                    "stats" => Format::Stats,
+                    "openings" => Format::Openings,
+                    "openings-json" => Format::OpeningsJson,
+                    other => return Err(format!("unknown format: {}", other)),
+                }
+            }
+            "-c" | "--columns" => {
+                parsed.columns = value(&arg)?.split(',').map(|c| c.to_string()).collect();
+                if let Some(c) = parsed.columns.iter().find(|c| {
+                    !COLUMNS.contains(&c.as_str()) && !OPTIONAL_COLUMNS.contains(&c.as_str())
+                }) {
+                    return Err(format!("unknown column: {}", c));
+                }
+            }
+            "-v" | "--variant" => {
+                let variant = value(&arg)?;
+                if !variant.eq_ignore_ascii_case("standard") {
+                    return Err(format!("unsupported variant: {}", variant));
+                }
+            }
+            "-b" | "--batch-size" => {
+                parsed.batch_size = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("batch size must be a positive integer")?;
+            }
+            "-n" | "--notation" => parsed.notation = value(&arg)?.parse()?,
+            "--figurine" => parsed.figurine = true,
+            "--lenient" => parsed.lenient = true,
+            "--bucket-width" => {
+                parsed.bucket_width = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("bucket width must be a positive integer")?;
+            }
+            "--opening-plies" => {
+                let plies = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("opening plies must be a positive integer")?;
+                parsed.opening_key = OpeningKey::Plies(plies);
+            }
+            "--min-elo" | "--max-elo" => {
+                let elo = value(&arg)?
+                    .parse()
+                    .map_err(|_| format!("invalid rating for {}", arg))?;
+                match arg.as_str() {
+                    "--min-elo" => parsed.filter.min_elo = Some(elo),
+                    _ => parsed.filter.max_elo = Some(elo),
+                }
+            }
+            "--time-control" => {
+                parsed.filter.time_controls =
+                    value(&arg)?.split(',').map(|t| t.to_string()).collect();
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option: {}", arg))
+            }
+            _ => parsed.files.push(arg),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Write all plies of a single game in the requested format.
+fn write_game<W: Write>(out: &mut W, args: &Args, idx: usize, game: &PgnGame) -> io::Result<()> {
+    // Reports cover illegal moves themselves instead of failing.
+    if args.format == Format::Report {
+        return writeln!(out, "{}", GameReport::of(game).to_json());
+    }
+    let columns: Vec<&str> = args.columns.iter().map(|c| c.as_str()).collect();
+    let moves = game.moves();
+    let invalid =
+        |e: String| io::Error::new(io::ErrorKind::InvalidData, format!("game {}: {}", idx, e));
+    let start = game.start_position().map_err(|e| invalid(e.to_string()))?;
+    let first_color = start.color;
+    let records = ply_records_from(start, &moves, None, &columns).map_err(invalid)?;
+    let time_spent = game.time_spent();
+    for mut record in records {
+        record.clock = game.clocks.get(record.ply - 1).copied().flatten();
+        record.time_spent = time_spent.get(record.ply - 1).copied().flatten();
+        if args.figurine {
+            // The side to move at the start of the game moves on odd plies.
+            let color = if record.ply % 2 == 1 {
+                first_color
+            } else {
+                first_color.next()
+            };
+            record.san = to_figurine_san(&record.san, color);
+        }
+        match args.format {
+            Format::Fen
+            | Format::Report
+            | Format::Stats
+            | Format::Openings
+            | Format::OpeningsJson => writeln!(out, "{}", record.fen)?,
+            Format::Jsonl => writeln!(out, "{}", record.to_json(idx, &columns))?,
+            Format::Csv => {
+                let values: Vec<String> = columns.iter().filter_map(|c| record.value(c)).collect();
+                writeln!(out, "{},{}", idx, values.join(","))?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run(args: Args) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.format == Format::Csv {
+        writeln!(out, "game,{}", args.columns.join(","))?;
+    }
+
+    // Read from stdin, if there are no files or "-" is given explicitly.
+    let sources: Vec<Box<dyn BufRead>> = if args.files.is_empty() {
+        vec![compression::decompress(io::stdin().lock())?]
+    } else {
+        args.files
+            .iter()
+            .map(|f| -> io::Result<Box<dyn BufRead>> {
+                match f.as_str() {
+                    "-" => compression::decompress(io::stdin().lock()),
+                    path => compression::open(path),
+                }
+            })
+            .collect::<io::Result<_>>()?
+    };
+
+    let mut stats = EloStats::new(args.bucket_width);
+    let mut openings = OpeningReport::new(args.opening_key);
+    let is_openings = matches!(args.format, Format::Openings | Format::OpeningsJson);
+    let mut idx = 0;
+    for source in sources {
+        for game in PgnReader::with_filter(source, args.filter.clone()) {
+            if args.format == Format::Stats {
+                stats.add(&game?);
+                continue;
+            }
+            let mut game = game?;
+            for mv in game.moves.iter_mut() {
+                *mv = args.notation.to_english(mv);
+            }
+            if args.lenient {
+                if let Some(error) = truncate_at_error(&mut game, idx) {
+                    eprintln!("warning: {} (truncated)", error);
+                }
+            }
+            if is_openings {
+                openings.add(&game).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("game {}: {}", idx, e))
+                })?;
+            } else {
+                write_game(&mut out, &args, idx, &game)?;
+            }
+            idx += 1;
+            if idx % args.batch_size == 0 {
+                out.flush()?;
+            }
+        }
+    }
+
+    match args.format {
+        Format::Stats => write!(out, "{}", stats.to_csv())?,
+        Format::Openings => write!(out, "{}", openings.to_csv())?,
+        Format::OpeningsJson => writeln!(out, "{}", openings.to_json())?,
+        Format::Fen | Format::Jsonl | Format::Csv | Format::Report => {}
+    }
+    out.flush()
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            exit(2);
+        }
+    };
+
+    if let Err(e) = run(args) {
+        // A closed pipe (e.g. `fency-pgn games.pgn | head`) is not an error.
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
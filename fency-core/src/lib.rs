@@ -0,0 +1,77 @@
+//! Pure-Rust core of fency-pgn: replays games given in Standard Algebraic Notation (SAN) and
+//! derives the resulting positions in Forsyth-Edwards-Notation (FEN). The Python bindings, the
+//! command-line tool and the WebAssembly bindings are thin layers on top of this crate.
+//!
+//! The types re-exported at the crate root form the stable API, while the `utils` modules may
+//! change between minor versions.
+//!
+//! ```
+//! use fency_core::{Color, Game, Move, Piece, Square};
+//!
+//! let mut game = Game::new();
+//! for san in ["e4", "e5", "Nf3"] {
+//!     game.play_move(san).unwrap();
+//! }
+//! assert_eq!(game.color, Color::B);
+//! assert_eq!(
+//!     game.clone().to_fen(),
+//!     "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+//! );
+//!
+//! let mv = Move::from_san(&game, "Nc6").unwrap();
+//! assert_eq!((mv.piece, mv.from, mv.uci.as_str()), (Piece::N, Square::from("b8"), "b8c6"));
+//! ```
+//!
+//! Games are read from PGN with the `PgnReader`, which accepts any buffered reader:
+//!
+//! ```
+//! use fency_core::{Game, GameResult, PgnReader};
+//! use std::str::FromStr;
+//!
+//! let pgn = "[White \"Kasparov\"]\n\n1. e4 c5 2. Nf3 1-0\n";
+//! let game = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+//! assert_eq!(game.header("White"), Some("Kasparov"));
+//! assert_eq!(game.moves(), vec!["e4", "c5", "Nf3"]);
+//! assert_eq!(GameResult::from_str(game.result.as_deref().unwrap()), Ok(GameResult::WhiteWins));
+//!
+//! let mut board = Game::new();
+//! for san in game.moves() {
+//!     board.play_move(san).unwrap();
+//! }
+//! assert_eq!(board.full_move_clock, 2);
+//! ```
+//!
+//! All types of the stable API are `Send + Sync` and share their lookup tables as statics, thus
+//! games can be replayed in parallel, e.g. by rayon, without copying any data per task.
+pub mod utils;
+
+pub use utils::castling::Castling;
+pub use utils::color::Color;
+pub use utils::coord::Coord as Square;
+pub use utils::error::Error;
+pub use utils::fentasize::FentasizeOptions;
+pub use utils::game::Game;
+pub use utils::moves::Move;
+pub use utils::outcome::GameResult;
+pub use utils::pgn::{PgnGame, PgnReader};
+pub use utils::piece::Piece;
+pub use utils::recovery::ReplayError;
+pub use utils::report::Anomaly;
+
+// Keep the stable API usable from multi-threaded pipelines.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Castling>();
+    assert_send_sync::<Color>();
+    assert_send_sync::<Square>();
+    assert_send_sync::<Error>();
+    assert_send_sync::<FentasizeOptions>();
+    assert_send_sync::<Game>();
+    assert_send_sync::<Move>();
+    assert_send_sync::<GameResult>();
+    assert_send_sync::<PgnGame>();
+    assert_send_sync::<PgnReader<&[u8]>>();
+    assert_send_sync::<Piece>();
+    assert_send_sync::<ReplayError>();
+    assert_send_sync::<Anomaly>();
+};
@@ -0,0 +1,167 @@
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::game::{Game, KNIGHT_STEPS};
+use crate::utils::piece::Piece;
+use crate::utils::rays::DIRECTIONS;
+
+/// Number of move types from a square: 56 queen moves (8 directions by 7 distances), 8 knight
+/// moves and 9 underpromotions (3 directions by 3 pieces).
+pub const ACTION_PLANES: usize = 73;
+
+/// Size of the AlphaZero action space, i.e. 8 x 8 x 73.
+pub const ACTION_SPACE: usize = 64 * ACTION_PLANES;
+
+const QUEEN_PLANES: usize = 56;
+const KNIGHT_PLANES: usize = 8;
+const UNDERPROMOTIONS: [Piece; 3] = [Piece::N, Piece::B, Piece::R];
+
+/// The square as seen by the side to move, i.e. with the ranks flipped for black.
+fn oriented(game: &Game, coord: Coord) -> Coord {
+    if game.color.is_white() {
+        coord
+    } else {
+        Coord::from_xy(coord.x, 7 - coord.y)
+    }
+}
+
+impl Game {
+    /// Map a move in UCI notation to its index in the AlphaZero action space, i.e. into an array
+    /// of shape (8, 8, 73) over the rank and file of the source square and the move type. Squares
+    /// are seen by the side to move, thus black's ranks are flipped. The move types are:
+    /// - 0 to 55: queen moves as 7 * direction + distance - 1, with directions as in `DIRECTIONS`,
+    /// - 56 to 63: knight moves in the order of `KNIGHT_STEPS`,
+    /// - 64 to 72: underpromotions as 64 + 3 * piece + file delta + 1, with pieces N, B, R.
+    ///
+    /// Castling is a king move by two squares, and promotions to a queen are queen moves. Only the
+    /// geometry of the move is checked, not whether it's legal.
+    pub fn action_index(&self, uci: &str) -> Result<usize, String> {
+        let invalid = |reason: &str| format!("invalid UCI move {}: {}", uci, reason);
+        let (src, tgt, promo) = match (uci.get(..2), uci.get(2..4), uci.get(4..)) {
+            (Some(src), Some(tgt), Some(promo)) if Coord::is_valid(src) && Coord::is_valid(tgt) => {
+                (Coord::from(src), Coord::from(tgt), promo)
+            }
+            _ => return Err(invalid("malformed")),
+        };
+        let (src, tgt) = (oriented(self, src), oriented(self, tgt));
+        let (dx, dy) = (tgt.x - src.x, tgt.y - src.y);
+
+        let plane = match promo {
+            "" | "q" => {
+                if let Some(k) = KNIGHT_STEPS.iter().position(|&step| step == (dx, dy)) {
+                    QUEEN_PLANES + k
+                } else {
+                    let direction = src
+                        .direction(&tgt)
+                        .and_then(|d| DIRECTIONS.iter().position(|&other| other == d))
+                        .ok_or_else(|| invalid("neither a queen nor a knight move"))?;
+                    7 * direction + src.distance(&tgt) as usize - 1
+                }
+            }
+            "r" | "b" | "n" => {
+                if dy != 1 || dx.abs() > 1 {
+                    return Err(invalid("not a pawn step forward"));
+                }
+                let piece = Piece::from(promo.chars().next().unwrap());
+                let p = UNDERPROMOTIONS.iter().position(|&u| u == piece).unwrap();
+                QUEEN_PLANES + KNIGHT_PLANES + 3 * p + (dx + 1) as usize
+            }
+            _ => return Err(invalid("unknown promotion piece")),
+        };
+
+        Ok((8 * src.y as usize + src.x as usize) * ACTION_PLANES + plane)
+    }
+
+    /// Map an index of the AlphaZero action space back to a move in UCI notation, see
+    /// `action_index`. Pawns that reach the last rank by a queen move promote to a queen. The move
+    /// is not checked for legality, e.g. to mask the policy with `legal_actions` first.
+    pub fn action_move(&self, index: usize) -> Result<String, String> {
+        if index >= ACTION_SPACE {
+            return Err(format!("action index {} is out of range", index));
+        }
+        let (square, plane) = (index / ACTION_PLANES, index % ACTION_PLANES);
+        let src = Coord::from_xy((square % 8) as i8, (square / 8) as i8);
+
+        let (dx, dy, mut promotion) = if plane < QUEEN_PLANES {
+            let (dx, dy) = DIRECTIONS[plane / 7];
+            let distance = (plane % 7) as i8 + 1;
+            (dx * distance, dy * distance, None)
+        } else if plane < QUEEN_PLANES + KNIGHT_PLANES {
+            let (dx, dy) = KNIGHT_STEPS[plane - QUEEN_PLANES];
+            (dx, dy, None)
+        } else {
+            let p = plane - QUEEN_PLANES - KNIGHT_PLANES;
+            ((p % 3) as i8 - 1, 1, Some(UNDERPROMOTIONS[p / 3]))
+        };
+        let tgt = src
+            .shifted(dx, dy)
+            .ok_or_else(|| format!("action index {} leaves the board", index))?;
+        let (src, tgt) = (oriented(self, src), oriented(self, tgt));
+
+        let is_pawn = self.position[src.idx as usize].is_some_and(|fig| fig.piece() == Piece::P);
+        if is_pawn && promotion.is_none() && (tgt.y == 0 || tgt.y == 7) {
+            promotion = Some(Piece::Q);
+        }
+        let promotion = promotion.map_or(String::new(), |p| p.to_char(Color::B).to_string());
+        Ok(format!("{}{}{}", src, tgt, promotion))
+    }
+
+    /// Indices of the legal moves in the AlphaZero action space, in the order of `legal_moves`.
+    pub fn legal_actions(&self) -> Vec<usize> {
+        self.legal_moves()
+            .iter()
+            .map(|uci| self.action_index(uci).unwrap())
+            .collect()
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_action_index() {
+    use std::str::FromStr;
+
+    let game = Game::new();
+    // e2 is square 12, e2e4 goes north (direction 0) by two squares.
+    assert_eq!(game.action_index("e2e4"), Ok(12 * 73 + 1));
+    assert_eq!(game.action_index("g1f3"), Ok(6 * 73 + 56));
+    assert_eq!(
+        game.action_index("a1h2"),
+        Err("invalid UCI move a1h2: neither a queen nor a knight move".to_string())
+    );
+
+    // Black sees the board from its side, thus mirrored moves share their index.
+    let mut black = game.clone();
+    black.play_move("e4").unwrap();
+    assert_eq!(black.action_index("e7e5"), game.action_index("e2e4"));
+    assert_eq!(black.action_index("g8f6"), game.action_index("g1f3"));
+
+    let game = Game::from_str("1r2k3/P7/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(game.action_index("a7a8q"), game.action_index("a7a8"));
+    assert_eq!(game.action_index("a7b8n"), Ok(48 * 73 + 64 + 2));
+    assert_eq!(game.action_index("a7b8r"), Ok(48 * 73 + 70 + 2));
+    assert_eq!(game.action_move(48 * 73), Ok("a7a8q".to_string()));
+    assert_eq!(game.action_move(4 * 73 + 14 + 1), Ok("e1g1".to_string()));
+    assert_eq!(
+        game.action_move(7 * 73 + 14),
+        Err("action index 525 leaves the board".to_string())
+    );
+}
+
+#[test]
+fn check_actions_round_trip() {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/pPppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPpP/R3K2R b KQkq - 0 1",
+    ] {
+        let game = Game::from_str(fen).unwrap();
+        let actions = game.legal_actions();
+        assert_eq!(actions.iter().collect::<HashSet<_>>().len(), actions.len());
+        for (uci, &index) in game.legal_moves().iter().zip(&actions) {
+            assert!(index < ACTION_SPACE);
+            assert_eq!(&game.action_move(index).unwrap(), uci);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::utils::board_fen::BoardEncoder;
+use crate::utils::game::Game;
+use crate::utils::replay::replay_plies;
+
+/// Number of FEN buffers a context keeps between games by default, which covers almost all games.
+const DEFAULT_RETAINED_PLIES: usize = 512;
+
+/// Reusable buffers for replaying many games one after the other, e.g. a whole PGN file on one
+/// thread. The FENs are written into pooled strings without cloning the game for every ply, thus
+/// the allocations for the FENs of a game are reused by the next. The board fields are updated
+/// rank by rank, see `BoardEncoder`. Buffers beyond `max_retained_plies` are freed before the next
+/// game, which bounds the memory kept for the rare long game. Only the FENs are pooled, the
+/// moves are resolved as by `Game::play_move`. Contexts are not shared, use one per thread
+/// instead.
+pub struct BatchContext {
+    start: Game,
+    game: Game,
+    encoder: BoardEncoder,
+    fens: Vec<String>,
+    max_retained_plies: usize,
+}
+
+impl Default for BatchContext {
+    fn default() -> Self {
+        BatchContext::new(Game::new())
+    }
+}
+
+impl BatchContext {
+    /// A context whose games start from the given position.
+    pub fn new(start: Game) -> Self {
+        BatchContext {
+            game: start.clone(),
+            encoder: BoardEncoder::new(&start),
+            start,
+            fens: Vec::new(),
+            max_retained_plies: DEFAULT_RETAINED_PLIES,
+        }
+    }
+
+    /// Keep at most the given number of FEN buffers between games.
+    pub fn max_retained_plies(mut self, max_retained_plies: usize) -> Self {
+        self.max_retained_plies = max_retained_plies;
+        self
+    }
+
+    /// Number of FEN buffers currently held, e.g. to check the memory bound.
+    pub fn retained_plies(&self) -> usize {
+        self.fens.len()
+    }
+
+    /// Replay moves in SAN from the start position and return the FEN after every ply. The FENs
+    /// are borrowed from the context and overwritten by the next game, thus copy what needs to
+    /// be kept. Result tokens like "1-0" are skipped.
+    pub fn fentasize(&mut self, moves: &[&str]) -> Result<&[String], String> {
+        self.release();
+        self.game = self.start.clone();
+        let mut plies = replay_plies(&mut self.game, moves);
+        let mut n_plies = 0;
+        while let Some((_, _, game)) = plies.next_ply()? {
+            if n_plies == self.fens.len() {
+                self.fens.push(String::new());
+            }
+            let fen = &mut self.fens[n_plies];
+            fen.clear();
+            game.write_fen_with(&mut self.encoder, fen);
+            n_plies += 1;
+        }
+        Ok(&self.fens[..n_plies])
+    }
+
+    /// Replay moves in SAN like `fentasize`, but append the FENs to the buffer, and return their
+    /// number. A failing game leaves the buffer as it was.
+    pub fn fentasize_into(
+        &mut self,
+        moves: &[&str],
+        buffer: &mut FenBuffer,
+    ) -> Result<usize, String> {
+        let n_before = buffer.len();
+        self.game = self.start.clone();
+        let mut plies = replay_plies(&mut self.game, moves);
+        loop {
+            match plies.next_ply() {
+                Ok(Some((_, _, game))) => {
+                    game.write_fen_with(&mut self.encoder, &mut buffer.data);
+                    buffer.offsets.push(buffer.data.len() as i64);
+                }
+                Ok(None) => return Ok(buffer.len() - n_before),
+                Err(e) => {
+                    buffer.truncate(n_before);
+                    return Err(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// The game after the last replay, e.g. to derive more than the FENs.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Free the buffers beyond the bound.
+    fn release(&mut self) {
+        if self.fens.len() > self.max_retained_plies {
+            self.fens.truncate(self.max_retained_plies);
+            self.fens.shrink_to_fit();
+        }
+    }
+}
+
+/// FENs of many plies in one contiguous string, where FEN i spans the bytes from `offsets[i]` to
+/// `offsets[i + 1]`. This is the layout of Arrow string arrays, thus the FENs can be handed over
+/// to data frames without a string object per FEN.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FenBuffer {
+    pub data: String,
+    pub offsets: Vec<i64>,
+}
+
+impl Default for FenBuffer {
+    fn default() -> Self {
+        FenBuffer {
+            data: String::new(),
+            offsets: vec![0],
+        }
+    }
+}
+
+impl FenBuffer {
+    pub fn new() -> Self {
+        FenBuffer::default()
+    }
+
+    /// Number of FENs.
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&str> {
+        let (start, end) = (*self.offsets.get(idx)?, *self.offsets.get(idx + 1)?);
+        Some(&self.data[start as usize..end as usize])
+    }
+
+    /// Append the FEN of a position.
+    pub fn push(&mut self, game: &Game) {
+        game.write_fen(&mut self.data);
+        self.offsets.push(self.data.len() as i64);
+    }
+
+    /// Drop the FENs from the given index on.
+    fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.data.truncate(self.offsets[len] as usize);
+            self.offsets.truncate(len + 1);
+        }
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_batch_context() {
+    use std::str::FromStr;
+
+    let mut context = BatchContext::default();
+    assert_eq!(
+        context.fentasize(&["e4", "e5", "1-0"]).unwrap(),
+        [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        ]
+    );
+    // The next game starts over and reuses the buffers.
+    assert_eq!(context.fentasize(&["d4"]).unwrap().len(), 1);
+    assert_eq!(context.retained_plies(), 2);
+    assert_eq!(
+        context.fentasize(&["e4", "Ke7"]),
+        Err("ply 2: illegal move: Ke7".to_string())
+    );
+
+    let fen = "4k3/8/8/8/8/8/8/R3K3 w Q - 0 30";
+    let mut context = BatchContext::new(Game::from_str(fen).unwrap());
+    assert_eq!(
+        context.fentasize(&["O-O-O"]).unwrap(),
+        ["4k3/8/8/8/8/8/8/2KR4 b - - 1 30"]
+    );
+    assert_eq!(context.game().uci, "e1c1");
+}
+
+#[test]
+fn check_retained_plies_are_bounded() {
+    let mut context = BatchContext::default().max_retained_plies(4);
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"].repeat(3);
+    assert_eq!(context.fentasize(&shuffle).unwrap().len(), 12);
+    assert_eq!(context.retained_plies(), 12);
+    context.fentasize(&["e4"]).unwrap();
+    assert_eq!(context.retained_plies(), 4);
+
+    // FENs match those of a game replayed on its own.
+    let mut game = Game::new();
+    for (mv, fen) in shuffle.iter().zip(context.fentasize(&shuffle).unwrap()) {
+        game.play_move(mv).unwrap();
+        assert_eq!(*fen, game.clone().to_fen());
+    }
+}
+
+#[test]
+fn check_fen_buffer() {
+    let mut context = BatchContext::default();
+    let mut buffer = FenBuffer::new();
+    assert_eq!(context.fentasize_into(&["e4", "e5"], &mut buffer), Ok(2));
+    assert_eq!(
+        context.fentasize_into(&["d4", "Ke7"], &mut buffer),
+        Err("ply 2: illegal move: Ke7".to_string())
+    );
+    assert_eq!(context.fentasize_into(&["d4"], &mut buffer), Ok(1));
+
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.offsets[0], 0);
+    assert_eq!(buffer.offsets[3] as usize, buffer.data.len());
+    assert_eq!(
+        buffer.get(1),
+        Some("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2")
+    );
+    assert_eq!(
+        buffer.get(2),
+        Some("rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1")
+    );
+    assert_eq!(buffer.get(3), None);
+}
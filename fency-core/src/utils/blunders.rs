@@ -0,0 +1,244 @@
+use crate::utils::color::Color;
+use crate::utils::game::Game;
+use crate::utils::lichess::Eval;
+use crate::utils::material::MaterialEvaluator;
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::replay::replay_plies;
+
+/// When an eval swing counts as a mistake. Evals are capped, such that e.g. a mate in 3 instead of
+/// a mate in 2 is no swing, while throwing away a won position is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlunderCriteria {
+    /// Minimum loss of the moving side in centipawns.
+    pub threshold: i32,
+
+    /// Evals (including mates) are clamped to plus/minus this value in centipawns.
+    pub cap: i32,
+}
+
+impl Default for BlunderCriteria {
+    fn default() -> Self {
+        BlunderCriteria {
+            threshold: 200,
+            cap: 1000,
+        }
+    }
+}
+
+/// A move after which the eval dropped for the side that played it, e.g. a puzzle candidate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Blunder {
+    pub ply: usize,
+
+    /// The position before the mistake, i.e. with the blundering side to move.
+    pub fen: String,
+    pub san: String,
+    pub uci: String,
+    pub eval_before: Eval,
+    pub eval_after: Eval,
+
+    /// Capped loss in centipawns from the point of view of the moving side.
+    pub loss: i32,
+}
+
+/// An eval in centipawns from the point of view of white, clamped to the cap. A mate that has
+/// already been delivered ("#0") counts for the side that moved last.
+fn capped(eval: Eval, last_mover: Color, cap: i32) -> i32 {
+    match eval {
+        Eval::Cp(cp) => cp.clamp(-cap, cap),
+        Eval::Mate(0) => cap * i32::from(last_mover.factor()),
+        Eval::Mate(n) => cap * n.signum(),
+    }
+}
+
+/// Find the plies where the eval dropped by at least the threshold for the moving side. Evals are
+/// given after every ply, from the point of view of white, thus the first ply is never a blunder.
+/// Result tokens are skipped and replay stops after the last eval.
+pub fn find_blunders(
+    moves: &[&str],
+    evals: &[Option<Eval>],
+    criteria: &BlunderCriteria,
+) -> Result<Vec<Blunder>, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut blunders = Vec::new();
+    while plies.ply() < evals.len() {
+        let fen = plies.game().clone().to_fen();
+        let mover = plies.game().color;
+        let Some((ply, mv, game)) = plies.next_ply()? else {
+            break;
+        };
+
+        let i = ply - 1;
+        let before = i.checked_sub(1).and_then(|j| evals[j]);
+        if let (Some(before), Some(after)) = (before, evals[i]) {
+            let loss = i32::from(mover.factor())
+                * (capped(before, mover.next(), criteria.cap) - capped(after, mover, criteria.cap));
+            if loss >= criteria.threshold {
+                blunders.push(Blunder {
+                    ply,
+                    fen,
+                    san: mv.to_string(),
+                    uci: game.uci.clone(),
+                    eval_before: before,
+                    eval_after: after,
+                    loss,
+                });
+            }
+        }
+    }
+
+    Ok(blunders)
+}
+
+impl PgnGame {
+    /// Find the blunders of a game according to its "[%eval]" comments, see `find_blunders`.
+    pub fn blunders(&self, criteria: &BlunderCriteria) -> Result<Vec<Blunder>, String> {
+        find_blunders(&self.moves(), &self.evals, criteria)
+    }
+}
+
+/// Find the blunders of all games of a PGN string as (game index, blunder).
+pub fn blunders_in_pgn(
+    pgn: &str,
+    criteria: &BlunderCriteria,
+) -> Result<Vec<(usize, Blunder)>, String> {
+    let mut blunders = Vec::new();
+    for (g, game) in PgnReader::new(pgn.as_bytes()).enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        let found = game
+            .blunders(criteria)
+            .map_err(|e| format!("game {}, {}", g, e))?;
+        blunders.extend(found.into_iter().map(|b| (g, b)));
+    }
+
+    Ok(blunders)
+}
+
+/// A move after which the opponent wins material by a capture, see `find_material_blunders`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaterialBlunder {
+    pub ply: usize,
+
+    /// The position before the mistake, i.e. with the blundering side to move.
+    pub fen: String,
+    pub san: String,
+    pub uci: String,
+
+    /// The most winning capture of the opponent in UCI notation.
+    pub refutation: String,
+
+    /// Material lost in centipawns, i.e. what the refutation wins by static exchange evaluation
+    /// minus what the move captured itself.
+    pub loss: i32,
+}
+
+/// Find the moves that hang material worth at least the threshold in centipawns, e.g. to find
+/// blunders in games without evals. Only captures refute a move, thus mating attacks and threats
+/// are missed, see `MaterialEvaluator::see`. Result tokens are skipped.
+pub fn find_material_blunders(
+    moves: &[&str],
+    evaluator: &MaterialEvaluator,
+    threshold: i32,
+) -> Result<Vec<MaterialBlunder>, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut blunders = Vec::new();
+    while let Some((ply, mv, before, game)) = plies.next_pair()? {
+        let gain = evaluator.gain(before, &game.uci);
+
+        let refutation = game
+            .legal_moves()
+            .into_iter()
+            .filter(|uci| evaluator.gain(game, uci) > 0)
+            .map(|uci| (evaluator.see(game, &uci).unwrap(), uci))
+            .max_by_key(|(see, _)| *see);
+        if let Some((see, refutation)) = refutation {
+            if see - gain >= threshold {
+                blunders.push(MaterialBlunder {
+                    ply,
+                    fen: before.clone().to_fen(),
+                    san: mv.to_string(),
+                    uci: game.uci.clone(),
+                    refutation,
+                    loss: see - gain,
+                });
+            }
+        }
+    }
+
+    Ok(blunders)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_find_blunders() {
+    let pgn = "[Event \"Scholar's mate\"]\n\n\
+               1. e4 { [%eval 0.3] } e5 { [%eval 0.25] } 2. Qh5 { [%eval 0.1] } \
+               Nc6 { [%eval 0.4] } 3. Bc4 { [%eval 0.35] } Nf6 { [%eval #1] } \
+               4. Qxf7# { [%eval #0] } 1-0\n";
+    let blunders = blunders_in_pgn(pgn, &BlunderCriteria::default()).unwrap();
+
+    assert_eq!(blunders.len(), 1);
+    let (g, blunder) = &blunders[0];
+    assert_eq!((*g, blunder.ply), (0, 6));
+    assert_eq!(blunder.san, "Nf6");
+    assert_eq!(blunder.uci, "g8f6");
+    assert_eq!(
+        blunder.fen,
+        "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 3 3"
+    );
+    assert_eq!(blunder.eval_after, Eval::Mate(1));
+    assert_eq!(blunder.eval_before.to_string(), "0.35");
+    assert_eq!(blunder.loss, 965);
+}
+
+#[test]
+fn check_blunder_criteria() {
+    let moves = ["e4", "e5", "Nf3", "Nc6"];
+    let evals = [
+        Some(Eval::Cp(30)),
+        Some(Eval::Cp(80)),
+        Some(Eval::Cp(-20)),
+        Some(Eval::Mate(-4)),
+    ];
+
+    // White lost 100cp on ply 3, black only 50cp on ply 2 and improved on ply 4.
+    let strict = BlunderCriteria {
+        threshold: 100,
+        ..BlunderCriteria::default()
+    };
+    let found = find_blunders(&moves, &evals, &strict).unwrap();
+    assert_eq!(found.iter().map(|b| b.ply).collect::<Vec<_>>(), vec![3]);
+    assert!(find_blunders(&moves, &evals, &BlunderCriteria::default())
+        .unwrap()
+        .is_empty());
+
+    // Winning mates are no swing within the cap.
+    let mates = [Some(Eval::Mate(3)), Some(Eval::Mate(5))];
+    assert!(find_blunders(&moves[..2], &mates, &strict)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn check_material_blunders() {
+    let evaluator = MaterialEvaluator::default();
+    // 2... Qh4 hangs the queen to 3. Nxh4, which captures the queen but hangs nothing.
+    let moves = ["e4", "e5", "Nf3", "Qh4", "Nxh4", "Nc6"];
+    let blunders = find_material_blunders(&moves, &evaluator, 200).unwrap();
+    let found: Vec<(usize, &str, i32)> = blunders
+        .iter()
+        .map(|b| (b.ply, b.refutation.as_str(), b.loss))
+        .collect();
+    assert_eq!(found, [(4, "f3h4", 900)]);
+    assert_eq!(blunders[0].uci, "d8h4");
+
+    // Trades lose nothing, and a lower threshold finds a hanging pawn.
+    let trade = ["e4", "d5", "exd5", "Qxd5"];
+    assert!(find_material_blunders(&trade, &evaluator, 100)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        find_material_blunders(&["e4", "f5", "1-0"], &evaluator, 100).unwrap()[0].ply,
+        2
+    );
+}
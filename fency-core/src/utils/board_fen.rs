@@ -0,0 +1,118 @@
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+
+/// Board field of the FEN that is updated rank by rank. A move changes at most four squares on at
+/// most four ranks, thus replaying a game only re-renders the ranks whose squares changed since the
+/// last update instead of scanning the whole board for every ply.
+#[derive(Clone, Debug)]
+pub struct BoardEncoder {
+    position: [Option<Figure>; 64],
+    ranks: [String; 8],
+    board: String,
+}
+
+impl Default for BoardEncoder {
+    fn default() -> Self {
+        BoardEncoder {
+            position: [None; 64],
+            ranks: std::array::from_fn(|_| "8".to_string()),
+            board: "8/8/8/8/8/8/8/8".to_string(),
+        }
+    }
+}
+
+impl BoardEncoder {
+    /// An encoder for the board of the game.
+    pub fn new(game: &Game) -> Self {
+        let mut encoder = BoardEncoder::default();
+        encoder.update(game);
+        encoder
+    }
+
+    /// Re-render the ranks that changed since the last update and return their number.
+    pub fn update(&mut self, game: &Game) -> usize {
+        let mut n_changed = 0;
+        for (r, rank) in self.ranks.iter_mut().enumerate() {
+            let squares = r * 8..r * 8 + 8;
+            if self.position[squares.clone()] == game.position[squares.clone()] {
+                continue;
+            }
+            self.position[squares.clone()].copy_from_slice(&game.position[squares.clone()]);
+            rank.clear();
+            write_rank(&self.position[squares], rank);
+            n_changed += 1;
+        }
+        if n_changed > 0 {
+            self.board.clear();
+            for (r, rank) in self.ranks.iter().enumerate() {
+                if r > 0 {
+                    self.board.push('/');
+                }
+                self.board.push_str(rank);
+            }
+        }
+        n_changed
+    }
+
+    /// Append the board field, i.e. the ranks from 8 to 1 separated by '/'.
+    pub fn write(&self, fen: &mut String) {
+        fen.push_str(&self.board);
+    }
+}
+
+fn write_rank(squares: &[Option<Figure>], fen: &mut String) {
+    let mut spacer = 0;
+    for square in squares {
+        match square {
+            None => spacer += 1,
+            Some(figure) => {
+                if spacer > 0 {
+                    fen.push(char::from_digit(spacer, 10).unwrap());
+                    spacer = 0;
+                }
+                fen.push(figure.to_char());
+            }
+        }
+    }
+    if spacer > 0 {
+        fen.push(char::from_digit(spacer, 10).unwrap());
+    }
+}
+
+impl Game {
+    /// Append the FEN like `write_fen`, but take the board field from an encoder that is kept up
+    /// to date with the plies of this game, see `BoardEncoder`.
+    pub fn write_fen_with(&self, encoder: &mut BoardEncoder, fen: &mut String) {
+        encoder.update(self);
+        encoder.write(fen);
+        self.write_fen_fields(fen);
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_board_encoder() {
+    use std::str::FromStr;
+
+    let mut game = Game::new();
+    let mut encoder = BoardEncoder::new(&game);
+    game.play_move("e4").unwrap();
+    assert_eq!(encoder.update(&game), 2);
+    assert_eq!(encoder.update(&game), 0);
+    for mv in ["Nf6", "e5", "d5"] {
+        game.play_move(mv).unwrap();
+        encoder.update(&game);
+    }
+    // The pawn captured en passant is on the rank the capturing pawn leaves.
+    game.play_move("exd6").unwrap();
+    assert_eq!(encoder.update(&game), 2);
+    let mut fen = String::new();
+    game.write_fen_with(&mut encoder, &mut fen);
+    assert_eq!(fen, game.clone().to_fen());
+
+    // An encoder follows jumps to unrelated positions, too.
+    let game = Game::from_str("8/7p/1k6/3Q4/2Q5/6Pp/5P1K/8 w - - 1 62").unwrap();
+    fen.clear();
+    game.write_fen_with(&mut encoder, &mut fen);
+    assert_eq!(fen, "8/7p/1k6/3Q4/2Q5/6Pp/5P1K/8 w - - 1 62");
+}
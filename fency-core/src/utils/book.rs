@@ -0,0 +1,288 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::replay::replay_plies;
+use crate::utils::writer::{write_movetext, write_tags};
+use crate::utils::zobrist::zobrist_hash;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// A move of the book with the number of games that played it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BookMove {
+    pub san: String,
+    pub uci: String,
+    pub games: u32,
+
+    /// Index of the node the move leads to.
+    node: usize,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct BookNode {
+    moves: Vec<BookMove>,
+}
+
+/// Opening book that merges many games into a single tree of variations, e.g. to build a
+/// repertoire from short PGNs. Lines are merged by their moves, and optionally by their positions,
+/// such that transpositions continue in the same node. In contrast to `PositionTree`, the book
+/// keeps the move orders, thus it can be written back as PGN.
+#[derive(Clone, Debug)]
+pub struct OpeningBook {
+    start: Game,
+    nodes: Vec<BookNode>,
+
+    /// Nodes by the Zobrist hash of their position, if transpositions are merged.
+    by_position: Option<HashMap<u64, usize>>,
+    max_plies: Option<usize>,
+}
+
+impl Default for OpeningBook {
+    fn default() -> Self {
+        OpeningBook::new(Game::new())
+    }
+}
+
+impl OpeningBook {
+    /// An empty book whose lines start from the given position.
+    pub fn new(start: Game) -> Self {
+        OpeningBook {
+            start,
+            nodes: vec![BookNode::default()],
+            by_position: None,
+            max_plies: None,
+        }
+    }
+
+    /// Merge lines that reach the same position by different move orders. Only games added
+    /// afterwards are merged this way.
+    pub fn merge_transpositions(mut self) -> Self {
+        self.by_position = Some(HashMap::from([(zobrist_hash(&self.start), 0)]));
+        self
+    }
+
+    /// Only add the first plies of every game.
+    pub fn max_plies(mut self, max_plies: usize) -> Self {
+        self.max_plies = Some(max_plies);
+        self
+    }
+
+    /// Add the moves of a game in SAN from the start position. Result tokens are skipped. A game
+    /// with an invalid move is not added at all.
+    pub fn add_game(&mut self, moves: &[&str]) -> Result<(), String> {
+        // Replay the whole game first, so that an invalid move leaves the book untouched.
+        let mut replay = replay_plies(self.start.clone(), moves);
+        let mut plies = Vec::new();
+        while replay.ply() < self.max_plies.unwrap_or(usize::MAX) {
+            let Some((ply, _, before, game)) = replay.next_pair()? else {
+                break;
+            };
+            let san = before.uci_to_san(&game.uci).map_err(at_ply(ply))?;
+            plies.push((san, game.uci.clone(), zobrist_hash(game)));
+        }
+
+        let mut node = 0;
+        for (san, uci, key) in plies {
+            node = self.follow(node, san, uci, key);
+        }
+        Ok(())
+    }
+
+    /// Add a parsed PGN game, which must start from the start position of the book.
+    pub fn add_pgn_game(&mut self, pgn_game: &PgnGame) -> Result<(), String> {
+        if zobrist_hash(&pgn_game.start_position()?) != zobrist_hash(&self.start) {
+            return Err("game starts from another position than the book".to_string());
+        }
+        self.add_game(&pgn_game.moves())
+    }
+
+    /// Add all games of a PGN string and return their number.
+    pub fn add_pgn(&mut self, pgn: &str) -> Result<usize, String> {
+        let mut n_games = 0;
+        for (g, game) in PgnReader::new(pgn.as_bytes()).enumerate() {
+            let game = game.map_err(|e| e.to_string())?;
+            self.add_pgn_game(&game)
+                .map_err(|e| format!("game {}, {}", g, e))?;
+            n_games += 1;
+        }
+
+        Ok(n_games)
+    }
+
+    /// Count the move from the node and return the node it leads to, which is created if needed.
+    fn follow(&mut self, node: usize, san: String, uci: String, key: u64) -> usize {
+        if let Some(mv) = self.nodes[node].moves.iter_mut().find(|mv| mv.uci == uci) {
+            mv.games += 1;
+            return mv.node;
+        }
+        let next = self.nodes.len();
+        let child = match self.by_position.as_mut() {
+            Some(by_position) => *by_position.entry(key).or_insert(next),
+            None => next,
+        };
+        if child == next {
+            self.nodes.push(BookNode::default());
+        }
+        self.nodes[node].moves.push(BookMove {
+            san,
+            uci,
+            games: 1,
+            node: child,
+        });
+        child
+    }
+
+    /// Moves played from the start position, the most popular first.
+    pub fn moves(&self) -> Vec<&BookMove> {
+        self.sorted_moves(0)
+    }
+
+    fn sorted_moves(&self, node: usize) -> Vec<&BookMove> {
+        let mut moves: Vec<&BookMove> = self.nodes[node].moves.iter().collect();
+        moves.sort_by_key(|mv| std::cmp::Reverse(mv.games));
+        moves
+    }
+
+    /// Drop the moves played by fewer than the given number of games, with all lines after them.
+    pub fn prune(&mut self, min_games: u32) {
+        for node in &mut self.nodes {
+            node.moves.retain(|mv| mv.games >= min_games);
+        }
+    }
+
+    /// Write the book as a single PGN game, where the most popular move is the main line and the
+    /// others are variations. A position that is reached by transposition is continued only once,
+    /// preferably in the main line.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let fen = self.start.clone().to_fen();
+        if fen != Game::new().to_fen() {
+            write_tags(
+                &mut pgn,
+                &[
+                    ("SetUp".to_string(), "1".to_string()),
+                    ("FEN".to_string(), fen),
+                ],
+            );
+        }
+
+        let mut owners = HashMap::from([(0, (0, 0))]);
+        self.claim(0, &mut owners);
+        let mut tokens = Vec::new();
+        self.write_node(0, 0, true, &owners, &mut tokens);
+        tokens.push("*".to_string());
+        write_movetext(&mut pgn, tokens);
+        pgn
+    }
+
+    /// Assign every node to the move that is written first leading to it, as (node, index into
+    /// its sorted moves). Main lines are claimed before their variations, thus they continue
+    /// through transpositions.
+    fn claim(&self, node: usize, owners: &mut HashMap<usize, (usize, usize)>) {
+        for (i, mv) in self.sorted_moves(node).into_iter().enumerate() {
+            if let Entry::Vacant(entry) = owners.entry(mv.node) {
+                entry.insert((node, i));
+                self.claim(mv.node, owners);
+            }
+        }
+    }
+
+    /// The move number before the ply, which black moves only show if forced, e.g. "12...".
+    fn move_number(&self, ply: usize, force: bool) -> Option<String> {
+        let half = ply + usize::from(self.start.color.is_black());
+        let number = self.start.full_move_clock as usize + half / 2;
+        if half.is_multiple_of(2) {
+            Some(format!("{}.", number))
+        } else {
+            force.then(|| format!("{}...", number))
+        }
+    }
+
+    fn write_node(
+        &self,
+        node: usize,
+        ply: usize,
+        force_number: bool,
+        owners: &HashMap<usize, (usize, usize)>,
+        tokens: &mut Vec<String>,
+    ) {
+        let moves = self.sorted_moves(node);
+        let Some((main, alternatives)) = moves.split_first() else {
+            return;
+        };
+        let continues = |i: usize, mv: &BookMove| owners.get(&mv.node) == Some(&(node, i));
+
+        tokens.extend(self.move_number(ply, force_number));
+        tokens.push(main.san.clone());
+        for (i, alternative) in alternatives.iter().enumerate() {
+            tokens.push(format!("({}", self.move_number(ply, true).unwrap()));
+            tokens.push(alternative.san.clone());
+            if continues(i + 1, alternative) {
+                self.write_node(alternative.node, ply + 1, false, owners, tokens);
+            }
+            tokens.last_mut().unwrap().push(')');
+        }
+        if continues(0, main) {
+            self.write_node(main.node, ply + 1, !alternatives.is_empty(), owners, tokens);
+        }
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_opening_book() {
+    let mut book = OpeningBook::default();
+    let pgn = "[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 1-0\n\n\
+               [Result \"0-1\"]\n\n1. e4 c5 2. Nf3 0-1\n\n\
+               [Result \"*\"]\n\n1. e4 e5 2. Nf3 Nf6 3. Nxe5 *\n\n\
+               [Result \"*\"]\n\n1. d4 *\n";
+    assert_eq!(book.add_pgn(pgn), Ok(4));
+    let moves: Vec<(&str, u32)> = book.moves().iter().map(|mv| (&*mv.san, mv.games)).collect();
+    assert_eq!(moves, [("e4", 3), ("d4", 1)]);
+    assert_eq!(
+        book.to_pgn(),
+        "1. e4 (1. d4) 1... e5 (1... c5 2. Nf3) 2. Nf3 Nc6 (2... Nf6 3. Nxe5) *\n"
+    );
+
+    book.prune(2);
+    assert_eq!(book.to_pgn(), "1. e4 e5 2. Nf3 *\n");
+
+    assert_eq!(
+        book.add_game(&["e4", "Ke7"]),
+        Err("ply 2: illegal move: Ke7".to_string())
+    );
+    assert_eq!(book.moves()[0].games, 3);
+}
+
+#[test]
+fn check_book_transpositions() {
+    use std::str::FromStr;
+
+    let lines: [&[&str]; 2] = [
+        &["d4", "Nf6", "c4", "e6"],
+        &["c4", "e6", "d4", "Nf6", "Nc3"],
+    ];
+    let mut book = OpeningBook::default();
+    let mut merged = OpeningBook::default().merge_transpositions();
+    for line in lines {
+        book.add_game(line).unwrap();
+        merged.add_game(line).unwrap();
+    }
+    assert_eq!(
+        book.to_pgn(),
+        "1. d4 (1. c4 e6 2. d4 Nf6 3. Nc3) 1... Nf6 2. c4 e6 *\n"
+    );
+    assert_eq!(
+        merged.to_pgn(),
+        "1. d4 (1. c4 e6 2. d4 Nf6) 1... Nf6 2. c4 e6 3. Nc3 *\n"
+    );
+
+    // Books from other positions continue their move numbers.
+    let start = Game::from_str("4k3/8/8/8/8/8/8/R3K3 b Q - 0 30").unwrap();
+    let mut book = OpeningBook::new(start).max_plies(2);
+    book.add_game(&["Kd7", "O-O-O+", "Ke7"]).unwrap();
+    assert_eq!(
+        book.to_pgn(),
+        "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 b Q - 0 30\"]\n\n30... Kd7 31. O-O-O+ *\n"
+    );
+    assert!(book.add_pgn("1. e4 *\n").is_err());
+}
@@ -0,0 +1,220 @@
+use crate::utils::color::Color;
+use crate::utils::coord::{Coord, BOARD};
+use crate::utils::game::Game;
+use crate::utils::zobrist::zobrist_hash;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Number of independently locked parts of a cache, such that threads rarely wait for each other.
+const SHARDS: usize = 16;
+
+/// Number of positions the global cache holds at most.
+const GLOBAL_CAPACITY: usize = 1 << 20;
+
+/// Artifacts of a position that are expensive to derive, but only depend on the position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionInfo {
+    /// Legal moves of the side to move in UCI notation.
+    pub legal_moves: Vec<String>,
+
+    /// Squares attacked by white and by black as bit sets over the square indices.
+    pub attacked: [u64; 2],
+}
+
+impl PositionInfo {
+    pub fn of(game: &Game) -> Self {
+        let attacked_by = |color: Color| {
+            BOARD
+                .iter()
+                .filter(|square| game.is_attacked(square, color))
+                .fold(0, |bits, square| bits | (1 << square.idx))
+        };
+        PositionInfo {
+            legal_moves: game.legal_moves(),
+            attacked: [attacked_by(Color::W), attacked_by(Color::B)],
+        }
+    }
+
+    pub fn is_attacked(&self, square: &Coord, by: Color) -> bool {
+        let bits = if by.is_white() {
+            self.attacked[0]
+        } else {
+            self.attacked[1]
+        };
+        bits & (1 << square.idx) != 0
+    }
+}
+
+/// Counters of a cache, e.g. to tune its capacity to the memory at hand.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+
+    /// Positions dropped to stay within the capacity.
+    pub evictions: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl CacheStats {
+    /// Share of lookups that were answered from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+/// Thread-safe cache of position artifacts by Zobrist hash, which is sharded by the hash to keep
+/// lock contention low. A full shard is cleared as a whole, which is cheap and keeps the positions
+/// of recent games, e.g. the common openings of a dataset.
+pub struct PositionCache {
+    shards: Vec<RwLock<HashMap<u64, Arc<PositionInfo>>>>,
+    shard_capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl PositionCache {
+    /// A cache that holds roughly the given number of positions at most.
+    pub fn new(capacity: usize) -> Self {
+        PositionCache {
+            shards: (0..SHARDS).map(|_| RwLock::default()).collect(),
+            shard_capacity: capacity.div_ceil(SHARDS),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// The cache shared by the whole process.
+    pub fn global() -> &'static PositionCache {
+        static GLOBAL: OnceLock<PositionCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| PositionCache::new(GLOBAL_CAPACITY))
+    }
+
+    fn shard(&self, key: u64) -> &RwLock<HashMap<u64, Arc<PositionInfo>>> {
+        &self.shards[(key % SHARDS as u64) as usize]
+    }
+
+    /// The artifacts of the position, which are derived and stored on a miss. Artifacts are
+    /// derived outside the lock, thus concurrent misses of the same position may both derive it.
+    pub fn get(&self, game: &Game) -> Arc<PositionInfo> {
+        let key = zobrist_hash(game);
+        let shard = self.shard(key);
+        if let Some(info) = shard.read().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return info.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let info = Arc::new(PositionInfo::of(game));
+
+        let mut entries = shard.write().unwrap();
+        if entries.len() >= self.shard_capacity {
+            self.evictions
+                .fetch_add(entries.len() as u64, Ordering::Relaxed);
+            entries.clear();
+        }
+        entries.entry(key).or_insert(info).clone()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.shards.iter().map(|s| s.read().unwrap().len()).sum(),
+            capacity: self.shard_capacity * SHARDS,
+        }
+    }
+
+    /// Drop all positions and reset the counters.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().unwrap().clear();
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Game {
+    /// The artifacts of the position from the global cache, see `PositionCache`.
+    pub fn cached_info(&self) -> Arc<PositionInfo> {
+        PositionCache::global().get(self)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_position_cache() {
+    let cache = PositionCache::new(64);
+    let mut game = Game::new();
+    let info = cache.get(&game);
+    assert_eq!(info.legal_moves.len(), 20);
+    assert!(info.is_attacked(&Coord::from("e3"), Color::W));
+    assert!(!info.is_attacked(&Coord::from("e4"), Color::W));
+    assert!(info.is_attacked(&Coord::from("f6"), Color::B));
+
+    // Transpositions hit the cache.
+    let mut other = Game::new();
+    for (a, b) in [
+        ("Nf3", "Nc3"),
+        ("Nf6", "Nc6"),
+        ("Nc3", "Nf3"),
+        ("Nc6", "Nf6"),
+    ] {
+        game.play_move(a).unwrap();
+        other.play_move(b).unwrap();
+        cache.get(&game);
+        cache.get(&other);
+    }
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 8, 8));
+    assert_eq!(stats.capacity, 64);
+
+    cache.clear();
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            capacity: 64,
+            ..CacheStats::default()
+        }
+    );
+}
+
+#[test]
+fn check_cache_evictions() {
+    // More distinct positions than shards, thus at least one shard overflows.
+    let cache = PositionCache::new(1);
+    let mut game = Game::new();
+    for mv in [
+        "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5", "Bb3",
+        "d6", "c3", "O-O", "h3", "Nb8", "d4", "Nbd7",
+    ] {
+        game.play_move(mv).unwrap();
+        assert_eq!(cache.get(&game).legal_moves, game.legal_moves());
+    }
+    let stats = cache.stats();
+    assert!(stats.evictions > 0 && stats.entries <= SHARDS);
+}
+
+#[test]
+fn check_cache_is_shared_by_threads() {
+    let cache = PositionCache::new(1024);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let mut game = Game::new();
+                for mv in ["d4", "d5", "c4", "e6"] {
+                    game.play_move(mv).unwrap();
+                    cache.get(&game);
+                }
+            });
+        }
+    });
+    let stats = cache.stats();
+    assert_eq!((stats.hits + stats.misses, stats.entries), (16, 4));
+}
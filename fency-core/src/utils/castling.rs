@@ -35,20 +35,10 @@ impl Castling {
     }
 
     pub fn update(&mut self, figure: Figure) {
-        if figure.piece == Piece::R {
-            if figure.color == Color::W {
-                if figure.coord.idx == 56 {
-                    self.white_queenside = false;
-                } else if figure.coord.idx == 63 {
-                    self.white_kingside = false;
-                }
-            } else if figure.coord.idx == 0 {
-                self.black_queenside = false;
-            } else if figure.coord.idx == 7 {
-                self.black_kingside = false;
-            }
-        } else if figure.piece == Piece::K {
-            if figure.color == Color::W {
+        if figure.piece() == Piece::R {
+            self.drop_rook(figure);
+        } else if figure.piece() == Piece::K {
+            if figure.color() == Color::W {
                 self.white_queenside = false;
                 self.white_kingside = false;
             } else {
@@ -57,6 +47,23 @@ impl Castling {
             }
         }
     }
+
+    /// Drop the right that depends on a captured rook on its initial square.
+    pub fn capture(&mut self, figure: Figure) {
+        if figure.piece() == Piece::R {
+            self.drop_rook(figure);
+        }
+    }
+
+    fn drop_rook(&mut self, rook: Figure) {
+        match (rook.color(), rook.square()) {
+            (Color::W, 56) => self.white_queenside = false,
+            (Color::W, 63) => self.white_kingside = false,
+            (Color::B, 0) => self.black_queenside = false,
+            (Color::B, 7) => self.black_kingside = false,
+            _ => (),
+        }
+    }
 }
 
 impl Default for Castling {
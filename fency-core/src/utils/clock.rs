@@ -0,0 +1,199 @@
+use crate::utils::color::Color;
+use crate::utils::pgn::PgnGame;
+use crate::utils::time_control::TimeControl;
+
+/// A chess clock running by a time control, which starts with white to move.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Clock {
+    time_control: TimeControl,
+    remaining: [u32; 2],
+    moves: [u32; 2],
+    color: Color,
+    flagged: Option<Color>,
+}
+
+fn side(color: Color) -> usize {
+    if color.is_white() {
+        0
+    } else {
+        1
+    }
+}
+
+impl Clock {
+    /// A clock for the time control, or None if there is no time on the clock, i.e. the time
+    /// control is unknown or unlimited.
+    pub fn new(time_control: &TimeControl) -> Option<Self> {
+        let seconds = time_control.initial_seconds()?;
+        Some(Clock {
+            time_control: time_control.clone(),
+            remaining: [seconds; 2],
+            moves: [0; 2],
+            color: Color::W,
+            flagged: None,
+        })
+    }
+
+    /// Remaining time of a color in seconds.
+    pub fn remaining(&self, color: Color) -> u32 {
+        self.remaining[side(color)]
+    }
+
+    /// The color to move.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// The color whose flag fell, after which the clock is stopped.
+    pub fn flagged(&self) -> Option<Color> {
+        self.flagged
+    }
+
+    /// Let the side to move spend the given seconds on a move and return its remaining time,
+    /// including the time added after the move. The flag falls if the time runs out, which leaves
+    /// the clock at zero.
+    pub fn play(&mut self, seconds: u32) -> u32 {
+        let mover = side(self.color);
+        if self.flagged.is_some() {
+            return self.remaining[mover];
+        }
+        if seconds >= self.remaining[mover] {
+            self.remaining[mover] = 0;
+            self.flagged = Some(self.color);
+            return 0;
+        }
+        self.remaining[mover] -= seconds;
+        self.moves[mover] += 1;
+        match self.time_control {
+            // The time used by one side is added to the other.
+            TimeControl::Sandclock(_) => self.remaining[1 - mover] += seconds,
+            _ => {
+                let added = self.time_control.added_after(self.moves[mover]);
+                self.remaining[mover] += added.unwrap_or(0);
+            }
+        }
+        self.color = self.color.next();
+        self.remaining[mover]
+    }
+}
+
+/// Remaining time of the side that played a ply.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClockReading {
+    pub ply: usize,
+    pub color: Color,
+    pub seconds: Option<u32>,
+
+    /// The clock was missing and is interpolated between neighbouring clocks of the same side.
+    pub is_interpolated: bool,
+
+    /// The side ran out of time with this ply.
+    pub is_flag_fall: bool,
+}
+
+/// Fill in the missing clocks of each side by linear interpolation between its known clocks,
+/// where the initial time of the time control is known before the first move. Clocks after the
+/// last known one of a side stay missing, and a flag falls with the first clock at zero.
+pub fn track_clocks(clocks: &[Option<u32>], time_control: &TimeControl) -> Vec<ClockReading> {
+    let mut seconds = clocks.to_vec();
+    for first in [0, 1] {
+        // Known clocks of the side as (move index, seconds), where -1 is before its first move.
+        let mut anchor: Option<(i64, i64)> = time_control.initial_seconds().map(|s| (-1, s as i64));
+        for k in 0..clocks.len().saturating_sub(first).div_ceil(2) {
+            let Some(clock) = clocks[first + 2 * k] else {
+                continue;
+            };
+            let (k, clock) = (k as i64, clock as i64);
+            if let Some((a, value)) = anchor {
+                for gap in a + 1..k {
+                    let interpolated = value + (clock - value) * (gap - a) / (k - a);
+                    seconds[first + 2 * gap as usize] = Some(interpolated as u32);
+                }
+            }
+            anchor = Some((k, clock));
+        }
+    }
+
+    let flag_fall = seconds.iter().position(|&s| s == Some(0));
+    seconds
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| ClockReading {
+            ply: i + 1,
+            color: if i % 2 == 0 { Color::W } else { Color::B },
+            seconds: s,
+            is_interpolated: clocks[i].is_none() && s.is_some(),
+            is_flag_fall: flag_fall == Some(i),
+        })
+        .collect()
+}
+
+impl PgnGame {
+    /// The clock of every ply with missing clocks interpolated, see `track_clocks`. Empty if the
+    /// game has no clock annotations at all.
+    pub fn clock_readings(&self) -> Vec<ClockReading> {
+        track_clocks(&self.clocks, &self.time_control())
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_clock() {
+    use std::str::FromStr;
+
+    let mut clock = Clock::new(&TimeControl::from_str("180+2").unwrap()).unwrap();
+    assert_eq!(clock.play(5), 177);
+    assert_eq!(clock.play(10), 172);
+    assert_eq!((clock.color(), clock.remaining(Color::W)), (Color::W, 177));
+    assert_eq!(clock.play(177), 0);
+    assert_eq!(clock.flagged(), Some(Color::W));
+    assert_eq!(clock.play(1), 0);
+    assert_eq!(clock.remaining(Color::B), 172);
+
+    let mut sandclock = Clock::new(&TimeControl::Sandclock(60)).unwrap();
+    sandclock.play(10);
+    assert_eq!(
+        (sandclock.remaining(Color::W), sandclock.remaining(Color::B)),
+        (50, 70)
+    );
+    assert_eq!(Clock::new(&TimeControl::Unknown), None);
+}
+
+#[test]
+fn check_track_clocks() {
+    use std::str::FromStr;
+
+    let blitz = TimeControl::from_str("180+2").unwrap();
+    let clocks = [
+        None,
+        Some(170),
+        Some(160),
+        None,
+        None,
+        Some(150),
+        Some(140),
+        None,
+    ];
+    let readings = track_clocks(&clocks, &blitz);
+    let seconds: Vec<Option<u32>> = readings.iter().map(|r| r.seconds).collect();
+    assert_eq!(
+        seconds,
+        vec![
+            Some(170),
+            Some(170),
+            Some(160),
+            Some(160),
+            Some(150),
+            Some(150),
+            Some(140),
+            None
+        ]
+    );
+    assert!(readings[0].is_interpolated && !readings[1].is_interpolated);
+    assert_eq!(readings[3].color, Color::B);
+
+    // Without a known time control, there is no anchor before the first move.
+    let readings = track_clocks(&[None, Some(60), Some(50), Some(0)], &TimeControl::Unknown);
+    assert_eq!(readings[0].seconds, None);
+    assert!(readings[3].is_flag_fall && !readings[2].is_flag_fall);
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "bzip2")]
+use bzip2::bufread::MultiBzDecoder;
+#[cfg(feature = "gzip")]
+use flate2::bufread::MultiGzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+use std::path::Path;
+
+/// Compression formats that are recognized by the magic bytes at the start of a stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    None,
+    Zstd,
+    Gzip,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detect the format from the first bytes of a stream. Plain text never starts like these.
+    pub fn detect(head: &[u8]) -> Self {
+        if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if head.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if head.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Wrap a source into a decoder if it is compressed, so compressed PGN can be streamed into a
+/// `PgnReader` just like plain text. Each format requires the feature of the same name.
+pub fn decompress<'a, R: BufRead + 'a>(mut source: R) -> std::io::Result<Box<dyn BufRead + 'a>> {
+    let compression = Compression::detect(source.fill_buf()?);
+
+    Ok(match compression {
+        Compression::None => Box::new(source),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(source)?)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(source))),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(source))),
+        #[allow(unreachable_patterns)]
+        Compression::Zstd => return Err(unsupported("zstd")),
+        #[allow(unreachable_patterns)]
+        Compression::Gzip => return Err(unsupported("gzip")),
+        #[allow(unreachable_patterns)]
+        Compression::Bzip2 => return Err(unsupported("bzip2")),
+    })
+}
+
+#[allow(dead_code)]
+fn unsupported(feature: &str) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "input is {}-compressed, but the feature is disabled",
+            feature
+        ),
+    )
+}
+
+/// Open a file for streaming, decompressing it on the fly if needed, e.g. a ".pgn.zst" dump.
+pub fn open(path: impl AsRef<Path>) -> std::io::Result<Box<dyn BufRead>> {
+    decompress(BufReader::new(File::open(path)?))
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const PGN: &str = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 d5 *\n";
+
+#[allow(dead_code)]
+fn read_games(source: Box<dyn BufRead + '_>) -> Vec<Vec<String>> {
+    crate::utils::pgn::PgnReader::new(source)
+        .map(|game| game.unwrap().moves)
+        .collect()
+}
+
+#[test]
+fn check_detect_compression() {
+    assert_eq!(Compression::detect(b"[Event"), Compression::None);
+    assert_eq!(Compression::detect(b""), Compression::None);
+    assert_eq!(Compression::detect(b"BZh91AY"), Compression::Bzip2);
+    assert_eq!(Compression::detect(&[0x1f, 0x8b, 8]), Compression::Gzip);
+    assert_eq!(
+        Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+        Compression::Zstd
+    );
+
+    assert_eq!(read_games(decompress(PGN.as_bytes()).unwrap()).len(), 2);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn check_zstd_input() {
+    let compressed = zstd::encode_all(PGN.as_bytes(), 3).unwrap();
+    let games = read_games(decompress(&compressed[..]).unwrap());
+    assert_eq!(games, vec![vec!["e4", "e5"], vec!["d4", "d5"]]);
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn check_gzip_input() {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(PGN.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(read_games(decompress(&compressed[..]).unwrap()).len(), 2);
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn check_bzip2_input() {
+    use std::io::Write;
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(PGN.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(read_games(decompress(&compressed[..]).unwrap()).len(), 2);
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn check_disabled_format() {
+    let err = decompress(&[0x28, 0xb5, 0x2f, 0xfd, 0][..]).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
@@ -0,0 +1,71 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::is_result_token;
+use std::str::FromStr;
+
+/// The game at the given FEN, or at the initial position if None.
+fn start(start_fen: Option<&str>) -> Result<Game, String> {
+    Ok(start_fen.map_or_else(|| Ok(Game::new()), Game::from_str)?)
+}
+
+/// Convert a sequence of moves in SAN into UCI notation, starting from the given FEN or the
+/// initial position. Result tokens like "1-0" are skipped.
+pub fn san_to_uci<S: AsRef<str>>(
+    start_fen: Option<&str>,
+    sans: &[S],
+) -> Result<Vec<String>, String> {
+    let mut game = start(start_fen)?;
+    sans.iter()
+        .map(|san| san.as_ref())
+        .filter(|san| !is_result_token(san))
+        .enumerate()
+        .map(|(i, san)| {
+            game.play_move(san).map_err(at_ply(i + 1))?;
+            Ok(game.uci.clone())
+        })
+        .collect()
+}
+
+/// Convert a sequence of moves in UCI notation into SAN, including check and checkmate markers,
+/// starting from the given FEN or the initial position.
+pub fn uci_to_san<S: AsRef<str>>(
+    start_fen: Option<&str>,
+    ucis: &[S],
+) -> Result<Vec<String>, String> {
+    let mut game = start(start_fen)?;
+    ucis.iter()
+        .map(|uci| uci.as_ref())
+        .enumerate()
+        .map(|(i, uci)| {
+            let san = game.uci_to_san(uci).map_err(at_ply(i + 1))?;
+            game.play_uci(uci).map_err(at_ply(i + 1))?;
+            Ok(san)
+        })
+        .collect()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_round_trip() {
+    let sans = ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "O-O", "Nf6", "1-0"];
+    let ucis = san_to_uci(None, &sans).unwrap();
+    assert_eq!(
+        ucis,
+        vec!["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "e1g1", "g8f6"]
+    );
+    assert_eq!(uci_to_san(None, &ucis).unwrap(), sans[..8]);
+}
+
+#[test]
+fn check_conversion_from_fen() {
+    let fen = Some("4k3/P7/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(
+        uci_to_san(fen, &["a7a8q", "e8d7", "e1g1"]).unwrap(),
+        vec!["a8=Q+", "Kd7", "O-O"]
+    );
+    assert_eq!(san_to_uci(fen, &["a8=N"]).unwrap(), vec!["a7a8n"]);
+    assert_eq!(
+        uci_to_san(None, &["e2e4", "e2e4"]),
+        Err("ply 2: illegal move: e2e4".to_string())
+    );
+    assert!(san_to_uci(Some("not a fen"), &["e4"]).is_err());
+}
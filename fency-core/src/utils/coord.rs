@@ -0,0 +1,243 @@
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Coord {
+    pub file: char,
+    pub rank: char,
+
+    // use signed integers to enable comparisons to other coords via subtraction.
+    pub x: i8,
+    pub y: i8,
+    pub idx: i8,
+
+    pub anti_diagonal: i8,
+    pub main_diagonal: i8,
+}
+
+// Traits
+pub trait FromIndex {
+    fn from_idx(idx: i8) -> Self;
+}
+
+// Implementations
+impl Coord {
+    /// Derive a coordinate from its file (x) and rank (y), both counting from 0.
+    pub const fn from_xy(x: i8, y: i8) -> Self {
+        assert!((0 <= x) && (x < 8) && (0 <= y) && (y < 8));
+        Coord {
+            file: (b'a' + x as u8) as char,
+            rank: (b'1' + y as u8) as char,
+            x,
+            y,
+            idx: Coord::idx_of(x, y),
+            anti_diagonal: x + y,
+            main_diagonal: 7 + y - x,
+        }
+    }
+
+    /// Index of the square with the given file and rank, indented to match the FEN.
+    pub const fn idx_of(x: i8, y: i8) -> i8 {
+        x + 8 * (7 - y)
+    }
+
+    /// Check whether a string describes a square, as `Coord::from` panics on broken inputs.
+    pub fn is_valid(field: &str) -> bool {
+        let mut chars = field.chars();
+        matches!(
+            (chars.next(), chars.next(), chars.next()),
+            (Some('a'..='h'), Some('1'..='8'), None)
+        )
+    }
+
+    /// The square shifted by the given file and rank deltas, if still on the board. Using both
+    /// axes rules out wraparounds, e.g. from the h-file to the a-file of the next rank.
+    pub fn shifted(&self, dx: i8, dy: i8) -> Option<Coord> {
+        let (x, y) = (self.x + dx, self.y + dy);
+        ((0..8).contains(&x) && (0..8).contains(&y)).then(|| BOARD[Coord::idx_of(x, y) as usize])
+    }
+
+    /// Number of king steps to the other square, e.g. 1 for adjacent squares.
+    pub fn distance(&self, other: &Coord) -> i8 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// Whether both squares share a diagonal of either direction.
+    pub fn same_diagonal(&self, other: &Coord) -> bool {
+        (self.main_diagonal == other.main_diagonal) || (self.anti_diagonal == other.anti_diagonal)
+    }
+
+    /// The file and rank deltas of a single step towards the other square, if it's on the same
+    /// file, rank or diagonal. None for the square itself.
+    pub fn direction(&self, other: &Coord) -> Option<(i8, i8)> {
+        let (dx, dy) = (other.x - self.x, other.y - self.y);
+        let is_aligned = (dx == 0) || (dy == 0) || self.same_diagonal(other);
+        (is_aligned && self != other).then_some((dx.signum(), dy.signum()))
+    }
+
+    /// The squares strictly between both squares on their common ray, ordered from this square.
+    /// Empty for adjacent or unaligned squares.
+    pub fn between(&self, other: &Coord) -> Vec<Coord> {
+        let Some((dx, dy)) = self.direction(other) else {
+            return Vec::new();
+        };
+        (1..self.distance(other))
+            .filter_map(|f| self.shifted(f * dx, f * dy))
+            .collect()
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file, self.rank)
+    }
+}
+
+impl From<&str> for Coord {
+    fn from(field: &str) -> Self {
+        // use early assertions to safeguard against broken inputs.
+        assert_eq!(field.len(), 2);
+
+        // if there are at least two characters, assign those as file and rank.
+        let file = field.chars().next().unwrap();
+        let rank = field.chars().nth(1).unwrap();
+
+        // derive coordinate system and check validity of the chars by their integer bounds.
+        let x: i8 = file as i8 - 'a' as i8;
+        let y: i8 = rank as i8 - '1' as i8;
+        assert!((0..8).contains(&x) & (0..8).contains(&y));
+
+        BOARD[Coord::idx_of(x, y) as usize]
+    }
+}
+
+impl FromIndex for Coord {
+    fn from_idx(idx: i8) -> Self {
+        BOARD[idx as usize]
+    }
+}
+
+/// All 64 squares, indexed from a8 to h1 as in the FEN. The table is built at compile time and
+/// shared by all games, such that looking up a square by its index is free.
+pub static BOARD: [Coord; 64] = build_board();
+
+const fn build_board() -> [Coord; 64] {
+    let mut board = [Coord::from_xy(0, 0); 64];
+    let mut idx = 0;
+    while idx < 64 {
+        board[idx as usize] = Coord::from_xy(idx % 8, 7 - idx / 8);
+        idx += 1;
+    }
+    board
+}
+
+//-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-_-
+
+#[test]
+fn check_x() {
+    assert_eq!(0, Coord::from("a1").x);
+    assert_eq!(4, Coord::from("e1").x);
+    assert_eq!(4, Coord::from("e4").x);
+    assert_eq!(7, Coord::from("h7").x);
+}
+
+#[test]
+fn check_y() {
+    assert_eq!(0, Coord::from("a1").y);
+    assert_eq!(0, Coord::from("e1").y);
+    assert_eq!(3, Coord::from("e4").y);
+    assert_eq!(6, Coord::from("h7").y);
+}
+
+#[test]
+fn check_idx() {
+    assert_eq!(0, Coord::from("a8").idx);
+    assert_eq!(4, Coord::from("e8").idx);
+    assert_eq!(28, Coord::from("e5").idx);
+    assert_eq!(63, Coord::from("h1").idx);
+}
+
+#[test]
+fn check_anti_diagonal() {
+    assert_eq!(0, Coord::from("a1").anti_diagonal);
+    assert_eq!(4, Coord::from("e1").anti_diagonal);
+    assert_eq!(7, Coord::from("e4").anti_diagonal);
+    assert_eq!(13, Coord::from("h7").anti_diagonal);
+}
+
+#[test]
+fn check_main_diagonal() {
+    assert_eq!(7, Coord::from("a1").main_diagonal);
+    assert_eq!(3, Coord::from("e1").main_diagonal);
+    assert_eq!(6, Coord::from("e4").main_diagonal);
+    assert_eq!(6, Coord::from("h7").main_diagonal);
+}
+
+#[test]
+fn check_identity() {
+    assert_eq!(Coord::from("a1"), Coord::from("a1"));
+    assert_eq!(Coord::from("b3"), Coord::from("b3"));
+    assert_eq!(Coord::from("h8"), Coord::from("h8"));
+}
+
+#[test]
+#[should_panic]
+fn check_illegal_coords_pt1() {
+    let _ = Coord::from("a9");
+}
+
+#[test]
+#[should_panic]
+fn check_illegal_coords_pt2() {
+    let _ = Coord::from("i1");
+}
+
+#[test]
+#[should_panic]
+fn check_illegal_coords_pt3() {
+    let _ = Coord::from("1a");
+}
+
+#[test]
+fn check_idx_conversion() {
+    assert_eq!(Coord::from("a1"), Coord::from_idx(Coord::from("a1").idx));
+    assert_eq!(Coord::from("h8"), Coord::from_idx(Coord::from("h8").idx));
+    assert_eq!(Coord::from("e3"), Coord::from_idx(Coord::from("e3").idx));
+}
+
+#[test]
+fn check_board() {
+    assert_eq!(BOARD.len(), 64);
+    for (idx, coord) in BOARD.iter().enumerate() {
+        assert_eq!(coord.idx as usize, idx);
+        assert_eq!(Coord::from(&coord.to_string()[..]), *coord);
+    }
+    assert_eq!(BOARD[0], Coord::from("a8"));
+    assert_eq!(BOARD[63], Coord::from("h1"));
+}
+
+#[test]
+fn check_validity() {
+    assert!(Coord::is_valid("a1"));
+    assert!(Coord::is_valid("h8"));
+    assert!(!Coord::is_valid("i1"));
+    assert!(!Coord::is_valid("a9"));
+    assert!(!Coord::is_valid("a10"));
+    assert!(!Coord::is_valid(""));
+}
+
+#[test]
+fn check_geometry() {
+    let (c1, f4, h6) = (Coord::from("c1"), Coord::from("f4"), Coord::from("h6"));
+    assert_eq!(c1.distance(&h6), 5);
+    assert!(c1.same_diagonal(&h6) && !c1.same_diagonal(&Coord::from("c3")));
+    assert_eq!(c1.direction(&h6), Some((1, 1)));
+    assert_eq!(h6.direction(&Coord::from("h1")), Some((0, -1)));
+    assert_eq!(c1.direction(&Coord::from("d3")), None);
+    assert_eq!(c1.direction(&c1), None);
+
+    let squares: Vec<String> = c1.between(&h6).iter().map(|c| c.to_string()).collect();
+    assert_eq!(squares, vec!["d2", "e3", "f4", "g5"]);
+    assert_eq!(f4.between(&Coord::from("g5")), vec![]);
+    assert_eq!(Coord::from("h1").shifted(1, 0), None);
+    assert_eq!(Coord::from("a8").shifted(1, -1), Some(Coord::from("b7")));
+}
@@ -0,0 +1,129 @@
+use crate::utils::coord::{Coord, BOARD};
+use crate::utils::figure::Figure;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::is_result_token;
+
+/// A square whose content changed by a ply, e.g. the source square of a move is left empty.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SquareChange {
+    pub square: Coord,
+    pub before: Option<Figure>,
+    pub after: Option<Figure>,
+}
+
+/// All changed squares of a single ply. Those are the source and target square of a move, plus
+/// the square of a pawn captured en passant or the squares of the rook in case of castling.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlyDiff {
+    pub ply: usize,
+    pub uci: String,
+    pub changes: Vec<SquareChange>,
+}
+
+/// Compare two positions square by square, ordered from a8 to h1 (as in the FEN).
+pub fn position_diff(before: &Game, after: &Game) -> Vec<SquareChange> {
+    BOARD
+        .iter()
+        .zip(before.position.iter().zip(after.position.iter()))
+        .filter(|(_, (b, a))| b != a)
+        .map(|(&square, (&before, &after))| SquareChange {
+            square,
+            before,
+            after,
+        })
+        .collect()
+}
+
+impl Game {
+    /// Whether both games are in the same position, with the same side to move, castling rights and
+    /// en passant square. Unlike `==`, the move clocks are ignored.
+    pub fn eq_position(&self, other: &Game) -> bool {
+        (self.position == other.position)
+            && (self.color == other.color)
+            && (self.castling == other.castling)
+            && (self.en_passant == other.en_passant)
+    }
+
+    /// The squares whose content differs in the other game, see `position_diff`.
+    pub fn diff(&self, other: &Game) -> Vec<SquareChange> {
+        position_diff(self, other)
+    }
+}
+
+/// Replay a game and collect the changed squares of every ply. Result tokens are skipped.
+pub fn ply_diffs(moves: &[&str]) -> Result<Vec<PlyDiff>, String> {
+    let mut game = Game::new();
+    moves
+        .iter()
+        .filter(|mv| !is_result_token(mv))
+        .enumerate()
+        .map(|(i, mv)| {
+            let before = game.clone();
+            game.play_move(mv).map_err(at_ply(i + 1))?;
+            Ok(PlyDiff {
+                ply: i + 1,
+                uci: game.uci.clone(),
+                changes: position_diff(&before, &game),
+            })
+        })
+        .collect()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+fn changed_squares(diff: &PlyDiff) -> Vec<String> {
+    diff.changes.iter().map(|c| c.square.to_string()).collect()
+}
+
+#[test]
+fn check_ply_diffs() {
+    let diffs = ply_diffs(&[
+        "e4", "d5", "exd5", "Nf6", "Bb5+", "c6", "Nf3", "cxb5", "O-O",
+    ])
+    .unwrap();
+
+    assert_eq!(diffs.len(), 9);
+    assert_eq!(changed_squares(&diffs[0]), vec!["e4", "e2"]);
+    assert_eq!(diffs[0].changes[0].before, None);
+    assert_eq!(diffs[0].changes[0].after, Some(Figure::from("Pe4")));
+    assert_eq!(diffs[0].changes[1].after, None);
+
+    // A capture changes the content of the target square.
+    assert_eq!(changed_squares(&diffs[2]), vec!["d5", "e4"]);
+    assert_eq!(diffs[2].changes[0].before, Some(Figure::from("pd5")));
+
+    // Castling moves the king and the rook.
+    assert_eq!(diffs[8].uci, "e1g1");
+    assert_eq!(changed_squares(&diffs[8]), vec!["e1", "f1", "g1", "h1"]);
+}
+
+#[test]
+fn check_en_passant_diff() {
+    let diffs = ply_diffs(&["e4", "a6", "e5", "d5", "exd6"]).unwrap();
+    assert_eq!(changed_squares(&diffs[4]), vec!["d6", "d5", "e5"]);
+}
+
+#[test]
+fn check_position_equality() {
+    use std::str::FromStr;
+
+    let mut replayed = Game::new();
+    for mv in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+        replayed.play_move(mv).unwrap();
+    }
+    assert!(replayed.eq_position(&Game::new()));
+    assert_ne!(replayed, Game::new());
+
+    let engine =
+        Game::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert!(!engine.eq_position(&Game::new()));
+    let changes = Game::new().diff(&engine);
+    let squares: Vec<String> = changes.iter().map(|c| c.square.to_string()).collect();
+    assert_eq!(squares, vec!["e4", "e2"]);
+    assert_eq!(changes[1].before, Some(Figure::from("Pe2")));
+
+    // The side to move differs, but the squares do not.
+    let mut black = engine.clone();
+    black.color = black.color.next();
+    assert!(!engine.eq_position(&black) && engine.diff(&black).is_empty());
+}
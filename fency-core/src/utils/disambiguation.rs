@@ -0,0 +1,79 @@
+use std::cell::Cell;
+
+/// Number of SAN moves resolved by each stage of the search for the moving figure, e.g. to check
+/// that pins are only analyzed if several candidates reach the target. Counted per thread, thus
+/// tests see their own moves only.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DisambiguationStats {
+    /// Only one figure of the moving piece and color is on the board.
+    pub by_piece: u64,
+
+    /// The file or rank of the SAN, e.g. "Nbd7", leaves one candidate.
+    pub by_remainder: u64,
+
+    /// One candidate reaches the target.
+    pub by_moves: u64,
+
+    /// Several candidates reach the target, thus the pins are analyzed.
+    pub by_pins: u64,
+
+    /// No candidate reaches the target, which is rejected without analyzing pins.
+    pub unreachable: u64,
+}
+
+thread_local! {
+    static STATS: Cell<DisambiguationStats> = Cell::new(DisambiguationStats::default());
+}
+
+/// The counters of the current thread since it started or since the last reset.
+pub fn disambiguation_stats() -> DisambiguationStats {
+    STATS.with(Cell::get)
+}
+
+pub fn reset_disambiguation_stats() {
+    STATS.with(|stats| stats.set(DisambiguationStats::default()));
+}
+
+/// Count a move for the stage selected by the function, which is also traced by its name.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn count(name: &'static str, stage: fn(&mut DisambiguationStats) -> &mut u64) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(stage = name, "disambiguated");
+    STATS.with(|stats| {
+        let mut counts = stats.get();
+        *stage(&mut counts) += 1;
+        stats.set(counts);
+    });
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_disambiguation_stats() {
+    use crate::utils::game::Game;
+    use std::str::FromStr;
+
+    reset_disambiguation_stats();
+    let mut game = Game::new();
+    for mv in ["Nf3", "d5", "Nc3", "Nf6", "Nd4", "Nbd7"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        disambiguation_stats(),
+        DisambiguationStats {
+            by_piece: 0,
+            by_remainder: 1,
+            by_moves: 5,
+            by_pins: 0,
+            unreachable: 0
+        }
+    );
+    assert!(game.play_move("Ne5").is_err());
+    assert_eq!(disambiguation_stats().unreachable, 1);
+
+    // Both knights reach b3, but the one on d2 is pinned by the bishop.
+    reset_disambiguation_stats();
+    let mut game = Game::from_str("4k3/8/8/b7/8/8/3N4/N3K3 w - - 0 1").unwrap();
+    game.play_move("Nb3").unwrap();
+    assert_eq!(game.uci, "a1b3");
+    assert_eq!(disambiguation_stats().by_pins, 1);
+}
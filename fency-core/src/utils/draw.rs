@@ -0,0 +1,496 @@
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::piece::Piece;
+use std::str::FromStr;
+
+// Letters and figurines of the moving piece and of the promoted piece in a SAN. Pawns are not
+// written, but the figurines allow for figurine algebraic notation (FAN), e.g. "♘f3" or "♙e4".
+const PIECE_CHARS: &str = "NBRQK♔♕♖♗♘♙♚♛♜♝♞♟";
+const PROMOTION_CHARS: &str = "NBRQK♔♕♖♗♘♚♛♜♝♞";
+
+fn is_file(c: char) -> bool {
+    ('a'..='h').contains(&c)
+}
+
+fn is_rank(c: char) -> bool {
+    ('1'..='8').contains(&c)
+}
+
+/// The parts of a SAN, which is castling excluded, laid out as
+/// `[piece][file][rank][x|-]target[=][promotion][+|#]`. The dash allows for long algebraic
+/// notation (LAN) as well, e.g. "Ng1-f3" or "e7-e8=Q".
+struct SanParts<'a> {
+    piece: Option<char>,
+    remainder_file: Option<char>,
+    remainder_rank: Option<char>,
+    is_dash: bool,
+    target: &'a str,
+    promotes_to: Option<char>,
+}
+
+/// Decompose a SAN from its end, where target, promotion and check are fixed, and then the
+/// disambiguation from its start. None if anything is left over.
+fn split_san(san: &str) -> Option<SanParts<'_>> {
+    let mut rest = san.strip_suffix(['+', '#']).unwrap_or(san);
+    let promotes_to = rest
+        .chars()
+        .next_back()
+        .filter(|&c| PROMOTION_CHARS.contains(c));
+    if let Some(c) = promotes_to {
+        rest = &rest[..rest.len() - c.len_utf8()];
+    }
+    rest = rest.strip_suffix('=').unwrap_or(rest);
+
+    let split = rest.len().checked_sub(2)?;
+    if !rest.is_char_boundary(split) {
+        return None;
+    }
+    let (prefix, target) = rest.split_at(split);
+    let mut target_chars = target.chars();
+    if !(target_chars.next().is_some_and(is_file) && target_chars.next().is_some_and(is_rank)) {
+        return None;
+    }
+
+    let mut chars = prefix.chars().peekable();
+    let piece = chars.next_if(|&c| PIECE_CHARS.contains(c));
+    let remainder_file = chars.next_if(|&c| is_file(c));
+    let remainder_rank = chars.next_if(|&c| is_rank(c));
+    let separator = chars.next_if(|&c| (c == 'x') || (c == '-'));
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(SanParts {
+        piece,
+        remainder_file,
+        remainder_rank,
+        is_dash: separator == Some('-'),
+        target,
+        promotes_to,
+    })
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Draw {
+    pub san: String,
+    pub target: Coord,
+    pub piece: Piece,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_promo: bool,
+    pub is_hit: bool,
+    pub promoted_piece: Option<Piece>,
+    pub remainder_file: Option<char>,
+    pub remainder_rank: Option<char>,
+    /// Move quality glyph that was attached to the SAN, e.g. "!?" or "??".
+    pub annotation: Option<String>,
+    /// Whether the SAN carried an explicit en passant marker, e.g. "exd6 e.p.".
+    pub is_marked_en_passant: bool,
+}
+
+/// Characters that may appear within scraped SAN tokens without any meaning, e.g. zero-width
+/// spaces or non-breaking spaces.
+pub fn is_invisible(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
+}
+
+/// Split a raw SAN token into the plain SAN, its move quality glyph and whether it carried an en
+/// passant marker. Unicode glyphs (e.g. '⁉') are normalized to their ASCII form (e.g. "!?").
+pub fn clean_san(raw: &str) -> (String, Option<String>, bool) {
+    let mut san = String::with_capacity(raw.len());
+    for c in raw.chars().filter(|&c| !is_invisible(c)) {
+        match c {
+            '‼' => san.push_str("!!"),
+            '⁇' => san.push_str("??"),
+            '⁉' => san.push_str("!?"),
+            '⁈' => san.push_str("?!"),
+            c => san.push(c),
+        }
+    }
+
+    // Glyphs and markers may appear in any order, thus strip them until nothing changes.
+    let mut glyph = String::new();
+    let mut is_marked_en_passant = false;
+    loop {
+        if let Some(stripped) = san.strip_suffix("e.p.").or(san.strip_suffix("ep")) {
+            san = stripped.to_string();
+            is_marked_en_passant = true;
+        } else if san.ends_with(['!', '?']) {
+            glyph.insert(0, san.pop().unwrap());
+        } else {
+            break;
+        }
+    }
+    let annotation = if glyph.is_empty() { None } else { Some(glyph) };
+
+    (san, annotation, is_marked_en_passant)
+}
+
+/// Convert a SAN into figurine algebraic notation, e.g. "Nf3" into "♘f3", using the figurines of
+/// the moving color. Files are lowercase, thus only the uppercase letters denote pieces.
+pub fn to_figurine_san(san: &str, color: Color) -> String {
+    san.chars()
+        .map(|c| match c {
+            'N' | 'B' | 'R' | 'Q' | 'K' => Piece::from(c).to_figurine(color),
+            c => c,
+        })
+        .collect()
+}
+
+impl FromStr for Draw {
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        // Strip annotations first, as those are not part of the SAN itself.
+        let (san, annotation, is_marked_en_passant) = clean_san(raw);
+        let san = &san[..];
+
+        let parts = split_san(san).ok_or(format!("invalid SAN: {}", raw))?;
+
+        // A dash is only valid in LAN, where the full source square is given.
+        if parts.is_dash && (parts.remainder_file.is_none() || parts.remainder_rank.is_none()) {
+            return Err(format!("invalid LAN: {}", raw));
+        }
+
+        // Pawns promote exactly when reaching the last rank, but never to a king.
+        let is_pawn = parts.piece.is_none();
+        let promotes_to = parts.promotes_to.map(Piece::from);
+        let is_last_rank = parts.target.ends_with(['1', '8']);
+        if (is_pawn && is_last_rank) != promotes_to.is_some() || promotes_to == Some(Piece::K) {
+            return Err(format!("invalid promotion: {}", raw));
+        }
+
+        Ok(Draw {
+            san: san.to_string(),
+
+            is_check: san.contains('+') | san.contains('#'),
+            is_checkmate: san.contains('#'),
+            is_promo: promotes_to.is_some(),
+            is_hit: san.contains('x'),
+
+            target: Coord::from(parts.target),
+            piece: parts.piece.map_or(Piece::P, Piece::from),
+            promoted_piece: promotes_to,
+            remainder_file: parts.remainder_file,
+            remainder_rank: parts.remainder_rank,
+            annotation,
+            is_marked_en_passant,
+        })
+    }
+
+    type Err = String;
+}
+
+/// Castling given in SAN, which is excluded from `Draw`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CastlingSan {
+    pub is_long: bool,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+}
+
+impl CastlingSan {
+    /// Recognize castling written with the letter O or with digit zeros as some sources do, e.g.
+    /// "0-0-0", followed by check or mate symbols and glyphs. None for any other move.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let san = clean_san(raw).0;
+        let plain = san.trim_end_matches(['+', '#']);
+        let is_long = match plain {
+            "O-O" | "0-0" => false,
+            "O-O-O" | "0-0-0" => true,
+            _ => return None,
+        };
+        Some(CastlingSan {
+            is_long,
+            is_check: plain.len() < san.len(),
+            is_checkmate: san.ends_with('#'),
+        })
+    }
+
+    /// The SAN without symbols in the letter spelling, e.g. "O-O-O".
+    pub fn san(&self) -> &'static str {
+        if self.is_long {
+            "O-O-O"
+        } else {
+            "O-O"
+        }
+    }
+}
+
+/// A figure put on the board from the hand in SAN of drop variants, e.g. "N@f3" or "@e4".
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DropSan {
+    pub piece: Piece,
+    pub target: Coord,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+}
+
+impl FromStr for DropSan {
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let san = clean_san(raw).0;
+        let invalid = || format!("invalid drop: {}", raw);
+
+        // Drops as in crazyhouse, where pawns are written with or without the letter.
+        let rest = san.strip_suffix(['+', '#']).unwrap_or(&san);
+        let (piece, target) = rest.split_once('@').ok_or_else(invalid)?;
+        let piece = match piece {
+            "" | "P" => Piece::P,
+            "N" | "B" | "R" | "Q" => Piece::from(piece.chars().next().unwrap()),
+            _ => return Err(invalid()),
+        };
+        if !Coord::is_valid(target) {
+            return Err(invalid());
+        }
+        let target = Coord::from(target);
+
+        // Pawns are never dropped to the first or last rank.
+        if (piece == Piece::P) && ((target.y == 0) || (target.y == 7)) {
+            return Err(invalid());
+        }
+        Ok(DropSan {
+            piece,
+            target,
+            is_check: rest.len() < san.len(),
+            is_checkmate: san.ends_with('#'),
+        })
+    }
+
+    type Err = String;
+}
+
+/// A move token of the movetext decomposed by its kind, such that callers match on the variant
+/// instead of looking at the string.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SanMove {
+    Normal(Draw),
+    Castle(CastlingSan),
+    Drop(DropSan),
+    /// The null move "--", also written "Z0" or "0000", which passes the turn in analysis.
+    Null,
+}
+
+impl SanMove {
+    /// Whether the move gives check as far as written, e.g. "Nf3+" or "O-O#".
+    pub fn is_check(&self) -> bool {
+        match self {
+            SanMove::Normal(draw) => draw.is_check,
+            SanMove::Castle(castling) => castling.is_check,
+            SanMove::Drop(drop) => drop.is_check,
+            SanMove::Null => false,
+        }
+    }
+
+    /// Whether the move is marked as checkmate, e.g. "Qxf7#".
+    pub fn is_checkmate(&self) -> bool {
+        match self {
+            SanMove::Normal(draw) => draw.is_checkmate,
+            SanMove::Castle(castling) => castling.is_checkmate,
+            SanMove::Drop(drop) => drop.is_checkmate,
+            SanMove::Null => false,
+        }
+    }
+}
+
+impl FromStr for SanMove {
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        if let Some(castling) = CastlingSan::parse(raw) {
+            return Ok(SanMove::Castle(castling));
+        }
+        let san = clean_san(raw).0;
+        if matches!(&san[..], "--" | "Z0" | "0000") {
+            return Ok(SanMove::Null);
+        }
+        if san.contains('@') {
+            return DropSan::from_str(raw).map(SanMove::Drop);
+        }
+        Draw::from_str(raw).map(SanMove::Normal)
+    }
+
+    type Err = String;
+}
+
+#[test]
+fn check_draw_from_san_pt1() {
+    let draw = Draw::from_str("a3").unwrap();
+
+    assert_eq!(draw.target, Coord::from("a3"));
+    assert_eq!(draw.piece, Piece::P);
+    assert!(!draw.is_check);
+    assert!(!draw.is_checkmate);
+    assert!(!draw.is_promo);
+    assert!(!draw.is_hit);
+    assert_eq!(draw.promoted_piece, None);
+    assert_eq!(draw.remainder_file, None);
+    assert_eq!(draw.remainder_rank, None);
+}
+
+#[test]
+fn check_draw_from_san_pt2() {
+    let draw = Draw::from_str("exd1=Q#").unwrap();
+
+    assert_eq!(draw.target, Coord::from("d1"));
+    assert_eq!(draw.piece, Piece::P);
+    assert!(draw.is_check);
+    assert!(draw.is_checkmate);
+    assert!(draw.is_promo);
+    assert!(draw.is_hit);
+    assert_eq!(draw.promoted_piece, Some(Piece::Q));
+    assert_eq!(draw.remainder_file, Some('e'));
+    assert_eq!(draw.remainder_rank, None);
+}
+
+#[test]
+fn check_draw_from_san_pt3() {
+    let draw = Draw::from_str("Raxc6+").unwrap();
+
+    assert_eq!(draw.target, Coord::from("c6"));
+    assert_eq!(draw.piece, Piece::R);
+    assert!(draw.is_check);
+    assert!(!draw.is_checkmate);
+    assert!(!draw.is_promo);
+    assert!(draw.is_hit);
+    assert_eq!(draw.promoted_piece, None);
+    assert_eq!(draw.remainder_file, Some('a'));
+    assert_eq!(draw.remainder_rank, None);
+}
+
+#[test]
+fn check_draw_from_san_pt4() {
+    let draw = Draw::from_str("N1c3").unwrap();
+
+    assert_eq!(draw.target, Coord::from("c3"));
+    assert_eq!(draw.piece, Piece::N);
+    assert!(!draw.is_check);
+    assert!(!draw.is_checkmate);
+    assert!(!draw.is_promo);
+    assert!(!draw.is_hit);
+    assert_eq!(draw.promoted_piece, None);
+    assert_eq!(draw.remainder_file, None);
+    assert_eq!(draw.remainder_rank, Some('1'));
+}
+
+#[test]
+fn check_draw_from_lan() {
+    let draw = Draw::from_str("Ng1-f3").unwrap();
+    assert_eq!(draw.target, Coord::from("f3"));
+    assert_eq!(draw.piece, Piece::N);
+    assert!(!draw.is_hit);
+    assert_eq!(draw.remainder_file, Some('g'));
+    assert_eq!(draw.remainder_rank, Some('1'));
+
+    let draw = Draw::from_str("e7-e8=Q+").unwrap();
+    assert_eq!(draw.target, Coord::from("e8"));
+    assert_eq!(draw.piece, Piece::P);
+    assert!(draw.is_check);
+    assert_eq!(draw.promoted_piece, Some(Piece::Q));
+    assert_eq!(draw.remainder_file, Some('e'));
+    assert_eq!(draw.remainder_rank, Some('7'));
+
+    let draw = Draw::from_str("Qd1xh5").unwrap();
+    assert!(draw.is_hit);
+    assert_eq!(draw.remainder_rank, Some('1'));
+
+    assert!(Draw::from_str("N-f3").is_err());
+    assert!(Draw::from_str("g-f3").is_err());
+}
+
+#[test]
+fn check_draw_from_fan() {
+    let draw = Draw::from_str("♘f3").unwrap();
+    assert_eq!(draw.target, Coord::from("f3"));
+    assert_eq!(draw.piece, Piece::N);
+
+    let draw = Draw::from_str("♛xd1+").unwrap();
+    assert_eq!(draw.piece, Piece::Q);
+    assert!(draw.is_hit);
+
+    let draw = Draw::from_str("exd8=♕#").unwrap();
+    assert_eq!(draw.piece, Piece::P);
+    assert_eq!(draw.promoted_piece, Some(Piece::Q));
+}
+
+#[test]
+fn check_to_figurine_san() {
+    assert_eq!(to_figurine_san("Nbd7", Color::B), "♞bd7");
+    assert_eq!(to_figurine_san("exd8=Q#", Color::W), "exd8=♕#");
+    assert_eq!(to_figurine_san("O-O", Color::W), "O-O");
+}
+
+#[test]
+fn check_draw_with_annotations() {
+    let draw = Draw::from_str("Nxf7!?").unwrap();
+    assert_eq!(draw.san, "Nxf7");
+    assert_eq!(draw.target, Coord::from("f7"));
+    assert_eq!(draw.annotation, Some("!?".to_string()));
+
+    let draw = Draw::from_str("Qh4#⁉").unwrap();
+    assert!(draw.is_checkmate);
+    assert_eq!(draw.annotation, Some("!?".to_string()));
+
+    let draw = Draw::from_str("\u{200b}exd6\u{a0}e.p.!!").unwrap();
+    assert_eq!(draw.san, "exd6");
+    assert!(draw.is_marked_en_passant);
+    assert_eq!(draw.annotation, Some("!!".to_string()));
+
+    let draw = Draw::from_str("e4??").unwrap();
+    assert_eq!(draw.annotation, Some("??".to_string()));
+    assert!(!draw.is_marked_en_passant);
+}
+
+#[test]
+fn check_invalid_draws_are_errors() {
+    assert!(Draw::from_str("").is_err());
+    assert!(Draw::from_str("Zz9").is_err());
+    assert!(Draw::from_str("e4 junk").is_err());
+    assert!(Draw::from_str("!?").is_err());
+}
+
+#[test]
+fn check_draw_from_promotion() {
+    let draw = Draw::from_str("gxh1=N+").unwrap();
+    assert!(draw.is_promo);
+    assert!(draw.is_hit);
+    assert!(draw.is_check);
+    assert_eq!(draw.remainder_file, Some('g'));
+    assert_eq!(draw.promoted_piece, Some(Piece::N));
+
+    // The equal sign is optional.
+    let draw = Draw::from_str("b8R").unwrap();
+    assert!(draw.is_promo);
+    assert_eq!(draw.promoted_piece, Some(Piece::R));
+
+    for san in ["e8", "axb1", "e6=Q", "Ne8=Q", "e8=K"] {
+        assert!(Draw::from_str(san).is_err(), "{}", san);
+    }
+}
+
+#[test]
+fn check_castling_san() {
+    let castling = CastlingSan::parse("0-0-0+").unwrap();
+    assert!(castling.is_long && castling.is_check && !castling.is_checkmate);
+    assert_eq!(castling.san(), "O-O-O");
+
+    let castling = CastlingSan::parse("0-0#!").unwrap();
+    assert!(!castling.is_long && castling.is_checkmate);
+    assert_eq!(CastlingSan::parse("O-O").map(|c| c.is_check), Some(false));
+    for san in ["O-0", "O-O-O-O", "Ke1-g1", "0-1"] {
+        assert_eq!(CastlingSan::parse(san), None, "{}", san);
+    }
+}
+
+#[test]
+fn check_san_move() {
+    assert!(
+        matches!(SanMove::from_str("Nf3"), Ok(SanMove::Normal(draw)) if draw.piece == Piece::N)
+    );
+    assert!(matches!(SanMove::from_str("0-0-0+"), Ok(SanMove::Castle(c)) if c.is_long));
+    assert_eq!(SanMove::from_str("--"), Ok(SanMove::Null));
+    assert_eq!(SanMove::from_str("Z0"), Ok(SanMove::Null));
+
+    let drop = DropSan::from_str("N@f7+").unwrap();
+    assert_eq!((drop.piece, drop.target), (Piece::N, Coord::from("f7")));
+    assert!(drop.is_check && !drop.is_checkmate);
+    assert_eq!(SanMove::from_str("@e4"), SanMove::from_str("P@e4"));
+    assert!(SanMove::from_str("Q@h7#").unwrap().is_check());
+    for san in ["P@e8", "@a1", "K@e4", "N@", "Nx@f3"] {
+        assert!(SanMove::from_str(san).is_err(), "{}", san);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::utils::color::Color;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::material::piece_value;
+use crate::utils::pgn::PgnReader;
+use crate::utils::piece::Piece;
+use std::io::BufRead;
+
+/// Material of both sides together in pawns, at or below which a position counts as an endgame,
+/// e.g. a queen against a rook.
+pub const ENDGAME_MATERIAL: i16 = 14;
+
+/// Pieces in the order of material signatures, as in the names of Syzygy tables.
+const SIGNATURE_ORDER: [Piece; 6] = [Piece::K, Piece::Q, Piece::R, Piece::B, Piece::N, Piece::P];
+
+/// The pieces of one side in signature order, e.g. "KRP".
+fn side_signature(pieces: impl Iterator<Item = Piece>) -> String {
+    let mut pieces: Vec<Piece> = pieces.collect();
+    pieces.sort_by_key(|p| SIGNATURE_ORDER.iter().position(|o| o == p));
+    pieces.iter().map(|p| p.to_char(Color::W)).collect()
+}
+
+/// Join the signatures of both sides, the stronger side first. Sides of equal material are
+/// ordered by their pieces, e.g. "KBvKN".
+fn join_sides(a: String, b: String) -> String {
+    let strength = |side: &str| {
+        let pieces: Vec<Piece> = side.chars().map(Piece::from).collect();
+        let material: i16 = pieces.iter().map(|&p| piece_value(p)).sum();
+        let ranks: Vec<usize> = pieces
+            .iter()
+            .map(|p| SIGNATURE_ORDER.len() - SIGNATURE_ORDER.iter().position(|o| o == p).unwrap())
+            .collect();
+        (material, ranks)
+    };
+    if strength(&a) >= strength(&b) {
+        format!("{}v{}", a, b)
+    } else {
+        format!("{}v{}", b, a)
+    }
+}
+
+/// Bring a material signature into its canonical form, e.g. "kvkr" into "KRvK".
+pub fn canonical_class(class: &str) -> Result<String, String> {
+    let invalid = || format!("invalid endgame class: {}", class);
+    let (a, b) = class.split_once(['v', 'V']).ok_or_else(invalid)?;
+    let side = |s: &str| {
+        let s = s.to_uppercase();
+        let is_valid =
+            s.chars().filter(|&c| c == 'K').count() == 1 && s.chars().all(|c| "KQRBNP".contains(c));
+        is_valid
+            .then(|| side_signature(s.chars().map(Piece::from)))
+            .ok_or_else(invalid)
+    };
+    Ok(join_sides(side(a)?, side(b)?))
+}
+
+impl Game {
+    /// The material on the board as signature, e.g. "KRPvKR", where the stronger side comes first
+    /// regardless of its color.
+    pub fn material_signature(&self) -> String {
+        let side = |color: Color| {
+            side_signature(
+                self.figures
+                    .iter()
+                    .filter(|f| f.color() == color)
+                    .map(|f| f.piece()),
+            )
+        };
+        join_sides(side(Color::W), side(Color::B))
+    }
+
+    /// The material signature once the material of both sides is at most `ENDGAME_MATERIAL`.
+    pub fn endgame_class(&self) -> Option<String> {
+        self.endgame_class_with(ENDGAME_MATERIAL)
+    }
+
+    /// The material signature once the material of both sides is at most the given one in pawns.
+    pub fn endgame_class_with(&self, max_material: i16) -> Option<String> {
+        let material: i16 = self.figures.iter().map(|f| piece_value(f.piece())).sum();
+        (material <= max_material).then(|| self.material_signature())
+    }
+}
+
+/// A position of a dataset with a given material signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClassPosition {
+    /// Index of the game in the dataset.
+    pub game: usize,
+
+    /// Number of plies played before the position.
+    pub ply: usize,
+    pub fen: String,
+}
+
+/// All positions of the games whose material signature matches the class, e.g. "KRPvKR". The
+/// class may be given in any order and case, see `canonical_class`.
+pub fn positions_of_class<R: BufRead>(
+    games: PgnReader<R>,
+    class: &str,
+) -> Result<Vec<ClassPosition>, String> {
+    let class = canonical_class(class)?;
+    let mut positions = Vec::new();
+    for (g, game) in games.enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        let mut board = game
+            .start_position()
+            .map_err(|e| format!("game {}, {}", g, e))?;
+        for ply in 0..=game.moves.len() {
+            if ply > 0 {
+                board
+                    .play_move(&game.moves[ply - 1])
+                    .map_err(|e| format!("game {}, {}", g, at_ply(ply)(e)))?;
+            }
+            if board.material_signature() == class {
+                positions.push(ClassPosition {
+                    game: g,
+                    ply,
+                    fen: board.clone().to_fen(),
+                });
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_endgame_class() {
+    use std::str::FromStr;
+
+    assert_eq!(Game::new().endgame_class(), None);
+    assert_eq!(
+        Game::new().material_signature(),
+        "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"
+    );
+
+    let game = Game::from_str("8/8/4k3/8/2r5/8/3PK3/5R2 w - - 0 50").unwrap();
+    assert_eq!(game.endgame_class(), Some("KRPvKR".to_string()));
+    assert_eq!(game.endgame_class_with(10), None);
+
+    // The stronger side comes first, whatever its color.
+    let game = Game::from_str("8/8/4k3/8/2q5/8/4K3/5R2 w - - 0 50").unwrap();
+    assert_eq!(game.endgame_class(), Some("KQvKR".to_string()));
+    let game = Game::from_str("8/8/4k3/8/2n5/8/4K3/5B2 b - - 0 50").unwrap();
+    assert_eq!(game.endgame_class(), Some("KBvKN".to_string()));
+
+    assert_eq!(canonical_class("kvkr"), Ok("KRvK".to_string()));
+    assert_eq!(canonical_class("KPRvKR"), Ok("KRPvKR".to_string()));
+    assert!(canonical_class("KRK").is_err());
+    assert!(canonical_class("RvK").is_err());
+}
+
+#[test]
+fn check_positions_of_class() {
+    let pgn = "[SetUp \"1\"]\n[FEN \"8/8/4k3/8/8/8/3PK3/5R2 w - - 0 50\"]\n\n\
+               50. Rf3 Kd5 51. Rd3+ Ke5 *\n\n\
+               [Result \"*\"]\n\n1. e4 e5 *\n";
+    let positions = positions_of_class(PgnReader::new(pgn.as_bytes()), "kvkrp").unwrap();
+    assert_eq!(positions.len(), 5);
+    assert_eq!((positions[4].game, positions[4].ply), (0, 4));
+    assert_eq!(positions[4].fen, "8/8/8/4k3/8/3R4/3PK3/8 w - - 4 52");
+    assert!(positions_of_class(PgnReader::new(pgn.as_bytes()), "KQvK")
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,271 @@
+use crate::utils::game::Game;
+use crate::utils::lichess::Eval;
+use crate::utils::replay::replay_plies;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// How long the engine searches each position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SearchLimit {
+    Depth(u32),
+    /// Time per position in milliseconds.
+    MoveTime(u32),
+    Nodes(u64),
+}
+
+/// How to start and configure a UCI engine, e.g. Stockfish.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EngineConfig {
+    pub path: String,
+    pub args: Vec<String>,
+
+    /// UCI options to set after startup, e.g. ("Threads", "4") or ("Hash", "256").
+    pub options: Vec<(String, String)>,
+    pub limit: SearchLimit,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            path: "stockfish".to_string(),
+            args: Vec::new(),
+            options: Vec::new(),
+            limit: SearchLimit::Depth(12),
+        }
+    }
+}
+
+/// Result of the search of a single position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Analysis {
+    /// Best move in UCI notation, or None if the side to move has no legal moves.
+    pub best_move: Option<String>,
+
+    /// Evaluation of the deepest search from the point of view of white.
+    pub eval: Option<Eval>,
+    pub depth: Option<u32>,
+}
+
+/// A running UCI engine process, which is asked to quit when dropped.
+pub struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    limit: SearchLimit,
+}
+
+/// Replies that do not follow the UCI protocol make the engine unusable.
+fn protocol_error(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Engine {
+    /// Start the engine, perform the UCI handshake and set the options.
+    pub fn spawn(config: &EngineConfig) -> io::Result<Self> {
+        let mut child = Command::new(&config.path)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(protocol_error("engine has no stdin or stdout".to_string()));
+        };
+        let mut engine = Engine {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            limit: config.limit,
+        };
+
+        engine.send("uci")?;
+        engine.read_until("uciok")?;
+        for (name, value) in &config.options {
+            engine.send(&format!("setoption name {} value {}", name, value))?;
+        }
+        engine.send("isready")?;
+        engine.read_until("readyok")?;
+
+        Ok(engine)
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    /// Read lines until one starts with the given token, which is returned.
+    fn read_until(&mut self, token: &str) -> io::Result<String> {
+        self.read_lines_until(token, |_| {})
+    }
+
+    fn read_lines_until(&mut self, token: &str, mut f: impl FnMut(&str)) -> io::Result<String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("engine quit while waiting for {}", token),
+                ));
+            }
+            let trimmed = line.trim();
+            if trimmed.split_whitespace().next() == Some(token) {
+                return Ok(trimmed.to_string());
+            }
+            f(trimmed);
+        }
+    }
+
+    /// Search a position and return the best move and the evaluation. The engine gets the moves
+    /// that led to the position, such that it can take repetitions into account.
+    pub fn analyse(&mut self, game: &Game) -> io::Result<Analysis> {
+        let go = match self.limit {
+            SearchLimit::Depth(depth) => format!("go depth {}", depth),
+            SearchLimit::MoveTime(ms) => format!("go movetime {}", ms),
+            SearchLimit::Nodes(nodes) => format!("go nodes {}", nodes),
+        };
+        self.send(&game.uci_position())?;
+        self.send(&go)?;
+
+        let mut analysis = Analysis {
+            best_move: None,
+            eval: None,
+            depth: None,
+        };
+        let factor = i32::from(game.color.factor());
+        let bestmove = self.read_lines_until("bestmove", |line| {
+            let Some(info) = line.strip_prefix("info ") else {
+                return;
+            };
+            let (depth, score) = parse_info(info);
+            if let Some(score) = score {
+                analysis.eval = Some(match score {
+                    Eval::Cp(cp) => Eval::Cp(factor * cp),
+                    Eval::Mate(n) => Eval::Mate(factor * n),
+                });
+                analysis.depth = depth.or(analysis.depth);
+            }
+        })?;
+        analysis.best_move = bestmove
+            .split_whitespace()
+            .nth(1)
+            .filter(|mv| *mv != "(none)" && *mv != "0000")
+            .map(|mv| mv.to_string());
+
+        Ok(analysis)
+    }
+
+    /// Analyse the initial position and the position after every ply of a game. Result tokens are
+    /// skipped.
+    pub fn analyse_game(&mut self, moves: &[&str]) -> io::Result<Vec<Analysis>> {
+        self.send("ucinewgame")?;
+        let mut plies = replay_plies(Game::new(), moves);
+        let mut analyses = vec![self.analyse(plies.game())?];
+        while let Some((_, _, game)) = plies
+            .next_ply()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        {
+            analyses.push(self.analyse(game)?);
+        }
+
+        Ok(analyses)
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        if self.send("quit").is_err() || self.child.wait().is_err() {
+            let _ = self.child.kill();
+        }
+    }
+}
+
+/// Depth and score of an info line like "depth 20 seldepth 30 score cp 35 nodes 1000 pv e2e4",
+/// where the score is from the point of view of the side to move.
+fn parse_info(info: &str) -> (Option<u32>, Option<Eval>) {
+    let tokens: Vec<&str> = info.split_whitespace().collect();
+    let value = |key: &str| {
+        let pos = tokens.iter().position(|&t| t == key)?;
+        tokens.get(pos + 1).copied()
+    };
+    let depth = value("depth").and_then(|d| d.parse().ok());
+    let score = match value("score") {
+        Some("cp") => value("cp").and_then(|cp| cp.parse().ok()).map(Eval::Cp),
+        Some("mate") => value("mate").and_then(|n| n.parse().ok()).map(Eval::Mate),
+        _ => None,
+    };
+
+    (depth, score)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+/// A fake engine, which evaluates every position as mate in 2 for the side to move.
+#[allow(dead_code)]
+const FAKE_ENGINE: &str = r#"#!/bin/sh
+while read -r line; do
+    case "$line" in
+        uci) echo "id name fake"; echo "uciok" ;;
+        isready) echo "readyok" ;;
+        "go depth 3")
+            echo "info depth 1 score cp 20 pv e2e4"
+            echo "info depth 3 score mate 2 pv e2e4"
+            echo "bestmove e2e4 ponder e7e5" ;;
+        go*) echo "bestmove (none)" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#;
+
+#[cfg(unix)]
+#[test]
+fn check_fake_engine() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("fency-fake-engine-{}", std::process::id()));
+    std::fs::write(&path, FAKE_ENGINE).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let config = EngineConfig {
+        path: path.to_string_lossy().to_string(),
+        limit: SearchLimit::Depth(3),
+        ..EngineConfig::default()
+    };
+
+    let mut engine = Engine::spawn(&config).unwrap();
+    let analyses = engine.analyse_game(&["e4", "1-0"]).unwrap();
+    assert_eq!(analyses.len(), 2);
+    assert_eq!(analyses[0].best_move.as_deref(), Some("e2e4"));
+    assert_eq!(analyses[0].eval, Some(Eval::Mate(2)));
+    assert_eq!(analyses[0].depth, Some(3));
+    assert_eq!(analyses[1].eval, Some(Eval::Mate(-2)));
+    assert!(engine.analyse_game(&["Ke2"]).is_err());
+    drop(engine);
+
+    let mut engine = Engine::spawn(&EngineConfig {
+        limit: SearchLimit::MoveTime(10),
+        ..config
+    })
+    .unwrap();
+    let analysis = engine.analyse(&Game::new()).unwrap();
+    assert_eq!(analysis.best_move, None);
+    assert_eq!(analysis.eval, None);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn check_parse_info() {
+    assert_eq!(
+        parse_info("depth 20 seldepth 31 multipv 1 score cp -35 lowerbound nodes 10 pv e2e4"),
+        (Some(20), Some(Eval::Cp(-35)))
+    );
+    assert_eq!(
+        parse_info("depth 5 score mate -1"),
+        (Some(5), Some(Eval::Mate(-1)))
+    );
+    assert_eq!(parse_info("string NNUE enabled"), (None, None));
+    assert!(Engine::spawn(&EngineConfig {
+        path: "/nonexistent/engine".to_string(),
+        ..EngineConfig::default()
+    })
+    .is_err());
+}
@@ -0,0 +1,107 @@
+use std::fmt::{Display, Formatter};
+
+/// Error of the stable API, i.e. of the types re-exported at the crate root. The variant tells
+/// the cause, and the message describes it as the `utils` modules do, e.g. "illegal move: Ke3".
+/// Further variants may be added, thus matches need a wildcard arm.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A FEN that cannot be read, e.g. with a wrong number of fields, or an invalid FEN tag.
+    InvalidFen(String),
+
+    /// A move that cannot be parsed or is not playable in the position.
+    IllegalMove(String),
+
+    /// A move clock that would exceed its maximum with another ply.
+    ClockOverflow(String),
+
+    /// An edit of the position that is rejected, e.g. a second king of a color.
+    InvalidEdit(String),
+
+    /// A ply beyond the plies played so far.
+    PlyOutOfRange(String),
+
+    /// A token other than the result tokens "1-0", "0-1", "1/2-1/2" and "*".
+    InvalidResult(String),
+
+    /// A game that is longer than the maximum number of plies to replay.
+    TooManyPlies(String),
+
+    /// An option that is not supported, e.g. a variant other than standard chess.
+    Unsupported(String),
+}
+
+impl Error {
+    /// Short name of the variant, e.g. "illegal_move", for reports and bindings.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::InvalidFen(_) => "invalid_fen",
+            Error::IllegalMove(_) => "illegal_move",
+            Error::ClockOverflow(_) => "clock_overflow",
+            Error::InvalidEdit(_) => "invalid_edit",
+            Error::PlyOutOfRange(_) => "ply_out_of_range",
+            Error::InvalidResult(_) => "invalid_result",
+            Error::TooManyPlies(_) => "too_many_plies",
+            Error::Unsupported(_) => "unsupported",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::InvalidFen(message)
+            | Error::IllegalMove(message)
+            | Error::ClockOverflow(message)
+            | Error::InvalidEdit(message)
+            | Error::PlyOutOfRange(message)
+            | Error::InvalidResult(message)
+            | Error::TooManyPlies(message)
+            | Error::Unsupported(message) => message,
+        }
+    }
+
+    /// The same error with its message prefixed by where it occurred, e.g. "ply 3".
+    pub(crate) fn located(self, location: impl Display) -> Self {
+        let locate = |message: String| format!("{}: {}", location, message);
+        match self {
+            Error::InvalidFen(message) => Error::InvalidFen(locate(message)),
+            Error::IllegalMove(message) => Error::IllegalMove(locate(message)),
+            Error::ClockOverflow(message) => Error::ClockOverflow(locate(message)),
+            Error::InvalidEdit(message) => Error::InvalidEdit(locate(message)),
+            Error::PlyOutOfRange(message) => Error::PlyOutOfRange(locate(message)),
+            Error::InvalidResult(message) => Error::InvalidResult(locate(message)),
+            Error::TooManyPlies(message) => Error::TooManyPlies(locate(message)),
+            Error::Unsupported(message) => Error::Unsupported(locate(message)),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+// The `utils` modules report errors as messages, which thus take errors of the stable API by `?`.
+impl From<Error> for String {
+    fn from(error: Error) -> Self {
+        error.to_string()
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_error_kinds() {
+    let error = Error::IllegalMove("illegal move: Ke3".to_string());
+    assert_eq!(error.kind(), "illegal_move");
+    assert_eq!(error.to_string(), "illegal move: Ke3");
+    assert_eq!(
+        error.located("ply 3"),
+        Error::IllegalMove("ply 3: illegal move: Ke3".to_string())
+    );
+    assert_eq!(
+        String::from(Error::InvalidFen("invalid color: x".to_string())),
+        "invalid color: x"
+    );
+}
@@ -0,0 +1,210 @@
+use crate::utils::figure::Figure;
+use crate::utils::game::{Game, KNIGHT_STEPS};
+use crate::utils::material::MaterialEvaluator;
+use crate::utils::phase::GamePhase;
+use crate::utils::piece::Piece;
+use crate::utils::rays::{ray, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use crate::utils::replay::replay_plies;
+
+/// Centipawns per square that a knight, bishop, rook or queen can move to.
+const MOBILITY_WEIGHT: i32 = 4;
+
+// Piece-square tables in centipawns for white, ordered like the board from a8 to h1, thus black
+// looks up the square mirrored by rank. The values are those of the simplified evaluation
+// function by Tomasz Michniewski.
+#[rustfmt::skip]
+const PAWN_TABLE: [i8; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i8; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i8; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i8; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i8; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+/// The king shelters behind its pawns until the endgame.
+#[rustfmt::skip]
+const KING_TABLE: [i8; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+/// In the endgame, the king heads for the center.
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i8; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// Bonus of a figure for its square from the point of view of its color.
+fn square_bonus(fig: &Figure, is_endgame: bool) -> i32 {
+    let table = match fig.piece() {
+        Piece::P => &PAWN_TABLE,
+        Piece::N => &KNIGHT_TABLE,
+        Piece::B => &BISHOP_TABLE,
+        Piece::R => &ROOK_TABLE,
+        Piece::Q => &QUEEN_TABLE,
+        Piece::K if is_endgame => &KING_ENDGAME_TABLE,
+        Piece::K => &KING_TABLE,
+    };
+    let square = if fig.color().is_white() {
+        fig.square()
+    } else {
+        fig.square() ^ 56
+    };
+    i32::from(table[square])
+}
+
+/// Number of empty or opposing squares a knight, bishop, rook or queen reaches, ignoring pins and
+/// checks, which is much cheaper than counting legal moves.
+fn pseudo_mobility(game: &Game, fig: &Figure) -> i32 {
+    let is_free = |idx: usize| game.position[idx].is_none_or(|f| f.color() != fig.color());
+    let directions = match fig.piece() {
+        Piece::N => {
+            let reached = KNIGHT_STEPS
+                .iter()
+                .filter_map(|&(dx, dy)| fig.coord().shifted(dx, dy))
+                .filter(|square| is_free(square.idx as usize));
+            return reached.count() as i32;
+        }
+        Piece::B => BISHOP_DIRECTIONS,
+        Piece::R => ROOK_DIRECTIONS,
+        Piece::Q => 0..8,
+        Piece::P | Piece::K => return 0,
+    };
+    let mut reached = 0;
+    for d in directions {
+        for &idx in ray(&fig.coord(), d) {
+            let idx = idx as usize;
+            reached += i32::from(is_free(idx));
+            if game.position[idx].is_some() {
+                break;
+            }
+        }
+    }
+    reached
+}
+
+impl Game {
+    /// Rough classical evaluation in centipawns from the point of view of white: the material by
+    /// `MaterialEvaluator`, a bonus per figure for its square and 4 centipawns per square that a
+    /// knight, bishop, rook or queen can move to. Mates and stalemates are not recognized, thus
+    /// this is meant to filter positions by a rough score, not to judge them.
+    pub fn evaluate(&self) -> i32 {
+        let is_endgame = self.phase() == GamePhase::Endgame;
+        let positional: i32 = self
+            .figures
+            .iter()
+            .map(|fig| {
+                let score =
+                    square_bonus(fig, is_endgame) + MOBILITY_WEIGHT * pseudo_mobility(self, fig);
+                score * i32::from(fig.color().factor())
+            })
+            .sum();
+        MaterialEvaluator::default().evaluate(self) + positional
+    }
+
+    /// The evaluation from the point of view of the side to move, see `evaluate`.
+    pub fn evaluate_relative(&self) -> i32 {
+        self.evaluate() * i32::from(self.color.factor())
+    }
+}
+
+/// Replay moves in SAN and return the evaluation after every ply, see `Game::evaluate`. Result
+/// tokens like "1-0" are skipped.
+pub fn evaluations(moves: &[&str]) -> Result<Vec<i32>, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut scores = Vec::with_capacity(moves.len());
+    while let Some((_, _, game)) = plies.next_ply()? {
+        scores.push(game.evaluate());
+    }
+
+    Ok(scores)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_evaluate() {
+    use std::str::FromStr;
+
+    assert_eq!(Game::new().evaluate(), 0);
+    let scores = evaluations(&["e4", "e5", "Nf3", "Nc6", "1-0"]).unwrap();
+    assert_eq!(scores.len(), 4);
+    // The knight on f3 beats the knight on g1, and white is better developed than black.
+    assert!(scores[2] > scores[1]);
+    assert!(scores[2] > 0);
+
+    // Mirrored positions score the same for the other side.
+    let white = Game::from_str("4k3/8/8/8/3P4/2N5/8/4K3 w - - 0 1").unwrap();
+    let black = Game::from_str("4k3/8/2n5/3p4/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(white.evaluate(), -black.evaluate());
+    assert_eq!(white.evaluate_relative(), black.evaluate_relative());
+    assert!(white.evaluate() > 300);
+
+    // A queen up is clearly winning, whatever the squares.
+    let game = Game::from_str("q3k3/8/8/8/8/8/PPP5/1K1Q3Q b - - 0 1").unwrap();
+    assert!(game.evaluate() > 500);
+    assert!(evaluations(&["e4", "e4"]).unwrap_err().starts_with("ply 2"));
+}
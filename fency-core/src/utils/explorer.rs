@@ -0,0 +1,253 @@
+use crate::utils::filter::GameFilter;
+use crate::utils::game::at_ply;
+use crate::utils::json::json_str;
+use crate::utils::labels::declared_result;
+use crate::utils::outcome::GameResult;
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::replay::replay_plies;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// How games are grouped into openings.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OpeningKey {
+    /// The ECO tag, where games without it count as "?".
+    Eco,
+    /// The first plies in SAN as derived from the position, e.g. "e4 c5 Nf3". Shorter games are
+    /// grouped by all of their moves.
+    Plies(usize),
+}
+
+/// Number of games of an opening and their results, as seen from white.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct OpeningStats {
+    pub games: usize,
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+    pub undecided: usize,
+}
+
+impl OpeningStats {
+    fn add(&mut self, result: Option<GameResult>) {
+        self.games += 1;
+        match result {
+            Some(GameResult::WhiteWins) => self.white_wins += 1,
+            Some(GameResult::Draw) => self.draws += 1,
+            Some(GameResult::BlackWins) => self.black_wins += 1,
+            Some(GameResult::Undecided) | None => self.undecided += 1,
+        }
+    }
+
+    /// Percentage of points scored by white in the decided games, where a draw counts half, or
+    /// None if no game is decided.
+    pub fn white_score(&self) -> Option<f64> {
+        let decided = self.games - self.undecided;
+        let points = self.white_wins as f64 + self.draws as f64 / 2.0;
+        (decided > 0).then(|| 100.0 * points / decided as f64)
+    }
+}
+
+/// Columns of the CSV table.
+pub const OPENING_COLUMNS: [&str; 7] = [
+    "opening",
+    "games",
+    "white_wins",
+    "draws",
+    "black_wins",
+    "undecided",
+    "white_score",
+];
+
+/// Counts and scores per opening over many games, i.e. the table of an opening explorer. Unlike
+/// `PositionTree`, transpositions into the same position count as different openings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpeningReport {
+    pub key: OpeningKey,
+    pub openings: HashMap<String, OpeningStats>,
+}
+
+impl OpeningReport {
+    pub fn new(key: OpeningKey) -> Self {
+        OpeningReport {
+            key,
+            openings: HashMap::new(),
+        }
+    }
+
+    /// The opening of a game. Only the first plies are replayed, which fails on an invalid move.
+    /// Games set up from a position have no opening and count as "?".
+    pub fn opening_of(&self, game: &PgnGame) -> Result<String, String> {
+        let OpeningKey::Plies(plies) = self.key else {
+            return Ok(game.header("ECO").unwrap_or("?").to_string());
+        };
+        if game.header("FEN").is_some() {
+            return Ok("?".to_string());
+        }
+        let mut replay = replay_plies(game.start_position()?, &game.moves);
+        let mut sans = Vec::with_capacity(plies);
+        while replay.ply() < plies {
+            let Some((ply, _, before, after)) = replay.next_pair()? else {
+                break;
+            };
+            sans.push(before.uci_to_san(&after.uci).map_err(at_ply(ply))?);
+        }
+        Ok(sans.join(" "))
+    }
+
+    /// Count a game with the result of its Result tag or termination marker.
+    pub fn add(&mut self, game: &PgnGame) -> Result<(), String> {
+        let opening = self.opening_of(game)?;
+        self.openings
+            .entry(opening)
+            .or_default()
+            .add(declared_result(game));
+        Ok(())
+    }
+
+    /// The openings, the most popular first, ties ordered by name.
+    pub fn rows(&self) -> Vec<(&str, OpeningStats)> {
+        let mut rows: Vec<(&str, OpeningStats)> = self
+            .openings
+            .iter()
+            .map(|(opening, &stats)| (opening.as_str(), stats))
+            .collect();
+        rows.sort_by(|a, b| b.1.games.cmp(&a.1.games).then(a.0.cmp(b.0)));
+        rows
+    }
+
+    /// The table as CSV with a header line, see `rows`. The score of an opening without decided
+    /// games is left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = OPENING_COLUMNS.join(",") + "\n";
+        for (opening, stats) in self.rows() {
+            let score = stats
+                .white_score()
+                .map_or(String::new(), |s| format!("{:.1}", s));
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                opening,
+                stats.games,
+                stats.white_wins,
+                stats.draws,
+                stats.black_wins,
+                stats.undecided,
+                score
+            ));
+        }
+        csv
+    }
+
+    /// The table as JSON array of objects with the keys of `OPENING_COLUMNS`, see `rows`.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows()
+            .iter()
+            .map(|(opening, stats)| {
+                let score = stats
+                    .white_score()
+                    .map_or("null".to_string(), |s| format!("{:.1}", s));
+                format!(
+                    "{{\"opening\":{},\"games\":{},\"white_wins\":{},\"draws\":{},\
+                     \"black_wins\":{},\"undecided\":{},\"white_score\":{}}}",
+                    json_str(opening),
+                    stats.games,
+                    stats.white_wins,
+                    stats.draws,
+                    stats.black_wins,
+                    stats.undecided,
+                    score
+                )
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+}
+
+/// Build the opening report of a PGN stream, counting only the games that match the filter, e.g.
+/// a rating band by `min_elo` and `max_elo` or a time control. Games are numbered from 0 in
+/// errors, counting only the matching ones.
+pub fn opening_report<R: BufRead>(
+    source: R,
+    key: OpeningKey,
+    filter: GameFilter,
+) -> Result<OpeningReport, String> {
+    let mut report = OpeningReport::new(key);
+    for (g, game) in PgnReader::with_filter(source, filter).enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        report
+            .add(&game)
+            .map_err(|e| format!("game {}, {}", g, e))?;
+    }
+    Ok(report)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const EXPLORER_GAMES: &str = "\
+[WhiteElo \"1500\"]\n[BlackElo \"1600\"]\n[ECO \"B20\"]\n[TimeControl \"180+2\"]\n\
+[Result \"1-0\"]\n\n1. e4 c5 2. Nf3 d6 1-0\n\n\
+[WhiteElo \"1550\"]\n[BlackElo \"1500\"]\n[ECO \"B20\"]\n[TimeControl \"180+2\"]\n\
+[Result \"1/2-1/2\"]\n\n1. e4 c5 2. Nc3 1/2-1/2\n\n\
+[WhiteElo \"2100\"]\n[BlackElo \"2200\"]\n[ECO \"D00\"]\n[TimeControl \"600+0\"]\n\
+[Result \"0-1\"]\n\n1. d4 d5 0-1\n\n\
+[WhiteElo \"1500\"]\n[BlackElo \"1500\"]\n[TimeControl \"180+2\"]\n[Result \"*\"]\n\n\
+1. e4 *\n";
+
+#[test]
+fn check_opening_report() {
+    let report = opening_report(
+        EXPLORER_GAMES.as_bytes(),
+        OpeningKey::Eco,
+        GameFilter::default(),
+    )
+    .unwrap();
+    let b20 = report.openings["B20"];
+    assert_eq!((b20.games, b20.white_wins, b20.draws), (2, 1, 1));
+    assert_eq!(b20.white_score(), Some(75.0));
+    assert_eq!(report.openings["?"].white_score(), None);
+    assert_eq!(
+        report.to_csv(),
+        "opening,games,white_wins,draws,black_wins,undecided,white_score\n\
+         B20,2,1,1,0,0,75.0\n\
+         ?,1,0,0,0,1,\n\
+         D00,1,0,0,1,0,0.0\n"
+    );
+
+    // Only blitz games below 2000, grouped by their first two plies.
+    let filter = GameFilter {
+        max_elo: Some(2000),
+        time_controls: vec!["180+2".to_string()],
+        ..GameFilter::default()
+    };
+    let report = opening_report(EXPLORER_GAMES.as_bytes(), OpeningKey::Plies(2), filter).unwrap();
+    assert_eq!(report.rows().len(), 2);
+    assert_eq!(report.rows()[0].0, "e4 c5");
+    assert_eq!(
+        report.to_json(),
+        "[{\"opening\":\"e4 c5\",\"games\":2,\"white_wins\":1,\"draws\":1,\"black_wins\":0,\
+         \"undecided\":0,\"white_score\":75.0},\
+         {\"opening\":\"e4\",\"games\":1,\"white_wins\":0,\"draws\":0,\"black_wins\":0,\
+         \"undecided\":1,\"white_score\":null}]"
+    );
+}
+
+#[test]
+fn check_opening_prefix() {
+    use crate::utils::pgn::parse_pgn;
+
+    // The SAN is derived from the position, thus differently written moves end up together.
+    let mut report = OpeningReport::new(OpeningKey::Plies(3));
+    let games = parse_pgn("1. e4 e5 2. Ng1f3 Nc6 1-0\n\n[Result \"*\"]\n\n1. e4 e5 2. Nf3 *\n");
+    for game in &games {
+        report.add(game).unwrap();
+    }
+    assert_eq!(report.rows(), [("e4 e5 Nf3", report.openings["e4 e5 Nf3"])]);
+    assert_eq!(report.openings["e4 e5 Nf3"].games, 2);
+
+    let games = parse_pgn("1. e4 Ke7 *\n");
+    assert_eq!(
+        report.add(&games[0]).unwrap_err(),
+        "ply 2: illegal move: Ke7"
+    );
+}
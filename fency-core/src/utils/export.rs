@@ -0,0 +1,332 @@
+use crate::utils::coord::Coord;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::json::{json_num, json_str};
+use crate::utils::outcome::{is_result_token, Status};
+use crate::utils::piece::Piece;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+type Evals = Vec<Option<f64>>;
+
+/// Everything that is known about a single ply after it has been played.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlyRecord {
+    pub ply: usize,
+    pub san: String,
+    pub uci: String,
+    pub fen: String,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_hit: bool,
+    pub is_promo: bool,
+    pub is_castling: bool,
+    pub half_move_clock: u16,
+    pub full_move_clock: u16,
+    pub eval: Option<f64>,
+
+    /// Number of legal moves of the side to move, if requested as it requires move generation.
+    pub mobility: Option<usize>,
+
+    /// Remaining time of the moving side and time spent on the move in seconds, if the game has
+    /// clock annotations, see `time_control::time_spent`.
+    pub clock: Option<u32>,
+    pub time_spent: Option<u32>,
+
+    /// UCI command with the full move history up to the ply, if requested, see
+    /// `Game::uci_position`.
+    pub position: Option<String>,
+}
+
+/// Names of all columns of a ply record in their default order.
+pub const COLUMNS: [&str; 12] = [
+    "ply",
+    "san",
+    "uci",
+    "fen",
+    "check",
+    "checkmate",
+    "capture",
+    "promotion",
+    "castling",
+    "half_move_clock",
+    "full_move_clock",
+    "eval",
+];
+
+/// Names of columns that are only derived on request.
+pub const OPTIONAL_COLUMNS: [&str; 4] = ["mobility", "clock", "time_spent", "position"];
+
+impl PlyRecord {
+    /// Record of the game right after the given move has been played as the given ply. The flags
+    /// are taken from the position rather than the SAN, which may lack markers like "+" or "x",
+    /// thus the caller tells whether the move captured, e.g. by a drop of the figure count.
+    pub(crate) fn new(
+        ply: usize,
+        mv: &str,
+        game: &Game,
+        is_capture: bool,
+        eval: Option<f64>,
+    ) -> Self {
+        let is_check = game.in_check();
+        let (src, tgt) = (Coord::from(&game.uci[..2]), Coord::from(&game.uci[2..4]));
+        let is_king = game.position[tgt.idx as usize].is_some_and(|f| f.piece() == Piece::K);
+        PlyRecord {
+            ply,
+            san: mv.to_string(),
+            uci: game.uci.clone(),
+            fen: game.clone().to_fen(),
+            is_check,
+            is_checkmate: is_check && (game.status() == Status::Checkmate),
+            is_hit: is_capture,
+            is_promo: game.uci.len() == 5,
+            is_castling: is_king && ((src.x - tgt.x).abs() == 2),
+            half_move_clock: game.half_move_clock,
+            full_move_clock: game.full_move_clock,
+            eval,
+            mobility: None,
+            clock: None,
+            time_spent: None,
+            position: None,
+        }
+    }
+
+    /// Textual value of a column, or None if the column is unknown. Missing evals are empty.
+    pub fn value(&self, column: &str) -> Option<String> {
+        Some(match column {
+            "ply" => self.ply.to_string(),
+            "san" => self.san.clone(),
+            "uci" => self.uci.clone(),
+            "fen" => self.fen.clone(),
+            "check" => self.is_check.to_string(),
+            "checkmate" => self.is_checkmate.to_string(),
+            "capture" => self.is_hit.to_string(),
+            "promotion" => self.is_promo.to_string(),
+            "castling" => self.is_castling.to_string(),
+            "half_move_clock" => self.half_move_clock.to_string(),
+            "full_move_clock" => self.full_move_clock.to_string(),
+            "eval" => self.eval.map(|e| e.to_string()).unwrap_or_default(),
+            "mobility" => self.mobility.map(|m| m.to_string()).unwrap_or_default(),
+            "clock" => self.clock.map(|c| c.to_string()).unwrap_or_default(),
+            "time_spent" => self.time_spent.map(|t| t.to_string()).unwrap_or_default(),
+            "position" => self.position.clone().unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    /// JSON representation of a column, i.e. strings are quoted while numbers and flags are not.
+    pub fn json_value(&self, column: &str) -> Option<String> {
+        match column {
+            "san" | "uci" | "fen" => self.value(column).map(|v| json_str(&v)),
+            "eval" => Some(json_num(self.eval)),
+            "mobility" => Some(json_num(self.mobility.map(|m| m as f64))),
+            "clock" => Some(json_num(self.clock.map(f64::from))),
+            "time_spent" => Some(json_num(self.time_spent.map(f64::from))),
+            "position" => Some(
+                self.position
+                    .as_deref()
+                    .map_or("null".to_string(), json_str),
+            ),
+            _ => self.value(column),
+        }
+    }
+
+    /// Render the given columns as a JSON object, prefixed with the index of the game.
+    pub fn to_json(&self, game_idx: usize, columns: &[&str]) -> String {
+        let mut fields: Vec<String> = vec![format!("\"game\":{}", game_idx)];
+        fields.extend(
+            columns
+                .iter()
+                .filter_map(|&c| Some(format!("\"{}\":{}", c, self.json_value(c)?))),
+        );
+
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Replay a game and collect one record per ply. Evals are optional and matched by ply, the
+/// optional columns "mobility" and "position" are only derived if requested. Result tokens like
+/// "1-0" are skipped.
+pub fn ply_records(
+    moves: &[&str],
+    evals: Option<&[Option<f64>]>,
+    optional: &[&str],
+) -> Result<Vec<PlyRecord>, String> {
+    ply_records_from(Game::new(), moves, evals, optional)
+}
+
+/// Collect the records like `ply_records`, but replay from the given position, e.g. the one of
+/// the FEN tag of a PGN.
+pub fn ply_records_from(
+    mut game: Game,
+    moves: &[&str],
+    evals: Option<&[Option<f64>]>,
+    optional: &[&str],
+) -> Result<Vec<PlyRecord>, String> {
+    let with_mobility = optional.contains(&"mobility");
+    let with_position = optional.contains(&"position");
+    moves
+        .iter()
+        .filter(|mv| !is_result_token(mv))
+        .enumerate()
+        .map(|(i, &mv)| {
+            let n_figures = game.figures.len();
+            game.play_move(mv).map_err(at_ply(i + 1))?;
+            let eval = evals.and_then(|e| e.get(i).copied().flatten());
+            let is_capture = game.figures.len() < n_figures;
+            let mut record = PlyRecord::new(i + 1, mv, &game, is_capture, eval);
+            if with_mobility {
+                record.mobility = Some(game.mobility(game.color));
+            }
+            if with_position {
+                record.position = Some(game.uci_position());
+            }
+            Ok(record)
+        })
+        .collect()
+}
+
+/// Moves that cannot be replayed make the input invalid.
+fn invalid_data(e: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+/// Write one JSON object per ply of the given game.
+pub fn write_plies<W: Write>(
+    writer: &mut W,
+    game_idx: usize,
+    moves: &[&str],
+    evals: Option<&[Option<f64>]>,
+) -> std::io::Result<()> {
+    for record in ply_records(moves, evals, &[]).map_err(invalid_data)? {
+        writeln!(writer, "{}", record.to_json(game_idx, &COLUMNS))?;
+    }
+    Ok(())
+}
+
+/// Write a single JSON object for the whole game, holding the per-ply values as arrays.
+pub fn write_game<W: Write>(
+    writer: &mut W,
+    game_idx: usize,
+    moves: &[&str],
+    evals: Option<&[Option<f64>]>,
+) -> std::io::Result<()> {
+    let records = ply_records(moves, evals, &[]).map_err(invalid_data)?;
+    let array = |f: &dyn Fn(&PlyRecord) -> String| {
+        format!("[{}]", records.iter().map(f).collect::<Vec<_>>().join(","))
+    };
+
+    writeln!(
+        writer,
+        "{{\"game\":{},\"plies\":{},\"san\":{},\"uci\":{},\"fen\":{},\"eval\":{}}}",
+        game_idx,
+        records.len(),
+        array(&|r| json_str(&r.san)),
+        array(&|r| json_str(&r.uci)),
+        array(&|r| json_str(&r.fen)),
+        array(&|r| json_num(r.eval)),
+    )
+}
+
+/// Export a batch of games as JSON Lines to the given path, either one line per ply or one line
+/// per game. Returns the number of lines written.
+pub fn to_jsonl(
+    path: &str,
+    games: &[Vec<&str>],
+    evals: Option<&[Evals]>,
+    per_game: bool,
+) -> std::io::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut n_lines = 0;
+    for (g, moves) in games.iter().enumerate() {
+        let game_evals = evals.and_then(|e| e.get(g)).map(|e| &e[..]);
+        if per_game {
+            write_game(&mut writer, g, moves, game_evals)?;
+            n_lines += 1;
+        } else {
+            write_plies(&mut writer, g, moves, game_evals)?;
+            n_lines += moves.iter().filter(|mv| !is_result_token(mv)).count();
+        }
+    }
+    writer.flush()?;
+
+    Ok(n_lines)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_ply_records() {
+    let records = ply_records(
+        &["e4", "d5", "exd5"],
+        Some(&[Some(0.3), None, Some(1.2)]),
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2].uci, "e4d5");
+    assert!(records[2].is_hit);
+    assert_eq!(records[1].eval, None);
+    assert_eq!(
+        records[2].to_json(7, &COLUMNS),
+        "{\"game\":7,\"ply\":3,\"san\":\"exd5\",\"uci\":\"e4d5\",\
+         \"fen\":\"rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2\",\
+         \"check\":false,\"checkmate\":false,\"capture\":true,\"promotion\":false,\
+         \"castling\":false,\"half_move_clock\":0,\"full_move_clock\":2,\"eval\":1.2}"
+    );
+}
+
+#[test]
+fn check_flags_without_markers() {
+    // The flags follow the position, even if the SAN lacks the markers of a capture or a mate.
+    let records = ply_records(
+        &["e4", "d5", "ed5", "e5", "f3", "Qh4", "g3", "Qg3"],
+        None,
+        &[],
+    )
+    .unwrap();
+    assert!(records[2].is_hit);
+    assert!(records[5].is_check);
+    assert!(!records[5].is_checkmate);
+    assert!(records[7].is_hit);
+    assert!(records[7].is_check);
+
+    let records = ply_records(&["f3", "e5", "g4", "Qh4"], None, &[]).unwrap();
+    assert!(records[3].is_check);
+    assert!(records[3].is_checkmate);
+    assert!(!records[3].is_hit);
+
+    let records = ply_records(&["e4", "d5", "exd5+"], None, &[]).unwrap();
+    assert!(!records[2].is_check);
+}
+
+#[test]
+fn check_write_game() {
+    let mut buffer: Vec<u8> = Vec::new();
+    write_game(&mut buffer, 0, &["e4", "e5"], None).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "{\"game\":0,\"plies\":2,\"san\":[\"e4\",\"e5\"],\"uci\":[\"e2e4\",\"e7e5\"],\
+         \"fen\":[\"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\",\
+         \"rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2\"],\"eval\":[null,null]}\n"
+    );
+}
+
+#[test]
+fn check_selected_columns() {
+    let records = ply_records(&["e4"], None, &OPTIONAL_COLUMNS).unwrap();
+    assert_eq!(
+        records[0].to_json(0, &["uci", "check", "eval", "unknown"]),
+        "{\"game\":0,\"uci\":\"e2e4\",\"check\":false,\"eval\":null}"
+    );
+    assert_eq!(records[0].value("eval"), Some("".to_string()));
+    assert_eq!(
+        records[0].to_json(0, &OPTIONAL_COLUMNS),
+        "{\"game\":0,\"mobility\":20,\"clock\":null,\"time_spent\":null,\
+         \"position\":\"position startpos moves e2e4\"}"
+    );
+    assert_eq!(
+        ply_records(&["e4"], None, &[]).unwrap()[0].value("mobility"),
+        Some("".to_string())
+    );
+}
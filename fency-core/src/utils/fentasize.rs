@@ -0,0 +1,448 @@
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::error::Error;
+use crate::utils::game::Game;
+use crate::utils::king_safety::KingSafety;
+use crate::utils::outcome::{is_result_token, Status};
+use crate::utils::phase::{GamePhase, PhaseScheme};
+use crate::utils::piece::Piece;
+use crate::utils::replay::{replay_plies, PlyReplay};
+use crate::utils::writer::numbered_san;
+use crate::utils::zobrist::{reduced_fen, zobrist_hash};
+use std::slice::Iter;
+use std::str::FromStr;
+
+/// What to derive while replaying moves, e.g.
+/// `FentasizeOptions::new().include_uci(true).reduced_fen(true)`. Everything beyond the FEN is
+/// off by default, as it costs time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FentasizeOptions {
+    pub include_uci: bool,
+    pub include_flags: bool,
+    pub include_hash: bool,
+    pub include_king_safety: bool,
+    pub include_phase: bool,
+
+    /// Pair each FEN with its move in display form, see `writer::numbered_san`.
+    pub include_move_label: bool,
+
+    /// How to classify the phase of a position, if included.
+    pub phase_scheme: PhaseScheme,
+
+    /// Write FENs without move clocks, see `zobrist::reduced_fen`.
+    pub reduced_fen: bool,
+
+    /// The position to start from instead of the initial one.
+    pub start_fen: Option<String>,
+
+    /// Chess variant, only "standard" is supported.
+    pub variant: String,
+
+    /// Reject castling without rights or through attacked squares, see `Game::play_move_lenient`.
+    pub strict: bool,
+
+    /// Reject games with more plies, e.g. corrupt or runaway engine games.
+    pub max_plies: Option<usize>,
+}
+
+impl Default for FentasizeOptions {
+    fn default() -> Self {
+        FentasizeOptions {
+            include_uci: false,
+            include_flags: false,
+            include_hash: false,
+            include_king_safety: false,
+            include_phase: false,
+            include_move_label: false,
+            phase_scheme: PhaseScheme::default(),
+            reduced_fen: false,
+            start_fen: None,
+            variant: "standard".to_string(),
+            strict: true,
+            max_plies: None,
+        }
+    }
+}
+
+/// Properties of a move, derived from the position rather than from the SAN.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MoveFlags {
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_capture: bool,
+    pub is_promotion: bool,
+    pub is_castling: bool,
+}
+
+/// The position after a ply, with the requested extras.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FenRecord {
+    pub ply: usize,
+
+    /// The move as given and the color that played it.
+    pub san: String,
+    pub color: Color,
+    pub fen: String,
+    pub uci: Option<String>,
+    pub flags: Option<MoveFlags>,
+    pub hash: Option<u64>,
+
+    /// King-safety features of both sides, white first, omitting colors without a king.
+    pub king_safety: Option<Vec<KingSafety>>,
+    pub phase: Option<GamePhase>,
+
+    /// The move that led to the position with its number, e.g. "12... Nf6".
+    pub move_label: Option<String>,
+}
+
+impl FentasizeOptions {
+    pub fn new() -> Self {
+        FentasizeOptions::default()
+    }
+
+    pub fn include_uci(mut self, include_uci: bool) -> Self {
+        self.include_uci = include_uci;
+        self
+    }
+
+    pub fn include_flags(mut self, include_flags: bool) -> Self {
+        self.include_flags = include_flags;
+        self
+    }
+
+    pub fn include_hash(mut self, include_hash: bool) -> Self {
+        self.include_hash = include_hash;
+        self
+    }
+
+    pub fn include_king_safety(mut self, include_king_safety: bool) -> Self {
+        self.include_king_safety = include_king_safety;
+        self
+    }
+
+    pub fn include_phase(mut self, include_phase: bool) -> Self {
+        self.include_phase = include_phase;
+        self
+    }
+
+    pub fn phase_scheme(mut self, scheme: PhaseScheme) -> Self {
+        self.phase_scheme = scheme;
+        self
+    }
+
+    pub fn include_move_label(mut self, include_move_label: bool) -> Self {
+        self.include_move_label = include_move_label;
+        self
+    }
+
+    pub fn reduced_fen(mut self, reduced_fen: bool) -> Self {
+        self.reduced_fen = reduced_fen;
+        self
+    }
+
+    pub fn start_fen(mut self, fen: impl Into<String>) -> Self {
+        self.start_fen = Some(fen.into());
+        self
+    }
+
+    pub fn variant(mut self, variant: impl Into<String>) -> Self {
+        self.variant = variant.into();
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn max_plies(mut self, max_plies: usize) -> Self {
+        self.max_plies = Some(max_plies);
+        self
+    }
+
+    /// Replay moves in SAN and return one record per ply. Result tokens like "1-0" are skipped.
+    pub fn fentasize(&self, moves: &[&str]) -> Result<Vec<FenRecord>, Error> {
+        let mut plies = self.replay(moves)?;
+        let mut records = Vec::with_capacity(moves.len());
+        loop {
+            let pieces_before = plies.game().figures.len();
+            let before = self.include_move_label.then(|| plies.game().clone());
+            let Some((ply, &mv, game)) = plies.next_ply()? else {
+                break;
+            };
+
+            // The SAN is derived from the position for the true markers, unless it is not
+            // derivable, e.g. for castling without rights in lenient mode.
+            let move_label = before.map(|before| {
+                let san = before.uci_to_san(&game.uci).unwrap_or(mv.to_string());
+                numbered_san(before.full_move_clock, before.color, &san)
+            });
+
+            let flags = self.include_flags.then(|| {
+                let is_check = game.in_check();
+                let src = Coord::from(&game.uci[..2]);
+                let tgt = Coord::from(&game.uci[2..4]);
+                let is_king =
+                    game.position[tgt.idx as usize].is_some_and(|f| f.piece() == Piece::K);
+                MoveFlags {
+                    is_check,
+                    is_checkmate: is_check && game.status() == Status::Checkmate,
+                    is_capture: game.figures.len() < pieces_before,
+                    is_promotion: game.uci.len() == 5,
+                    is_castling: is_king && (src.x - tgt.x).abs() == 2,
+                }
+            });
+            records.push(FenRecord {
+                ply,
+                san: mv.to_string(),
+                color: game.color.next(),
+                fen: if self.reduced_fen {
+                    reduced_fen(game)
+                } else {
+                    game.clone().to_fen()
+                },
+                uci: self.include_uci.then(|| game.uci.clone()),
+                flags,
+                hash: self.include_hash.then(|| zobrist_hash(game)),
+                king_safety: self.include_king_safety.then(|| {
+                    [Color::W, Color::B]
+                        .into_iter()
+                        .filter_map(|color| game.king_safety(color))
+                        .collect()
+                }),
+                phase: self.include_phase.then(|| self.phase_scheme.phase(game)),
+                move_label,
+            });
+        }
+
+        Ok(records)
+    }
+
+    /// Replay moves in SAN like `fentasize`, but only derive the FEN of the final position, e.g. to
+    /// verify results or to deduplicate games. Extras beyond the FEN are ignored.
+    pub fn final_fen(&self, moves: &[&str]) -> Result<String, Error> {
+        let game = self.replay(moves)?.finish()?;
+        Ok(if self.reduced_fen {
+            reduced_fen(&game)
+        } else {
+            game.to_fen()
+        })
+    }
+
+    /// Replay of the moves from the start position, which is strict and bounded as configured.
+    fn replay<'m>(&self, moves: &'m [&'m str]) -> Result<PlyReplay<Iter<'m, &'m str>>, Error> {
+        if !self.variant.eq_ignore_ascii_case("standard") {
+            return Err(Error::Unsupported(format!(
+                "unsupported variant: {}",
+                self.variant
+            )));
+        }
+        let game = match &self.start_fen {
+            Some(fen) => Game::from_str(fen)?,
+            None => Game::new(),
+        };
+        Ok(replay_plies(game, moves)
+            .lenient(!self.strict)
+            .max_plies(self.max_plies))
+    }
+
+    /// Replay a flat stream of several games, see `split_games`, and return the records per game.
+    /// Every game starts from the same position.
+    pub fn fentasize_games(&self, tokens: &[&str]) -> Result<Vec<Vec<FenRecord>>, Error> {
+        split_games(tokens)
+            .iter()
+            .enumerate()
+            .map(|(i, moves)| {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::debug_span!("replay_game", game = i).entered();
+                self.fentasize(moves)
+                    .map_err(|e| e.located(format!("game {}", i)))
+            })
+            .collect()
+    }
+}
+
+/// Split a flat stream of moves into games, which are separated by result tokens like "1-0" or
+/// by empty tokens. Consecutive separators do not make empty games.
+pub fn split_games<'a>(tokens: &[&'a str]) -> Vec<Vec<&'a str>> {
+    tokens
+        .split(|token| token.trim().is_empty() || is_result_token(token))
+        .filter(|game| !game.is_empty())
+        .map(|game| game.to_vec())
+        .collect()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_default_options() {
+    let records = FentasizeOptions::new()
+        .fentasize(&["e4", "e5", "1-0"])
+        .unwrap();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[1].fen,
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+    assert_eq!(
+        (records[1].uci.clone(), records[1].flags, records[1].hash),
+        (None, None, None)
+    );
+    assert_eq!(
+        (records[1].king_safety.clone(), records[1].phase),
+        (None, None)
+    );
+    assert_eq!(records[1].move_label, None);
+    assert_eq!(
+        (records[1].san.as_str(), records[1].color),
+        ("e5", Color::B)
+    );
+}
+
+#[test]
+fn check_all_options() {
+    let options = FentasizeOptions::new()
+        .include_uci(true)
+        .include_flags(true)
+        .include_hash(true)
+        .reduced_fen(true)
+        .start_fen("r3k2r/1P6/8/8/8/8/8/4K3 w kq - 0 1");
+    let records = options.fentasize(&["bxa8=Q+", "Ke7", "Qxh8"]).unwrap();
+
+    assert_eq!(records[0].uci.as_deref(), Some("b7a8q"));
+    let flags = records[0].flags.unwrap();
+    assert!(flags.is_capture && flags.is_promotion && flags.is_check && !flags.is_checkmate);
+    assert!(!records[1].flags.unwrap().is_capture);
+    assert_eq!(records[2].fen, "7Q/4k3/8/8/8/8/8/4K3 b - -");
+    assert_eq!(
+        records[2].hash,
+        Some(zobrist_hash(
+            &Game::from_str("7Q/4k3/8/8/8/8/8/4K3 b - - 0 2").unwrap()
+        ))
+    );
+
+    let castling = FentasizeOptions::new().include_flags(true);
+    let records = castling
+        .fentasize(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"])
+        .unwrap();
+    assert!(records[6].flags.unwrap().is_castling);
+
+    let records = castling
+        .include_king_safety(true)
+        .fentasize(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"])
+        .unwrap();
+    let safety = records[6].king_safety.as_ref().unwrap();
+    assert_eq!((safety[0].color, safety[1].color), (Color::W, Color::B));
+    assert!(safety[0].castled.is_some() && safety[1].castled.is_none());
+
+    let records = FentasizeOptions::new()
+        .include_phase(true)
+        .start_fen("4k3/pp3r2/8/8/8/8/PP3B2/4K3 w - - 0 40")
+        .fentasize(&["Bc5", "Rf1+"])
+        .unwrap();
+    assert_eq!(records[0].phase, Some(GamePhase::Endgame));
+    let scheme = PhaseScheme {
+        endgame_material: 2,
+        ..PhaseScheme::default()
+    };
+    let records = FentasizeOptions::new()
+        .include_phase(true)
+        .phase_scheme(scheme)
+        .fentasize(&["e4", "e5"])
+        .unwrap();
+    assert_eq!(records[1].phase, Some(GamePhase::Opening));
+
+    let records = FentasizeOptions::new()
+        .include_move_label(true)
+        .start_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 12")
+        .fentasize(&["Kd7", "Ra7"])
+        .unwrap();
+    assert_eq!(records[0].move_label.as_deref(), Some("12... Kd7"));
+    assert_eq!(records[1].move_label.as_deref(), Some("13. Ra7+"));
+}
+
+#[test]
+fn check_multiple_games() {
+    let tokens = ["e4", "e5", "1-0", "", "d4", "", "", "c4", "*"];
+    assert_eq!(
+        split_games(&tokens),
+        vec![vec!["e4", "e5"], vec!["d4"], vec!["c4"]]
+    );
+
+    let games = FentasizeOptions::new().fentasize_games(&tokens).unwrap();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].len(), 2);
+    assert_eq!(
+        games[2][0].fen,
+        "rnbqkbnr/pppppppp/8/8/2P5/8/PP1PPPPP/RNBQKBNR b KQkq - 0 1"
+    );
+    assert_eq!(
+        FentasizeOptions::new().fentasize_games(&["e4", "1-0", "Ke3"]),
+        Err(Error::IllegalMove(
+            "game 1: ply 1: illegal move: Ke3".to_string()
+        ))
+    );
+}
+
+#[test]
+fn check_strict_and_variant() {
+    // Castling through the attacked f1 square.
+    let fen = "4k3/8/8/8/8/8/5r2/4K2R w K - 0 1";
+    let options = FentasizeOptions::new().start_fen(fen);
+    assert!(options.fentasize(&["O-O"]).is_err());
+    assert!(options.clone().strict(false).fentasize(&["O-O"]).is_ok());
+    assert_eq!(
+        FentasizeOptions::new().variant("atomic").fentasize(&["e4"]),
+        Err(Error::Unsupported(
+            "unsupported variant: atomic".to_string()
+        ))
+    );
+}
+
+#[test]
+fn check_max_plies() {
+    let options = FentasizeOptions::new().max_plies(2);
+    assert_eq!(options.fentasize(&["e4", "e5", "1-0"]).unwrap().len(), 2);
+    assert_eq!(
+        options.fentasize(&["e4", "e5", "Nf3"]),
+        Err(Error::TooManyPlies(
+            "ply 3: game exceeds 2 plies".to_string()
+        ))
+    );
+    assert!(options.final_fen(&["e4", "e5", "Nf3"]).is_err());
+
+    // Corrupt clocks fail the ply instead of wrapping around.
+    let options = FentasizeOptions::new().start_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 65535 9");
+    assert_eq!(
+        options.fentasize(&["Ra2"]),
+        Err(Error::ClockOverflow(
+            "ply 1: half-move clock overflows: 65535".to_string()
+        ))
+    );
+}
+
+#[test]
+fn check_final_fen() {
+    let moves = ["e4", "e5", "Nf3", "Nc6", "1-0"];
+    let options = FentasizeOptions::new();
+    assert_eq!(
+        options.final_fen(&moves).unwrap(),
+        options.fentasize(&moves).unwrap()[3].fen
+    );
+    assert_eq!(
+        options.final_fen(&[]).unwrap(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    );
+    assert_eq!(
+        options.final_fen(&["e4", "e5", "Ke3"]),
+        Err(Error::IllegalMove("ply 3: illegal move: Ke3".to_string()))
+    );
+
+    let options = FentasizeOptions::new()
+        .reduced_fen(true)
+        .start_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 30");
+    assert_eq!(
+        options.final_fen(&["O-O-O"]).unwrap(),
+        "4k3/8/8/8/8/8/8/2KR4 b - -"
+    );
+}
@@ -0,0 +1,166 @@
+use crate::utils::color::Color;
+use crate::utils::coord::{Coord, BOARD};
+use crate::utils::piece::Piece;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::num::NonZeroU16;
+
+/// A figure packed into 16 bits, from low to high: the square index (a8 = 0 to h1 = 63, 6 bits),
+/// the piece (3 bits) and the color (1 bit). The highest bit is always set, such that an
+/// `Option<Figure>` takes no extra space. Coordinates are derived on demand from the board table.
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+pub struct Figure(NonZeroU16);
+
+const PIECES: [Piece; 6] = [Piece::P, Piece::R, Piece::N, Piece::B, Piece::Q, Piece::K];
+const MARKER: u16 = 1 << 15;
+
+impl Figure {
+    pub fn new(color: Color, coord: Coord, piece: Piece) -> Self {
+        let piece_bits = PIECES.iter().position(|&p| p == piece).unwrap() as u16;
+        let color_bit = match color {
+            Color::W => 0,
+            Color::B => 1,
+        };
+        let bits = MARKER | (color_bit << 9) | (piece_bits << 6) | (coord.idx as u16);
+        Figure(NonZeroU16::new(bits).unwrap())
+    }
+
+    pub fn color(self) -> Color {
+        if self.0.get() & (1 << 9) == 0 {
+            Color::W
+        } else {
+            Color::B
+        }
+    }
+
+    pub fn piece(self) -> Piece {
+        PIECES[((self.0.get() >> 6) & 0b111) as usize]
+    }
+
+    /// Index of the square, from a8 = 0 to h1 = 63 as in the FEN.
+    pub fn square(self) -> usize {
+        (self.0.get() & 0b11_1111) as usize
+    }
+
+    pub fn coord(self) -> Coord {
+        BOARD[self.square()]
+    }
+
+    pub fn to_char(self) -> char {
+        self.piece().to_char(self.color())
+    }
+
+    /// Index of the figure kind from 0 to 11, white pieces first in the order P, N, B, R, Q, K.
+    pub fn index(self) -> usize {
+        let piece_idx = match self.piece() {
+            Piece::P => 0,
+            Piece::N => 1,
+            Piece::B => 2,
+            Piece::R => 3,
+            Piece::Q => 4,
+            Piece::K => 5,
+        };
+        match self.color() {
+            Color::W => piece_idx,
+            Color::B => piece_idx + 6,
+        }
+    }
+
+    pub fn move_to(self, coord: &Coord) -> Self {
+        Figure::new(self.color(), *coord, self.piece())
+    }
+}
+
+impl Debug for Figure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Figure")
+            .field("color", &self.color())
+            .field("coord", &self.coord())
+            .field("piece", &self.piece())
+            .finish()
+    }
+}
+
+/// Figures are ordered by square from a8 to h1 as in the FEN, then by color and piece, which
+/// only matters for figures that do not share a board.
+impl Ord for Figure {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.square()
+            .cmp(&other.square())
+            .then(self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for Figure {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Figure {
+    /// Check whether a string describes a figure, as `Figure::from` panics on broken inputs.
+    pub fn is_valid(figstr: &str) -> bool {
+        figstr.len() == 3
+            && figstr.is_char_boundary(1)
+            && "PRNBQKprnbqk".contains(&figstr[..1])
+            && Coord::is_valid(&figstr[1..])
+    }
+}
+
+impl From<&str> for Figure {
+    /// Derive a figure from some minimal information about piece and coord, plus the color encoded
+    /// in whether the piece is upper- or lowercase, e.g. Nc1 vs ng8.
+    fn from(figstr: &str) -> Self {
+        let pchar = figstr.chars().next().unwrap();
+
+        let color = match pchar.is_uppercase() {
+            true => Color::W,
+            false => Color::B,
+        };
+        Figure::new(color, Coord::from(&figstr[1..3]), Piece::from(pchar))
+    }
+}
+
+impl Display for Figure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", self.to_char(), self.coord())
+    }
+}
+
+#[test]
+fn check_figure_from() {
+    assert_eq!(
+        Figure::from("Ba3"),
+        Figure::new(Color::W, Coord::from("a3"), Piece::B)
+    );
+
+    assert_eq!(
+        Figure::from("na3"),
+        Figure::new(Color::B, Coord::from("a3"), Piece::N)
+    );
+}
+
+#[test]
+fn check_figure_index() {
+    assert_eq!(Figure::from("Pa2").index(), 0);
+    assert_eq!(Figure::from("Ke1").index(), 5);
+    assert_eq!(Figure::from("pa7").index(), 6);
+    assert_eq!(Figure::from("qd8").index(), 10);
+}
+
+#[test]
+fn check_figure_packing() {
+    assert_eq!(std::mem::size_of::<Figure>(), 2);
+    assert_eq!(std::mem::size_of::<Option<Figure>>(), 2);
+    for piece in PIECES {
+        for color in [Color::W, Color::B] {
+            for coord in BOARD {
+                let fig = Figure::new(color, coord, piece);
+                assert_eq!(
+                    (fig.color(), fig.coord(), fig.piece()),
+                    (color, coord, piece)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::utils::outcome::GameResult;
+use crate::utils::pgn::PgnGame;
+use std::str::FromStr;
+
+/// Criteria on the tag pairs of a game, which `PgnReader` checks before it parses the movetext.
+/// Empty lists and None do not restrict anything, while all given criteria must be met.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GameFilter {
+    /// Lower bound for the ratings of both players (WhiteElo and BlackElo).
+    pub min_elo: Option<u16>,
+
+    /// Upper bound for the ratings of both players.
+    pub max_elo: Option<u16>,
+
+    /// Accepted values of the TimeControl tag, e.g. "180+2".
+    pub time_controls: Vec<String>,
+
+    /// Accepted variants, compared case-insensitively. A game without Variant tag is "Standard".
+    pub variants: Vec<String>,
+
+    /// Accepted results of the Result tag.
+    pub results: Vec<GameResult>,
+
+    /// Accepted prefixes of the ECO code, e.g. "B" for all semi-open games or "C42".
+    pub eco: Vec<String>,
+
+    /// First and last accepted date (inclusive) as "YYYY.MM.DD", compared with the Date tag, or
+    /// UTCDate as fallback. Unknown parts like "2023.??.??" count as zeros.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+
+    /// Name of a player of either color, compared case-insensitively.
+    pub player: Option<String>,
+}
+
+impl GameFilter {
+    /// Check the tag pairs of a game against all criteria. A missing tag fails any criterion on
+    /// it, e.g. a game without ratings is rejected as soon as an Elo bound is given.
+    pub fn matches(&self, game: &PgnGame) -> bool {
+        self.matches_elo(game)
+            && is_listed(&self.time_controls, game.header("TimeControl"), |a, b| {
+                a == b
+            })
+            && is_listed(
+                &self.variants,
+                Some(game.header("Variant").unwrap_or("Standard")),
+                |a, b| a.eq_ignore_ascii_case(b),
+            )
+            && (self.results.is_empty()
+                || game
+                    .header("Result")
+                    .and_then(|r| GameResult::from_str(r).ok())
+                    .is_some_and(|r| self.results.contains(&r)))
+            && is_listed(&self.eco, game.header("ECO"), |eco, prefix| {
+                eco.starts_with(prefix)
+            })
+            && self.matches_date(game)
+            && self.player.as_ref().is_none_or(|player| {
+                [game.header("White"), game.header("Black")]
+                    .iter()
+                    .flatten()
+                    .any(|name| name.eq_ignore_ascii_case(player))
+            })
+    }
+
+    fn matches_elo(&self, game: &PgnGame) -> bool {
+        if self.min_elo.is_none() && self.max_elo.is_none() {
+            return true;
+        }
+        ["WhiteElo", "BlackElo"].iter().all(|key| {
+            game.header(key)
+                .and_then(|elo| elo.parse::<u16>().ok())
+                .is_some_and(|elo| {
+                    self.min_elo.is_none_or(|min| elo >= min)
+                        && self.max_elo.is_none_or(|max| elo <= max)
+                })
+        })
+    }
+
+    fn matches_date(&self, game: &PgnGame) -> bool {
+        if self.date_from.is_none() && self.date_to.is_none() {
+            return true;
+        }
+        let Some(date) = game.header("Date").or_else(|| game.header("UTCDate")) else {
+            return false;
+        };
+        let date = date.replace('?', "0");
+        self.date_from.as_ref().is_none_or(|from| date >= *from)
+            && self.date_to.as_ref().is_none_or(|to| date <= *to)
+    }
+}
+
+/// Check a tag value against a list of accepted values, where an empty list accepts anything.
+fn is_listed(accepted: &[String], value: Option<&str>, eq: impl Fn(&str, &str) -> bool) -> bool {
+    accepted.is_empty() || value.is_some_and(|v| accepted.iter().any(|a| eq(v, a)))
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+fn game_with(headers: &[(&str, &str)]) -> PgnGame {
+    PgnGame {
+        headers: headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..PgnGame::default()
+    }
+}
+
+#[test]
+fn check_elo_filter() {
+    let filter = GameFilter {
+        min_elo: Some(2000),
+        max_elo: Some(2500),
+        ..GameFilter::default()
+    };
+    assert!(filter.matches(&game_with(&[("WhiteElo", "2100"), ("BlackElo", "2500")])));
+    assert!(!filter.matches(&game_with(&[("WhiteElo", "2100"), ("BlackElo", "1999")])));
+    assert!(!filter.matches(&game_with(&[("WhiteElo", "2100"), ("BlackElo", "?")])));
+    assert!(!filter.matches(&game_with(&[("WhiteElo", "2600")])));
+    assert!(GameFilter::default().matches(&game_with(&[])));
+}
+
+#[test]
+fn check_date_filter() {
+    let filter = GameFilter {
+        date_to: Some("2023.01.01".to_string()),
+        ..GameFilter::default()
+    };
+    assert!(filter.matches(&game_with(&[("Date", "2023.??.??")])));
+    assert!(!filter.matches(&game_with(&[("Date", "2023.01.02")])));
+    assert!(!filter.matches(&game_with(&[])));
+}
+
+#[test]
+fn check_header_filters() {
+    let game = game_with(&[
+        ("White", "Alice"),
+        ("Black", "bob"),
+        ("Result", "0-1"),
+        ("UTCDate", "2023.07.15"),
+        ("TimeControl", "180+2"),
+        ("ECO", "B22"),
+    ]);
+    let filter = GameFilter {
+        time_controls: vec!["60+0".to_string(), "180+2".to_string()],
+        variants: vec!["standard".to_string()],
+        results: vec![GameResult::BlackWins, GameResult::Draw],
+        eco: vec!["B2".to_string()],
+        date_from: Some("2023.07.01".to_string()),
+        date_to: Some("2023.07.31".to_string()),
+        player: Some("BOB".to_string()),
+        ..GameFilter::default()
+    };
+    assert!(filter.matches(&game));
+
+    let rejects = [
+        GameFilter {
+            time_controls: vec!["60+0".to_string()],
+            ..filter.clone()
+        },
+        GameFilter {
+            variants: vec!["Chess960".to_string()],
+            ..filter.clone()
+        },
+        GameFilter {
+            results: vec![GameResult::WhiteWins],
+            ..filter.clone()
+        },
+        GameFilter {
+            eco: vec!["C".to_string()],
+            ..filter.clone()
+        },
+        GameFilter {
+            date_from: Some("2023.08.01".to_string()),
+            ..filter.clone()
+        },
+        GameFilter {
+            player: Some("carol".to_string()),
+            ..filter.clone()
+        },
+    ];
+    for reject in rejects {
+        assert!(!reject.matches(&game), "{:?}", reject);
+    }
+}
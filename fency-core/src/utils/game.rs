@@ -0,0 +1,3041 @@
+use crate::utils::castling::Castling;
+use crate::utils::color::Color;
+use crate::utils::coord::{Coord, BOARD};
+use crate::utils::disambiguation::count;
+use crate::utils::draw::{Draw, SanMove};
+use crate::utils::error::Error;
+use crate::utils::figure::Figure;
+use crate::utils::history::{History, Snapshot};
+use crate::utils::legality::{check_uci, IllegalReason};
+use crate::utils::notation::NotationConfig;
+use crate::utils::outcome::Status;
+use crate::utils::piece::Piece;
+use crate::utils::rays::{direction_to, ray, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+// Define types for improved readability.
+type Fen = String;
+type CoordIdx = Vec<i8>;
+type Coords = Vec<Coord>;
+type Figures = Vec<Figure>;
+type OptFigures = [Option<Figure>; 64];
+type FigSet = BTreeSet<Figure>;
+
+/// File and rank deltas of the single steps of kings and knights, see `rays` for sliding pieces.
+const KING_STEPS: [(i8, i8); 8] = [
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+pub(crate) const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (-1, 2),
+    (1, 2),
+    (-2, 1),
+    (2, 1),
+    (-2, -1),
+    (2, -1),
+    (-1, -2),
+    (1, -2),
+];
+
+/// Use a constant to prepare all strings that describe the 32 starting position figures.
+const FIGURE_STR_VEC: [&str; 32] = [
+    "ra8", "nb8", "bc8", "qd8", "ke8", "bf8", "ng8", "rh8", "pa7", "pb7", "pc7", "pd7", "pe7",
+    "pf7", "pg7", "ph7", "Pa2", "Pb2", "Pc2", "Pd2", "Pe2", "Pf2", "Pg2", "Ph2", "Ra1", "Nb1",
+    "Bc1", "Qd1", "Ke1", "Bf1", "Ng1", "Rh1",
+];
+
+/// Semantics of the En-Passant square in a FEN.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum EnPassantMode {
+    /// Only if a pawn can legally capture, as in X-FEN and for Polyglot books.
+    #[default]
+    Capturable,
+    /// After every double pawn push, as written by the PGN standard.
+    DoublePush,
+}
+
+/// Core API for derivation from Forsyth-Edwards-Notation (FEN) or to FEN. Thus, the fields are
+/// one-to-one derivations of the parts of the FEN.
+///
+/// Games are `Send + Sync`: the tables of squares, rays and Zobrist keys are statics shared by all
+/// games, and the history is shared through `Arc`. Thus a game can be borrowed by many threads at
+/// once, e.g. as start position of a parallel pipeline, and each thread replays its own clone.
+#[derive(Clone, Debug)]
+pub struct Game {
+    /// A position reflects figures on the board.
+    pub position: OptFigures,
+
+    /// Set of Figures that are on the board, which iterates from a8 to h1 like `position`, thus
+    /// anything derived from it is reproducible across runs.
+    pub figures: FigSet,
+
+    /// Currently active color (w/b).
+    pub color: Color,
+
+    /// Castling rights (KQkq).
+    pub castling: Castling,
+
+    /// Option of a coordinate, if En-Passant is available.
+    pub en_passant: Option<Coord>,
+
+    /// Current state of the half-move clock.
+    pub half_move_clock: u16,
+
+    /// Current state of the full-move clock.
+    pub full_move_clock: u16,
+
+    /// UCI Notation of the move that has been played
+    pub uci: String,
+
+    /// Snapshots of the plies played so far, which are not part of the FEN.
+    pub(crate) history: History,
+}
+
+// Games are equal if their FENs are, no matter how they were reached.
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        (self.position == other.position)
+            && (self.color == other.color)
+            && (self.castling == other.castling)
+            && (self.en_passant == other.en_passant)
+            && (self.half_move_clock == other.half_move_clock)
+            && (self.full_move_clock == other.full_move_clock)
+    }
+}
+
+impl Eq for Game {}
+
+impl Game {
+    /// Constructs a new game that reflects the game state at the beginning of a standard match.
+    pub fn new() -> Self {
+        let mut position: OptFigures = [None; 64];
+        for fstr in FIGURE_STR_VEC {
+            let fig = Figure::from(fstr);
+            position[fig.square()] = Some(fig);
+        }
+        Game::from_parts(position, Color::W, Castling::new(), None, 0, 1)
+    }
+
+    /// Assemble a game from the fields of a FEN. As the FEN does not reveal the last move, the
+    /// null move is set, and the history starts with this position.
+    pub(crate) fn from_parts(
+        position: OptFigures,
+        color: Color,
+        castling: Castling,
+        en_passant: Option<Coord>,
+        half_move_clock: u16,
+        full_move_clock: u16,
+    ) -> Self {
+        let mut game = Game {
+            position,
+            figures: position.iter().flatten().copied().collect(),
+            color,
+            castling,
+            en_passant,
+            half_move_clock,
+            full_move_clock,
+            uci: "0000".to_string(),
+            history: History::default(),
+        };
+        game.record();
+        game
+    }
+
+    /// Number of plies since the initial position of a standard game as implied by the move number
+    /// and the side to move, e.g. 3 after "1. e4 e5 2. Nf3". Unlike `history`, this includes the
+    /// plies before a position that was read from a FEN.
+    pub fn ply(&self) -> usize {
+        usize::from(self.full_move_clock.saturating_sub(1)) * 2
+            + usize::from(!self.color.is_white())
+    }
+
+    /// Snapshots of the initial position and of every ply played since, in order.
+    pub fn history(&self) -> Vec<&Snapshot> {
+        let mut snapshots: Vec<&Snapshot> = self.history.iter().collect();
+        snapshots.reverse();
+        snapshots
+    }
+
+    /// The UCI command that sets up the game with its full move history, e.g. "position startpos
+    /// moves e2e4 e7e5", such that engines can detect repetitions. Games constructed from a FEN
+    /// start with "position fen".
+    pub fn uci_position(&self) -> String {
+        let history = self.history();
+        let initial = self.position_after(0).map(|g| g.to_fen());
+        let mut command = match initial {
+            Some(fen) if fen != Game::new().to_fen() => format!("position fen {}", fen),
+            _ => "position startpos".to_string(),
+        };
+        if history.len() > 1 {
+            command.push_str(" moves");
+            for snapshot in &history[1..] {
+                command.push(' ');
+                command.push_str(&snapshot.uci);
+            }
+        }
+        command
+    }
+
+    /// The game after the given ply (0 for the initial position) without replaying the moves.
+    pub fn position_after(&self, ply: usize) -> Option<Game> {
+        self.history.game_after(ply)
+    }
+
+    /// An independent copy to continue with other moves, e.g. to explore a variation after
+    /// `truncate`. The history up to now is shared with the original, not copied.
+    pub fn fork(&self) -> Game {
+        self.clone()
+    }
+
+    /// Take back all plies after the given one (0 for the initial position), restoring the board,
+    /// castling rights, En-Passant square and move clocks of that ply. The history is kept up to
+    /// that ply, thus repetitions are still detected when the game continues.
+    pub fn truncate(&mut self, ply: usize) -> Result<(), Error> {
+        *self = self.position_after(ply).ok_or_else(|| {
+            let played = self.history.len().saturating_sub(1);
+            Error::PlyOutOfRange(format!(
+                "cannot truncate to ply {}, only {} played",
+                ply, played
+            ))
+        })?;
+        Ok(())
+    }
+
+    fn record(&mut self) {
+        let mut history = std::mem::take(&mut self.history);
+        history.push(self);
+        self.history = history;
+    }
+
+    pub fn to_fen_list(self) -> [String; 6] {
+        [
+            position_to_fen(self.position),
+            self.color.to_string(),
+            self.castling.to_string(),
+            match self.en_passant {
+                None => "-".to_string(),
+                Some(c) => c.to_string(),
+            },
+            self.half_move_clock.to_string(),
+            self.full_move_clock.to_string(),
+        ]
+    }
+
+    pub fn to_fen_map(self) -> HashMap<String, String> {
+        let keys: [String; 6] = [
+            "FEN",
+            "Color",
+            "Castling",
+            "EnPassant",
+            "HalfMoveClock",
+            "FullMoveClock",
+        ]
+        .map(|x| x.to_string());
+
+        let values = self.to_fen_list();
+
+        HashMap::from_iter(
+            keys.into_iter()
+                .enumerate()
+                .map(|(k, key)| (key, values[k].clone())),
+        )
+    }
+
+    pub fn to_fen(self) -> String {
+        self.to_fen_list().join(" ")
+    }
+
+    /// Append the FEN to a buffer, which neither consumes the game nor allocates for its fields,
+    /// e.g. to reuse one buffer for all plies, see `batch::BatchContext`.
+    pub fn write_fen(&self, fen: &mut String) {
+        write_position(&self.position, fen);
+        self.write_fen_fields(fen);
+    }
+
+    /// Append the fields of the FEN after the board, each preceded by a space.
+    pub(crate) fn write_fen_fields(&self, fen: &mut String) {
+        let en_passant = self.en_passant.map_or("-".to_string(), |c| c.to_string());
+        // Writing to a String cannot fail.
+        let _ = write!(
+            fen,
+            " {} {} {} {} {}",
+            self.color, self.castling, en_passant, self.half_move_clock, self.full_move_clock
+        );
+    }
+
+    /// Derive the FEN with the En-Passant square in the given mode. `to_fen` uses the capturable
+    /// (X-FEN) mode, which is also what the Zobrist hash reflects.
+    pub fn to_fen_with(self, mode: EnPassantMode) -> String {
+        let en_passant = self.en_passant_field(mode);
+        let mut fen = self.to_fen_list();
+        fen[3] = en_passant.map_or("-".to_string(), |c| c.to_string());
+        fen.join(" ")
+    }
+
+    /// The FEN without move clocks, where castling rights require king and rook on their initial
+    /// squares and the En-Passant square requires a legal capture. This is the key convention of
+    /// opening books and the Lichess opening explorer, thus it also normalizes FENs read from
+    /// sources that set these fields more liberally.
+    pub fn normalized_fen(&self) -> String {
+        let castling = self.possible_castling();
+        let en_passant = self
+            .en_passant
+            .filter(|_| self.is_en_passant_capturable())
+            .map_or("-".to_string(), |c| c.to_string());
+        format!(
+            "{} {} {} {}",
+            position_to_fen(self.position),
+            self.color,
+            castling,
+            en_passant
+        )
+    }
+
+    /// Render the board as a diagram with letters as in the FEN, e.g. "N" for a white knight.
+    pub fn to_ascii(&self) -> String {
+        self.to_diagram(|fig| fig.to_char(), '.')
+    }
+
+    /// Render the board as a diagram with Unicode chess symbols, e.g. '♘' for a white knight.
+    pub fn to_unicode(&self) -> String {
+        self.to_diagram(|fig| fig.piece().to_figurine(fig.color()), '·')
+    }
+
+    /// Draw the 8x8 board from white's perspective with rank and file labels, followed by the
+    /// side to move.
+    fn to_diagram(&self, symbol: impl Fn(&Figure) -> char, empty: char) -> String {
+        let mut diagram = String::new();
+        for (r, rank) in self.position.chunks(8).enumerate() {
+            let squares: Vec<String> = rank
+                .iter()
+                .map(|fig| fig.as_ref().map_or(empty, &symbol).to_string())
+                .collect();
+            diagram.push_str(&format!("{} {}\n", 8 - r, squares.join(" ")));
+        }
+        diagram.push_str("  a b c d e f g h\n");
+        let side = if self.color.is_white() {
+            "White"
+        } else {
+            "Black"
+        };
+        diagram.push_str(&format!("{} to move", side));
+
+        diagram
+    }
+
+    /// Play a move given in SAN, e.g. "Nf3" or "O-O", where castling may be spelled with zeros,
+    /// e.g. "0-0". Castling is only played if it is legal, i.e. the castling right is given, the
+    /// path is empty and the king does not pass attacked squares.
+    /// Any other move is only played if the moving figure can actually reach the target and the
+    /// own king is not in check afterwards.
+    pub fn play_move(&mut self, mv: &str) -> Result<(), Error> {
+        self.play_san(mv, false)
+    }
+
+    /// Play a move given in SAN like `play_move`, but castle as long as king and rook are on their
+    /// initial squares, no matter of castling rights or attacks, and move a figure without checking
+    /// its path or the safety of its king. This is meant for sources that are known to be sloppy,
+    /// e.g. hand-typed PGN with an unrecorded loss of castling rights.
+    pub fn play_move_lenient(&mut self, mv: &str) -> Result<(), Error> {
+        self.play_san(mv, true)
+    }
+
+    fn play_san(&mut self, mv: &str, lenient: bool) -> Result<(), Error> {
+        // Separate castling, drops and null moves from a "normal draw" where one piece is moved.
+        let draw = match SanMove::from_str(mv).map_err(Error::IllegalMove)? {
+            SanMove::Normal(draw) => draw,
+            SanMove::Castle(castling) => {
+                let is_playable = if lenient {
+                    self.has_castling_figures(self.color, castling.is_long)
+                } else {
+                    self.can_castle(castling.san())
+                };
+                if !is_playable {
+                    return Err(Error::IllegalMove(format!("illegal move: {}", mv)));
+                }
+                self.check_clocks(false)?;
+                self.castle(castling.san());
+                self.record();
+                return Ok(());
+            }
+            SanMove::Drop(_) | SanMove::Null => {
+                return Err(Error::IllegalMove(format!(
+                    "not a move of standard chess: {}",
+                    mv
+                )))
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("disambiguate", san = mv).entered();
+        let moving_figure = filter_mover(&draw, self).map_err(Error::IllegalMove)?;
+
+        // A single candidate is picked without looking at its moves, thus verify it here.
+        if !lenient
+            && !get_moves(&moving_figure, self).contains(&draw.target)
+            && !get_hits(&moving_figure, self).contains(&draw.target)
+        {
+            return Err(Error::IllegalMove(format!("illegal move: {}", mv)));
+        }
+        self.check_clocks(self.is_irreversible(&moving_figure, &draw.target))?;
+
+        if !lenient && self.exposes_king(&moving_figure, &draw.target) {
+            return Err(Error::IllegalMove(format!("illegal move: {}", mv)));
+        }
+        self.make_move(moving_figure, draw.target, draw.promoted_piece);
+        self.record();
+        Ok(())
+    }
+
+    /// Play a move given in a localized SAN, e.g. "Sf3" in German notation.
+    pub fn play_localized_move(
+        &mut self,
+        mv: &str,
+        notation: &NotationConfig,
+    ) -> Result<(), Error> {
+        self.play_move(&notation.to_english(mv))
+    }
+
+    /// Play a move given in UCI notation, e.g. "g1f3" or "e7e8q". Castling is expected as the
+    /// king's move, e.g. "e1g1".
+    pub fn play_uci(&mut self, uci: &str) -> Result<(), Error> {
+        let invalid =
+            |reason: &str| Error::IllegalMove(format!("invalid UCI move {}: {}", uci, reason));
+        let (src, tgt, promo) = match (uci.get(..2), uci.get(2..4), uci.get(4..)) {
+            (Some(src), Some(tgt), Some(promo)) if Coord::is_valid(src) && Coord::is_valid(tgt) => {
+                (Coord::from(src), Coord::from(tgt), promo)
+            }
+            _ => return Err(invalid("malformed")),
+        };
+        let promoted_piece = match promo {
+            "" => None,
+            "q" | "r" | "b" | "n" => Some(Piece::from(promo.chars().next().unwrap())),
+            _ => return Err(invalid("unknown promotion piece")),
+        };
+
+        let moving_figure = match self.position[src.idx as usize] {
+            Some(fig) if fig.color() == self.color => fig,
+            _ => {
+                return Err(invalid(
+                    "no figure of the active color on the source square",
+                ))
+            }
+        };
+        // Besides the path, this checks promotions and that the own king is safe afterwards.
+        check_uci(self, src, tgt, promoted_piece).map_err(|reason| invalid(&reason.to_string()))?;
+        self.check_clocks(self.is_irreversible(&moving_figure, &tgt))?;
+
+        self.make_move(moving_figure, tgt, promoted_piece);
+        self.record();
+        Ok(())
+    }
+
+    /// Apply a move of a figure, which is already known to be the moving one, to the target.
+    /// Captures, en passant, promotions and castling (king moves by two files) are derived from
+    /// the position.
+    pub(crate) fn make_move(
+        &mut self,
+        moving_figure: Figure,
+        target: Coord,
+        promoted_piece: Option<Piece>,
+    ) {
+        if (moving_figure.piece() == Piece::K) && ((moving_figure.coord().x - target.x).abs() == 2)
+        {
+            self.castle(if target.x == 6 { "O-O" } else { "O-O-O" });
+            return;
+        }
+        let is_pawn = moving_figure.piece() == Piece::P;
+        let is_ep = is_pawn && (self.en_passant == Some(target));
+        let is_hit = is_ep || self.position[target.idx as usize].is_some();
+        let mut captured = None;
+
+        // update figures & position
+        self.position[moving_figure.square()] = None;
+        self.figures.remove(&moving_figure);
+        if is_ep {
+            let ep_figure = self.figures.iter().copied().find(|f| {
+                (f.color() == self.color.next())
+                    && (f.coord().x == target.x)
+                    && (f.coord().y == target.y + self.color.next().factor())
+            });
+            if let Some(ep_figure) = ep_figure {
+                self.position[ep_figure.square()] = None;
+                self.figures.remove(&ep_figure);
+            }
+        } else if let Some(hit_figure) = self.position[target.idx as usize] {
+            self.position[hit_figure.square()] = None;
+            self.figures.remove(&hit_figure);
+            captured = Some(hit_figure);
+        }
+        if let Some(piece) = promoted_piece {
+            let promoted_figure = Figure::new(self.color, target, piece);
+            self.position[promoted_figure.square()] = Some(promoted_figure);
+            self.figures.insert(promoted_figure);
+        } else {
+            let moved_figure = moving_figure.move_to(&target);
+            self.position[moved_figure.square()] = Some(moved_figure);
+            self.figures.insert(moved_figure);
+        }
+
+        // Account for En-Passant, which is only set if an adjacent pawn can legally capture.
+        self.en_passant = None;
+        let mut ep_square = None;
+        if is_pawn && ((moving_figure.coord().y - target.y).abs() == 2) {
+            let ep_idx = (target.idx + self.color.factor() * 8) as usize;
+            ep_square = Some(BOARD[ep_idx]);
+        }
+
+        // Design UCI representation of a move.
+        let mut uci: String = "".to_string();
+        uci.push_str(&moving_figure.coord().to_string()[..]);
+        uci.push_str(&target.to_string()[..]);
+        if let Some(piece) = promoted_piece {
+            // uci is always lowercase, thus use lowercase char induced by black.
+            uci.push(piece.to_char(Color::B));
+        }
+
+        // Update game
+        self.uci = uci;
+        self.castling.update(moving_figure);
+        if let Some(captured) = captured {
+            // A rook captured on its initial square takes the castling right along.
+            self.castling.capture(captured);
+        }
+        self.end_ply(is_hit || is_pawn);
+
+        self.en_passant = ep_square;
+        if !self.is_en_passant_capturable() {
+            self.en_passant = None;
+        }
+    }
+
+    /// Check whether a pawn of the active color can legally capture on the En-Passant square,
+    /// which requires a pawn of the opponent right in front of the empty square.
+    pub(crate) fn is_en_passant_capturable(&self) -> bool {
+        let Some(ep) = self.en_passant else {
+            return false;
+        };
+        let victim = ep.idx + self.color.factor() * 8;
+        let is_victim = (0..64).contains(&victim)
+            && self.position[victim as usize]
+                .is_some_and(|f| (f.piece() == Piece::P) && (f.color() == self.color.next()));
+        if !is_victim || self.position[ep.idx as usize].is_some() {
+            return false;
+        }
+
+        let victim = BOARD[victim as usize];
+        self.figures
+            .iter()
+            .filter(|f| {
+                (f.color() == self.color)
+                    && (f.piece() == Piece::P)
+                    && (f.coord().y == victim.y)
+                    && ((f.coord().x - victim.x).abs() == 1)
+            })
+            .any(|pawn| {
+                let mut alt_game = self.clone();
+                alt_game.make_move(*pawn, ep, None);
+                !alt_game.in_check_of(self.color)
+            })
+    }
+
+    /// The En-Passant square as written to the FEN in the given mode.
+    fn en_passant_field(&self, mode: EnPassantMode) -> Option<Coord> {
+        match mode {
+            EnPassantMode::Capturable => self.en_passant,
+            EnPassantMode::DoublePush => {
+                // The last ply is only known when played, not when read from a FEN.
+                if self.uci.len() != 4 || self.uci == "0000" {
+                    return self.en_passant;
+                }
+                let src = Coord::from(&self.uci[..2]);
+                let tgt = Coord::from(&self.uci[2..]);
+                let is_pawn =
+                    self.position[tgt.idx as usize].is_some_and(|f| f.piece() == Piece::P);
+                if is_pawn && ((src.y - tgt.y).abs() == 2) {
+                    Some(BOARD[((src.idx + tgt.idx) / 2) as usize])
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Advance the clocks and pass the turn, which concludes every ply. Captures and pawn moves
+    /// are irreversible and thus reset the half-move clock, castling is not. The clocks saturate,
+    /// as moves are also tried out on copies, see `check_clocks` for played moves.
+    fn end_ply(&mut self, is_irreversible: bool) {
+        self.half_move_clock = if is_irreversible {
+            0
+        } else {
+            self.half_move_clock.saturating_add(1)
+        };
+        if self.color == Color::B {
+            self.full_move_clock = self.full_move_clock.saturating_add(1);
+        }
+        self.color = self.color.next();
+    }
+
+    /// Whether moving the figure to the target resets the half-move clock, i.e. moves a pawn or
+    /// captures. Castling moves the king onto an empty square, thus continues the clock.
+    fn is_irreversible(&self, fig: &Figure, target: &Coord) -> bool {
+        (fig.piece() == Piece::P) || self.position[target.idx as usize].is_some()
+    }
+
+    /// Check that the move clocks can advance by another ply, instead of wrapping around after
+    /// corrupt counters or absurdly long games. An irreversible move resets the half-move clock,
+    /// thus it cannot overflow.
+    fn check_clocks(&self, is_irreversible: bool) -> Result<(), Error> {
+        if !is_irreversible && (self.half_move_clock == u16::MAX) {
+            Err(Error::ClockOverflow(format!(
+                "half-move clock overflows: {}",
+                self.half_move_clock
+            )))
+        } else if self.full_move_clock == u16::MAX && self.color == Color::B {
+            Err(Error::ClockOverflow(format!(
+                "full-move clock overflows: {}",
+                self.full_move_clock
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolve a SAN into the UCI notation of the according legal move, e.g. "Nf3" into "g1f3".
+    pub fn san_to_uci(&self, san: &str) -> Result<String, Error> {
+        let legal_moves = self.legal_moves();
+        let (king_src, rank) = if self.color == Color::W {
+            ("e1", '1')
+        } else {
+            ("e8", '8')
+        };
+        let draw = match SanMove::from_str(san).map_err(Error::IllegalMove)? {
+            SanMove::Normal(draw) => draw,
+            SanMove::Castle(castling) => {
+                let file = if castling.is_long { 'c' } else { 'g' };
+                let uci = format!("{}{}{}", king_src, file, rank);
+                return match self.position[Coord::from(king_src).idx as usize] {
+                    Some(king) if king.piece() == Piece::K && legal_moves.contains(&uci) => Ok(uci),
+                    _ => Err(Error::IllegalMove(format!("illegal move: {}", san))),
+                };
+            }
+            SanMove::Drop(_) | SanMove::Null => {
+                return Err(Error::IllegalMove(format!(
+                    "not a move of standard chess: {}",
+                    san
+                )))
+            }
+        };
+        let candidates: Vec<&String> = legal_moves
+            .iter()
+            .filter(|uci| {
+                let src = Coord::from(&uci[..2]);
+                let fig = self.position[src.idx as usize].unwrap();
+                (fig.piece() == draw.piece)
+                    && (Coord::from(&uci[2..4]) == draw.target)
+                    && draw.remainder_file.is_none_or(|f| f == src.file)
+                    && draw.remainder_rank.is_none_or(|r| r == src.rank)
+                    && (uci[4..].chars().next().map(Piece::from) == draw.promoted_piece)
+            })
+            .collect();
+
+        match candidates[..] {
+            [uci] => Ok(uci.clone()),
+            [] => Err(Error::IllegalMove(format!("illegal move: {}", san))),
+            _ => Err(Error::IllegalMove(format!("ambiguous move: {}", san))),
+        }
+    }
+
+    /// Describe a legal move given in UCI notation as SAN, e.g. "g1f3" as "Nf3". Disambiguation
+    /// and check or checkmate markers are derived from the position.
+    pub fn uci_to_san(&self, uci: &str) -> Result<String, Error> {
+        let legal_moves = self.legal_moves();
+        if !legal_moves.iter().any(|mv| mv == uci) {
+            return Err(Error::IllegalMove(format!("illegal move: {}", uci)));
+        }
+        let src = Coord::from(&uci[..2]);
+        let tgt = Coord::from(&uci[2..4]);
+        let fig = self.position[src.idx as usize].unwrap();
+
+        let mut san = String::new();
+        if (fig.piece() == Piece::K) && ((src.x - tgt.x).abs() == 2) {
+            san.push_str(if tgt.x == 6 { "O-O" } else { "O-O-O" });
+        } else {
+            let is_hit = self.position[tgt.idx as usize].is_some()
+                || ((fig.piece() == Piece::P) && (self.en_passant == Some(tgt)));
+            if fig.piece() == Piece::P {
+                if is_hit {
+                    san.push(src.file);
+                }
+            } else {
+                san.push(fig.piece().to_char(Color::W));
+                // Disambiguate by file if possible, by rank otherwise and by both if necessary.
+                let rivals: Vec<Coord> = legal_moves
+                    .iter()
+                    .map(|mv| (Coord::from(&mv[..2]), Coord::from(&mv[2..4])))
+                    .filter(|(s, t)| {
+                        (*t == tgt)
+                            && (*s != src)
+                            && self.position[s.idx as usize]
+                                .is_some_and(|f| f.piece() == fig.piece())
+                    })
+                    .map(|(s, _)| s)
+                    .collect();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|r| r.file != src.file) {
+                        san.push(src.file);
+                    } else if rivals.iter().all(|r| r.rank != src.rank) {
+                        san.push(src.rank);
+                    } else {
+                        san.push_str(&src.to_string());
+                    }
+                }
+            }
+            if is_hit {
+                san.push('x');
+            }
+            san.push_str(&tgt.to_string());
+            if let Some(promo) = uci[4..].chars().next() {
+                san.push('=');
+                san.push(Piece::from(promo).to_char(Color::W));
+            }
+        }
+
+        let mut alt_game = self.clone();
+        alt_game.play_uci(uci)?;
+        match alt_game.status() {
+            Status::Checkmate => san.push('#'),
+            _ if alt_game.in_check() => san.push('+'),
+            _ => {}
+        }
+
+        Ok(san)
+    }
+
+    /// Check whether a square is attacked by any figure of the given color.
+    pub fn is_attacked(&self, coord: &Coord, by: Color) -> bool {
+        self.figures
+            .iter()
+            .any(|f| (f.color() == by) && self.attacks(f, coord))
+    }
+
+    /// List the figures of the given color that attack a square, ordered from a8 to h1. A square
+    /// held by the same color counts as attacked, i.e. its figure is defended.
+    pub fn attackers_of(&self, coord: &Coord, by: Color) -> Figures {
+        self.position
+            .iter()
+            .flatten()
+            .filter(|f| (f.color() == by) && self.attacks(f, coord))
+            .copied()
+            .collect()
+    }
+
+    /// Check whether a figure attacks a square, no matter which color holds the square.
+    pub(crate) fn attacks(&self, fig: &Figure, coord: &Coord) -> bool {
+        let steps_to = |steps: &[(i8, i8)]| {
+            steps
+                .iter()
+                .any(|&(dx, dy)| shifted_idx(&fig.coord(), dx, dy) == Some(coord.idx))
+        };
+        match fig.piece() {
+            Piece::P => get_pawn_attacks(fig).contains(&coord.idx),
+            Piece::R => slides_to(fig, coord, self, ROOK_DIRECTIONS),
+            Piece::B => slides_to(fig, coord, self, BISHOP_DIRECTIONS),
+            Piece::Q => slides_to(fig, coord, self, 0..8),
+            Piece::N => steps_to(&KNIGHT_STEPS),
+            Piece::K => steps_to(&KING_STEPS),
+        }
+    }
+
+    /// Check whether the king of the active color is attacked.
+    pub fn in_check(&self) -> bool {
+        self.figures
+            .iter()
+            .find(|f| (f.piece() == Piece::K) && (f.color() == self.color))
+            .is_some_and(|king| self.is_attacked(&king.coord(), self.color.next()))
+    }
+
+    /// Determine whether the game has ended on the board by checkmate or stalemate.
+    pub fn status(&self) -> Status {
+        if !self.legal_moves().is_empty() {
+            Status::Ongoing
+        } else if self.in_check() {
+            Status::Checkmate
+        } else {
+            Status::Stalemate
+        }
+    }
+
+    /// List all legal moves of the active color in UCI notation. The ordering is stable, such that
+    /// indices into the list can serve as labels, e.g. for policy networks:
+    /// - by the square of the moving figure, a8 to h1 as in the FEN,
+    /// - pawns push before they capture, towards the a-file first,
+    /// - knights and kings step in the order of `KNIGHT_STEPS` and `KING_STEPS`,
+    /// - sliding figures go along the `DIRECTIONS` of rays, nearest square first,
+    /// - promotions follow the order queen, rook, bishop, knight,
+    /// - castling comes last, short before long.
+    pub fn legal_moves(&self) -> Vec<String> {
+        let mut moves: Vec<String> = Vec::new();
+        for fig in self.position.iter().flatten() {
+            if fig.color() != self.color {
+                continue;
+            }
+            let mut targets = get_moves(fig, self);
+            if fig.piece() == Piece::P {
+                targets.extend(get_hits(fig, self));
+            }
+
+            for target in targets {
+                let promotes = (fig.piece() == Piece::P) && ((target.y == 0) | (target.y == 7));
+                let promotions = if promotes {
+                    vec![
+                        Some(Piece::Q),
+                        Some(Piece::R),
+                        Some(Piece::B),
+                        Some(Piece::N),
+                    ]
+                } else {
+                    vec![None]
+                };
+                for promoted_piece in promotions {
+                    let mut alt_game = self.clone();
+                    alt_game.make_move(*fig, target, promoted_piece);
+                    if !alt_game.in_check_of(self.color) {
+                        moves.push(alt_game.uci);
+                    }
+                }
+            }
+        }
+
+        for mv in ["O-O", "O-O-O"] {
+            if self.can_castle(mv) {
+                let mut alt_game = self.clone();
+                alt_game.castle(mv);
+                moves.push(alt_game.uci);
+            }
+        }
+
+        moves
+    }
+
+    /// Number of legal moves of the given color, counted as if it were its turn. En-Passant is
+    /// only available to the side to move.
+    pub fn mobility(&self, color: Color) -> usize {
+        if color == self.color {
+            return self.legal_moves().len();
+        }
+        let mut game = self.clone();
+        game.color = color;
+        game.en_passant = None;
+        game.legal_moves().len()
+    }
+
+    /// Check whether the king of the given color is attacked, e.g. after a move was made.
+    pub(crate) fn in_check_of(&self, color: Color) -> bool {
+        self.find_king(color)
+            .is_some_and(|king| self.is_attacked(&king.coord(), color.next()))
+    }
+
+    /// Check whether moving the figure to the target leaves the own king in check. The move is
+    /// tried on a copy of the board only, which is much cheaper than a copy of the whole game.
+    fn exposes_king(&self, fig: &Figure, target: &Coord) -> bool {
+        let mut position = self.position;
+        position[fig.square()] = None;
+        if (fig.piece() == Piece::P) && (self.en_passant == Some(*target)) {
+            let victim = target.idx + self.color.factor() * 8;
+            if valid_idx(victim) {
+                position[victim as usize] = None;
+            }
+        }
+        position[target.idx as usize] = Some(fig.move_to(target));
+
+        let king = if fig.piece() == Piece::K {
+            Some(*target)
+        } else {
+            self.find_king(self.color).map(|king| king.coord())
+        };
+        king.is_some_and(|king| is_attacked_on(&position, &king, self.color))
+    }
+
+    /// Castling right and the indexes of king and rook for castling of the given color.
+    fn castling_squares(&self, color: Color, long: bool) -> (bool, usize, usize) {
+        match (color, long) {
+            (Color::W, false) => (self.castling.white_kingside, 60, 63),
+            (Color::W, true) => (self.castling.white_queenside, 60, 56),
+            (Color::B, false) => (self.castling.black_kingside, 4, 7),
+            (Color::B, true) => (self.castling.black_queenside, 4, 0),
+        }
+    }
+
+    /// Check whether king and rook of the given color are on their initial squares.
+    fn has_castling_figures(&self, color: Color, long: bool) -> bool {
+        let (_, king_src, rook_src) = self.castling_squares(color, long);
+        let is_own = |idx: usize, piece: Piece| {
+            self.position[idx].is_some_and(|f| (f.piece() == piece) && (f.color() == color))
+        };
+        is_own(king_src, Piece::K) && is_own(rook_src, Piece::R)
+    }
+
+    /// The castling rights for which king and rook are still on their initial squares.
+    pub(crate) fn possible_castling(&self) -> Castling {
+        let is_possible =
+            |right: bool, color: Color, long: bool| right && self.has_castling_figures(color, long);
+        Castling {
+            white_kingside: is_possible(self.castling.white_kingside, Color::W, false),
+            white_queenside: is_possible(self.castling.white_queenside, Color::W, true),
+            black_kingside: is_possible(self.castling.black_kingside, Color::B, false),
+            black_queenside: is_possible(self.castling.black_queenside, Color::B, true),
+        }
+    }
+
+    /// Check castling rights, an empty path between king and rook and that the king neither
+    /// stands in, passes through nor lands on an attacked square.
+    fn can_castle(&self, mv: &str) -> bool {
+        self.check_castling(mv.contains("O-O-O")).is_ok()
+    }
+
+    /// Like `can_castle`, but tell which of the conditions fails first.
+    pub(crate) fn check_castling(&self, long: bool) -> Result<(), IllegalReason> {
+        let (rights, king_src, rook_src) = self.castling_squares(self.color, long);
+        if !rights || !self.has_castling_figures(self.color, long) {
+            return Err(IllegalReason::NoCastlingRight);
+        }
+
+        let (path, transit) = if long {
+            (rook_src + 1..king_src, king_src - 2..king_src + 1)
+        } else {
+            (king_src + 1..rook_src, king_src..king_src + 3)
+        };
+        if path.into_iter().any(|idx| self.position[idx].is_some()) {
+            return Err(IllegalReason::BlockedPath);
+        }
+        if transit
+            .into_iter()
+            .any(|idx| self.is_attacked(&BOARD[idx], self.color.next()))
+        {
+            return Err(IllegalReason::CastlingThroughCheck);
+        }
+        Ok(())
+    }
+
+    fn castle(&mut self, mv: &str) {
+        // prepare indexes with
+        let king_src: usize;
+        let king_tgt: usize;
+        let rook_src: usize;
+        let rook_tgt: usize;
+
+        // Get the coordinates of the involved king and rook.
+        if self.color == Color::B {
+            king_src = 4;
+            if mv.contains("O-O-O") {
+                rook_src = 0;
+                king_tgt = 2;
+                rook_tgt = 3;
+                self.uci = "e8c8".to_string();
+            } else {
+                rook_tgt = 5;
+                king_tgt = 6;
+                rook_src = 7;
+                self.uci = "e8g8".to_string();
+            }
+        } else {
+            king_src = 60;
+            if mv.contains("O-O-O") {
+                rook_src = 56;
+                king_tgt = 58;
+                rook_tgt = 59;
+                self.uci = "e1c1".to_string();
+            } else {
+                rook_tgt = 61;
+                king_tgt = 62;
+                rook_src = 63;
+                self.uci = "e1g1".to_string();
+            }
+        }
+
+        // get the according figures that will be involved.
+        let king = self.position[king_src].unwrap();
+        let rook = self.position[rook_src].unwrap();
+        let new_king = king.move_to(&BOARD[king_tgt]);
+        let new_rook = rook.move_to(&BOARD[rook_tgt]);
+
+        // update figures by removing king and rook and putting them into their new positions.
+        self.figures.remove(&king);
+        self.figures.remove(&rook);
+        self.figures.insert(new_king);
+        self.figures.insert(new_rook);
+
+        // update position by setting appropriate Figure Options.
+        self.position[king_src] = None;
+        self.position[rook_src] = None;
+        self.position[king_tgt] = Some(new_king);
+        self.position[rook_tgt] = Some(new_rook);
+
+        self.castling.castle(self.color);
+        self.en_passant = None;
+        self.end_ply(false);
+    }
+
+    /// Put a figure like "Nf3" or "ke8" on the board, replacing and returning the figure that was
+    /// on the square before. Pawns on the first or last rank and a second king of a color are
+    /// rejected. Castling rights and En-Passant are invalidated if the position does no longer
+    /// allow them, and the history restarts with the composed position.
+    pub fn put(&mut self, figstr: &str) -> Result<Option<Figure>, Error> {
+        if !Figure::is_valid(figstr) {
+            return Err(Error::InvalidEdit(format!("invalid figure: {}", figstr)));
+        }
+        let figure = Figure::from(figstr);
+        let coord = figure.coord();
+        if (figure.piece() == Piece::P) && ((coord.y == 0) || (coord.y == 7)) {
+            return Err(Error::InvalidEdit(format!(
+                "pawn on the first or last rank: {}",
+                figstr
+            )));
+        }
+        let is_second_king = (figure.piece() == Piece::K)
+            && self.figures.iter().any(|f| {
+                (f.piece() == Piece::K) && (f.color() == figure.color()) && (f.coord() != coord)
+            });
+        if is_second_king {
+            return Err(Error::InvalidEdit(format!(
+                "a king of that color is on the board: {}",
+                figstr
+            )));
+        }
+
+        let replaced = self.position[figure.square()];
+        if let Some(replaced) = replaced {
+            self.remove_figure(&replaced);
+        }
+        self.position[figure.square()] = Some(figure);
+        self.figures.insert(figure);
+        self.compose();
+        Ok(replaced)
+    }
+
+    /// Remove and return the figure on a square like "e4", if any. See `put` for the effects on
+    /// castling, En-Passant and history.
+    pub fn remove(&mut self, square: &str) -> Result<Option<Figure>, Error> {
+        if !Coord::is_valid(square) {
+            return Err(Error::InvalidEdit(format!("invalid square: {}", square)));
+        }
+        let removed = self.position[Coord::from(square).idx as usize];
+        if let Some(removed) = removed {
+            self.remove_figure(&removed);
+            self.compose();
+        }
+        Ok(removed)
+    }
+
+    /// Remove all figures, which also drops all castling rights and En-Passant.
+    pub fn clear(&mut self) {
+        self.position = [None; 64];
+        self.figures.clear();
+        self.compose();
+    }
+
+    /// Drop castling rights and En-Passant that an edited position does not allow anymore, and
+    /// start a new history, as the edit is no move.
+    fn compose(&mut self) {
+        self.castling = self.possible_castling();
+        if !self.is_en_passant_capturable() {
+            self.en_passant = None;
+        }
+        self.uci = "0000".to_string();
+        self.history = History::default();
+        self.record();
+    }
+
+    /// The king of the given color, which an edited position may lack.
+    fn find_king(&self, color: Color) -> Option<Figure> {
+        self.figures
+            .iter()
+            .find(|f| (f.piece() == Piece::K) & (f.color() == color))
+            .copied()
+    }
+
+    fn remove_figure(&mut self, figure: &Figure) {
+        self.figures.remove(figure);
+        self.position[figure.square()] = None;
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for Game {
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Game::from_fen(fen).map_err(Error::InvalidFen)
+    }
+
+    type Err = Error;
+}
+
+impl Game {
+    fn from_fen(fen: &str) -> Result<Self, String> {
+        // Split FEN and assign according variables.
+        let fen_parts: Vec<&str> = fen.split(' ').collect();
+        let [position_str, color_str, castling_str, ep_str, hmc_str, fmc_str] = fen_parts[..]
+        else {
+            return Err(format!("invalid FEN, expected six fields: {}", fen));
+        };
+
+        // Derive fields from Strings.
+        let position: OptFigures = fen_to_position(&position_str.to_string())?;
+        let color = match color_str {
+            "w" => Color::W,
+            "b" => Color::B,
+            _ => return Err(format!("invalid color: {}", color_str)),
+        };
+        let is_castling_valid = (castling_str == "-")
+            || (!castling_str.is_empty() && castling_str.chars().all(|c| "KQkq".contains(c)));
+        if !is_castling_valid {
+            return Err(format!("invalid castling rights: {}", castling_str));
+        }
+        let castling = Castling::from(castling_str);
+        let en_passant: Option<Coord> = match ep_str {
+            "-" => None,
+            _ if Coord::is_valid(ep_str) => Some(Coord::from(ep_str)),
+            _ => return Err(format!("invalid en passant square: {}", ep_str)),
+        };
+        // Drop a square that is not empty and behind a pawn of the opponent just pushed by two,
+        // as no move could have set it.
+        let en_passant = en_passant.filter(|ep| {
+            let ep_y = if color.is_white() { 5 } else { 2 };
+            (ep.y == ep_y)
+                && position[ep.idx as usize].is_none()
+                && position[(ep.idx + color.factor() * 8) as usize]
+                    .is_some_and(|f| (f.piece() == Piece::P) && (f.color() == color.next()))
+        });
+        let parse_clock = |clock: &str| {
+            clock
+                .parse::<u16>()
+                .map_err(|_| format!("invalid move clock: {}", clock))
+        };
+        let half_move_clock = parse_clock(hmc_str)?;
+        let full_move_clock = parse_clock(fmc_str)?;
+
+        Ok(Game::from_parts(
+            position,
+            color,
+            castling,
+            en_passant,
+            half_move_clock,
+            full_move_clock,
+        ))
+    }
+}
+
+/// Prefix the error of a replayed move with its ply (counting from 1), to locate it in a game,
+/// e.g. `game.play_move(mv).map_err(at_ply(3))`.
+pub fn at_ply<E: Display>(ply: usize) -> impl Fn(E) -> String {
+    move |e| format!("ply {}: {}", ply, e)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+fn valid_idx(idx: i8) -> bool {
+    (0..64).contains(&idx)
+}
+
+fn fen_to_position(fen: &Fen) -> Result<OptFigures, String> {
+    // Use intermediate structure to parse the FEN
+    let mut figures: OptFigures = [None; 64];
+    let invalid = || format!("invalid position: {}", fen);
+
+    let ranks: Vec<&str> = fen.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(invalid());
+    }
+    for (r, rank) in ranks.into_iter().enumerate() {
+        // count through the rank using i, which must end up at exactly 8 squares.
+        let mut i: usize = 0;
+        for l in rank.chars() {
+            if ('1'..='8').contains(&l) {
+                i += l.to_digit(10).unwrap() as usize;
+            } else if "PNBRQKpnbrqk".contains(l) && (i < 8) {
+                let color = if l.is_lowercase() { Color::B } else { Color::W };
+                figures[r * 8 + i] = Some(Figure::new(color, BOARD[r * 8 + i], Piece::from(l)));
+                i += 1_usize;
+            } else {
+                return Err(invalid());
+            }
+        }
+        if i != 8 {
+            return Err(invalid());
+        }
+    }
+
+    Ok(figures)
+}
+
+fn position_to_fen(position: OptFigures) -> Fen {
+    let mut fen = String::new();
+    write_position(&position, &mut fen);
+    fen
+}
+
+fn write_position(position: &OptFigures, fen: &mut Fen) {
+    // At several positions numbers have to be added. Thus, use a separate function.
+    fn unload_space(mut spacer: u8, fen: &mut Fen) -> u8 {
+        if spacer > 0 {
+            fen.push(char::from_digit(spacer as u32, 10).unwrap());
+            spacer = 0
+        }
+        spacer
+    }
+
+    // Basically, this function wanders through the position and derives letters.
+    let mut spacer: u8 = 0;
+    for (f, figure) in position.iter().enumerate() {
+        // Set row separators.
+        if (f > 0) & (f % 8 == 0) {
+            spacer = unload_space(spacer, fen);
+            fen.push('/')
+        }
+
+        // Either increase empty space counter (spacer) or set figure.
+        if let Some(figure) = figure {
+            spacer = unload_space(spacer, fen);
+            fen.push(figure.to_char());
+        } else {
+            spacer += 1
+        }
+    }
+
+    // Repeat writing the empty spaces if there are some:
+    unload_space(spacer, fen);
+}
+
+fn filter_mover(draw: &Draw, game: &Game) -> Result<Figure, String> {
+    let figs: FigSet = game
+        .figures
+        .iter()
+        .cloned()
+        .filter(|f| (f.color() == game.color) & (f.piece() == draw.piece))
+        .collect();
+    if figs.len() == 1 {
+        count("by_piece", |stats| &mut stats.by_piece);
+        Ok(figs.into_iter().next().unwrap())
+    } else {
+        filter_on_remainder(figs, draw, game)
+    }
+}
+
+fn filter_on_remainder(figures: FigSet, draw: &Draw, game: &Game) -> Result<Figure, String> {
+    let figs: FigSet = if draw.remainder_file.is_none() & draw.remainder_rank.is_none() {
+        figures
+    } else if draw.remainder_file.is_some() & draw.remainder_rank.is_some() {
+        figures
+            .into_iter()
+            .filter(|f| {
+                (f.coord().file == draw.remainder_file.unwrap())
+                    & (f.coord().rank == draw.remainder_rank.unwrap())
+            })
+            .collect()
+    } else if draw.remainder_file.is_some() {
+        figures
+            .into_iter()
+            .filter(|f| f.coord().file == draw.remainder_file.unwrap())
+            .collect()
+    } else if draw.remainder_rank.is_some() {
+        figures
+            .into_iter()
+            .filter(|f| f.coord().rank == draw.remainder_rank.unwrap())
+            .collect()
+    } else {
+        figures
+    };
+
+    if figs.len() == 1 {
+        count("by_remainder", |stats| &mut stats.by_remainder);
+        Ok(figs.into_iter().next().unwrap())
+    } else {
+        filter_on_moves(figs, draw, game)
+    }
+}
+
+fn filter_on_moves(figures: FigSet, draw: &Draw, game: &Game) -> Result<Figure, String> {
+    let figs: FigSet = if draw.is_hit {
+        figures
+            .into_iter()
+            .filter(|f| get_hits(f, game).contains(&draw.target))
+            .collect()
+    } else {
+        figures
+            .into_iter()
+            .filter(|f| get_moves(f, game).contains(&draw.target))
+            .collect()
+    };
+    // Only analyze pins if there is more than one candidate to choose from.
+    match figs.len() {
+        0 => {
+            count("unreachable", |stats| &mut stats.unreachable);
+            Err(format!("illegal move: {}", draw.san))
+        }
+        1 => {
+            count("by_moves", |stats| &mut stats.by_moves);
+            Ok(figs.into_iter().next().unwrap())
+        }
+        _ => {
+            count("by_pins", |stats| &mut stats.by_pins);
+            filter_on_pins(figs, draw, game)
+        }
+    }
+}
+
+fn filter_on_pins(figures: FigSet, draw: &Draw, game: &Game) -> Result<Figure, String> {
+    // store the kings coordinate of the current moving party, without a king nothing is pinned.
+    let king_coord = game.find_king(game.color).map(|king| king.coord());
+
+    // Only the board is needed to find pins, thus copy it instead of cloning the whole game.
+    let mut figs: Figures = Vec::new();
+    for fig in figures {
+        let mut position = game.position;
+        position[fig.square()] = None;
+        position[draw.target.idx as usize] = Some(fig.move_to(&draw.target));
+
+        if king_coord.is_none_or(|king| !is_exposed_to_sliders(&position, &king, game.color)) {
+            figs.push(fig);
+        }
+    }
+
+    // Never guess between several candidates, e.g. two pawns capturing onto the same square.
+    match figs[..] {
+        [fig] => Ok(fig),
+        [] => Err(format!("illegal move: {}", draw.san)),
+        _ => Err(format!("ambiguous move: {}", draw.san)),
+    }
+}
+
+/// Check whether a rook, bishop or queen of the opponent attacks the king along a free ray, i.e.
+/// whether the king is in check by a slider.
+fn is_exposed_to_sliders(position: &OptFigures, king: &Coord, color: Color) -> bool {
+    (0..8).any(|d| {
+        let slider = if ROOK_DIRECTIONS.contains(&d) {
+            Piece::R
+        } else {
+            Piece::B
+        };
+        ray(king, d)
+            .iter()
+            .find_map(|&ti| position[ti as usize])
+            .is_some_and(|f| {
+                (f.color() != color) && ((f.piece() == slider) || (f.piece() == Piece::Q))
+            })
+    })
+}
+
+/// Check whether the opponent attacks the king of the given color on the board.
+fn is_attacked_on(position: &OptFigures, king: &Coord, color: Color) -> bool {
+    let opponent = |ti: i8, piece: Piece| {
+        position[ti as usize].filter(|f| (f.color() != color) && (f.piece() == piece))
+    };
+    let around = |steps: &'static [(i8, i8)]| {
+        steps
+            .iter()
+            .filter_map(|&(dx, dy)| shifted_idx(king, dx, dy))
+    };
+    is_exposed_to_sliders(position, king, color)
+        || around(&KNIGHT_STEPS).any(|ti| opponent(ti, Piece::N).is_some())
+        || around(&KING_STEPS).any(|ti| opponent(ti, Piece::K).is_some())
+        // Pawns next to the king only attack it if they face it.
+        || around(&KING_STEPS)
+            .filter_map(|ti| opponent(ti, Piece::P))
+            .any(|pawn| get_pawn_attacks(&pawn).contains(&king.idx))
+}
+
+pub(crate) fn get_moves(fig: &Figure, game: &Game) -> Coords {
+    let coordis: CoordIdx = match fig.piece() {
+        Piece::P => get_pawn_moves(fig, game),
+        Piece::R => get_rook_moves(fig, game),
+        Piece::N => get_knight_moves(fig, game),
+        Piece::B => get_bishop_moves(fig, game),
+        Piece::Q => get_queen_moves(fig, game),
+        Piece::K => get_king_moves(fig, game),
+    };
+
+    coordis
+        .into_iter()
+        .map(|ci| BOARD[ci as usize])
+        .collect::<Coords>()
+}
+
+pub(crate) fn get_hits(fig: &Figure, game: &Game) -> Coords {
+    match fig.piece() {
+        Piece::P => get_pawn_hits(fig, game)
+            .into_iter()
+            .map(|ci| BOARD[ci as usize])
+            .collect::<Coords>(),
+        _ => get_moves(fig, game),
+    }
+}
+
+/// Squares diagonally in front of a pawn, regardless of whether they are occupied.
+fn get_pawn_attacks(fig: &Figure) -> CoordIdx {
+    let (ci, f) = (fig.coord().idx, fig.color().factor());
+
+    // Exclude squares that wrap around the board edge, e.g. from the h-file to the a-file.
+    [7, 9]
+        .into_iter()
+        .map(|i| ci - f * i)
+        .filter(|&ti| valid_idx(ti) && ((fig.coord().x - ti % 8).abs() == 1))
+        .collect()
+}
+
+fn get_pawn_hits(fig: &Figure, game: &Game) -> CoordIdx {
+    // prepare empty vec to be pushed with possible moves.
+    let mut coordix: CoordIdx = vec![];
+
+    // Add hits if appropriate.
+    for ti in get_pawn_attacks(fig) {
+        if game.position[ti as usize].is_some() {
+            if game.position[ti as usize].unwrap().color() != fig.color() {
+                coordix.push(ti);
+            }
+        } else if game.en_passant.is_some() && (game.en_passant.unwrap().idx == ti) {
+            coordix.push(ti);
+        }
+    }
+
+    coordix
+}
+
+fn get_pawn_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    // prepare empty vec to be pushed with possible moves.
+    let mut coordix: CoordIdx = vec![];
+    let (ci, f) = (fig.coord().idx, fig.color().factor());
+
+    // add the index of the square in front, if unblocked.
+    let ti: i8 = ci - f * 8; // target Index
+    if valid_idx(ti) && game.position[ti as usize].is_none() {
+        coordix.push(ti);
+    }
+
+    // if the pawn hasn't moved yet, add the square two apart, if unblocked.
+    //  Note: The square in front must be accessible to make the 2nd valid.
+    if (fig.color().is_white() & (fig.coord().y == 1))
+        | (fig.color().is_black() & (fig.coord().y == 6))
+    {
+        let tii: i8 = ci - f * 16;
+        if valid_idx(tii) && game.position[tii as usize].is_none() && !coordix.is_empty() {
+            coordix.push(tii);
+        }
+    }
+
+    coordix
+}
+
+fn get_knight_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    get_steps(fig, game, &KNIGHT_STEPS)
+}
+
+/// Index of the square that is shifted by the given file and rank deltas, see `Coord::shifted`.
+fn shifted_idx(coord: &Coord, dx: i8, dy: i8) -> Option<i8> {
+    coord.shifted(dx, dy).map(|c| c.idx)
+}
+
+/// Targets of single steps (as opposed to sliding), which are empty or held by the opponent.
+fn get_steps(fig: &Figure, game: &Game, steps: &[(i8, i8)]) -> CoordIdx {
+    steps
+        .iter()
+        .filter_map(|&(dx, dy)| shifted_idx(&fig.coord(), dx, dy))
+        .filter(|&ti| game.position[ti as usize].is_none_or(|f| f.color() != fig.color()))
+        .collect()
+}
+
+fn get_bishop_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    get_slides(fig, game, BISHOP_DIRECTIONS)
+}
+
+fn get_rook_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    get_slides(fig, game, ROOK_DIRECTIONS)
+}
+
+/// Targets along the precomputed rays in the given directions, up to and including the first
+/// occupied square, if it's held by the opponent.
+fn get_slides(fig: &Figure, game: &Game, directions: std::ops::Range<usize>) -> CoordIdx {
+    let mut coordix: CoordIdx = vec![];
+    let coord = fig.coord();
+    for d in directions {
+        for &ti in ray(&coord, d) {
+            match game.position[ti as usize] {
+                None => coordix.push(ti),
+                Some(other) => {
+                    if other.color() != fig.color() {
+                        coordix.push(ti);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    coordix
+}
+
+/// Check whether a sliding figure reaches the square along a ray in one of the given directions,
+/// like `get_slides` but without listing all targets.
+fn slides_to(fig: &Figure, coord: &Coord, game: &Game, directions: std::ops::Range<usize>) -> bool {
+    let Some(d) = direction_to(&fig.coord(), coord).filter(|d| directions.contains(d)) else {
+        return false;
+    };
+    for &ti in ray(&fig.coord(), d) {
+        if ti == coord.idx {
+            return true;
+        } else if game.position[ti as usize].is_some() {
+            return false;
+        }
+    }
+    false
+}
+
+fn get_queen_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    let mut coordix: CoordIdx = vec![];
+
+    // As the queen unions the moves from bishop and rook, mirror the union.
+    let bishop_coordix = get_bishop_moves(fig, game);
+    let rook_coordix = get_rook_moves(fig, game);
+
+    coordix.extend(bishop_coordix);
+    coordix.extend(rook_coordix);
+
+    coordix
+}
+
+fn get_king_moves(fig: &Figure, game: &Game) -> CoordIdx {
+    get_steps(fig, game, &KING_STEPS)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+fn coords_from_san(coords: Vec<&str>) -> Coords {
+    coords.into_iter().map(Coord::from).collect::<Coords>()
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_pawn_a2() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Pa2"), &game),
+        coords_from_san(Vec::from(["a3", "a4"]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_black_pawn_g7() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("pg7"), &game),
+        coords_from_san(Vec::from(["g6", "g5"]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_knight_b1() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Nb1"), &game),
+        coords_from_san(Vec::from(["a3", "c3"]))
+    );
+}
+
+#[test]
+fn check_king_and_knight_moves_on_edges() {
+    let game = Game::from_str("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+    let moves = |fig: &str| get_moves(&Figure::from(fig), &game);
+
+    assert_eq!(moves("Kh1"), coords_from_san(vec!["g2", "h2", "g1"]));
+    assert_eq!(
+        moves("Ka2"),
+        coords_from_san(vec!["a3", "b3", "b2", "a1", "b1"])
+    );
+    assert_eq!(
+        moves("kh5"),
+        coords_from_san(vec!["g6", "h6", "g5", "g4", "h4"])
+    );
+    assert_eq!(moves("Nh1"), coords_from_san(vec!["g3", "f2"]));
+    assert_eq!(moves("na8"), coords_from_san(vec!["c7", "b6"]));
+    assert_eq!(
+        moves("Nb4"),
+        coords_from_san(vec!["a6", "c6", "d5", "d3", "a2", "c2"])
+    );
+
+    // No step of any edge square may wrap around to the opposite side of the board.
+    let is_edge = |c: &&Coord| [0, 7].contains(&c.x) || [0, 7].contains(&c.y);
+    for &coord in BOARD.iter().filter(is_edge) {
+        for (piece, max_distance) in [('K', 1), ('N', 2)] {
+            let fig = Figure::from(&format!("{}{}", piece, coord)[..]);
+            for target in get_moves(&fig, &game) {
+                let (dx, dy) = ((target.x - coord.x).abs(), (target.y - coord.y).abs());
+                assert!(dx.max(dy) == max_distance, "{} {}", fig.piece(), target);
+                if piece == 'N' {
+                    assert_eq!(dx + dy, 3);
+                }
+            }
+        }
+        let n_king_moves = get_moves(&Figure::from(&format!("K{}", coord)[..]), &game).len();
+        let is_corner = [0, 7, 56, 63].contains(&coord.idx);
+        assert_eq!(n_king_moves, if is_corner { 3 } else { 5 });
+    }
+}
+
+#[test]
+fn check_sliding_moves_on_edges() {
+    let game = Game::from_str("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+    // A rook on the h-file must not continue on the a-file of the next rank and vice versa.
+    for coord in BOARD {
+        for piece in ['R', 'B', 'Q'] {
+            let fig = Figure::from(&format!("{}{}", piece, coord)[..]);
+            let targets = get_moves(&fig, &game);
+            let distinct: std::collections::HashSet<Coord> = targets.iter().copied().collect();
+            assert_eq!(distinct.len(), targets.len(), "{}", fig);
+            if piece == 'R' {
+                assert_eq!(targets.len(), 14);
+            }
+            for target in targets {
+                let (dx, dy) = ((target.x - coord.x).abs(), (target.y - coord.y).abs());
+                assert!((dx == 0) || (dy == 0) || (dx == dy), "{} {}", fig, target);
+            }
+        }
+    }
+}
+
+#[test]
+fn check_slider_attacks_match_moves() {
+    let game =
+        Game::from_str("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2NBPN2/P4PPP/R1BQ1RK1 w - - 0 9").unwrap();
+    for fig in game
+        .figures
+        .iter()
+        .filter(|f| [Piece::R, Piece::B, Piece::Q].contains(&f.piece()))
+    {
+        let targets = get_moves(fig, &game);
+        // Besides its moves, a slider attacks the first figure of its own color on each ray.
+        for coord in BOARD.into_iter().filter(|c| {
+            game.position[c.idx as usize].is_none_or(|other| other.color() != fig.color())
+        }) {
+            assert_eq!(
+                game.attacks(fig, &coord),
+                targets.contains(&coord),
+                "{} {}",
+                fig,
+                coord
+            );
+        }
+    }
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_bishop_c1() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Bc1"), &game),
+        coords_from_san(Vec::from([]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_black_rook_h8() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("rh8"), &game),
+        coords_from_san(Vec::from([]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_queen_d1() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Qd1"), &game),
+        coords_from_san(Vec::from([]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_king_e1() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Ke1"), &game),
+        coords_from_san(Vec::from([]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_bishop_a3() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Ba3"), &game),
+        coords_from_san(Vec::from(["b4", "c5", "d6", "e7"]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_black_bishop_a3() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("ba3"), &game),
+        coords_from_san(Vec::from(["b4", "c5", "d6", "b2"]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_white_rook_e4() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("Re4"), &game),
+        coords_from_san(Vec::from([
+            "e5", "e6", "e7", "d4", "c4", "b4", "a4", "f4", "g4", "h4", "e3"
+        ]))
+    );
+}
+
+#[test]
+fn check_moves_and_blocks_in_new_game_for_black_rook_e4() {
+    let game = Game::new();
+    assert_eq!(
+        get_moves(&Figure::from("re4"), &game),
+        coords_from_san(Vec::from([
+            "e5", "e6", "d4", "c4", "b4", "a4", "f4", "g4", "h4", "e3", "e2"
+        ]))
+    );
+}
+
+#[test]
+fn check_game_from_fen_base() {
+    let fen: String = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string();
+    let game = Game::from_str(&fen).unwrap();
+    assert_eq!(game, Game::new());
+}
+
+#[test]
+/// Final position from https://lichess.org/U1N9Qa74/black
+fn check_game_from_fen() {
+    let fen: String = "5rk1/1b2n1pp/4R3/1p3pN1/2pP4/r5PP/P4P2/2RQ2Kq w - - 1 24".to_string();
+    let game = Game::from_str(&fen).unwrap();
+
+    // Write down individual position by hand
+    let figures = [
+        "rf8", "kg8", "bb7", "ne7", "pg7", "ph7", "Re6", "pb5", "pf5", "Ng5", "pc4", "Pd4", "ra3",
+        "Pg3", "Ph3", "Pa2", "Pf2", "Rc1", "Qd1", "Kg1", "qh1",
+    ];
+    // Test easy translations first and use different paths to derive the same:
+    let mut position: OptFigures = [None; 64];
+    for fig_str in figures {
+        let fig = Figure::from(fig_str);
+        position[fig.coord().idx as usize] = Some(fig);
+    }
+
+    let empty_castle = Castling {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+
+    assert_eq!(game.color, Color::W);
+    assert_eq!(game.castling, empty_castle);
+    assert_eq!(game.en_passant, None);
+    assert_eq!(game.half_move_clock, 1);
+    assert_eq!(game.full_move_clock, 24);
+    assert_eq!(game.position, position);
+}
+
+#[test]
+/// Final position from https://lichess.org/U1N9Qa74/black
+fn check_fen_conversion_pt0() {
+    let fen = "5rk1/1b2n1pp/4R3/1p3pN1/2pP4/r5PP/P4P2/2RQ2Kq w - - 1 24".to_string();
+    let game = Game::from_str(&fen).unwrap();
+    assert_eq!(game.to_fen(), fen);
+}
+
+#[test]
+fn check_king_extraction() {
+    let game = Game::new();
+    assert_eq!(game.find_king(Color::W), Some(Figure::from("Ke1")));
+    assert_eq!(game.find_king(Color::B), Some(Figure::from("ke8")));
+}
+
+#[test]
+fn check_equality_across_move_orders() {
+    let mut game = Game::new();
+    let mut other = Game::new();
+    for (mv, other_mv) in [
+        ("Nf3", "Nc3"),
+        ("Nf6", "Nc6"),
+        ("Nc3", "Nf3"),
+        ("Nc6", "Nf6"),
+    ] {
+        game.play_move(mv).unwrap();
+        other.play_move(other_mv).unwrap();
+    }
+    assert_ne!(game.uci, other.uci);
+    assert_eq!(game, other);
+
+    // The clocks are part of the FEN, thus a repetition is not the same game.
+    other.play_move("Ng1").unwrap();
+    other.play_move("Ng8").unwrap();
+    other.play_move("Nf3").unwrap();
+    other.play_move("Nf6").unwrap();
+    assert_ne!(game, other);
+}
+
+#[test]
+fn check_moves_without_king() {
+    let mut game = Game::new();
+    game.remove("e1").unwrap();
+    assert_eq!(game.find_king(Color::W), None);
+    game.play_move("e4").unwrap();
+    game.play_move("e5").unwrap();
+    assert!(!game.legal_moves().is_empty());
+
+    // Without a king nothing is pinned, thus both rooks remain candidates.
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/R6R w - - 0 1").unwrap();
+    assert_eq!(
+        game.play_move("Rd1"),
+        Err(Error::IllegalMove("ambiguous move: Rd1".to_string()))
+    );
+    game.play_move("Rad1").unwrap();
+}
+
+#[test]
+fn check_filter_mover_detection_base() {
+    let game = Game::new();
+    let draw = Draw::from_str("Nc3").unwrap();
+    assert_eq!(Figure::from("Nb1"), filter_mover(&draw, &game).unwrap())
+}
+
+#[test]
+fn check_filter_mover_detection_pawn_hit() {
+    let game = Game::from_str("k7/8/2q3q1/1PP5/8/8/NR6/KN1N3B w - - 0 1").unwrap();
+    let draw = Draw::from_str("bxc6").unwrap();
+    assert_eq!(Figure::from("Pb5"), filter_mover(&draw, &game).unwrap())
+}
+
+#[test]
+fn check_filter_mover_detection_pawn_move() {
+    let game = Game::from_str("k7/8/2q3q1/1PP5/8/8/NR6/KN1N3B w - - 0 1").unwrap();
+    let draw = Draw::from_str("b6").unwrap();
+    assert_eq!(Figure::from("Pb5"), filter_mover(&draw, &game).unwrap())
+}
+
+#[test]
+fn check_mover_detection_with_remainder() {
+    let game = Game::from_str("k7/8/q1q3q1/1PP5/8/8/RR6/KN5B b - - 0 1").unwrap();
+    let draw = Draw::from_str("Qgg2").unwrap();
+
+    assert_eq!(Figure::from("qg6"), filter_mover(&draw, &game).unwrap());
+}
+
+#[test]
+fn check_mover_detection_with_pinned_queen() {
+    let game = Game::from_str("k7/8/q1q3q1/1PP5/8/8/RR6/KN5B b - - 0 1").unwrap();
+    let draw = Draw::from_str("Qd6").unwrap();
+
+    assert_eq!(Figure::from("qg6"), filter_mover(&draw, &game).unwrap());
+}
+
+#[test]
+fn check_mover_detection_with_movable_pinned_queen() {
+    let game = Game::from_str("k7/8/q1q3q1/1PP5/8/8/RR6/KN5B b - - 0 1").unwrap();
+    let draw = Draw::from_str("Qb7").unwrap();
+
+    assert_eq!(Figure::from("qc6"), filter_mover(&draw, &game).unwrap());
+}
+
+#[test]
+fn check_mover_detection_with_hit_from_queen() {
+    let game = Game::from_str("k3R3/8/q1q3q1/1PP5/8/8/RR6/KN5B b - - 0 1").unwrap();
+    let draw = Draw::from_str("Qxe8").unwrap();
+
+    assert_eq!(Figure::from("qg6"), filter_mover(&draw, &game).unwrap());
+}
+
+#[test]
+fn check_castling() {
+    let mut game = Game::from_str("4k2r/8/8/8/8/8/8/R3K3 w Qk - 0 1").unwrap();
+
+    game.play_move("O-O-O").unwrap();
+    game.play_move("O-O").unwrap();
+
+    assert_eq!(
+        game.figures,
+        BTreeSet::from_iter(["Kc1", "Rd1", "rf8", "kg8"].map(Figure::from))
+    );
+
+    assert_eq!(game.uci, "e8g8".to_string());
+}
+
+#[test]
+/// Giuoco Piano as in many Lichess games, where castling continues the half-move clock.
+fn check_clocks_on_castling() {
+    let mut game = Game::new();
+    for mv in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        game.clone().to_fen(),
+        "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4"
+    );
+    game.play_move("Nf6").unwrap();
+    assert_eq!(game.half_move_clock, 6);
+    assert_eq!(game.full_move_clock, 5);
+
+    // Castling after a pawn move counts from zero and clears a pending en passant square.
+    let mut game = Game::from_str("4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 2").unwrap();
+    game.play_move("O-O").unwrap();
+    assert_eq!(game.clone().to_fen(), "4k3/8/8/3pP3/8/8/8/5RK1 b - - 1 2");
+    game.play_move("Kd7").unwrap();
+    assert_eq!(game.clone().to_fen(), "8/3k4/8/3pP3/8/8/8/5RK1 w - - 2 3");
+}
+
+#[test]
+fn check_clock_overflow() {
+    // Legal moves are still listed, as tried out moves saturate the clocks.
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/R3K3 b Q - 65534 65535").unwrap();
+    assert_eq!(game.legal_moves().len(), 5);
+    assert_eq!(
+        game.play_move("Kd7"),
+        Err(Error::ClockOverflow(
+            "full-move clock overflows: 65535".to_string()
+        ))
+    );
+    assert_eq!(game.full_move_clock, 65535);
+
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/R3K3 w Q - 65534 100").unwrap();
+    game.play_uci("a1a2").unwrap();
+    assert_eq!(
+        game.play_uci("e8d7"),
+        Err(Error::ClockOverflow(
+            "half-move clock overflows: 65535".to_string()
+        ))
+    );
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/R3K3 w Q - 65535 100").unwrap();
+    assert_eq!(
+        game.play_move("O-O-O"),
+        Err(Error::ClockOverflow(
+            "half-move clock overflows: 65535".to_string()
+        ))
+    );
+    assert!(Game::from_str("4k3/8/8/8/8/8/8/R3K3 w Q - 0 65536").is_err());
+
+    // Pawn moves and captures reset the half-move clock, thus are still played.
+    let fen = "4k3/p7/8/8/8/8/8/R3K3 b Q - 65535 100";
+    let mut game = Game::from_str(fen).unwrap();
+    game.play_move("a5").unwrap();
+    assert_eq!(game.half_move_clock, 0);
+    let mut game = Game::from_str(fen).unwrap();
+    game.play_uci("a7a6").unwrap();
+    let mut game = Game::from_str("4k3/r7/8/8/8/8/8/R3K3 b Q - 65535 100").unwrap();
+    game.play_move("Rxa1+").unwrap();
+    assert_eq!(game.half_move_clock, 0);
+}
+
+#[test]
+fn check_impossible_en_passant_square() {
+    let fens = [
+        // No pawn was pushed in front of the square.
+        "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+        // The square is on the wrong rank for the side to move.
+        "4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1",
+        // The pushed pawn is of the side to move.
+        "4k3/8/8/4P3/8/8/8/4K3 w - e6 0 1",
+        // The square is occupied.
+        "4k3/8/4n3/4p3/8/8/8/4K3 w - e6 0 1",
+    ];
+    for fen in fens {
+        let game = Game::from_str(fen).unwrap();
+        assert_eq!(game.en_passant, None, "{}", fen);
+        // Moving onto the dropped square is no capture en passant.
+        assert!(!game.legal_moves().is_empty());
+    }
+    // The capture onto the bogus square has nothing to take.
+    let mut game = Game::from_str("4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1").unwrap();
+    assert!(game.play_uci("d5e6").is_err());
+    game.play_uci("d5d6").unwrap();
+
+    // A square that no pawn can capture on is still kept as written.
+    let game = Game::from_str("4k3/8/8/4p3/8/8/8/4K3 w - e6 0 1").unwrap();
+    assert_eq!(game.en_passant, Some(Coord::from("e6")));
+    assert_eq!(game.legal_moves().len(), 5);
+}
+
+#[test]
+fn check_castling_with_zeros() {
+    let mut game = Game::from_str("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    assert_eq!(game.san_to_uci("0-0-0+").unwrap(), "e1c1");
+    game.play_move("0-0-0+").unwrap();
+    assert_eq!(game.clone().to_fen(), "3k4/8/8/8/8/8/8/2KR4 b - - 1 1");
+
+    let mut game = Game::from_str("5k2/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert!(game.clone().play_move("0-0-0").is_err());
+    game.play_move("0-0+").unwrap();
+    assert_eq!(game.uci, "e1g1");
+}
+
+#[test]
+/// Capturing a rook on its initial square drops the right of the opponent to castle with it.
+fn check_castling_rights_on_rook_captures() {
+    for (mvs, castling) in [
+        (vec!["g3", "b6", "Bg2", "e6", "Bxa8"], "KQk"),
+        (vec!["b3", "g6", "Bb2", "Bh6", "Bxh8"], "KQq"),
+        (vec!["b4", "g6", "b5", "Bg7", "e3", "Bxa1"], "Kkq"),
+        (vec!["g4", "b6", "g5", "Bb7", "e3", "Bxh1"], "Qkq"),
+    ] {
+        let mut game = Game::new();
+        for mv in mvs {
+            game.play_move(mv).unwrap();
+        }
+        assert_eq!(game.clone().to_fen_list()[2], castling);
+    }
+
+    // The capturing rook loses its own right, too.
+    let mut game = Game::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    game.play_move("Rxa8+").unwrap();
+    assert_eq!(game.clone().to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+}
+
+#[test]
+fn check_en_passant_only_if_capturable() {
+    let play = |fen: &str, mv: &str| {
+        let mut game = Game::from_str(fen).unwrap();
+        game.play_move(mv).unwrap();
+        game
+    };
+
+    // An adjacent pawn can capture.
+    let game = play("4k3/3p4/8/4P3/8/8/8/K7 b - - 0 1", "d5");
+    assert_eq!(game.en_passant, Some(Coord::from("d6")));
+    assert!(game.legal_moves().contains(&"e5d6".to_string()));
+
+    // Both pawns leave the rank of the king, which would expose it to the rook.
+    let game = play("4k3/3p4/8/K3P2r/8/8/8/8 b - - 0 1", "d5");
+    assert_eq!(game.en_passant, None);
+    assert_eq!(
+        game.clone().to_fen_with(EnPassantMode::DoublePush),
+        "4k3/8/8/K2pP2r/8/8/8/8 w - d6 0 2"
+    );
+    assert_eq!(game.to_fen(), "4k3/8/8/K2pP2r/8/8/8/8 w - - 0 2");
+
+    // The capturing pawn is pinned diagonally.
+    let game = play("4k2b/3p4/8/4P3/8/2K5/8/8 b - - 0 1", "d5");
+    assert_eq!(game.en_passant, None);
+
+    // Without any adjacent pawn, only the double push mode writes the square.
+    let game = play(&Game::new().to_fen(), "e4");
+    assert_eq!(game.en_passant, None);
+    assert_eq!(
+        game.to_fen_with(EnPassantMode::DoublePush),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+}
+
+#[test]
+fn check_unplayable_moves() {
+    // The only pawn of the e-file is blocked by its own knight.
+    let fen = "4k3/8/8/8/8/4N3/4P3/4K3 w - - 0 1";
+    let mut game = Game::from_str(fen).unwrap();
+    assert_eq!(
+        game.play_move("e4"),
+        Err(Error::IllegalMove("illegal move: e4".to_string()))
+    );
+    assert_eq!(game.clone().to_fen(), fen);
+    for mv in ["e3", "Ke3", "Nxe5", "Nf6"] {
+        assert!(game.play_move(mv).is_err(), "{}", mv);
+    }
+
+    // The lenient mode trusts the source and teleports the pawn.
+    game.play_move_lenient("e4").unwrap();
+    assert_eq!(game.uci, "e2e4");
+}
+
+#[test]
+fn check_moves_exposing_the_king() {
+    let cases = [
+        // The king steps along the rank of the attacking rook.
+        ("4k3/8/8/8/8/8/8/r3K3 w - - 0 1", "Kd1"),
+        // The knight is pinned to its king.
+        ("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1", "Nf4"),
+        // The pawn move does not resolve the check.
+        ("4k3/8/8/8/8/8/7P/r3K3 w - - 0 1", "h3"),
+        // Nor against a knight or a pawn.
+        ("4k3/8/8/8/8/5n2/7P/4K3 w - - 0 1", "h3"),
+        ("4k3/8/8/8/8/8/3p3P/4K3 w - - 0 1", "h3"),
+        // The kings never stand next to each other.
+        ("8/8/8/8/8/3k4/8/4K3 w - - 0 1", "Ke2"),
+    ];
+    for (fen, mv) in cases {
+        let mut game = Game::from_str(fen).unwrap();
+        assert_eq!(
+            game.play_move(mv),
+            Err(Error::IllegalMove(format!("illegal move: {}", mv)))
+        );
+        assert_eq!(game.clone().to_fen(), fen);
+        // The lenient mode does not look after the king.
+        game.play_move_lenient(mv).unwrap();
+    }
+
+    // Capturing en passant removes both pawns from the rank of the king.
+    let mut game = Game::from_str("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1").unwrap();
+    assert!(game.play_move("bxc6").is_err());
+    game.play_move("Ka4").unwrap();
+}
+
+#[test]
+fn check_illegal_castling() {
+    let fens = [
+        // The rights are lost, although king and rook are in place.
+        "4k3/8/8/8/8/8/8/R3K2R w - - 0 1",
+        // The transit square f1 is attacked.
+        "4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+        // The path is blocked by the own bishop.
+        "4k3/8/8/8/8/8/8/R3KB1R w KQ - 0 1",
+        // The rook is missing, despite the rights.
+        "4k3/8/8/8/8/8/8/R3K3 w KQ - 0 1",
+    ];
+    for fen in fens {
+        let mut game = Game::from_str(fen).unwrap();
+        assert!(game.play_move("O-O").is_err(), "{}", fen);
+        // A rejected move leaves the position untouched.
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    // The lenient mode only needs king and rook on their squares.
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+    assert!(game.play_move("O-O").is_err());
+    game.play_move_lenient("O-O").unwrap();
+    assert_eq!(game.uci, "e1g1");
+    assert!(Game::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")
+        .unwrap()
+        .play_move_lenient("O-O")
+        .is_err());
+}
+
+#[test]
+fn check_fen_map() {
+    let game = Game::from_str("rnbqk2r/pppp1ppp/3b1n2/8/1PPPp3/P1N1P3/5PPP/R1BQKBNR b KQkq d3 0 6")
+        .unwrap();
+
+    let fen_map = game.to_fen_map();
+
+    assert_eq!(
+        fen_map["FEN"],
+        "rnbqk2r/pppp1ppp/3b1n2/8/1PPPp3/P1N1P3/5PPP/R1BQKBNR"
+    );
+    assert_eq!(fen_map["Color"], "b");
+    assert_eq!(fen_map["Castling"], "KQkq");
+    assert_eq!(fen_map["EnPassant"], "d3");
+    assert_eq!(fen_map["HalfMoveClock"], "0");
+    assert_eq!(fen_map["FullMoveClock"], "6");
+}
+
+#[test]
+/// Somehow, in a previous approach the initial construction of the figures went wrong,
+/// thus add a lengthy test...
+fn check_board() {
+    let game = Game::new();
+
+    assert_eq!(
+        game.position,
+        [
+            Some(Figure::from("ra8")),
+            Some(Figure::from("nb8")),
+            Some(Figure::from("bc8")),
+            Some(Figure::from("qd8")),
+            Some(Figure::from("ke8")),
+            Some(Figure::from("bf8")),
+            Some(Figure::from("ng8")),
+            Some(Figure::from("rh8")),
+            Some(Figure::from("pa7")),
+            Some(Figure::from("pb7")),
+            Some(Figure::from("pc7")),
+            Some(Figure::from("pd7")),
+            Some(Figure::from("pe7")),
+            Some(Figure::from("pf7")),
+            Some(Figure::from("pg7")),
+            Some(Figure::from("ph7")),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(Figure::from("Pa2")),
+            Some(Figure::from("Pb2")),
+            Some(Figure::from("Pc2")),
+            Some(Figure::from("Pd2")),
+            Some(Figure::from("Pe2")),
+            Some(Figure::from("Pf2")),
+            Some(Figure::from("Pg2")),
+            Some(Figure::from("Ph2")),
+            Some(Figure::from("Ra1")),
+            Some(Figure::from("Nb1")),
+            Some(Figure::from("Bc1")),
+            Some(Figure::from("Qd1")),
+            Some(Figure::from("Ke1")),
+            Some(Figure::from("Bf1")),
+            Some(Figure::from("Ng1")),
+            Some(Figure::from("Rh1")),
+        ]
+    );
+}
+
+#[test]
+/// https://lichess.org/hWMPaRcI
+fn check_playing_games_pt1() {
+    let mut game = Game::new();
+    let mvs = [
+        "c4", "c5", "Nc3", "e5", "e3", "Nf6", "Nf3", "Nc6", "b3", "e4", "Ng1", "d6", "d4", "Bg4",
+        "Qd2", "Bd7", "dxc5", "dxc5", "Nd5", "Nxd5", "cxd5", "Nb4", "Qc3", "b6", "Qc4", "Bc8",
+        "a3", "Na6", "Qxe4+", "Be7", "Bb2", "Bb7", "Rd1", "O-O", "Bc4", "Nc7", "Bd3", "g6", "Bc4",
+        "Bf6", "Bxf6", "Qxf6", "Ne2", "Rae8", "Qg4", "Rd8", "e4", "Bc8", "Qf4", "Qxf4", "Nxf4",
+        "b5", "d6", "Na6", "Bxb5", "Nb8", "e5", "a6", "Bc4", "Nc6", "O-O", "Nxe5", "Rfe1", "Nxc4",
+        "bxc4", "Bb7", "Re7", "Bc6", "Ra7", "Rfe8", "h3", "Ba4", "Rd2", "Re1+", "Kh2", "Re4",
+        "Rxa6", "Rxc4", "g3", "Rc2", "Rxc2", "Bxc2", "a4", "c4", "Rc6", "Bb3", "a5", "Bd1", "a6",
+        "g5", "Ne2", "Bxe2", "a7", "Bf3", "Rb6", "Ra8", "Rb8+", "Rxb8", "axb8=Q+", "Kg7", "d7",
+        "g4", "d8=Q", "gxh3", "Qd4+", "f6", "Qb7+", "Kg6", "Qxf3", "Kf7", "Qdxf6+", "Ke8", "Qe4+",
+        "Kd7", "Qfe6+", "Kc7", "Qd4", "Kb7", "Qed5+", "Kc7", "Q4xc4+", "Kb6",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(game.uci, "c7b6".to_string());
+    assert_eq!(
+        game.to_fen(),
+        "8/7p/1k6/3Q4/2Q5/6Pp/5P1K/8 w - - 1 62".to_string()
+    )
+}
+
+/// https://lichess.org/BpKMwGdB
+#[test]
+fn check_playing_games_pt2() {
+    let mut game = Game::new();
+    let mvs = [
+        "c4", "e5", "Nc3", "Bc5", "a3", "Nf6", "e3", "e4", "b4", "Bd6", "d4", "exd3", "Bxd3",
+        "Be5", "Bb2", "d6", "Nf3", "h6", "Bc2", "O-O", "Nxe5", "Nbd7", "Nxd7", "Bxd7", "Nd5",
+        "Bg4", "f3", "Bh5", "Nxf6+", "gxf6", "O-O", "Qe7", "Re1", "Rae8", "Qd2", "Bg6", "e4",
+        "Kh7", "a4", "Rg8", "a5", "Bh5", "Bc1", "Rg6", "a6", "b6", "Rb1", "Bxf3", "e5", "fxe5",
+        "Bxg6+", "Kxg6", "Qxh6+", "Kf5", "Rf1", "e4", "gxf3", "Rg8+", "Kh1", "Rg6", "fxe4+", "Ke6",
+        "Qh3+", "Ke5", "Qf5+", "Kd4", "Qxf7", "Qxe4+", "Qf3", "Qxb1", "Qe3+", "Kxc4", "Qf4+",
+        "Kb5", "Qf5+", "Qxf5", "Rxf5+", "Kxa6", "h4", "Rg4", "h5", "Rxb4", "Rf4", "Rb1", "Rf1",
+        "Rb5", "h6", "Rh5+", "Kg2", "Re5", "Rf7", "Re8", "h7", "Rh8", "Bb2", "Rxh7", "Rxh7", "c5",
+        "Kf2", "d5", "Ke2", "b5", "Kd2", "c4", "Kc3", "Kb6", "Ba3", "a6", "Rh5", "Kc6", "Rh6+",
+        "Kd7", "Kd4", "a5", "Kxd5", "c3", "Kc5", "b4", "Bc1", "b3", "Rh2", "a4", "Kb4", "b2",
+        "Bxb2", "cxb2", "Rxb2", "Kc6", "Kxa4", "Kd5", "Rb4", "Kc5", "Ka5", "Kd5", "Kb5", "Ke5",
+        "Rc4", "Kd5", "Kb4", "Ke5", "Kc5", "Kf5", "Rd4", "Ke5", "Kc4", "Kf5", "Kd5", "Kf6", "Re4",
+        "Kf5", "Kd4", "Kf6", "Re5", "Kg6", "Ke4", "Kf6", "Kf4", "Kg6", "Rf5", "Kg7", "Ke5", "Kg6",
+        "Ke4", "Kg7", "Ke5", "Kg6", "Ke6", "Kg7", "Rf6", "Kg8", "Ke7", "Kg7", "Ke6", "Kg8", "Kf5",
+        "Kg7", "Kg5", "Kh7", "Rg6", "Kh8", "Kf6", "Kh7", "Kf7", "Kh8", "Kf8", "Kh7", "Kf7", "Kh8",
+        "Rh6#",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(game.uci, "g6h6".to_string());
+    assert_eq!(game.to_fen(), "7k/5K2/7R/8/8/8/8/8 b - - 60 95".to_string())
+}
+
+/// https://lichess.org/K8nhk3Jx
+#[test]
+fn check_playing_games_pt3() {
+    let mut game = Game::new();
+    let mvs = [
+        "c4", "e5", "Nc3", "Nf6", "e3", "d5", "cxd5", "Nxd5", "Nxd5", "Qxd5", "b3", "Bb4", "Nf3",
+        "Bg4", "Bc4", "Qd6", "O-O", "e4", "h3", "exf3", "hxg4", "fxg2", "Qf3", "Qe5", "d4", "Qa5",
+        "Rd1", "Bc3", "Qxf7+", "Kd8", "Qd5+", "Qxd5", "Bxd5", "Bxa1", "Ba3", "Bc3", "Kxg2", "Nd7",
+        "f4", "c6", "Bc4", "Kc7", "e4", "Rae8", "e5", "Kb8", "Rd3", "Be1", "Bf7", "Ref8", "Bxf8",
+        "Rxf8", "Bc4", "Rxf4", "g5", "Rg4+", "Kf1", "Bh4", "e6", "Nb6", "Re3", "Bxg5", "Re5",
+        "Nc8", "d5", "cxd5", "Bxd5", "h6", "Kf2", "Ne7", "Bf3", "Rf4", "Kg3", "Rf5", "Rxf5",
+        "Nxf5+", "Kg4", "Ne3+", "Kh5", "b5", "Kg6", "Kc7", "Kxg7", "Kd6", "Be2", "a6", "a4", "b4",
+        "Bxa6", "Kxe6", "Bc8+", "Ke7", "a5", "Nd5", "Bh3", "Nc7", "Bf1", "Ke6", "a6", "Nxa6",
+        "Bxa6", "Kf5", "Bc4", "h5", "Bd3+", "Kg4", "Kg6", "h4", "Be2+", "Kf4", "Kh5", "h3", "Ba6",
+        "Bf6", "Bb7", "h2", "Kg6", "Bc3", "Kh5", "Kg3", "Kg5", "Kf2", "Kg4", "Kg1", "Kh3", "h1=Q+",
+        "Bxh1", "Kxh1", "Kg3", "Kg1", "Kf3", "Kf1", "Ke3", "Ke1", "Kd3", "Kd1", "Kc4", "Kc2",
+        "Kb5", "Kxb3", "Ka5", "Ka3", "Kb5", "b3", "Kc4", "Ba1", "Kd3", "b2", "Kc2", "Ka2", "Kc3",
+        "b1=Q+", "Kc4", "Qc1+", "Kb5", "Ka3", "Kb6", "Bd4+", "Kb7", "Ka4", "Ka6", "Qc6#",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(game.uci, "c1c6".to_string());
+    assert_eq!(
+        game.to_fen(),
+        "8/8/K1q5/8/k2b4/8/8/8 w - - 10 82".to_string()
+    );
+}
+
+/// https://lichess.org/9opx3qh7
+#[test]
+fn check_playing_games_pt4() {
+    let mut game = Game::new();
+    let mvs = [
+        "d4", "e5", "dxe5", "d6", "exd6", "Bxd6", "Nf3", "Nf6", "Nc3", "O-O", "a3", "Nc6", "e3",
+        "a6", "Be2", "h6", "O-O", "Ne5", "Bd2", "Nxf3+", "Bxf3", "Be5", "Rc1", "c6", "Qe2", "Qd6",
+        "Rfd1", "Bxh2+", "Kh1", "Be5", "e4", "Bxc3", "Bxc3", "Qe6", "Rd3", "Bd7", "Rcd1", "Rad8",
+        "Bxf6", "gxf6", "Rd6", "Qe7", "Rd1d2", "Be6", "Rxd8", "Rxd8", "Rxd8+", "Qxd8", "c4", "Qd4",
+        "c5", "Qxc5", "Qd2", "f5", "exf5", "Bxf5", "Qxh6", "Bg6", "Be4", "Bxe4", "Qh4", "Bg6",
+        "Qd8+", "Kg7", "Qc7", "b5", "b4", "Qc1+", "Kh2", "Qxa3", "Qe5+", "Kg8", "Qe8+", "Kg7",
+        "Qxc6", "Qxb4", "Qxa6", "Qh4+", "Kg1", "b4", "Qa1+", "Qf6", "Qa4", "Qc3", "f3", "b3",
+        "Qa3", "Qc2", "Kh2", "b2",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(game.uci, "b3b2".to_string());
+    assert_eq!(
+        game.to_fen(),
+        "8/5pk1/6b1/8/8/Q4P2/1pq3PK/8 w - - 0 46".to_string()
+    )
+}
+
+/// https://lichess.org/1hi3aveq
+#[test]
+fn check_playing_games_pt5() {
+    let mut game = Game::new();
+    let mvs = [
+        "e4", "g6", "d4", "d6", "Nf3", "c6", "h3", "Nf6", "Bg5", "Nxe4", "Qe2", "Bf5", "Nbd2",
+        "Qa5", "c3", "Nxd2", "Bxd2", "Nd7", "b4", "Qa3", "Ng5", "h5", "Qc4", "d5", "Qe2", "Qb2",
+        "Qd1", "Bc2", "Qc1", "Qxc1+", "Rxc1", "Ba4", "Bd3", "Nb6", "O-O", "Nc4", "Bxc4", "dxc4",
+        "Bf4", "Bh6", "Rfe1", "O-O", "Rxe7", "Rae8", "Rxb7", "f6", "Ne6", "Rxe6", "Bxh6", "Rf7",
+        "Rb8+", "Kh7", "Bf4", "g5", "Bd2", "Re2", "Be1", "Rfe7", "Kf1", "Bc2", "Rc8", "Bd3",
+        "Rxc6", "Rc2+", "Kg1", "Rxc1", "Rxf6", "h4", "g4", "Rexe1+", "Kg2", "Be4+", "f3", "Rc2#",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(game.uci, "c1c2".to_string());
+    assert_eq!(
+        game.to_fen(),
+        "8/p6k/5R2/6p1/1PpPb1Pp/2P2P1P/P1r3K1/4r3 w - - 1 38".to_string()
+    )
+}
+
+///https://lichess.org/qdwt3dtw
+#[test]
+fn check_playing_games_pt6() {
+    let mut game = Game::new();
+    let mvs = [
+        "e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "Nc3", "d5", "exd5", "Bf5", "dxc6", "Rb8", "Ng5",
+        "Qd4", "Bxf7+", "Kd8", "Ne6+", "Bxe6", "Bxe6", "bxc6", "d3", "Qc5", "Bg5", "Qe7", "Bc4",
+        "Rb4", "b3", "h6", "Bd2", "Rxc4", "bxc4", "Qe6", "Rb1", "Qc8", "f3", "Bc5", "Na4", "Bd4",
+        "Bb4", "c5", "Bxc5", "Kd7", "Bxd4", "Ke8", "Bxe5", "Ng4", "Bxg7", "Kf7", "Bxh8", "Qxh8",
+        "fxg4", "Qf6", "Qf3", "Ke7", "Qxf6+", "Kxf6", "O-O+",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(
+        game.to_fen(),
+        "8/p1p5/5k1p/8/N1P3P1/3P4/P1P3PP/1R3RK1 b - - 1 29".to_string()
+    )
+}
+
+/// https://lichess.org/ktey4t74
+#[test]
+fn check_playing_games_pt7() {
+    let mut game = Game::new();
+    let mvs = [
+        "d4", "d5", "c4", "e6", "Nc3", "Bb4", "e3", "dxc4", "Ne2", "Nf6", "a3", "Bxc3+", "Nxc3",
+        "O-O", "Bxc4", "a6", "e4", "b5", "Bb3", "e5", "Bg5", "exd4", "Nd5", "Bg4", "f3", "Be6",
+        "Bxf6", "gxf6", "Qxd4", "Bxd5", "Bxd5", "c6", "O-O", "cxd5", "exd5", "Nc6", "Qg4+", "Kh8",
+        "dxc6", "Qd6", "Rac1", "Rac8", "Qb4", "Qe5", "Rfe1", "Qg5", "c7", "Rg8", "g3", "f5", "Rc6",
+        "f4", "Qd4+", "Rg7", "Re8+", "Rxe8", "c8=Q", "Rg8", "Qxg8+", "Kxg8", "Rc8+",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(
+        game.to_fen(),
+        "2R3k1/5prp/p7/1p4q1/3Q1p2/P4PP1/1P5P/6K1 b - - 1 31".to_string()
+    )
+}
+
+#[test]
+/// https://lichess.org/tGpzk7yJ
+fn check_playing_games_pt8() {
+    let mut game = Game::new();
+    let mvs = [
+        "e4", "e5", "f4", "exf4", "Nf3", "Nf6", "e5", "Nh5", "Bc4", "g5", "h4", "Ng3", "Nxg5",
+        "Nxh1", "Bxf7+", "Ke7", "Nc3", "c6", "d4", "h6", "Qh5", "Bg7", "Nge4", "Qf8", "Nd6", "Na6",
+        "Bxf4", "Nb4", "Kd2", "Nf2", "Rf1", "Rh7", "Rxf2", "Bh8", "Bg5+", "hxg5", "Qxg5+",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(
+        game.to_fen(),
+        "r1b2q1b/pp1pkB1r/2pN4/4P1Q1/1n1P3P/2N5/PPPK1RP1/8 b - - 0 19".to_string()
+    )
+}
+
+#[test]
+/// https://lichess.org/j3sNSaKS
+fn check_playing_games_pt9() {
+    let mut game = Game::new();
+    let mvs = [
+        "e4", "e6", "d4", "d5", "Nc3", "Bb4", "e5", "Bxc3+", "bxc3", "b6", "Nf3", "Bb7", "Bd3",
+        "Nc6", "O-O", "Nge7", "Ba3", "f5", "exf6", "gxf6", "Re1", "Qd7", "Qe2", "Nd8", "Bxe7",
+        "Qxe7", "a4", "Bc6", "a5", "Kf7", "axb6", "axb6", "Rxa8", "Bxa8", "Nd2", "Kf8", "c4",
+        "Rg8", "cxd5", "Bxd5", "Be4", "Bxe4", "Qxe4", "f5", "Qe5", "Qg5", "g3", "Qxd2", "Qf6+",
+        "Nf7", "Rxe6", "Qxc2", "Re7", "Qc1+", "Kg2", "Rg7", "Rd7", "Kg8", "Qe7", "Qc6+", "Kg1",
+        "h6", "Rxc7", "Qd6", "Qe8+", "Qf8", "Qd7", "Ng5", "Qd5+", "Kh7", "Rxg7+", "Kxg7", "Qb7+",
+        "Qf7", "Qxb6", "Qe6", "Qc5", "Nf3+", "Kg2", "Qe4", "Kh3", "Ng5#",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(
+        game.to_fen(),
+        "8/6k1/7p/2Q2pn1/3Pq3/6PK/5P1P/8 w - - 7 42".to_string()
+    )
+}
+
+#[test]
+/// https://lichess.org/kz3z6c79
+fn check_playing_games_pt10() {
+    let mut game = Game::new();
+    let mvs = [
+        "d4", "Nf6", "c4", "e6", "Nc3", "b6", "e4", "Bb4", "e5", "Ng8", "Nf3", "Ne7", "Bg5", "h6",
+        "Bh4", "Bb7", "a3", "Bxc3+", "bxc3", "g5", "Bg3", "Nf5", "Bd3", "Nxg3", "hxg3", "Na6",
+        "Bc2", "Qe7", "Qd2", "O-O-O", "a4", "c5", "O-O", "Nc7", "a5", "b5", "cxb5", "Nxb5", "c4",
+        "Nc7", "a6", "Bc6", "Ba4", "Be4", "Qa5", "Na8", "dxc5", "h5", "Nd4", "h4", "Nb5", "d5",
+        "cxd6", "Qd7", "Nd4", "Qc7", "dxc7", "Rxd4", "gxh4", "Rxh4", "Rac1", "Nxc7", "Qc5", "Ba8",
+        "Qxa7", "Rh8", "Qxd4",
+    ];
+
+    for mv in mvs {
+        game.play_move(mv).unwrap();
+    }
+
+    assert_eq!(
+        game.to_fen(),
+        "b1k4r/2n2p2/P3p3/4P1p1/B1PQ4/8/5PP1/2R2RK1 b - - 0 34".to_string()
+    )
+}
+
+#[test]
+fn check_legal_moves_in_new_game() {
+    let moves = Game::new().legal_moves();
+    assert_eq!(moves.len(), 20);
+    assert_eq!(&moves[..4], &["a2a3", "a2a4", "b2b3", "b2b4"]);
+    assert_eq!(&moves[16..], &["b1a3", "b1c3", "g1f3", "g1h3"]);
+}
+
+#[test]
+fn check_legal_move_ordering() {
+    let game = Game::from_str("4k3/1P6/8/8/8/8/8/R3K1N1 w Q - 0 1").unwrap();
+    assert_eq!(
+        game.legal_moves(),
+        [
+            "b7b8q", "b7b8r", "b7b8b", "b7b8n", "a1a2", "a1a3", "a1a4", "a1a5", "a1a6", "a1a7",
+            "a1a8", "a1b1", "a1c1", "a1d1", "e1d2", "e1e2", "e1f2", "e1d1", "e1f1", "g1f3", "g1h3",
+            "g1e2", "e1c1"
+        ]
+    );
+}
+
+#[test]
+fn check_legal_moves_respect_pins_and_checks() {
+    // The knight on d2 is pinned and the king may not step into the rook's file.
+    let game = Game::from_str("3rk3/8/8/8/8/8/3N4/3K4 w - - 0 1").unwrap();
+    let mut moves = game.legal_moves();
+    moves.sort();
+    assert_eq!(moves, vec!["d1c1", "d1c2", "d1e1", "d1e2"]);
+    assert!(!game.in_check());
+}
+
+#[test]
+fn check_legal_moves_with_castling_and_promotion() {
+    let game = Game::from_str("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let moves = game.legal_moves();
+    assert!(moves.contains(&"e1g1".to_string()));
+    assert!(moves.contains(&"e1c1".to_string()));
+    for promo in ["b7b8q", "b7b8r", "b7b8b", "b7b8n", "b7a8q"] {
+        assert!(moves.contains(&promo.to_string()));
+    }
+
+    // castling through an attacked square is not allowed.
+    let game = Game::from_str("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+    let moves = game.legal_moves();
+    assert!(!moves.contains(&"e1g1".to_string()));
+    assert!(moves.contains(&"e1c1".to_string()));
+}
+
+#[test]
+fn check_pawn_double_step_is_blocked_on_target() {
+    let game = Game::from_str("4k3/8/8/8/7q/8/7P/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        get_moves(&Figure::from("Ph2"), &game),
+        coords_from_san(Vec::from(["h3"]))
+    );
+}
+
+#[test]
+fn check_pawn_hits_do_not_wrap_around() {
+    let game = Game::from_str("4k3/8/8/8/8/8/p6P/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        get_hits(&Figure::from("Ph2"), &game),
+        coords_from_san(Vec::from([]))
+    );
+}
+
+#[test]
+fn check_play_uci() {
+    let mut game = Game::new();
+    for mv in [
+        "e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "g1f3", "c6b5", "e1g1",
+    ] {
+        game.play_uci(mv).unwrap();
+    }
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkb1r/pp2pppp/5n2/1p1P4/8/5N2/PPPP1PPP/RNBQ1RK1 b kq - 1 5"
+    );
+}
+
+#[test]
+fn check_play_uci_errors() {
+    let mut game = Game::new();
+    assert!(game.play_uci("e2").is_err());
+    assert!(game.play_uci("e7e5").is_err());
+    assert!(game.play_uci("e2e5").is_err());
+    assert!(game.play_uci("e2e4x").is_err());
+    assert!(game.play_uci("e1g1").is_err());
+    assert_eq!(
+        game.play_uci("g1f3q"),
+        Err(Error::IllegalMove(
+            "invalid UCI move g1f3q: invalid promotion".to_string()
+        ))
+    );
+    assert!(game.play_uci("e2e3q").is_err());
+    assert_eq!(game, Game::new());
+
+    let mut game = Game::from_str("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(game.play_uci("a7a8").is_err());
+    game.play_uci("a7a8q").unwrap();
+
+    // The knight on e2 is pinned, and the king must not stay on the first rank.
+    let mut game = Game::from_str("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        game.play_uci("e2f4"),
+        Err(Error::IllegalMove(
+            "invalid UCI move e2f4: leaves the king in check".to_string()
+        ))
+    );
+    let mut game = Game::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    assert!(game.play_uci("e1d1").is_err());
+    game.play_uci("e1d2").unwrap();
+}
+
+#[test]
+fn check_play_lan() {
+    let mut san_game = Game::new();
+    let mut lan_game = Game::new();
+    for (san, lan) in [
+        ("e4", "e2-e4"),
+        ("d5", "d7-d5"),
+        ("exd5", "e4xd5"),
+        ("Nf6", "Ng8-f6"),
+        ("Bb5+", "Bf1-b5+"),
+        ("c6", "c7-c6"),
+        ("dxc6", "d5xc6"),
+        ("Qb6", "Qd8-b6"),
+        ("cxb7+", "c6xb7+"),
+        ("Kd8", "Ke8-d8"),
+        ("bxa8=Q", "b7xa8=Q"),
+    ] {
+        san_game.play_move(san).unwrap();
+        lan_game.play_move(lan).unwrap();
+        assert_eq!(lan_game, san_game);
+    }
+}
+
+#[test]
+fn check_play_localized_move() {
+    let mut game = Game::new();
+    let mut localized_game = Game::new();
+    let german = NotationConfig::german();
+    for (san, localized) in [
+        ("e4", "e4"),
+        ("e5", "e5"),
+        ("Nf3", "Sf3"),
+        ("Nc6", "Sc6"),
+        ("Bb5", "Lb5"),
+        ("a6", "a6"),
+        ("O-O", "O-O"),
+    ] {
+        game.play_move(san).unwrap();
+        localized_game
+            .play_localized_move(localized, &german)
+            .unwrap();
+    }
+    assert_eq!(localized_game, game);
+}
+
+#[test]
+fn check_diagrams() {
+    let mut game = Game::new();
+    game.play_move("e4").unwrap();
+    assert_eq!(
+        game.to_ascii(),
+        "8 r n b q k b n r\n\
+         7 p p p p p p p p\n\
+         6 . . . . . . . .\n\
+         5 . . . . . . . .\n\
+         4 . . . . P . . .\n\
+         3 . . . . . . . .\n\
+         2 P P P P . P P P\n\
+         1 R N B Q K B N R\n  \
+         a b c d e f g h\n\
+         Black to move"
+    );
+    assert!(game.to_unicode().starts_with("8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜\n"));
+    assert!(game.to_unicode().contains("\n4 · · · · ♙ · · ·\n"));
+}
+
+#[test]
+fn check_san_to_uci() {
+    let game = Game::from_str("r3k2r/8/8/8/8/8/1P6/R3K1NR w KQkq - 0 1").unwrap();
+    assert_eq!(game.san_to_uci("Nf3").unwrap(), "g1f3");
+    assert_eq!(game.san_to_uci("b4").unwrap(), "b2b4");
+    assert_eq!(game.san_to_uci("O-O-O+").unwrap(), "e1c1");
+    assert_eq!(game.san_to_uci("Rxa8+").unwrap(), "a1a8");
+    assert!(game.san_to_uci("O-O").is_err());
+    assert!(game.san_to_uci("Nf4").is_err());
+    assert!(game.san_to_uci("junk").is_err());
+
+    let game = Game::from_str("4k3/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+    assert!(game
+        .san_to_uci("Rd1")
+        .unwrap_err()
+        .message()
+        .contains("ambiguous"));
+    assert_eq!(game.san_to_uci("Rhd1").unwrap(), "h1d1");
+}
+
+#[test]
+fn check_uci_to_san() {
+    let game = Game::from_str("r3k2r/8/8/8/8/8/1P6/R3K1NR w KQkq - 0 1").unwrap();
+    assert_eq!(game.uci_to_san("g1f3").unwrap(), "Nf3");
+    assert_eq!(game.uci_to_san("e1c1").unwrap(), "O-O-O");
+    assert_eq!(game.uci_to_san("a1a8").unwrap(), "Rxa8+");
+    assert!(game.uci_to_san("e1g1").is_err());
+
+    // Disambiguation by file, by rank and by both.
+    let game = Game::from_str("4k3/8/8/8/8/N3N3/8/N3K3 w - - 0 1").unwrap();
+    assert_eq!(game.uci_to_san("e3c2").unwrap(), "Nec2");
+    assert_eq!(game.uci_to_san("a1c2").unwrap(), "N1c2");
+    assert_eq!(game.uci_to_san("a3c2").unwrap(), "Na3c2");
+
+    // Promotion with capture and checkmate, as well as en passant.
+    let game = Game::from_str("1r4k1/P4ppp/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+    assert_eq!(game.uci_to_san("a7b8q").unwrap(), "axb8=Q#");
+    let game = Game::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert_eq!(game.uci_to_san("e5d6").unwrap(), "exd6");
+}
+
+#[test]
+fn check_attackers_of() {
+    let game = Game::from_str("4k3/8/8/3p4/4R3/2N5/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        game.attackers_of(&Coord::from("d5"), Color::W),
+        vec![Figure::from("Nc3")]
+    );
+    assert_eq!(
+        game.attackers_of(&Coord::from("e4"), Color::B),
+        vec![Figure::from("pd5")]
+    );
+    assert_eq!(
+        game.attackers_of(&Coord::from("e2"), Color::W),
+        vec![
+            Figure::from("Re4"),
+            Figure::from("Nc3"),
+            Figure::from("Ke1")
+        ]
+    );
+
+    // Figures of every kind defend the squares of their own side.
+    assert_eq!(
+        game.attackers_of(&Coord::from("e4"), Color::W),
+        vec![Figure::from("Nc3")]
+    );
+    assert_eq!(
+        game.attackers_of(&Coord::from("c3"), Color::W),
+        Vec::<Figure>::new()
+    );
+    let game = Game::from_str("4k3/8/8/3p4/2p1R3/1N6/2K5/8 b - - 0 1").unwrap();
+    assert_eq!(
+        game.attackers_of(&Coord::from("c4"), Color::B),
+        vec![Figure::from("pd5")]
+    );
+    assert_eq!(
+        game.attackers_of(&Coord::from("c4"), Color::W),
+        vec![Figure::from("Re4")]
+    );
+    assert_eq!(
+        game.attackers_of(&Coord::from("b3"), Color::W),
+        vec![Figure::from("Kc2")]
+    );
+}
+
+#[test]
+fn check_promotions() {
+    // (FEN, SAN, expected FEN)
+    let cases = [
+        // Underpromotions.
+        (
+            "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+            "e8=N",
+            "4N3/8/8/8/8/8/k7/4K3 b - - 0 1",
+        ),
+        (
+            "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+            "e8=R",
+            "4R3/8/8/8/8/8/k7/4K3 b - - 0 1",
+        ),
+        (
+            "4k3/8/8/8/8/8/1p6/4K3 b - - 0 1",
+            "b1=B",
+            "4k3/8/8/8/8/8/8/1b2K3 w - - 0 2",
+        ),
+        // Capture-promotions on both wings, where two pawns attack the same square.
+        (
+            "1r2k3/P1P5/8/8/8/8/8/4K3 w - - 0 1",
+            "axb8=Q+",
+            "1Q2k3/2P5/8/8/8/8/8/4K3 b - - 0 1",
+        ),
+        (
+            "1r2k3/P1P5/8/8/8/8/8/4K3 w - - 0 1",
+            "cxb8=R+",
+            "1R2k3/P7/8/8/8/8/8/4K3 b - - 0 1",
+        ),
+        (
+            "4k3/8/8/8/8/8/5p1p/K5R1 b - - 0 1",
+            "hxg1=N",
+            "4k3/8/8/8/8/8/5p2/K5n1 w - - 0 2",
+        ),
+        (
+            "4k3/8/8/8/8/8/5p1p/K5R1 b - - 0 1",
+            "fxg1=Q+",
+            "4k3/8/8/8/8/8/7p/K5q1 w - - 0 2",
+        ),
+        // Promotion with check and with mate.
+        (
+            "3r2k1/4Pppp/8/8/8/8/8/6K1 w - - 0 1",
+            "exd8=Q#",
+            "3Q2k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+        ),
+        (
+            "3r4/4P1k1/8/8/8/8/8/6K1 w - - 0 1",
+            "exd8=N",
+            "3N4/6k1/8/8/8/8/8/6K1 b - - 0 1",
+        ),
+        (
+            "8/2k1P3/8/8/8/8/8/6K1 w - - 0 1",
+            "e8=N+",
+            "4N3/2k5/8/8/8/8/8/6K1 b - - 0 1",
+        ),
+        // Promotion without the equal sign.
+        (
+            "1r2k3/P1P5/8/8/8/8/8/4K3 w - - 0 1",
+            "axb8Q+",
+            "1Q2k3/2P5/8/8/8/8/8/4K3 b - - 0 1",
+        ),
+    ];
+    for (fen, san, expected) in cases {
+        let mut game = Game::from_str(fen).unwrap();
+        let uci = game.san_to_uci(san).unwrap();
+        assert_eq!(game.uci_to_san(&uci).unwrap(), san.replace("b8Q", "b8=Q"));
+        game.play_move(san).unwrap();
+        assert_eq!(game.uci, uci);
+        assert_eq!(game.to_fen(), expected, "{}", san);
+    }
+
+    // Without the file, both pawns could capture and no pawn must be guessed.
+    let mut game = Game::from_str("1r2k3/P1P5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        game.play_move("xb8=Q"),
+        Err(Error::IllegalMove("ambiguous move: xb8=Q".to_string()))
+    );
+    assert!(game.play_move("a8").is_err());
+}
+
+#[test]
+fn check_normalized_fen() {
+    let mut game = Game::new();
+    for mv in ["e4", "Nf6", "e5", "d5"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        game.normalized_fen(),
+        "rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6"
+    );
+
+    // A FEN may set the En-Passant square after every double push and keep stale castling rights.
+    let game = Game::from_str("r3k2r/8/8/8/4P3/8/8/R3K3 b KQkq e3 0 1").unwrap();
+    assert_eq!(game.normalized_fen(), "r3k2r/8/8/8/4P3/8/8/R3K3 b Qkq -");
+
+    // The capturing pawn would expose its king, which is pinned along the rank.
+    let game = Game::from_str("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1").unwrap();
+    assert_eq!(game.normalized_fen(), "8/8/8/K2pP2r/8/8/8/7k w - -");
+
+    // A bogus square without a pawn in front of it is dropped as well.
+    let game = Game::from_str("4k3/8/8/8/8/8/8/4K3 w - e6 0 1").unwrap();
+    assert_eq!(game.normalized_fen(), "4k3/8/8/8/8/8/8/4K3 w - -");
+}
+
+#[test]
+fn check_position_composition() {
+    let mut game = Game::new();
+    assert_eq!(game.remove("h1").unwrap(), Some(Figure::from("Rh1")));
+    assert_eq!(game.remove("h3").unwrap(), None);
+    assert_eq!(game.put("Qd4").unwrap(), None);
+    assert_eq!(game.put("ba8").unwrap(), Some(Figure::from("ra8")));
+    assert_eq!(
+        game.clone().to_fen(),
+        "bnbqkbnr/pppppppp/8/8/3Q4/8/PPPPPPPP/RNBQKBN1 w Qk - 0 1"
+    );
+    assert_eq!(game.history().len(), 1);
+
+    assert!(game.put("Pe8").is_err());
+    assert!(game.put("Kd3").is_err());
+    assert!(game.put("Xe4").is_err());
+    assert!(game.remove("i9").is_err());
+
+    // A king may be put on the square of its own king, or after removing it.
+    assert!(game.put("Ke1").is_ok());
+    game.remove("e1").unwrap();
+    assert!(game.put("Kd3").is_ok());
+    assert_eq!(game.castling.to_string(), "k");
+
+    game.clear();
+    game.put("Ke1").unwrap();
+    game.put("ke8").unwrap();
+    game.put("Ra1").unwrap();
+    assert_eq!(game.clone().to_fen(), "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    game.play_move("Ra8+").unwrap();
+    assert!(game.in_check());
+}
+
+#[test]
+fn check_en_passant_after_composition() {
+    let mut game = Game::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    game.put("Nc3").unwrap();
+    assert_eq!(game.en_passant, Some(Coord::from("d6")));
+    game.remove("e5").unwrap();
+    assert_eq!(game.en_passant, None);
+}
+
+#[test]
+fn check_mobility() {
+    let mut game = Game::new();
+    assert_eq!(game.mobility(Color::W), 20);
+    assert_eq!(game.mobility(Color::B), 20);
+    game.play_move("e4").unwrap();
+    assert_eq!(game.mobility(Color::B), 20);
+    assert_eq!(game.mobility(Color::W), 30);
+
+    // No legal moves in a stalemate.
+    let game = Game::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(game.mobility(Color::B), 0);
+}
+
+#[test]
+fn check_uci_position() {
+    let mut game = Game::new();
+    assert_eq!(game.uci_position(), "position startpos");
+    for mv in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        game.uci_position(),
+        "position startpos moves g1f3 g8f6 f3g1 f6g8"
+    );
+
+    let mut game = Game::from_str("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    game.play_move("e4").unwrap();
+    assert_eq!(
+        game.uci_position(),
+        "position fen 4k3/8/8/8/8/8/4P3/4K3 w - - 0 1 moves e2e4"
+    );
+}
+
+#[test]
+fn check_games_replay_concurrently() {
+    let lines = [
+        ["e4", "c5", "Nf3", "d6", "d4", "cxd4"],
+        ["d4", "Nf6", "c4", "e6", "Nc3", "Bb4"],
+        ["c4", "e5", "Nc3", "Nf6", "g3", "d5"],
+        ["Nf3", "d5", "g3", "Nf6", "Bg2", "e6"],
+    ];
+    let replay = |start: &Game, line: &[&str]| {
+        let mut game = start.clone();
+        line.iter()
+            .map(|mv| {
+                game.play_move(mv).unwrap();
+                game.clone().to_fen()
+            })
+            .collect::<Vec<String>>()
+    };
+
+    // All threads borrow the same start position and agree with a sequential replay.
+    let start = Game::new();
+    let fens: Vec<Vec<String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = lines
+            .iter()
+            .map(|line| scope.spawn(|| replay(&start, line)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (line, fens) in lines.iter().zip(fens) {
+        assert_eq!(fens, replay(&start, line));
+    }
+
+    // Games with a history can be moved to other threads and continued there.
+    let mut game = Game::new();
+    game.play_move("e4").unwrap();
+    let game = std::thread::spawn(move || {
+        game.play_move("e5").unwrap();
+        game
+    })
+    .join()
+    .unwrap();
+    assert_eq!(game.history().len(), 3);
+}
+
+#[test]
+fn check_ply() {
+    let mut game = Game::new();
+    assert_eq!(game.ply(), 0);
+    for mv in ["e4", "e5", "Nf3"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(game.ply(), 3);
+    assert_eq!(
+        Game::from_str("4k3/8/8/8/8/8/8/4K3 b - - 12 40")
+            .unwrap()
+            .ply(),
+        79
+    );
+    assert_eq!(
+        Game::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 0")
+            .unwrap()
+            .ply(),
+        0
+    );
+}
+
+#[test]
+fn check_fork_and_truncate() {
+    let mut game = Game::new();
+    for mv in ["e4", "d5", "exd5", "Qxd5", "Nc3"] {
+        game.play_move(mv).unwrap();
+    }
+    let mut variation = game.fork();
+    variation.truncate(2).unwrap();
+    assert_eq!(
+        variation.clone().to_fen(),
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+    variation.play_move("e5").unwrap();
+    variation.play_move("f5").unwrap();
+    assert_eq!(variation.en_passant, Some(Coord::from("f6")));
+    assert_eq!(variation.history().len(), 5);
+    assert_eq!(variation.history()[2], game.history()[2]);
+
+    // The original game is unaffected, and cannot be truncated beyond its plies.
+    assert_eq!(game.history().len(), 6);
+    assert_eq!(
+        game.truncate(6),
+        Err(Error::PlyOutOfRange(
+            "cannot truncate to ply 6, only 5 played".to_string()
+        ))
+    );
+    game.truncate(5).unwrap();
+    assert_eq!(game.uci, "b1c3");
+}
+
+#[test]
+fn check_play_moves_of_variants() {
+    let mut game = Game::new();
+    for mv in ["N@f3", "--", "Z0"] {
+        assert_eq!(
+            game.play_move(mv),
+            Err(Error::IllegalMove(format!(
+                "not a move of standard chess: {}",
+                mv
+            )))
+        );
+        assert!(game.san_to_uci(mv).is_err());
+    }
+    assert_eq!(game.san_to_uci("e4"), Ok("e2e4".to_string()));
+    assert_eq!(game.history().len(), 1);
+}
+
+#[test]
+fn check_figures_order() {
+    let mut game = Game::new();
+    for mv in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5"] {
+        game.play_move(mv).unwrap();
+    }
+    let figures: Vec<Figure> = game.figures.iter().copied().collect();
+    let position: Vec<Figure> = game.position.iter().flatten().copied().collect();
+    assert_eq!(figures, position);
+    assert_eq!(figures[0], Figure::from("ra8"));
+    assert_eq!(figures.last(), Some(&Figure::from("Rh1")));
+}
@@ -0,0 +1,187 @@
+use crate::utils::castling::Castling;
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+use crate::utils::zobrist::{reduced_fen, zobrist_hash};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Compact record of the position after a ply, which is enough to restore the game without
+/// replaying its moves. Ply 0 is the position the game was constructed with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+    pub ply: usize,
+    pub hash: u64,
+    pub uci: String,
+    position: [Option<Figure>; 64],
+    color: Color,
+    castling: Castling,
+    en_passant: Option<Coord>,
+    half_move_clock: u16,
+    full_move_clock: u16,
+}
+
+impl Snapshot {
+    fn of(game: &Game, ply: usize) -> Self {
+        Snapshot {
+            ply,
+            hash: zobrist_hash(game),
+            uci: game.uci.clone(),
+            position: game.position,
+            color: game.color,
+            castling: game.castling.clone(),
+            en_passant: game.en_passant,
+            half_move_clock: game.half_move_clock,
+            full_move_clock: game.full_move_clock,
+        }
+    }
+
+    /// The figures on the board after the ply, ordered from a8 to h1.
+    pub fn figures(&self) -> impl Iterator<Item = &Figure> {
+        self.position.iter().flatten()
+    }
+
+    /// The FEN without the move clocks, see `zobrist::reduced_fen`.
+    pub fn reduced_fen(&self) -> String {
+        reduced_fen(&self.restore(History::default()))
+    }
+
+    fn restore(&self, history: History) -> Game {
+        Game {
+            position: self.position,
+            figures: self.position.iter().flatten().copied().collect(),
+            color: self.color,
+            castling: self.castling.clone(),
+            en_passant: self.en_passant,
+            half_move_clock: self.half_move_clock,
+            full_move_clock: self.full_move_clock,
+            uci: self.uci.clone(),
+            history,
+        }
+    }
+}
+
+struct Node {
+    snapshot: Snapshot,
+    prev: Option<Arc<Node>>,
+}
+
+/// Snapshots of all plies played so far. Nodes are shared between clones of a game, thus cloning
+/// and pushing are cheap, which matters as move generation clones games all the time.
+#[derive(Clone, Default)]
+pub struct History {
+    last: Option<Arc<Node>>,
+}
+
+impl History {
+    /// Number of snapshots, i.e. the plies played plus the initial position.
+    pub fn len(&self) -> usize {
+        self.last.as_ref().map_or(0, |node| node.snapshot.ply + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last.is_none()
+    }
+
+    /// Record the current position of the game as the next ply.
+    pub(crate) fn push(&mut self, game: &Game) {
+        let snapshot = Snapshot::of(game, self.len());
+        self.last = Some(Arc::new(Node {
+            snapshot,
+            prev: self.last.take(),
+        }));
+    }
+
+    /// Iterate over the snapshots from the latest back to the initial position.
+    pub fn iter(&self) -> impl Iterator<Item = &Snapshot> {
+        std::iter::successors(self.last.as_deref(), |node| node.prev.as_deref())
+            .map(|node| &node.snapshot)
+    }
+
+    /// Restore the game after the given ply, holding the history up to that ply.
+    pub fn game_after(&self, ply: usize) -> Option<Game> {
+        let node = std::iter::successors(self.last.as_ref(), |node| node.prev.as_ref())
+            .find(|node| node.snapshot.ply == ply)?;
+        Some(node.snapshot.restore(History {
+            last: Some(node.clone()),
+        }))
+    }
+}
+
+impl Debug for History {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "History({} plies)", self.len().saturating_sub(1))
+    }
+}
+
+impl Drop for History {
+    // Unlink nodes one by one, as dropping a long list recursively could overflow the stack.
+    fn drop(&mut self) {
+        let mut next = self.last.take();
+        while let Some(node) = next {
+            next = match Arc::try_unwrap(node) {
+                Ok(mut node) => node.prev.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_history() {
+    let mut game = Game::new();
+    for mv in ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "O-O"] {
+        game.play_move(mv).unwrap();
+    }
+    let history = game.history();
+
+    assert_eq!(history.len(), 8);
+    assert_eq!(history[0].uci, "0000");
+    assert_eq!(history[7].uci, "e1g1");
+    assert_eq!(history[7].hash, zobrist_hash(&game));
+    assert_eq!(
+        history[2].reduced_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq -"
+    );
+
+    let after = game.position_after(3).unwrap();
+    assert_eq!(
+        after.clone().to_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+    assert_eq!(after.history().len(), 4);
+    assert_eq!(game.position_after(0).unwrap(), Game::new());
+    assert_eq!(game.position_after(8), None);
+}
+
+#[test]
+fn check_history_is_shared_by_clones() {
+    let mut game = Game::new();
+    game.play_move("d4").unwrap();
+    let mut other = game.clone();
+    game.play_move("d5").unwrap();
+    other.play_uci("g8f6").unwrap();
+
+    assert_eq!(game.history()[2].uci, "d7d5");
+    assert_eq!(other.history()[2].uci, "g8f6");
+    assert_eq!(game.history()[1], other.history()[1]);
+
+    // Replaying from a restored position continues its history.
+    let mut restored = game.position_after(1).unwrap();
+    restored.play_move("Nf6").unwrap();
+    assert_eq!(restored.history(), other.history());
+}
+
+#[test]
+fn check_long_history() {
+    let mut game = Game::new();
+    for _ in 0..5000 {
+        for mv in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            game.play_move(mv).unwrap();
+        }
+    }
+    assert_eq!(game.history().len(), 20001);
+    assert_eq!(game.position_after(20000).unwrap().history().len(), 20001);
+}
@@ -0,0 +1,162 @@
+use crate::utils::color::Color;
+use crate::utils::coord::{Coord, FromIndex};
+use crate::utils::game::Game;
+use crate::utils::piece::Piece;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+/// The wing a king has settled on, judged by its square rather than by the castling move.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CastledSide {
+    Kingside,
+    Queenside,
+}
+
+impl Display for CastledSide {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CastledSide::Kingside => write!(f, "kingside"),
+            CastledSide::Queenside => write!(f, "queenside"),
+        }
+    }
+}
+
+/// Features of the shelter and the threats around a king, e.g. as inputs of a model.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KingSafety {
+    pub color: Color,
+    pub square: Coord,
+
+    /// Files at and next to the king with an own pawn one or two ranks in front of it (0 to 3).
+    pub pawn_shield: u8,
+
+    /// Files at and next to the king without any pawn.
+    pub open_files: u8,
+
+    /// Files at and next to the king with an opposing but without an own pawn.
+    pub half_open_files: u8,
+
+    /// Opposing figures that attack the king zone, i.e. the king's square and its neighbours.
+    pub attackers: u8,
+
+    /// Sum of the attackers' weights, see `attack_weight`.
+    pub attack_weight: u16,
+
+    /// The wing of a king on its back rank, i.e. queenside on the a- to c-file and kingside on the
+    /// g- and h-file.
+    pub castled: Option<CastledSide>,
+}
+
+/// Weight of a figure attacking the king zone, where heavier figures are more dangerous.
+pub fn attack_weight(piece: Piece) -> u16 {
+    match piece {
+        Piece::P => 1,
+        Piece::N | Piece::B => 2,
+        Piece::R => 3,
+        Piece::Q => 5,
+        Piece::K => 0,
+    }
+}
+
+impl Game {
+    /// King-safety features of the given color, or None if it has no king on the board.
+    pub fn king_safety(&self, color: Color) -> Option<KingSafety> {
+        let king = self
+            .figures
+            .iter()
+            .find(|f| (f.piece() == Piece::K) && (f.color() == color))?
+            .coord();
+        let pawn_ranks = |x: i8, pawn_color: Color| -> Vec<i8> {
+            (0..8)
+                .filter(|&y| {
+                    self.position[Coord::idx_of(x, y) as usize]
+                        .is_some_and(|f| (f.piece() == Piece::P) && (f.color() == pawn_color))
+                })
+                .collect()
+        };
+
+        let (mut pawn_shield, mut open_files, mut half_open_files) = (0, 0, 0);
+        for x in (king.x - 1..=king.x + 1).filter(|x| (0..8).contains(x)) {
+            let own = pawn_ranks(x, color);
+            let opposing = pawn_ranks(x, color.next());
+            let ahead = |y: &i8| [1, 2].contains(&((y - king.y) * color.factor()));
+            if own.iter().any(ahead) {
+                pawn_shield += 1;
+            }
+            match (own.is_empty(), opposing.is_empty()) {
+                (true, true) => open_files += 1,
+                (true, false) => half_open_files += 1,
+                _ => {}
+            }
+        }
+
+        let zone = (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (king.x + dx, king.y + dy)))
+            .filter(|(x, y)| (0..8).contains(x) && (0..8).contains(y))
+            .map(|(x, y)| Coord::from_idx(Coord::idx_of(x, y)));
+        let attackers: HashSet<_> = zone
+            .flat_map(|square| self.attackers_of(&square, color.next()))
+            .filter(|f| f.piece() != Piece::K)
+            .collect();
+
+        let back_rank = if color.is_white() { 0 } else { 7 };
+        let castled = match king.x {
+            _ if king.y != back_rank => None,
+            0..=2 => Some(CastledSide::Queenside),
+            6..=7 => Some(CastledSide::Kingside),
+            _ => None,
+        };
+
+        Some(KingSafety {
+            color,
+            square: king,
+            pawn_shield,
+            open_files,
+            half_open_files,
+            attackers: attackers.len() as u8,
+            attack_weight: attackers.iter().map(|f| attack_weight(f.piece())).sum(),
+            castled,
+        })
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_initial_king_safety() {
+    let safety = Game::new().king_safety(Color::W).unwrap();
+    assert_eq!(safety.square, Coord::from("e1"));
+    assert_eq!(
+        (
+            safety.pawn_shield,
+            safety.open_files,
+            safety.half_open_files
+        ),
+        (3, 0, 0)
+    );
+    assert_eq!((safety.attackers, safety.attack_weight), (0, 0));
+    assert_eq!(safety.castled, None);
+}
+
+#[test]
+fn check_king_under_attack() {
+    use std::str::FromStr;
+
+    // White castled short with a broken shield, facing a queen, a knight and a rook on the open
+    // g-file.
+    let game = Game::from_str("6rk/5p1p/8/8/8/5n2/PP3P1q/R4RK1 w - - 0 1").unwrap();
+    let white = game.king_safety(Color::W).unwrap();
+    assert_eq!(white.castled, Some(CastledSide::Kingside));
+    assert_eq!(white.pawn_shield, 1);
+    assert_eq!((white.open_files, white.half_open_files), (1, 1));
+    assert_eq!((white.attackers, white.attack_weight), (3, 10));
+
+    let black = game.king_safety(Color::B).unwrap();
+    assert_eq!((black.pawn_shield, black.attackers), (1, 0));
+    assert_eq!(black.castled, Some(CastledSide::Kingside));
+    assert_eq!(
+        Game::from_str("8/8/8/8/8/8/8/4K3 w - - 0 1")
+            .unwrap()
+            .king_safety(Color::B),
+        None
+    );
+}
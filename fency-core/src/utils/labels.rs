@@ -0,0 +1,166 @@
+use crate::utils::color::Color;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::GameResult;
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::replay::replay_plies;
+use std::str::FromStr;
+
+/// A position labeled with the final result of its game, as used for supervised learning.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LabeledPosition {
+    pub fen: String,
+    pub side_to_move: Color,
+
+    /// Result from the point of view of the side to move: 1 for a win, 0 for a draw, -1 for a loss.
+    pub outcome: i8,
+
+    /// Number of plies played before the position, i.e. 0 for the initial position.
+    pub ply: usize,
+    pub total_plies: usize,
+}
+
+/// The result of a game as given by its Result tag, or by the termination marker otherwise.
+pub(crate) fn declared_result(game: &PgnGame) -> Option<GameResult> {
+    game.header("Result")
+        .or(game.result.as_deref())
+        .and_then(|r| GameResult::from_str(r).ok())
+}
+
+/// Label every position of a game, from the initial one to the final one, with the result. Games
+/// without a decided result (e.g. "*") have no labels.
+pub fn label_positions(game: &PgnGame) -> Result<Vec<LabeledPosition>, String> {
+    let Some(result) = declared_result(game).filter(|&r| r != GameResult::Undecided) else {
+        return Ok(Vec::new());
+    };
+    let total_plies = game.moves.len();
+    let mut board = game.start_position()?;
+    let mut labeled = Vec::with_capacity(total_plies + 1);
+    for ply in 0..=total_plies {
+        if ply > 0 {
+            board.play_move(&game.moves[ply - 1]).map_err(at_ply(ply))?;
+        }
+        labeled.push(LabeledPosition {
+            fen: board.clone().to_fen(),
+            side_to_move: board.color,
+            outcome: result.score_for(board.color).unwrap_or_default(),
+            ply,
+            total_plies,
+        });
+    }
+
+    Ok(labeled)
+}
+
+/// Label the positions of all games of a PGN string with their results, see `label_positions`.
+pub fn positions_with_outcome(pgn: &str) -> Result<Vec<LabeledPosition>, String> {
+    let mut labeled = Vec::new();
+    for (g, game) in PgnReader::new(pgn.as_bytes()).enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        labeled.extend(label_positions(&game).map_err(|e| format!("game {}, {}", g, e))?);
+    }
+
+    Ok(labeled)
+}
+
+/// A position with the move played in it, as used to train policy networks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyPair {
+    /// The position before the move.
+    pub fen: String,
+    pub uci: String,
+
+    /// Index of the played move in `Game::legal_moves` of the position, whose ordering is stable.
+    pub index: usize,
+}
+
+/// Pair every position of a game with the move played in it, from the start position to the one
+/// before the last move. Result tokens like "1-0" are skipped.
+pub fn policy_pairs(start: &Game, moves: &[&str]) -> Result<Vec<PolicyPair>, String> {
+    let mut plies = replay_plies(start.clone(), moves);
+    let mut pairs = Vec::with_capacity(moves.len());
+    while let Some((ply, _, before, game)) = plies.next_pair()? {
+        let index = before
+            .legal_moves()
+            .iter()
+            .position(|uci| *uci == game.uci)
+            .ok_or_else(|| format!("ply {}: {} is not among the legal moves", ply, game.uci))?;
+        pairs.push(PolicyPair {
+            fen: before.clone().to_fen(),
+            uci: game.uci.clone(),
+            index,
+        });
+    }
+
+    Ok(pairs)
+}
+
+/// The policy pairs of all games of a PGN string, see `policy_pairs`.
+pub fn policy_pairs_pgn(pgn: &str) -> Result<Vec<PolicyPair>, String> {
+    let mut pairs = Vec::new();
+    for (g, game) in PgnReader::new(pgn.as_bytes()).enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        let start = game
+            .start_position()
+            .map_err(|e| format!("game {}, {}", g, e))?;
+        pairs
+            .extend(policy_pairs(&start, &game.moves()).map_err(|e| format!("game {}, {}", g, e))?);
+    }
+
+    Ok(pairs)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_positions_with_outcome() {
+    let pgn = "[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n\
+               [Result \"*\"]\n\n1. e4 *\n\n\
+               [Result \"1/2-1/2\"]\n\n1. d4 d5 1/2-1/2\n";
+    let labeled = positions_with_outcome(pgn).unwrap();
+
+    assert_eq!(labeled.len(), 5 + 3);
+    assert_eq!(labeled[0].ply, 0);
+    assert_eq!(labeled[0].side_to_move, Color::W);
+    assert_eq!(labeled[0].outcome, -1);
+    assert_eq!(labeled[1].outcome, 1);
+    assert_eq!(labeled[4].total_plies, 4);
+    assert_eq!(
+        labeled[4].fen,
+        "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"
+    );
+    assert!(labeled[5..].iter().all(|p| p.outcome == 0));
+}
+
+#[test]
+fn check_invalid_labeled_game() {
+    let pgn = "[Result \"1-0\"]\n\n1. e4 1-0\n\n[Result \"1-0\"]\n\n1. e4 e5 2. Ke3 1-0\n";
+    assert_eq!(
+        positions_with_outcome(pgn).unwrap_err(),
+        "game 1, ply 3: illegal move: Ke3"
+    );
+}
+
+#[test]
+fn check_policy_pairs() {
+    let pairs = policy_pairs(&Game::new(), &["e4", "e5", "Nf3", "1-0"]).unwrap();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(
+        pairs[0].fen,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    );
+    assert_eq!((pairs[0].uci.as_str(), pairs[0].index), ("e2e4", 9));
+    assert_eq!((pairs[2].uci.as_str(), pairs[2].index), ("g1f3", 26));
+    for pair in &pairs {
+        let game = Game::from_str(&pair.fen).unwrap();
+        assert_eq!(game.legal_moves()[pair.index], pair.uci);
+    }
+
+    let pgn = "[FEN \"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1\"]\n\n1. O-O-O Kf7 *\n\n\
+               [Result \"*\"]\n\n1. d4 d5 *\n";
+    let pairs = policy_pairs_pgn(pgn).unwrap();
+    assert_eq!(pairs.len(), 4);
+    assert_eq!(pairs[0].uci, "e1c1");
+    assert_eq!(
+        policy_pairs_pgn("1. e4 Ke7 *\n").unwrap_err(),
+        "game 0, ply 2: illegal move: Ke7"
+    );
+}
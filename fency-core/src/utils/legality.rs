@@ -0,0 +1,384 @@
+use crate::utils::coord::Coord;
+use crate::utils::draw::{Draw, SanMove};
+use crate::utils::figure::Figure;
+use crate::utils::game::{get_hits, get_moves, Game, KNIGHT_STEPS};
+use crate::utils::piece::Piece;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Why a move cannot be played in a position, see `Game::why_illegal`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum IllegalReason {
+    /// Neither SAN nor UCI, e.g. "Nz9".
+    Malformed,
+    /// The move would be possible for the other color.
+    WrongTurn,
+    /// No figure of the active color matches the move, e.g. "Nf3" without a knight.
+    NoSuchPiece,
+    /// The figure does not move this way, not even on an empty board.
+    CannotMoveThere,
+    /// A pawn moves diagonally to an empty square that is not the en passant square.
+    NothingToCapture,
+    /// The target is occupied by a figure of the active color.
+    OccupiedByOwnPiece,
+    /// A figure stands between source and target, or between king and rook for castling.
+    BlockedPath,
+    /// The own king would be in check after the move.
+    LeavesKingInCheck,
+    /// A pawn reaches the last rank without promoting, or a promotion elsewhere or to a king.
+    InvalidPromotion,
+    /// The castling right is lost, or king or rook have left their initial squares.
+    NoCastlingRight,
+    /// The king castles out of, through or into check.
+    CastlingThroughCheck,
+    /// Several figures of the active color can make the move.
+    Ambiguous,
+    /// A drop or null move, which only variants or analysis know.
+    NotStandardChess,
+}
+
+impl IllegalReason {
+    /// Short name of the reason, e.g. "blocked_path".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IllegalReason::Malformed => "malformed",
+            IllegalReason::WrongTurn => "wrong_turn",
+            IllegalReason::NoSuchPiece => "no_such_piece",
+            IllegalReason::CannotMoveThere => "cannot_move_there",
+            IllegalReason::NothingToCapture => "nothing_to_capture",
+            IllegalReason::OccupiedByOwnPiece => "occupied_by_own_piece",
+            IllegalReason::BlockedPath => "blocked_path",
+            IllegalReason::LeavesKingInCheck => "leaves_king_in_check",
+            IllegalReason::InvalidPromotion => "invalid_promotion",
+            IllegalReason::NoCastlingRight => "no_castling_right",
+            IllegalReason::CastlingThroughCheck => "castling_through_check",
+            IllegalReason::Ambiguous => "ambiguous",
+            IllegalReason::NotStandardChess => "not_standard_chess",
+        }
+    }
+
+    /// How close a move of a figure comes to being legal, to report the most telling reason if
+    /// several figures could be meant.
+    fn closeness(&self) -> u8 {
+        match self {
+            IllegalReason::CannotMoveThere => 0,
+            IllegalReason::NothingToCapture => 1,
+            IllegalReason::OccupiedByOwnPiece => 2,
+            IllegalReason::BlockedPath => 3,
+            IllegalReason::InvalidPromotion => 4,
+            IllegalReason::LeavesKingInCheck => 5,
+            _ => 6,
+        }
+    }
+}
+
+impl Display for IllegalReason {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let text = match self {
+            IllegalReason::Malformed => "neither SAN nor UCI",
+            IllegalReason::WrongTurn => "it is the other color's turn",
+            IllegalReason::NoSuchPiece => "no such piece of the active color",
+            IllegalReason::CannotMoveThere => "the piece cannot move there",
+            IllegalReason::NothingToCapture => "nothing to capture",
+            IllegalReason::OccupiedByOwnPiece => "target occupied by an own piece",
+            IllegalReason::BlockedPath => "path is blocked",
+            IllegalReason::LeavesKingInCheck => "leaves the king in check",
+            IllegalReason::InvalidPromotion => "invalid promotion",
+            IllegalReason::NoCastlingRight => "no castling right",
+            IllegalReason::CastlingThroughCheck => "king castles out of, through or into check",
+            IllegalReason::Ambiguous => "ambiguous move",
+            IllegalReason::NotStandardChess => "not a move of standard chess",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Whether a figure could move from its square to the target if the board were otherwise empty.
+fn reaches_on_empty_board(fig: &Figure, target: &Coord) -> bool {
+    let (dx, dy) = (
+        target.x - fig.coord().x,
+        (target.y - fig.coord().y) * fig.color().factor(),
+    );
+    let is_straight = ((dx == 0) != (dy == 0)) && (dx.abs() <= 7) && (dy.abs() <= 7);
+    let is_diagonal = (dx != 0) && (dx.abs() == dy.abs());
+    match fig.piece() {
+        Piece::P => {
+            let initial_y = if fig.color().is_white() { 1 } else { 6 };
+            ((dx == 0) && ((dy == 1) || ((dy == 2) && (fig.coord().y == initial_y))))
+                || ((dx.abs() == 1) && (dy == 1))
+        }
+        Piece::N => KNIGHT_STEPS.contains(&(dx, dy)),
+        Piece::B => is_diagonal,
+        Piece::R => is_straight,
+        Piece::Q => is_straight || is_diagonal,
+        Piece::K => (dx.abs() <= 1) && (dy.abs() <= 1) && ((dx, dy) != (0, 0)),
+    }
+}
+
+/// Check a move of a figure of the active color other than castling, from the coarsest reason to
+/// the finest.
+fn check_figure_move(
+    game: &Game,
+    fig: &Figure,
+    target: &Coord,
+    promoted_piece: Option<Piece>,
+) -> Result<(), IllegalReason> {
+    if !reaches_on_empty_board(fig, target) {
+        return Err(IllegalReason::CannotMoveThere);
+    }
+    let occupant = game.position[target.idx as usize];
+    if occupant.is_some_and(|f| f.color() == fig.color()) {
+        return Err(IllegalReason::OccupiedByOwnPiece);
+    }
+    let is_pawn = fig.piece() == Piece::P;
+    if is_pawn
+        && (target.x != fig.coord().x)
+        && occupant.is_none()
+        && (game.en_passant != Some(*target))
+    {
+        return Err(IllegalReason::NothingToCapture);
+    }
+    if !get_moves(fig, game).contains(target) && !get_hits(fig, game).contains(target) {
+        return Err(IllegalReason::BlockedPath);
+    }
+    let is_last_rank = (target.y == 0) || (target.y == 7);
+    let is_valid_promotion = match promoted_piece {
+        None => !(is_pawn && is_last_rank),
+        Some(piece) => is_pawn && is_last_rank && !matches!(piece, Piece::P | Piece::K),
+    };
+    if !is_valid_promotion {
+        return Err(IllegalReason::InvalidPromotion);
+    }
+    let mut after = game.clone();
+    after.make_move(*fig, *target, promoted_piece);
+    if after.in_check_of(game.color) {
+        return Err(IllegalReason::LeavesKingInCheck);
+    }
+    Ok(())
+}
+
+/// Check a move in UCI notation, where castling is the king's move by two files.
+pub(crate) fn check_uci(
+    game: &Game,
+    src: Coord,
+    target: Coord,
+    promoted_piece: Option<Piece>,
+) -> Result<(), IllegalReason> {
+    let fig = match game.position[src.idx as usize] {
+        None => return Err(IllegalReason::NoSuchPiece),
+        Some(fig) if fig.color() != game.color => return Err(IllegalReason::WrongTurn),
+        Some(fig) => fig,
+    };
+    let initial_y = if game.color.is_white() { 0 } else { 7 };
+    let is_castling = (fig.piece() == Piece::K)
+        && (src.x == 4)
+        && (src.y == initial_y)
+        && (target.y == initial_y)
+        && ((target.x == 2) || (target.x == 6));
+    if is_castling {
+        if promoted_piece.is_some() {
+            return Err(IllegalReason::InvalidPromotion);
+        }
+        return game.check_castling(target.x == 2);
+    }
+    check_figure_move(game, &fig, &target, promoted_piece)
+}
+
+/// Check a move in SAN other than castling against every figure it may refer to.
+fn check_san(game: &Game, draw: &Draw) -> Result<(), IllegalReason> {
+    let candidates: Vec<Figure> = game
+        .position
+        .iter()
+        .flatten()
+        .filter(|f| (f.color() == game.color) && (f.piece() == draw.piece))
+        .filter(|f| {
+            draw.remainder_file
+                .is_none_or(|file| f.coord().file == file)
+        })
+        .filter(|f| {
+            draw.remainder_rank
+                .is_none_or(|rank| f.coord().rank == rank)
+        })
+        .copied()
+        .collect();
+    let results: Vec<Result<(), IllegalReason>> = candidates
+        .iter()
+        .map(|fig| check_figure_move(game, fig, &draw.target, draw.promoted_piece))
+        .collect();
+    match results.iter().filter(|r| r.is_ok()).count() {
+        0 => Err(results
+            .into_iter()
+            .filter_map(Result::err)
+            .max_by_key(IllegalReason::closeness)
+            .unwrap_or(IllegalReason::NoSuchPiece)),
+        1 => Ok(()),
+        _ => Err(IllegalReason::Ambiguous),
+    }
+}
+
+/// Explain a move in SAN or UCI, ignoring whose turn it is.
+fn check_move(game: &Game, mv: &str) -> Result<(), IllegalReason> {
+    let is_uci = (mv.len() >= 4)
+        && mv.is_char_boundary(2)
+        && mv.is_char_boundary(4)
+        && Coord::is_valid(&mv[..2])
+        && Coord::is_valid(&mv[2..4]);
+    if is_uci {
+        let promoted_piece = match &mv[4..] {
+            "" => None,
+            // Promotions to a king or a pawn are well-formed, just never legal.
+            "q" | "r" | "b" | "n" | "k" | "p" => Some(Piece::from(mv.chars().nth(4).unwrap())),
+            _ => return Err(IllegalReason::Malformed),
+        };
+        return check_uci(
+            game,
+            Coord::from(&mv[..2]),
+            Coord::from(&mv[2..4]),
+            promoted_piece,
+        );
+    }
+    // The SAN parser rejects promotions that no position allows, e.g. "e8" or "e8=K".
+    let san_move = SanMove::from_str(mv).map_err(|e| {
+        if e.starts_with("invalid promotion") {
+            IllegalReason::InvalidPromotion
+        } else {
+            IllegalReason::Malformed
+        }
+    })?;
+    match san_move {
+        SanMove::Normal(draw) => check_san(game, &draw),
+        SanMove::Castle(castling) => game.check_castling(castling.is_long),
+        SanMove::Drop(_) | SanMove::Null => Err(IllegalReason::NotStandardChess),
+    }
+}
+
+impl Game {
+    /// Explain why a move in SAN or UCI cannot be played, or None if it is legal, e.g. for a
+    /// training tool that shows more than "illegal move". If several figures could be meant, the
+    /// reason of the one that comes closest to a legal move is given. A move the other color could
+    /// play is reported as `IllegalReason::WrongTurn`.
+    pub fn why_illegal(&self, mv: &str) -> Option<IllegalReason> {
+        let reason = check_move(self, mv).err()?;
+        let may_be_other_turn = matches!(
+            reason,
+            IllegalReason::NoSuchPiece | IllegalReason::CannotMoveThere
+        );
+        if may_be_other_turn {
+            let mut other = self.clone();
+            other.color = self.color.next();
+            other.en_passant = None;
+            if check_move(&other, mv).is_ok() {
+                return Some(IllegalReason::WrongTurn);
+            }
+        }
+        Some(reason)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_why_illegal() {
+    let mut game = Game::new();
+    assert_eq!(game.why_illegal("e4"), None);
+    assert_eq!(game.why_illegal("g1f3"), None);
+    assert_eq!(
+        game.why_illegal("Nf4"),
+        Some(IllegalReason::CannotMoveThere)
+    );
+    assert_eq!(game.why_illegal("Bc4"), Some(IllegalReason::BlockedPath));
+    assert_eq!(
+        game.why_illegal("Nd2"),
+        Some(IllegalReason::OccupiedByOwnPiece)
+    );
+    assert_eq!(
+        game.why_illegal("exd3"),
+        Some(IllegalReason::NothingToCapture)
+    );
+    assert_eq!(game.why_illegal("O-O"), Some(IllegalReason::BlockedPath));
+    assert_eq!(game.why_illegal("e5"), Some(IllegalReason::WrongTurn));
+    assert_eq!(game.why_illegal("e7e5"), Some(IllegalReason::WrongTurn));
+    assert_eq!(game.why_illegal("e3e4"), Some(IllegalReason::NoSuchPiece));
+    assert_eq!(game.why_illegal("Zz9"), Some(IllegalReason::Malformed));
+    assert_eq!(
+        game.why_illegal("N@f3"),
+        Some(IllegalReason::NotStandardChess)
+    );
+    assert_eq!(IllegalReason::BlockedPath.kind(), "blocked_path");
+
+    for mv in ["e4", "e5", "Bc4", "Nf6", "Nf3", "Nc6"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(game.why_illegal("Ke7"), Some(IllegalReason::WrongTurn));
+    assert_eq!(game.why_illegal("O-O"), None);
+    game.play_move("Ke2").unwrap();
+    game.play_move("Ke7").unwrap();
+    game.play_move("Ke1").unwrap();
+    game.play_move("Ke8").unwrap();
+    assert_eq!(
+        game.why_illegal("O-O"),
+        Some(IllegalReason::NoCastlingRight)
+    );
+
+    let game = Game::from_str("4k3/8/8/8/8/8/8/N1N1K3 w - - 0 1").unwrap();
+    assert_eq!(game.why_illegal("Nb3"), Some(IllegalReason::Ambiguous));
+    assert_eq!(game.why_illegal("Nab3"), None);
+}
+
+#[test]
+fn check_why_illegal_pins_and_checks() {
+    // The knight on e2 is pinned and the pawn on b7 has to promote.
+    let game = Game::from_str("4k3/1P2r3/8/8/8/8/4N3/4K2R w K - 0 1").unwrap();
+    assert_eq!(
+        game.why_illegal("Nc3"),
+        Some(IllegalReason::LeavesKingInCheck)
+    );
+    assert_eq!(game.why_illegal("Kd1"), None);
+    assert_eq!(
+        game.why_illegal("b8"),
+        Some(IllegalReason::InvalidPromotion)
+    );
+    assert_eq!(
+        game.why_illegal("b7b8k"),
+        Some(IllegalReason::InvalidPromotion)
+    );
+    assert_eq!(
+        game.why_illegal("b7b8p"),
+        Some(IllegalReason::InvalidPromotion)
+    );
+    assert_eq!(game.why_illegal("b7b8x"), Some(IllegalReason::Malformed));
+    assert_eq!(game.why_illegal("b8=Q"), None);
+    assert_eq!(game.why_illegal("Rh2"), None);
+
+    let game = Game::from_str("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(game.why_illegal("e1g1"), None);
+    let game = Game::from_str("4kr2/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(
+        game.why_illegal("O-O"),
+        Some(IllegalReason::CastlingThroughCheck)
+    );
+
+    // Explanations agree with the legal moves for every pair of squares.
+    for fen in [
+        "4k3/1P2r3/8/8/8/8/4N3/4K2R w K - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let game = Game::from_str(fen).unwrap();
+        let legal = game.legal_moves();
+        for src in crate::utils::coord::BOARD.iter() {
+            for tgt in crate::utils::coord::BOARD.iter() {
+                let uci = format!("{}{}{}{}", src.file, src.rank, tgt.file, tgt.rank);
+                for uci in [uci.clone(), format!("{}q", uci)] {
+                    let is_legal = legal.contains(&uci);
+                    assert_eq!(
+                        game.why_illegal(&uci).is_none(),
+                        is_legal,
+                        "{}: {}",
+                        fen,
+                        uci
+                    );
+                }
+            }
+        }
+    }
+}
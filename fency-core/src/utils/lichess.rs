@@ -0,0 +1,565 @@
+use crate::utils::color::Color;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::json::Json;
+use crate::utils::labels::declared_result;
+use crate::utils::outcome::{ClaimPolicy, GameResult, Status};
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::piece::Piece;
+use crate::utils::replay::replay_plies;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+/// Engine evaluation from the point of view of white, as provided in the analysis array.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Eval {
+    /// Evaluation in centipawns.
+    Cp(i32),
+    /// Forced mate in the given number of moves, negative if black mates.
+    Mate(i32),
+}
+
+impl Display for Eval {
+    /// Format as in "[%eval]" comments of a PGN, i.e. in pawns like "0.35" or as mate like "#-3".
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Eval::Cp(cp) => write!(f, "{:.2}", f64::from(*cp) / 100.0),
+            Eval::Mate(n) => write!(f, "#{}", n),
+        }
+    }
+}
+
+/// A game as exported by the Lichess API in NDJSON format (one JSON object per line).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LichessGame {
+    pub id: Option<String>,
+    pub variant: Option<String>,
+    pub speed: Option<String>,
+    pub status: Option<String>,
+    pub winner: Option<String>,
+    pub white: Option<String>,
+    pub black: Option<String>,
+    pub white_rating: Option<u16>,
+    pub black_rating: Option<u16>,
+
+    /// Moves as given by the API, either in SAN or in UCI notation.
+    pub moves: Vec<String>,
+
+    /// Remaining clock time in centiseconds after every ply, if clocks were requested.
+    pub clocks: Vec<u32>,
+
+    /// Evaluation after every ply, if the game was analysed.
+    pub analysis: Vec<Option<Eval>>,
+}
+
+impl LichessGame {
+    /// Parse a single NDJSON line.
+    pub fn from_json(line: &str) -> Result<Self, String> {
+        let json = Json::parse(line)?;
+        let string = |value: Option<&Json>| value.and_then(|v| v.as_str()).map(|s| s.to_string());
+        let player = |color: &str| json.get("players").and_then(|p| p.get(color));
+        let name = |color: &str| {
+            let player = player(color)?;
+            string(player.get("user").and_then(|u| u.get("name")))
+                .or_else(|| string(player.get("name")))
+        };
+        let rating = |color: &str| player(color)?.get("rating")?.as_i64().map(|r| r as u16);
+
+        Ok(LichessGame {
+            id: string(json.get("id")),
+            variant: string(json.get("variant")),
+            speed: string(json.get("speed")),
+            status: string(json.get("status")),
+            winner: string(json.get("winner")),
+            white: name("white"),
+            black: name("black"),
+            white_rating: rating("white"),
+            black_rating: rating("black"),
+            moves: json
+                .get("moves")
+                .and_then(|m| m.as_str())
+                .map(|m| m.split_whitespace().map(|s| s.to_string()).collect())
+                .unwrap_or_default(),
+            clocks: json
+                .get("clocks")
+                .and_then(|c| c.as_array())
+                .map(|c| {
+                    c.iter()
+                        .filter_map(|t| t.as_i64())
+                        .map(|t| t as u32)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            analysis: json
+                .get("analysis")
+                .and_then(|a| a.as_array())
+                .map(|a| a.iter().map(parse_eval).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Check whether the moves are given in UCI instead of SAN, e.g. "e2e4" instead of "e4".
+    pub fn is_uci(&self) -> bool {
+        self.moves.first().is_some_and(|mv| is_uci_move(mv))
+    }
+
+    /// Replay the game and return the FEN after every ply.
+    pub fn fens(&self) -> Result<Vec<String>, String> {
+        let mut game = Game::new();
+        let is_uci = self.is_uci();
+        self.moves
+            .iter()
+            .enumerate()
+            .map(|(i, mv)| {
+                if is_uci {
+                    game.play_uci(mv).map_err(at_ply(i + 1))?;
+                } else {
+                    game.play_move(mv).map_err(at_ply(i + 1))?;
+                }
+                Ok(game.clone().to_fen())
+            })
+            .collect()
+    }
+}
+
+fn parse_eval(entry: &Json) -> Option<Eval> {
+    if let Some(cp) = entry.get("eval").and_then(|e| e.as_i64()) {
+        Some(Eval::Cp(cp as i32))
+    } else {
+        entry
+            .get("mate")
+            .and_then(|m| m.as_i64())
+            .map(|m| Eval::Mate(m as i32))
+    }
+}
+
+fn is_uci_move(mv: &str) -> bool {
+    let b = mv.as_bytes();
+    (b.len() == 4 || b.len() == 5)
+        && (b'a'..=b'h').contains(&b[0])
+        && (b'1'..=b'8').contains(&b[1])
+        && (b'a'..=b'h').contains(&b[2])
+        && (b'1'..=b'8').contains(&b[3])
+}
+
+/// Read games from an NDJSON stream, skipping empty lines.
+pub fn read_ndjson<R: BufRead>(reader: R) -> impl Iterator<Item = Result<LichessGame, String>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(LichessGame::from_json(&line)),
+        Err(e) => Some(Err(e.to_string())),
+    })
+}
+
+/// How a game ended according to the Termination tag of Lichess exports.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LichessTermination {
+    /// Mate, resignation, draw agreement or a draw by rule.
+    Normal,
+    TimeForfeit,
+    Abandoned,
+    RulesInfraction,
+    Unterminated,
+    Other(String),
+}
+
+impl From<&str> for LichessTermination {
+    fn from(tag: &str) -> Self {
+        match tag {
+            "Normal" => LichessTermination::Normal,
+            "Time forfeit" => LichessTermination::TimeForfeit,
+            "Abandoned" => LichessTermination::Abandoned,
+            "Rules infraction" => LichessTermination::RulesInfraction,
+            "Unterminated" => LichessTermination::Unterminated,
+            other => LichessTermination::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for LichessTermination {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let termination = match self {
+            LichessTermination::Normal => "normal",
+            LichessTermination::TimeForfeit => "time_forfeit",
+            LichessTermination::Abandoned => "abandoned",
+            LichessTermination::RulesInfraction => "rules_infraction",
+            LichessTermination::Unterminated => "unterminated",
+            LichessTermination::Other(other) => other,
+        };
+        write!(f, "{}", termination)
+    }
+}
+
+/// Chess variant according to the Variant tag of Lichess exports.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Variant {
+    Standard,
+    Chess960,
+    Crazyhouse,
+    Antichess,
+    Atomic,
+    Horde,
+    KingOfTheHill,
+    RacingKings,
+    ThreeCheck,
+    FromPosition,
+    Other(String),
+}
+
+impl From<&str> for Variant {
+    fn from(tag: &str) -> Self {
+        match tag {
+            "Standard" => Variant::Standard,
+            "Chess960" => Variant::Chess960,
+            "Crazyhouse" => Variant::Crazyhouse,
+            "Antichess" => Variant::Antichess,
+            "Atomic" => Variant::Atomic,
+            "Horde" => Variant::Horde,
+            "King of the Hill" => Variant::KingOfTheHill,
+            "Racing Kings" => Variant::RacingKings,
+            "Three-check" => Variant::ThreeCheck,
+            "From Position" => Variant::FromPosition,
+            other => Variant::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for Variant {
+    /// Format as the key of the Lichess API, e.g. "kingOfTheHill", as in `LichessGame::variant`.
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let variant = match self {
+            Variant::Standard => "standard",
+            Variant::Chess960 => "chess960",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Antichess => "antichess",
+            Variant::Atomic => "atomic",
+            Variant::Horde => "horde",
+            Variant::KingOfTheHill => "kingOfTheHill",
+            Variant::RacingKings => "racingKings",
+            Variant::ThreeCheck => "threeCheck",
+            Variant::FromPosition => "fromPosition",
+            Variant::Other(other) => other,
+        };
+        write!(f, "{}", variant)
+    }
+}
+
+/// Point in time in UTC as given by the UTCDate and UTCTime tags.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct UtcDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl UtcDateTime {
+    /// Parse a date like "2023.07.15" and a time like "18:04:31". None if any part is unknown,
+    /// e.g. "2023.??.??", or out of range.
+    pub fn parse(date: &str, time: &str) -> Option<Self> {
+        let parts = |text: &str, separator: char| -> Option<Vec<u16>> {
+            text.split(separator).map(|p| p.parse().ok()).collect()
+        };
+        let (date, time) = (parts(date, '.')?, parts(time, ':')?);
+        let (&[year, month, day], &[hour, minute, second]) = (&date[..], &time[..]) else {
+            return None;
+        };
+        let is_valid = (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && (hour < 24)
+            && (minute < 60)
+            && (second < 60);
+        is_valid.then_some(UtcDateTime {
+            year,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+        })
+    }
+
+    /// Seconds since 1970-01-01 00:00:00 UTC.
+    pub fn timestamp(&self) -> i64 {
+        // Count days in years starting in March, such that leap days are last, see
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil.
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+}
+
+/// The tags of Lichess PGN exports with typed values. Missing or unknown values like "?" are None.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LichessTags {
+    pub white_elo: Option<u16>,
+    pub black_elo: Option<u16>,
+    pub white_rating_diff: Option<i16>,
+    pub black_rating_diff: Option<i16>,
+    pub opening: Option<String>,
+    pub termination: Option<LichessTermination>,
+    pub utc: Option<UtcDateTime>,
+    pub variant: Option<Variant>,
+}
+
+impl PgnGame {
+    pub fn lichess_tags(&self) -> LichessTags {
+        let tag = |key| self.header(key).filter(|v| !v.is_empty() && *v != "?");
+        let number = |key| tag(key).and_then(|v| v.trim_start_matches('+').parse().ok());
+        LichessTags {
+            white_elo: tag("WhiteElo").and_then(|v| v.parse().ok()),
+            black_elo: tag("BlackElo").and_then(|v| v.parse().ok()),
+            white_rating_diff: number("WhiteRatingDiff"),
+            black_rating_diff: number("BlackRatingDiff"),
+            opening: tag("Opening").map(|v| v.to_string()),
+            termination: tag("Termination").map(LichessTermination::from),
+            utc: UtcDateTime::parse(tag("UTCDate").unwrap_or(""), tag("UTCTime").unwrap_or("")),
+            variant: tag("Variant").map(Variant::from),
+        }
+    }
+}
+
+/// Whether the Termination tag of a game agrees with its replayed final position and result.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TerminationVerdict {
+    Consistent,
+    /// The tag contradicts the moves or the result, e.g. "Time forfeit" after a checkmate.
+    Mismatch(String),
+    /// The tag cannot be checked by the moves, e.g. "Rules infraction" or a missing tag.
+    Unverifiable,
+}
+
+impl TerminationVerdict {
+    /// Short name of the verdict, i.e. "consistent", "mismatch" or "unverifiable".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TerminationVerdict::Consistent => "consistent",
+            TerminationVerdict::Mismatch(_) => "mismatch",
+            TerminationVerdict::Unverifiable => "unverifiable",
+        }
+    }
+}
+
+/// Check whether a color has only its king or its king and a single minor piece, with which it
+/// cannot win on time.
+fn lacks_mating_material(game: &Game, color: Color) -> bool {
+    let mut others = game
+        .figures
+        .iter()
+        .filter(|f| (f.color() == color) && (f.piece() != Piece::K));
+    match (others.next(), others.next()) {
+        (None, _) => true,
+        (Some(fig), None) => matches!(fig.piece(), Piece::B | Piece::N),
+        _ => false,
+    }
+}
+
+/// Reconcile the Termination tag with the final position and the declared result. A game that
+/// ended on the board, by checkmate, stalemate or an automatic draw, cannot end by time forfeit or
+/// abandonment afterwards, and an unterminated game has no result. A draw on time requires one
+/// side to lack mating material.
+pub fn reconcile_termination(
+    termination: Option<&LichessTermination>,
+    game: &Game,
+    declared: Option<GameResult>,
+) -> TerminationVerdict {
+    let status = game.status_with(ClaimPolicy::OverTheBoard);
+    let is_over = status != Status::Ongoing;
+    let is_decided = declared.is_some_and(|r| r != GameResult::Undecided);
+    let can_draw_on_time =
+        lacks_mating_material(game, Color::W) || lacks_mating_material(game, Color::B);
+    let reason = match termination {
+        None | Some(LichessTermination::RulesInfraction | LichessTermination::Other(_)) => {
+            return TerminationVerdict::Unverifiable;
+        }
+        Some(tag @ (LichessTermination::TimeForfeit | LichessTermination::Abandoned))
+            if is_over =>
+        {
+            format!("{} after {}", tag, status)
+        }
+        Some(LichessTermination::Unterminated) if is_over => {
+            format!("unterminated after {}", status)
+        }
+        Some(LichessTermination::Unterminated) if is_decided => {
+            format!("unterminated with result {}", declared.unwrap())
+        }
+        Some(tag @ (LichessTermination::Normal | LichessTermination::TimeForfeit))
+            if !is_decided =>
+        {
+            format!("{} without result", tag)
+        }
+        Some(LichessTermination::TimeForfeit)
+            if (declared == Some(GameResult::Draw)) && !can_draw_on_time =>
+        {
+            "draw on time with mating material on both sides".to_string()
+        }
+        Some(_) => return TerminationVerdict::Consistent,
+    };
+    TerminationVerdict::Mismatch(reason)
+}
+
+/// A game with its Termination tag, replayed final status and the verdict on both.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TerminationCheck {
+    pub termination: Option<LichessTermination>,
+    pub status: Status,
+    pub declared: Option<GameResult>,
+    pub verdict: TerminationVerdict,
+}
+
+impl PgnGame {
+    /// Replay the game and reconcile its Termination tag, see `reconcile_termination`.
+    pub fn check_termination(&self) -> Result<TerminationCheck, String> {
+        let board = replay_plies(self.start_position()?, &self.moves).finish()?;
+        let termination = self.lichess_tags().termination;
+        let declared = declared_result(self);
+        Ok(TerminationCheck {
+            verdict: reconcile_termination(termination.as_ref(), &board, declared),
+            status: board.status_with(ClaimPolicy::OverTheBoard),
+            termination,
+            declared,
+        })
+    }
+}
+
+/// Reconcile the Termination tags of all games of a PGN stream, one check per game, such that a
+/// broken game does not stop the scan of a large dump.
+pub fn check_terminations<R: BufRead>(
+    source: R,
+) -> impl Iterator<Item = Result<TerminationCheck, String>> {
+    PgnReader::new(source).enumerate().map(|(g, game)| {
+        game.map_err(|e| e.to_string())?
+            .check_termination()
+            .map_err(|e| format!("game {}, {}", g, e))
+    })
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const NDJSON: &str = r#"{"id":"q7ZvsdUF","rated":true,"variant":"standard","speed":"blitz","perf":"blitz","status":"mate","players":{"white":{"user":{"name":"alice","id":"alice"},"rating":1512},"black":{"user":{"name":"bob","id":"bob"},"rating":1498}},"winner":"white","moves":"e4 e5 Qh5 Nc6 Bc4 Nf6 Qxf7#","clocks":[18003,18003,17800,17500,17000,16900,16000],"analysis":[{"eval":30},{"eval":25},{"eval":10},{"eval":40},{"eval":35},{"mate":1,"judgment":{"name":"Blunder"}},{}],"clock":{"initial":180,"increment":0,"totalTime":180}}
+
+{"id":"abcdefgh","variant":"standard","status":"resign","players":{"white":{"aiLevel":3},"black":{"user":{"name":"carol"}}},"moves":"d2d4 d7d5 c2c4"}
+"#;
+
+#[test]
+fn check_lichess_game_from_json() {
+    let games: Vec<LichessGame> = read_ndjson(NDJSON.as_bytes()).map(|g| g.unwrap()).collect();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].id.as_deref(), Some("q7ZvsdUF"));
+    assert_eq!(games[0].white.as_deref(), Some("alice"));
+    assert_eq!(games[0].black_rating, Some(1498));
+    assert_eq!(games[0].winner.as_deref(), Some("white"));
+    assert_eq!(games[0].moves.len(), 7);
+    assert_eq!(games[0].clocks[2], 17800);
+    assert_eq!(games[0].analysis[0], Some(Eval::Cp(30)));
+    assert_eq!(games[0].analysis[5], Some(Eval::Mate(1)));
+    assert_eq!(games[0].analysis[6], None);
+    assert!(!games[0].is_uci());
+
+    assert_eq!(games[1].white, None);
+    assert_eq!(games[1].black.as_deref(), Some("carol"));
+    assert!(games[1].is_uci());
+}
+
+#[test]
+fn check_lichess_game_fens() {
+    let games: Vec<LichessGame> = read_ndjson(NDJSON.as_bytes()).map(|g| g.unwrap()).collect();
+
+    assert_eq!(
+        games[0].fens().unwrap().last().unwrap(),
+        "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4"
+    );
+    assert_eq!(
+        games[1].fens().unwrap().last().unwrap(),
+        "rnbqkbnr/ppp1pppp/8/3p4/2PP4/8/PP2PPPP/RNBQKBNR b KQkq - 0 2"
+    );
+}
+
+#[test]
+fn check_lichess_tags() {
+    let pgn = "[Event \"Rated Blitz game\"]\n[UTCDate \"2023.07.15\"]\n[UTCTime \"18:04:31\"]\n\
+               [WhiteElo \"1512\"]\n[BlackElo \"?\"]\n[WhiteRatingDiff \"+6\"]\n\
+               [BlackRatingDiff \"-6\"]\n[Variant \"King of the Hill\"]\n\
+               [Termination \"Time forfeit\"]\n[Opening \"King's Pawn Game\"]\n\n1. e4 1-0\n";
+    let tags = crate::utils::pgn::parse_pgn(pgn)[0].lichess_tags();
+
+    assert_eq!((tags.white_elo, tags.black_elo), (Some(1512), None));
+    assert_eq!(
+        (tags.white_rating_diff, tags.black_rating_diff),
+        (Some(6), Some(-6))
+    );
+    assert_eq!(tags.opening.as_deref(), Some("King's Pawn Game"));
+    assert_eq!(tags.termination, Some(LichessTermination::TimeForfeit));
+    assert_eq!(tags.variant.unwrap().to_string(), "kingOfTheHill");
+    assert_eq!(tags.utc.unwrap().timestamp(), 1_689_444_271);
+
+    assert_eq!(PgnGame::default().lichess_tags(), LichessTags::default());
+    assert_eq!(UtcDateTime::parse("2023.??.??", "18:04:31"), None);
+    assert_eq!(
+        UtcDateTime::parse("2000.02.29", "00:00:00")
+            .unwrap()
+            .timestamp(),
+        951_782_400
+    );
+}
+
+#[test]
+fn check_termination_reconciliation() {
+    let pgn = "[Termination \"Time forfeit\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n\
+               [Termination \"Normal\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n\
+               [Termination \"Time forfeit\"]\n[Result \"1/2-1/2\"]\n\n1. e4 e5 1/2-1/2\n\n\
+               [Termination \"Unterminated\"]\n[Result \"1-0\"]\n\n1. e4 e5 1-0\n\n\
+               [Termination \"Abandoned\"]\n[Result \"1-0\"]\n\n1. e4 1-0\n\n\
+               [Termination \"Rules infraction\"]\n[Result \"1-0\"]\n\n1. e4 1-0\n\n\
+               [Termination \"Normal\"]\n[Result \"*\"]\n\n1. e4 Ke7 *\n";
+    let checks: Vec<Result<TerminationCheck, String>> =
+        check_terminations(pgn.as_bytes()).collect();
+    let verdicts: Vec<String> = checks[..6]
+        .iter()
+        .map(|check| match &check.as_ref().unwrap().verdict {
+            TerminationVerdict::Mismatch(reason) => reason.clone(),
+            verdict => verdict.kind().to_string(),
+        })
+        .collect();
+    assert_eq!(
+        verdicts,
+        [
+            "time_forfeit after checkmate",
+            "consistent",
+            "draw on time with mating material on both sides",
+            "unterminated with result 1-0",
+            "consistent",
+            "unverifiable",
+        ]
+    );
+    assert_eq!(checks[0].as_ref().unwrap().status, Status::Checkmate);
+    assert_eq!(
+        checks[6],
+        Err("game 6, ply 2: illegal move: Ke7".to_string())
+    );
+
+    // A draw on time is fine when the side that did not flag cannot mate.
+    let game: Game = "8/8/4k3/8/8/2K5/6Q1/8 w - - 0 60".parse().unwrap();
+    assert_eq!(
+        reconcile_termination(
+            Some(&LichessTermination::TimeForfeit),
+            &game,
+            Some(GameResult::Draw)
+        ),
+        TerminationVerdict::Consistent
+    );
+    assert_eq!(
+        reconcile_termination(Some(&LichessTermination::Normal), &game, None),
+        TerminationVerdict::Mismatch("normal without result".to_string())
+    );
+}
@@ -0,0 +1,275 @@
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+use crate::utils::piece::Piece;
+use crate::utils::replay::replay_plies;
+
+/// Pieces that count as material, from the most to the least valuable by default.
+const MATERIAL_PIECES: [Piece; 5] = [Piece::Q, Piece::R, Piece::B, Piece::N, Piece::P];
+
+/// Conventional value of a piece in pawns, where the king does not count, see
+/// `MaterialEvaluator` for centipawns.
+pub fn piece_value(piece: Piece) -> i16 {
+    (MaterialEvaluator::default().value(piece) / 100) as i16
+}
+
+/// Values of the pieces in centipawns, which score the material of positions, exchanges and
+/// blunders alike. The default values are the conventional ones, 1, 3, 3, 5 and 9 pawns.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MaterialEvaluator {
+    pub pawn: i32,
+    pub knight: i32,
+    pub bishop: i32,
+    pub rook: i32,
+    pub queen: i32,
+}
+
+impl Default for MaterialEvaluator {
+    fn default() -> Self {
+        MaterialEvaluator {
+            pawn: 100,
+            knight: 300,
+            bishop: 300,
+            rook: 500,
+            queen: 900,
+        }
+    }
+}
+
+/// Pieces that one side has in excess of the other, e.g. the exchange for a bishop and a pawn.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Imbalance {
+    pub white: Vec<Piece>,
+    pub black: Vec<Piece>,
+
+    /// Material of white minus the material of black in centipawns.
+    pub score: i32,
+}
+
+impl MaterialEvaluator {
+    /// Value of a piece in centipawns, where the king does not count.
+    pub fn value(&self, piece: Piece) -> i32 {
+        match piece {
+            Piece::P => self.pawn,
+            Piece::N => self.knight,
+            Piece::B => self.bishop,
+            Piece::R => self.rook,
+            Piece::Q => self.queen,
+            Piece::K => 0,
+        }
+    }
+
+    /// Material of a color on the board in centipawns.
+    pub fn material(&self, game: &Game, color: Color) -> i32 {
+        game.figures
+            .iter()
+            .filter(|f| f.color() == color)
+            .map(|f| self.value(f.piece()))
+            .sum()
+    }
+
+    /// Material of white minus the material of black in centipawns.
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        self.material(game, Color::W) - self.material(game, Color::B)
+    }
+
+    /// The pieces that either side has more of than the other, the most valuable first.
+    pub fn imbalance(&self, game: &Game) -> Imbalance {
+        let count = |color: Color, piece: Piece| {
+            game.figures
+                .iter()
+                .filter(|f| f.color() == color && f.piece() == piece)
+                .count()
+        };
+        let mut pieces = MATERIAL_PIECES;
+        pieces.sort_by_key(|&piece| std::cmp::Reverse(self.value(piece)));
+        let excess = |color: Color| {
+            pieces
+                .iter()
+                .flat_map(|&piece| {
+                    let n = count(color, piece).saturating_sub(count(color.next(), piece));
+                    std::iter::repeat_n(piece, n)
+                })
+                .collect()
+        };
+        Imbalance {
+            white: excess(Color::W),
+            black: excess(Color::B),
+            score: self.evaluate(game),
+        }
+    }
+
+    /// Material that a move in UCI notation wins at once: the captured figure, including en
+    /// passant, and the gain of a promotion.
+    pub(crate) fn gain(&self, game: &Game, uci: &str) -> i32 {
+        let (src, tgt) = (Coord::from(&uci[..2]), Coord::from(&uci[2..4]));
+        let captured = match (
+            game.position[src.idx as usize],
+            game.position[tgt.idx as usize],
+        ) {
+            (_, Some(captured)) => self.value(captured.piece()),
+            (Some(mover), None) if mover.piece() == Piece::P && src.x != tgt.x => self.pawn,
+            _ => 0,
+        };
+        let promoted = match uci.chars().nth(4) {
+            Some(promoted) => self.value(Piece::from(promoted)) - self.pawn,
+            None => 0,
+        };
+        captured + promoted
+    }
+
+    /// Static exchange evaluation of a legal move in UCI notation: the material that the moving
+    /// side wins in centipawns, if both sides then recapture on the target square with their least
+    /// valuable figure as long as it pays off. Checks and threats elsewhere are not considered.
+    pub fn see(&self, game: &Game, uci: &str) -> Result<i32, String> {
+        let mut after = game.clone();
+        after.play_uci(uci)?;
+        Ok(self.gain(game, uci) - self.recapture(&after, &Coord::from(&uci[2..4])))
+    }
+
+    /// The best gain of the side to move by recapturing on the square, which is 0 if it does not.
+    fn recapture(&self, game: &Game, square: &Coord) -> i32 {
+        let least_valuable = game
+            .legal_moves()
+            .into_iter()
+            .filter(|uci| Coord::from(&uci[2..4]) == *square)
+            .min_by_key(|uci| {
+                let mover = game.position[Coord::from(&uci[..2]).idx as usize];
+                mover.map_or(0, |f| self.value(f.piece()))
+            });
+        let Some(uci) = least_valuable else {
+            return 0;
+        };
+        let gain = self.gain(game, &uci);
+        let mut after = game.clone();
+        after.play_uci(&uci).unwrap();
+        (gain - self.recapture(&after, square)).max(0)
+    }
+}
+
+impl Game {
+    /// Pieces of the given color captured since the game was constructed, in order of capture.
+    /// Promoted pawns count as the piece they were promoted to.
+    pub fn captured_pieces(&self, color: Color) -> Vec<Piece> {
+        let count = |figures: &[&Figure], piece: Piece| {
+            figures
+                .iter()
+                .filter(|f| (f.color() == color) && (f.piece() == piece))
+                .count()
+        };
+        let history = self.history();
+        history
+            .windows(2)
+            .filter_map(|plies| {
+                let before: Vec<&Figure> = plies[0].figures().collect();
+                let after: Vec<&Figure> = plies[1].figures().collect();
+                // A promotion replaces a pawn but leaves the number of figures unchanged.
+                if after.len() == before.len() {
+                    return None;
+                }
+                [Piece::P, Piece::N, Piece::B, Piece::R, Piece::Q]
+                    .into_iter()
+                    .find(|&piece| count(&after, piece) < count(&before, piece))
+            })
+            .collect()
+    }
+
+    /// Material of white minus the material of black in pawns, see `piece_value`.
+    pub fn material_balance(&self) -> i16 {
+        (MaterialEvaluator::default().evaluate(self) / 100) as i16
+    }
+}
+
+/// Replay moves in SAN and return the material balance after every ply, e.g. to draw a material
+/// graph. Result tokens like "1-0" are skipped.
+pub fn material_balances(moves: &[&str]) -> Result<Vec<i16>, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut balances = Vec::with_capacity(moves.len());
+    while let Some((_, _, game)) = plies.next_ply()? {
+        balances.push(game.material_balance());
+    }
+
+    Ok(balances)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_captured_pieces() {
+    let mut game = Game::new();
+    for mv in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qxa2", "Rxa2"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(game.captured_pieces(Color::W), vec![Piece::P, Piece::P]);
+    assert_eq!(game.captured_pieces(Color::B), vec![Piece::P, Piece::Q]);
+    assert_eq!(game.material_balance(), 8);
+}
+
+#[test]
+fn check_captured_promotions() {
+    use std::str::FromStr;
+
+    let mut game = Game::from_str("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    game.play_move("b8=Q+").unwrap();
+    assert!(game.captured_pieces(Color::W).is_empty());
+    game.play_move("Rxb8").unwrap();
+    assert_eq!(game.captured_pieces(Color::W), vec![Piece::Q]);
+    assert_eq!(game.material_balance(), -5);
+}
+
+#[test]
+fn check_material_balances() {
+    assert_eq!(
+        material_balances(&["e4", "d5", "exd5", "Qxd5", "Nc3", "Qxa2", "Rxa2", "1-0"]),
+        Ok(vec![0, 0, 1, 0, 0, -1, 8])
+    );
+    assert!(material_balances(&["e4", "e4"])
+        .unwrap_err()
+        .starts_with("ply 2"));
+}
+
+#[test]
+fn check_material_evaluator() {
+    use std::str::FromStr;
+
+    let evaluator = MaterialEvaluator::default();
+    assert_eq!(evaluator.material(&Game::new(), Color::W), 3900);
+    assert_eq!(piece_value(Piece::R), 5);
+
+    // White has the exchange for a bishop and a pawn.
+    let game = Game::from_str("4k3/1pp5/2b5/8/8/8/2P5/3RK3 w - - 0 1").unwrap();
+    let imbalance = evaluator.imbalance(&game);
+    assert_eq!(
+        (imbalance.white, imbalance.black, imbalance.score),
+        (vec![Piece::R], vec![Piece::B, Piece::P], 100)
+    );
+    let bishops = MaterialEvaluator {
+        bishop: 350,
+        ..evaluator
+    };
+    assert_eq!(bishops.evaluate(&game), 50);
+    assert_eq!(bishops.imbalance(&game).black, [Piece::B, Piece::P]);
+}
+
+#[test]
+fn check_static_exchange() {
+    use std::str::FromStr;
+
+    let evaluator = MaterialEvaluator::default();
+    // The pawn on e5 is defended by d6, thus Nxe5 loses the knight for a pawn.
+    let game = Game::from_str("4k3/8/3p4/4p3/8/5N2/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(evaluator.see(&game, "f3e5"), Ok(-200));
+    // Undefended, the pawn is won.
+    let game = Game::from_str("4k3/8/8/4p3/8/5N2/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(evaluator.see(&game, "f3e5"), Ok(100));
+    // Rook takes rook, queen takes rook, rook takes queen and the king takes the last rook.
+    let game = Game::from_str("3qk3/3r4/8/8/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    assert_eq!(evaluator.see(&game, "d2d7"), Ok(400));
+    // En passant and promotions count as well.
+    let game = Game::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+    assert_eq!(evaluator.see(&game, "e5d6"), Ok(100));
+    let game = Game::from_str("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(evaluator.see(&game, "b7b8q"), Ok(800));
+    assert!(evaluator.see(&game, "b7b6").is_err());
+    assert!(evaluator.see(&game, "b7").is_err());
+}
@@ -0,0 +1,62 @@
+pub mod action;
+pub mod batch;
+pub mod blunders;
+pub mod board_fen;
+pub mod book;
+pub mod cache;
+pub mod castling;
+pub mod clock;
+pub mod color;
+pub mod compression;
+pub mod conversion;
+pub mod coord;
+pub mod diff;
+pub mod disambiguation;
+pub mod draw;
+pub mod endgame;
+pub mod engine;
+pub mod error;
+pub mod evaluation;
+pub mod explorer;
+pub mod export;
+pub mod fentasize;
+pub mod figure;
+pub mod filter;
+pub mod game;
+pub mod history;
+pub mod json;
+pub mod king_safety;
+pub mod labels;
+pub mod legality;
+pub mod lichess;
+pub mod material;
+pub mod motifs;
+pub mod moves;
+pub mod notation;
+pub mod occupancy;
+pub mod outcome;
+pub mod pgn;
+pub mod pgn_index;
+pub mod phase;
+pub mod piece;
+pub mod progress;
+#[cfg(test)]
+mod proptests;
+pub mod puzzle;
+pub mod rays;
+pub mod recovery;
+pub mod render;
+pub mod replay;
+pub mod report;
+pub mod sampling;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod stats;
+#[cfg(feature = "syzygy")]
+pub mod tablebase;
+pub mod time_control;
+pub mod transform;
+pub mod transposition;
+pub mod tree;
+pub mod writer;
+pub mod zobrist;
@@ -0,0 +1,264 @@
+use crate::utils::color::Color;
+use crate::utils::coord::{Coord, BOARD};
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+use crate::utils::material::piece_value;
+use crate::utils::outcome::Status;
+use crate::utils::piece::Piece;
+use crate::utils::rays::{ray, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use crate::utils::replay::replay_plies;
+use std::fmt::{Display, Formatter};
+
+/// Tactical pattern that a move creates, e.g. to tag puzzles.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Motif {
+    Fork,
+    Pin,
+    Skewer,
+    DiscoveredAttack,
+    BackRankThreat,
+}
+
+impl Display for Motif {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Motif::Fork => write!(f, "fork"),
+            Motif::Pin => write!(f, "pin"),
+            Motif::Skewer => write!(f, "skewer"),
+            Motif::DiscoveredAttack => write!(f, "discovered_attack"),
+            Motif::BackRankThreat => write!(f, "back_rank_threat"),
+        }
+    }
+}
+
+/// The motifs of a ply of a game.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlyMotifs {
+    pub ply: usize,
+    pub san: String,
+    pub motifs: Vec<Motif>,
+}
+
+/// Value of a figure for comparisons between targets, where the king outweighs everything.
+fn worth(piece: Piece) -> i16 {
+    match piece {
+        Piece::K => i16::MAX,
+        piece => piece_value(piece),
+    }
+}
+
+/// Whether a figure is protected by its own side, i.e. its square is attacked by its own color.
+fn is_defended(game: &Game, fig: &Figure) -> bool {
+    !game.attackers_of(&fig.coord(), fig.color()).is_empty()
+}
+
+/// Opposing figures that the figure attacks.
+fn targets(game: &Game, fig: &Figure) -> Vec<Figure> {
+    BOARD
+        .iter()
+        .filter_map(|square| game.position[square.idx as usize])
+        .filter(|target| target.color() != fig.color() && game.attacks(fig, &target.coord()))
+        .collect()
+}
+
+/// The first two figures along each line of a sliding figure, if both are opposing ones.
+fn lined_up(game: &Game, fig: &Figure) -> Vec<(Figure, Figure)> {
+    let directions = match fig.piece() {
+        Piece::R => ROOK_DIRECTIONS,
+        Piece::B => BISHOP_DIRECTIONS,
+        Piece::Q => 0..8,
+        _ => return Vec::new(),
+    };
+    directions
+        .filter_map(|d| {
+            let mut hits = ray(&fig.coord(), d)
+                .iter()
+                .filter_map(|&ti| game.position[ti as usize]);
+            match (hits.next(), hits.next()) {
+                (Some(front), Some(back))
+                    if front.color() != fig.color() && back.color() != fig.color() =>
+                {
+                    Some((front, back))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Whether the side that just moved could mate with a rook or queen on the back rank of the
+/// opposing king, if it were its turn again. Only kings on their back rank that are walled in by
+/// their own figures are considered.
+fn threatens_back_rank_mate(after: &Game, color: Color) -> bool {
+    let Some(king) = after
+        .figures
+        .iter()
+        .find(|f| f.piece() == Piece::K && f.color() != color)
+    else {
+        return false;
+    };
+    let (back_rank, forward) = if color.is_white() { (7, -1) } else { (0, 1) };
+    let square = king.coord();
+    let is_walled_in = (-1..=1)
+        .filter_map(|dx| square.shifted(dx, forward))
+        .all(|front| after.position[front.idx as usize].is_some_and(|f| f.color() != color));
+    if square.y != back_rank || !is_walled_in {
+        return false;
+    }
+
+    let mut game = after.clone();
+    game.color = color;
+    game.en_passant = None;
+    game.legal_moves().iter().any(|uci| {
+        let (src, tgt) = (Coord::from(&uci[..2]), Coord::from(&uci[2..4]));
+        let is_heavy = game.position[src.idx as usize]
+            .is_some_and(|f| matches!(f.piece(), Piece::R | Piece::Q));
+        let mut mated = game.clone();
+        is_heavy
+            && tgt.y == back_rank
+            && mated.play_uci(uci).is_ok()
+            && mated.status() == Status::Checkmate
+    })
+}
+
+/// The motifs that a move creates, given the positions before and after it:
+/// - fork: the moved figure attacks two opposing figures that are the king, worth more than the
+///   moved figure or undefended pieces,
+/// - pin: a sliding moved figure attacks a figure in front of a more valuable one, which is also
+///   worth more than the moved figure,
+/// - skewer: a sliding moved figure attacks a figure at least as valuable as itself in front of
+///   a less valuable piece,
+/// - discovered attack: the move opens the line of another sliding figure to an opposing piece,
+/// - back-rank threat: the opposing king is walled in on its back rank and could be mated there.
+pub fn move_motifs(before: &Game, after: &Game) -> Vec<Motif> {
+    let color = before.color;
+    let (src, tgt) = (Coord::from(&after.uci[..2]), Coord::from(&after.uci[2..4]));
+    let Some(mover) = after.position[tgt.idx as usize] else {
+        return Vec::new();
+    };
+    let mut motifs = Vec::new();
+
+    let forked = targets(after, &mover)
+        .iter()
+        .filter(|t| {
+            t.piece() == Piece::K
+                || worth(t.piece()) > worth(mover.piece())
+                || (t.piece() != Piece::P && !is_defended(after, t))
+        })
+        .count();
+    if forked >= 2 {
+        motifs.push(Motif::Fork);
+    }
+
+    let lines = lined_up(after, &mover);
+    let front_to_back =
+        |front: &Figure, back: &Figure| worth(front.piece()).cmp(&worth(back.piece()));
+    let is_pin = |front: &Figure, back: &Figure| {
+        front_to_back(front, back).is_lt() && worth(back.piece()) > worth(mover.piece())
+    };
+    if lines.iter().any(|(front, back)| is_pin(front, back)) {
+        motifs.push(Motif::Pin);
+    }
+    let is_skewer = |front: &Figure, back: &Figure| {
+        front_to_back(front, back).is_gt()
+            && back.piece() != Piece::P
+            && worth(front.piece()) >= worth(mover.piece())
+    };
+    if lines.iter().any(|(front, back)| is_skewer(front, back)) {
+        motifs.push(Motif::Skewer);
+    }
+
+    let is_discovered = after
+        .figures
+        .iter()
+        .filter(|f| f.color() == color && **f != mover)
+        .filter(|f| matches!(f.piece(), Piece::R | Piece::B | Piece::Q))
+        .any(|slider| {
+            targets(after, slider)
+                .iter()
+                .any(|t| t.piece() != Piece::P && slider.coord().between(&t.coord()).contains(&src))
+        });
+    if is_discovered {
+        motifs.push(Motif::DiscoveredAttack);
+    }
+
+    if threatens_back_rank_mate(after, color) {
+        motifs.push(Motif::BackRankThreat);
+    }
+    motifs
+}
+
+/// Replay the moves in SAN and detect the motifs of every ply, see `move_motifs`. Result tokens
+/// are skipped.
+pub fn detect_motifs(moves: &[&str]) -> Result<Vec<PlyMotifs>, String> {
+    let mut replay = replay_plies(Game::new(), moves);
+    let mut plies = Vec::with_capacity(moves.len());
+    while let Some((ply, mv, before, after)) = replay.next_pair()? {
+        plies.push(PlyMotifs {
+            ply,
+            san: mv.to_string(),
+            motifs: move_motifs(before, after),
+        });
+    }
+
+    Ok(plies)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+fn motifs_of(fen: &str, san: &str) -> Vec<Motif> {
+    use std::str::FromStr;
+
+    let before = Game::from_str(fen).unwrap();
+    let mut after = before.clone();
+    after.play_move(san).unwrap();
+    move_motifs(&before, &after)
+}
+
+#[test]
+fn check_motifs() {
+    // The knight forks king and rook, which are both worth more than the knight.
+    assert_eq!(
+        motifs_of("8/5k2/2r5/8/8/3N4/8/4K3 w - - 0 1", "Ne5+"),
+        [Motif::Fork]
+    );
+    // The bishop pins the knight to the king, the rook skewers the king and the queen.
+    assert_eq!(
+        motifs_of("4k3/3n4/8/8/8/8/8/4KB2 w - - 0 1", "Bb5"),
+        [Motif::Pin]
+    );
+    assert_eq!(
+        motifs_of("4k2q/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra8+"),
+        [Motif::Skewer]
+    );
+    // The knight clears the file of the rook towards the queen.
+    assert_eq!(
+        motifs_of("k3q3/8/8/8/4N3/8/8/4R2K w - - 0 1", "Nc5"),
+        [Motif::DiscoveredAttack]
+    );
+    assert_eq!(
+        motifs_of("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "Rd1"),
+        [Motif::BackRankThreat]
+    );
+    assert_eq!(Motif::DiscoveredAttack.to_string(), "discovered_attack");
+}
+
+#[test]
+fn check_detect_motifs() {
+    let plies = detect_motifs(&["d4", "Nf6", "c4", "e6", "Nc3", "Bb4", "1-0"]).unwrap();
+    assert_eq!(plies.len(), 6);
+    assert!(plies[..5].iter().all(|p| p.motifs.is_empty()));
+    // The bishop pins the knight to the king.
+    assert_eq!(
+        (plies[5].ply, plies[5].motifs.as_slice()),
+        (6, [Motif::Pin].as_slice())
+    );
+
+    // The knight in front of a pawn is neither pinned nor skewered.
+    let plies = detect_motifs(&["e4", "e5", "Nf3", "Nc6", "Bb5"]).unwrap();
+    assert!(plies[4].motifs.is_empty());
+    assert_eq!(
+        detect_motifs(&["e4", "Ke7"]).unwrap_err(),
+        "ply 2: illegal move: Ke7"
+    );
+}
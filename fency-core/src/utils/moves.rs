@@ -0,0 +1,84 @@
+use crate::utils::color::Color;
+use crate::utils::coord::Coord;
+use crate::utils::error::Error;
+use crate::utils::game::Game;
+use crate::utils::piece::Piece;
+
+/// A legal move within a given position, with everything that is known about it. In contrast to a
+/// `Draw`, which only decomposes a SAN, the source square is always resolved.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Move {
+    pub piece: Piece,
+    pub color: Color,
+    pub from: Coord,
+    pub to: Coord,
+    pub is_capture: bool,
+    pub promotion: Option<Piece>,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_castling: bool,
+    /// Canonical SAN of the move, i.e. minimal disambiguation and without annotations.
+    pub san: String,
+    pub uci: String,
+}
+
+impl Move {
+    /// Describe a move of the position given in UCI notation, e.g. "g1f3".
+    pub fn from_uci(game: &Game, uci: &str) -> Result<Self, Error> {
+        let san = game.uci_to_san(uci)?;
+        let from = Coord::from(&uci[..2]);
+        let to = Coord::from(&uci[2..4]);
+        let fig = game.position[from.idx as usize].unwrap();
+
+        Ok(Move {
+            piece: fig.piece(),
+            color: fig.color(),
+            from,
+            to,
+            is_capture: san.contains('x'),
+            promotion: uci[4..].chars().next().map(Piece::from),
+            is_check: san.ends_with(['+', '#']),
+            is_checkmate: san.ends_with('#'),
+            is_castling: san.starts_with("O-O"),
+            san,
+            uci: uci.to_string(),
+        })
+    }
+
+    /// Describe a move of the position given in SAN, e.g. "Nf3".
+    pub fn from_san(game: &Game, san: &str) -> Result<Self, Error> {
+        Move::from_uci(game, &game.san_to_uci(san)?)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_move_from_san() {
+    use std::str::FromStr;
+    let game = Game::from_str("1r4k1/P4ppp/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+    let mv = Move::from_san(&game, "axb8=Q").unwrap();
+
+    assert_eq!(mv.piece, Piece::P);
+    assert_eq!(mv.color, Color::W);
+    assert_eq!(mv.from, Coord::from("a7"));
+    assert_eq!(mv.to, Coord::from("b8"));
+    assert!(mv.is_capture);
+    assert_eq!(mv.promotion, Some(Piece::Q));
+    assert!(mv.is_check);
+    assert!(mv.is_checkmate);
+    assert!(!mv.is_castling);
+    assert_eq!(mv.san, "axb8=Q#");
+    assert_eq!(mv.uci, "a7b8q");
+}
+
+#[test]
+fn check_move_from_uci() {
+    let mv = Move::from_uci(&Game::new(), "g1f3").unwrap();
+    assert_eq!(mv.piece, Piece::N);
+    assert_eq!(mv.san, "Nf3");
+    assert!(!mv.is_capture);
+    assert!(!mv.is_check);
+
+    assert!(Move::from_uci(&Game::new(), "e2e5").is_err());
+    assert!(Move::from_san(&Game::new(), "Ke2").is_err());
+}
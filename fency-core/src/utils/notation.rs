@@ -0,0 +1,152 @@
+use crate::utils::piece::Piece;
+use std::str::FromStr;
+
+/// Piece letters of a SAN dialect, which allows to read e.g. German notation, where a knight is
+/// written as "S" (Springer) instead of "N". Pawns have no letter in SAN, thus are not mapped.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct NotationConfig {
+    pub king: char,
+    pub queen: char,
+    pub rook: char,
+    pub bishop: char,
+    pub knight: char,
+}
+
+impl NotationConfig {
+    pub fn new(king: char, queen: char, rook: char, bishop: char, knight: char) -> Self {
+        NotationConfig {
+            king,
+            queen,
+            rook,
+            bishop,
+            knight,
+        }
+    }
+
+    /// The standard English letters (KQRBN), which the SAN parser expects.
+    pub fn english() -> Self {
+        NotationConfig::new('K', 'Q', 'R', 'B', 'N')
+    }
+
+    /// König, Dame, Turm, Läufer, Springer.
+    pub fn german() -> Self {
+        NotationConfig::new('K', 'D', 'T', 'L', 'S')
+    }
+
+    /// Rey, Dama, Torre, Alfil, Caballo.
+    pub fn spanish() -> Self {
+        NotationConfig::new('R', 'D', 'T', 'A', 'C')
+    }
+
+    /// Roi, Dame, Tour, Fou, Cavalier.
+    pub fn french() -> Self {
+        NotationConfig::new('R', 'D', 'T', 'F', 'C')
+    }
+
+    /// Re, Donna, Torre, Alfiere, Cavallo.
+    pub fn italian() -> Self {
+        NotationConfig::new('R', 'D', 'T', 'A', 'C')
+    }
+
+    /// Koning, Dame, Toren, Loper, Paard.
+    pub fn dutch() -> Self {
+        NotationConfig::new('K', 'D', 'T', 'L', 'P')
+    }
+
+    /// The piece that is denoted by a letter of this notation, if any.
+    pub fn piece(&self, letter: char) -> Option<Piece> {
+        [
+            (self.king, Piece::K),
+            (self.queen, Piece::Q),
+            (self.rook, Piece::R),
+            (self.bishop, Piece::B),
+            (self.knight, Piece::N),
+        ]
+        .into_iter()
+        .find(|&(c, _)| c == letter)
+        .map(|(_, piece)| piece)
+    }
+
+    /// The letter of a piece in this notation.
+    pub fn letter(&self, piece: Piece) -> Option<char> {
+        match piece {
+            Piece::K => Some(self.king),
+            Piece::Q => Some(self.queen),
+            Piece::R => Some(self.rook),
+            Piece::B => Some(self.bishop),
+            Piece::N => Some(self.knight),
+            Piece::P => None,
+        }
+    }
+
+    /// Translate a SAN of this notation into standard (English) SAN, e.g. "Sf3" into "Nf3".
+    /// Files are lowercase, thus only uppercase letters are considered to be pieces.
+    pub fn to_english(&self, san: &str) -> String {
+        san.chars()
+            .map(|c| match self.piece(c) {
+                Some(piece) if c.is_uppercase() => NotationConfig::english().letter(piece).unwrap(),
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Translate a standard (English) SAN into this notation, e.g. "Nf3" into "Sf3".
+    pub fn localize(&self, san: &str) -> String {
+        let english = NotationConfig::english();
+        san.chars()
+            .map(|c| match english.piece(c) {
+                Some(piece) => self.letter(piece).unwrap(),
+                None => c,
+            })
+            .collect()
+    }
+}
+
+impl Default for NotationConfig {
+    fn default() -> Self {
+        NotationConfig::english()
+    }
+}
+
+/// Lookup a notation by its language code (e.g. "de") or name (e.g. "german").
+impl FromStr for NotationConfig {
+    fn from_str(language: &str) -> Result<Self, Self::Err> {
+        match language.to_lowercase().as_str() {
+            "en" | "english" => Ok(NotationConfig::english()),
+            "de" | "german" => Ok(NotationConfig::german()),
+            "es" | "spanish" => Ok(NotationConfig::spanish()),
+            "fr" | "french" => Ok(NotationConfig::french()),
+            "it" | "italian" => Ok(NotationConfig::italian()),
+            "nl" | "dutch" => Ok(NotationConfig::dutch()),
+            _ => Err(format!("unknown notation: {}", language)),
+        }
+    }
+
+    type Err = String;
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_to_english() {
+    let german = NotationConfig::german();
+    assert_eq!(german.to_english("Sf3"), "Nf3");
+    assert_eq!(german.to_english("Lxb5+"), "Bxb5+");
+    assert_eq!(german.to_english("exd8=D#"), "exd8=Q#");
+    assert_eq!(german.to_english("Tad1"), "Rad1");
+    assert_eq!(german.to_english("O-O"), "O-O");
+
+    // The Spanish king letter is the English rook letter.
+    let spanish = NotationConfig::spanish();
+    assert_eq!(spanish.to_english("Rf1"), "Kf1");
+    assert_eq!(spanish.to_english("Tf1"), "Rf1");
+    assert_eq!(spanish.to_english("Cc3"), "Nc3");
+}
+
+#[test]
+fn check_localize() {
+    let german = NotationConfig::from_str("de").unwrap();
+    assert_eq!(german.localize("Nbd7"), "Sbd7");
+    assert_eq!(german.localize("exd8=Q"), "exd8=D");
+    assert_eq!(NotationConfig::spanish().localize("Kxf2"), "Rxf2");
+    assert!(NotationConfig::from_str("klingon").is_err());
+}
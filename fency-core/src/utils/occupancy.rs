@@ -0,0 +1,97 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::is_result_token;
+
+/// Number of figure kinds, i.e. six pieces of two colors (see `Figure::index`).
+pub const N_FIGURES: usize = 12;
+pub const N_SQUARES: usize = 64;
+
+/// Counts of how often each figure kind occupies each square, accumulated over many games and
+/// bucketed by ply, e.g. plies 0-9, 10-19 and so on. These are also known as piece heatmaps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Occupancy {
+    pub bucket_size: usize,
+    pub n_buckets: usize,
+
+    /// Flat counts, indexed by bucket, figure index and square (a8 to h1, as in the FEN).
+    counts: Vec<u32>,
+
+    /// Number of positions per bucket, to normalize the counts into frequencies.
+    positions: Vec<u32>,
+}
+
+impl Occupancy {
+    /// Positions beyond the last bucket are accumulated in the last bucket.
+    pub fn new(bucket_size: usize, n_buckets: usize) -> Self {
+        assert!(
+            bucket_size > 0 && n_buckets > 0,
+            "buckets must not be empty"
+        );
+        Occupancy {
+            bucket_size,
+            n_buckets,
+            counts: vec![0; n_buckets * N_FIGURES * N_SQUARES],
+            positions: vec![0; n_buckets],
+        }
+    }
+
+    fn add_position(&mut self, game: &Game, ply: usize) {
+        let bucket = (ply / self.bucket_size).min(self.n_buckets - 1);
+        self.positions[bucket] += 1;
+        for fig in game.position.iter().flatten() {
+            let idx = (bucket * N_FIGURES + fig.index()) * N_SQUARES + fig.square();
+            self.counts[idx] += 1;
+        }
+    }
+
+    /// Replay a game and add every position, starting with the initial one as ply 0. A game with
+    /// an invalid move is not added at all.
+    pub fn add_game(&mut self, moves: &[&str]) -> Result<(), String> {
+        let mut game = Game::new();
+        let mut positions = vec![game.clone()];
+        for (i, mv) in moves.iter().filter(|mv| !is_result_token(mv)).enumerate() {
+            game.play_move(mv).map_err(at_ply(i + 1))?;
+            positions.push(game.clone());
+        }
+        for (ply, position) in positions.iter().enumerate() {
+            self.add_position(position, ply);
+        }
+        Ok(())
+    }
+
+    /// How often the figure kind occupied the square within the bucket.
+    pub fn count(&self, bucket: usize, figure_idx: usize, square_idx: usize) -> u32 {
+        self.counts[(bucket * N_FIGURES + figure_idx) * N_SQUARES + square_idx]
+    }
+
+    /// All counts in row-major order of the shape (n_buckets, 12, 64).
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_occupancy() {
+    use crate::utils::figure::Figure;
+    let white_pawn = Figure::from("Pe2").index();
+    let black_knight = Figure::from("nb8").index();
+    let (e2, e4, f6) = (52, 36, 21);
+
+    let mut occupancy = Occupancy::new(2, 2);
+    occupancy.add_game(&["e4", "Nf6", "Nc3", "1-0"]).unwrap();
+    occupancy.add_game(&["d4"]).unwrap();
+    assert!(occupancy.add_game(&["e4", "O-O"]).is_err());
+
+    // Bucket 0 holds the plies 0 and 1, bucket 1 everything from ply 2 on.
+    assert_eq!(occupancy.positions(), &[4, 2]);
+    assert_eq!(occupancy.count(0, white_pawn, e2), 3);
+    assert_eq!(occupancy.count(0, white_pawn, e4), 1);
+    assert_eq!(occupancy.count(1, white_pawn, e4), 2);
+    assert_eq!(occupancy.count(1, black_knight, f6), 2);
+    assert_eq!(occupancy.counts().len(), 2 * 12 * 64);
+    assert_eq!(occupancy.counts().iter().sum::<u32>(), 6 * 32);
+}
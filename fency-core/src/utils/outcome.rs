@@ -0,0 +1,593 @@
+use crate::utils::color::Color;
+use crate::utils::draw::SanMove;
+use crate::utils::error::Error;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::pgn::RESULT_TOKENS;
+use crate::utils::piece::Piece;
+use crate::utils::replay::replay_plies;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Result of a game as declared by a PGN termination marker or Result tag.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// Game still in progress, abandoned or result unknown ("*").
+    Undecided,
+}
+
+impl GameResult {
+    pub fn is_decisive(self) -> bool {
+        matches!(self, GameResult::WhiteWins | GameResult::BlackWins)
+    }
+
+    /// Score from the point of view of the given color: 1 for a win, 0 for a draw and -1 for a
+    /// loss, or None if the game is undecided.
+    pub fn score_for(self, color: Color) -> Option<i8> {
+        match self {
+            GameResult::WhiteWins => Some(color.factor()),
+            GameResult::BlackWins => Some(-color.factor()),
+            GameResult::Draw => Some(0),
+            GameResult::Undecided => None,
+        }
+    }
+
+    /// The result implied by the given color having won.
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::W => GameResult::WhiteWins,
+            Color::B => GameResult::BlackWins,
+        }
+    }
+}
+
+impl FromStr for GameResult {
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        match token {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Undecided),
+            _ => Err(Error::InvalidResult(format!(
+                "not a result token: {}",
+                token
+            ))),
+        }
+    }
+
+    type Err = Error;
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let token = match self {
+            GameResult::WhiteWins => RESULT_TOKENS[0],
+            GameResult::BlackWins => RESULT_TOKENS[1],
+            GameResult::Draw => RESULT_TOKENS[2],
+            GameResult::Undecided => RESULT_TOKENS[3],
+        };
+        write!(f, "{}", token)
+    }
+}
+
+/// State of the board regarding the end of a game.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Status {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    /// A draw by a rule beyond the board, which only `Game::status_with` reports.
+    Draw(DrawRule),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let status = match self {
+            Status::Ongoing => "ongoing",
+            Status::Checkmate => "checkmate",
+            Status::Stalemate => "stalemate",
+            Status::Draw(rule) => return write!(f, "{}", rule),
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// A rule by which a position is drawn apart from stalemate. Some end the game automatically,
+/// others only give a player the right to claim the draw.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DrawRule {
+    /// Neither side can mate anymore, see `is_insufficient_material`.
+    InsufficientMaterial,
+    /// Seventy-five moves without capture or pawn move.
+    SeventyFiveMoves,
+    /// The position occurred for the fifth time.
+    FivefoldRepetition,
+    /// Fifty moves without capture or pawn move, which may be claimed.
+    FiftyMoves,
+    /// The position occurred for the third time, which may be claimed.
+    ThreefoldRepetition,
+}
+
+impl DrawRule {
+    /// Whether the rule ends the game without a claim.
+    pub fn is_automatic(self) -> bool {
+        !matches!(self, DrawRule::FiftyMoves | DrawRule::ThreefoldRepetition)
+    }
+
+    /// The termination, which does not tell claimed and automatic draws apart.
+    pub fn termination(self) -> Termination {
+        match self {
+            DrawRule::InsufficientMaterial => Termination::InsufficientMaterial,
+            DrawRule::SeventyFiveMoves | DrawRule::FiftyMoves => Termination::FiftyMoves,
+            DrawRule::FivefoldRepetition | DrawRule::ThreefoldRepetition => Termination::Repetition,
+        }
+    }
+}
+
+impl Display for DrawRule {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let rule = match self {
+            DrawRule::InsufficientMaterial => "insufficient_material",
+            DrawRule::SeventyFiveMoves => "seventy_five_moves",
+            DrawRule::FivefoldRepetition => "fivefold_repetition",
+            DrawRule::FiftyMoves => "fifty_moves",
+            DrawRule::ThreefoldRepetition => "threefold_repetition",
+        };
+        write!(f, "{}", rule)
+    }
+}
+
+/// Whether claimable draws end a replayed game, as the moves do not tell whether a player claimed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ClaimPolicy {
+    /// Over-the-board rules: only automatic draws end the game, claimable ones might have been
+    /// played on.
+    #[default]
+    OverTheBoard,
+    /// Rules of online platforms like Lichess, where threefold repetition and the fifty-move rule
+    /// end the game right away.
+    Online,
+}
+
+impl ClaimPolicy {
+    /// Whether a draw by the rule ends the game under this policy.
+    pub fn ends_game(self, rule: DrawRule) -> bool {
+        rule.is_automatic() || (self == ClaimPolicy::Online)
+    }
+}
+
+impl FromStr for ClaimPolicy {
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "otb" | "over-the-board" => Ok(ClaimPolicy::OverTheBoard),
+            "online" => Ok(ClaimPolicy::Online),
+            _ => Err(format!("unknown claim policy: {}", policy)),
+        }
+    }
+
+    type Err = String;
+}
+
+impl Game {
+    /// The rule by which the position is drawn, where automatic draws take precedence over
+    /// claimable ones. Checkmate and stalemate are left to `status`.
+    pub fn draw_rule(&self) -> Option<DrawRule> {
+        let n_repetitions = repetitions(self);
+        if is_insufficient_material(self) {
+            Some(DrawRule::InsufficientMaterial)
+        } else if self.half_move_clock >= 150 {
+            Some(DrawRule::SeventyFiveMoves)
+        } else if n_repetitions >= 5 {
+            Some(DrawRule::FivefoldRepetition)
+        } else if self.half_move_clock >= 100 {
+            Some(DrawRule::FiftyMoves)
+        } else if n_repetitions >= 3 {
+            Some(DrawRule::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// Like `status`, but a game that goes on on the board is drawn by a rule that ends it under
+    /// the policy. Checkmate takes precedence, e.g. on the seventy-fifth move.
+    pub fn status_with(&self, policy: ClaimPolicy) -> Status {
+        match self.status() {
+            Status::Ongoing => match self.draw_rule() {
+                Some(rule) if policy.ends_game(rule) => Status::Draw(rule),
+                _ => Status::Ongoing,
+            },
+            status => status,
+        }
+    }
+}
+
+/// Comparison of a declared result with the final position of the replayed game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResultCheck {
+    pub declared: Option<GameResult>,
+    pub status: Status,
+
+    /// Whether the declared result is possible given the final position, e.g. a checkmate must
+    /// be declared as a win for the mating side.
+    pub is_consistent: bool,
+
+    /// Whether the game ended with a result while the position was not terminal, i.e. by
+    /// resignation, agreement, time forfeit or abandonment.
+    pub is_mid_game_termination: bool,
+
+    /// Whether the last move was marked as checkmate, e.g. "Qh4#", which is only consistent with
+    /// a checkmate on the board.
+    pub is_declared_mate: bool,
+}
+
+/// Check the declared result against the final state of a game.
+pub fn check_result(game: &Game, declared: Option<GameResult>) -> ResultCheck {
+    check_result_and_mate(game, declared, false)
+}
+
+/// Check the declared result and the mate marker of the last move against the final state.
+fn check_result_and_mate(
+    game: &Game,
+    declared: Option<GameResult>,
+    is_declared_mate: bool,
+) -> ResultCheck {
+    let status = game.status();
+    let is_consistent = match (status, declared) {
+        (_, None) => true,
+        (Status::Checkmate, Some(result)) => result == GameResult::win_for(game.color.next()),
+        (Status::Stalemate | Status::Draw(_), Some(result)) => result == GameResult::Draw,
+        (Status::Ongoing, Some(_)) => true,
+    } && (!is_declared_mate || (status == Status::Checkmate));
+    let is_mid_game_termination =
+        (status == Status::Ongoing) && declared.is_some_and(|r| r != GameResult::Undecided);
+
+    ResultCheck {
+        declared,
+        status,
+        is_consistent,
+        is_mid_game_termination,
+        is_declared_mate,
+    }
+}
+
+/// Check whether a token of a move list is a game termination marker, e.g. "1-0".
+pub fn is_result_token(token: &str) -> bool {
+    RESULT_TOKENS.contains(&token)
+}
+
+/// Replay a list of tokens, where result tokens are not played but taken as the declared
+/// result, and check the result and a mate marker of the last move against the final position.
+pub fn replay_and_check(tokens: &[&str]) -> Result<(Game, ResultCheck), String> {
+    let mut game = Game::new();
+    let mut declared = None;
+    let mut is_declared_mate = false;
+    let mut ply = 0;
+    for &token in tokens {
+        match GameResult::from_str(token) {
+            Ok(result) => declared = Some(result),
+            Err(_) => {
+                ply += 1;
+                game.play_move(token).map_err(at_ply(ply))?;
+                is_declared_mate = SanMove::from_str(token).is_ok_and(|mv| mv.is_checkmate());
+            }
+        }
+    }
+    let check = check_result_and_mate(&game, declared, is_declared_mate);
+
+    Ok((game, check))
+}
+
+/// How certain an inferred result is.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Confidence {
+    /// Nothing in the position decides the game, e.g. it may have ended by resignation.
+    Unknown,
+    /// A draw could have been claimed (fifty-move rule or threefold repetition).
+    Likely,
+    /// The rules end the game, e.g. by checkmate or with insufficient material.
+    Certain,
+}
+
+impl Display for Confidence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let confidence = match self {
+            Confidence::Unknown => "unknown",
+            Confidence::Likely => "likely",
+            Confidence::Certain => "certain",
+        };
+        write!(f, "{}", confidence)
+    }
+}
+
+/// Why a game ended, as far as it can be told from the moves alone.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    /// Fifty moves without capture or pawn move, which is automatic after seventy-five.
+    FiftyMoves,
+    /// The final position occurred three times, which is automatic after five.
+    Repetition,
+    Unknown,
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let termination = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient_material",
+            Termination::FiftyMoves => "fifty_moves",
+            Termination::Repetition => "repetition",
+            Termination::Unknown => "unknown",
+        };
+        write!(f, "{}", termination)
+    }
+}
+
+/// A result derived from the final position of a game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InferredResult {
+    pub result: GameResult,
+    pub termination: Termination,
+    pub confidence: Confidence,
+}
+
+/// Check whether neither side can mate anymore: only kings, a single minor piece or bishops that
+/// are all on squares of the same color remain.
+pub fn is_insufficient_material(game: &Game) -> bool {
+    let others: Vec<_> = game
+        .position
+        .iter()
+        .flatten()
+        .filter(|fig| fig.piece() != Piece::K)
+        .collect();
+    match others.as_slice() {
+        [] => true,
+        [fig] => matches!(fig.piece(), Piece::B | Piece::N),
+        figs => {
+            let square_color = |x: i8, y: i8| (x + y) % 2;
+            figs.iter().all(|fig| fig.piece() == Piece::B)
+                && figs.iter().all(|fig| {
+                    let (c, first) = (fig.coord(), figs[0].coord());
+                    square_color(c.x, c.y) == square_color(first.x, first.y)
+                })
+        }
+    }
+}
+
+/// Number of times the current position occurred in the game, including now.
+fn repetitions(game: &Game) -> usize {
+    let hash = game.history.iter().next().map(|s| s.hash);
+    game.history.iter().filter(|s| Some(s.hash) == hash).count()
+}
+
+/// Infer the result from the final position of a game. Games that did not end by the rules yield
+/// an undecided result ("*") with unknown confidence, as resignations cannot be told apart from
+/// truncated games. Claimable draws are likely under over-the-board rules, see
+/// `infer_game_result_with`.
+pub fn infer_game_result(game: &Game) -> InferredResult {
+    infer_game_result_with(game, ClaimPolicy::default())
+}
+
+/// Infer the result like `infer_game_result`, where draws that end the game under the policy are
+/// certain, e.g. a threefold repetition with `ClaimPolicy::Online`.
+pub fn infer_game_result_with(game: &Game, policy: ClaimPolicy) -> InferredResult {
+    let (result, termination, confidence) = match game.status() {
+        Status::Checkmate => (
+            GameResult::win_for(game.color.next()),
+            Termination::Checkmate,
+            Confidence::Certain,
+        ),
+        Status::Stalemate => (
+            GameResult::Draw,
+            Termination::Stalemate,
+            Confidence::Certain,
+        ),
+        _ => match game.draw_rule() {
+            Some(rule) => {
+                let confidence = if policy.ends_game(rule) {
+                    Confidence::Certain
+                } else {
+                    Confidence::Likely
+                };
+                (GameResult::Draw, rule.termination(), confidence)
+            }
+            None => (
+                GameResult::Undecided,
+                Termination::Unknown,
+                Confidence::Unknown,
+            ),
+        },
+    };
+
+    InferredResult {
+        result,
+        termination,
+        confidence,
+    }
+}
+
+/// Replay the moves and infer the result, e.g. to repair games without a termination marker.
+/// Result tokens in the moves are ignored.
+pub fn infer_result(moves: &[&str]) -> Result<InferredResult, String> {
+    infer_result_with(moves, ClaimPolicy::default())
+}
+
+/// Replay the moves and infer the result under the policy, see `infer_game_result_with`.
+pub fn infer_result_with(moves: &[&str], policy: ClaimPolicy) -> Result<InferredResult, String> {
+    let game = replay_plies(Game::new(), moves).finish()?;
+
+    Ok(infer_game_result_with(&game, policy))
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_result_tokens() {
+    for token in RESULT_TOKENS {
+        assert!(is_result_token(token));
+        assert_eq!(GameResult::from_str(token).unwrap().to_string(), token);
+    }
+    assert!(!is_result_token("e4"));
+    assert!(GameResult::from_str("1-1").is_err());
+
+    assert_eq!(GameResult::BlackWins.score_for(Color::W), Some(-1));
+    assert_eq!(GameResult::BlackWins.score_for(Color::B), Some(1));
+    assert_eq!(GameResult::Draw.score_for(Color::B), Some(0));
+    assert_eq!(GameResult::Undecided.score_for(Color::W), None);
+}
+
+#[test]
+fn check_declared_mate() {
+    let (game, check) =
+        replay_and_check(&["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#", "1-0"]).unwrap();
+
+    assert_eq!(game.uci, "h5f7");
+    assert_eq!(check.status, Status::Checkmate);
+    assert_eq!(check.declared, Some(GameResult::WhiteWins));
+    assert!(check.is_consistent);
+    assert!(!check.is_mid_game_termination);
+    assert!(check.is_declared_mate);
+
+    let (_, check) =
+        replay_and_check(&["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#", "0-1"]).unwrap();
+    assert!(!check.is_consistent);
+
+    // The mate marker is kept like the check marker, and a false one is inconsistent.
+    let (_, check) = replay_and_check(&["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]).unwrap();
+    assert_eq!(check.status, Status::Checkmate);
+    assert!(check.is_declared_mate);
+    let (_, check) = replay_and_check(&["e4", "e5", "Qh5#", "*"]).unwrap();
+    assert_eq!(check.status, Status::Ongoing);
+    assert!(check.is_declared_mate);
+    assert!(!check.is_consistent);
+    let (_, check) = replay_and_check(&["e4", "e5", "Qh5+", "*"]).unwrap();
+    assert!(!check.is_declared_mate);
+    assert!(check.is_consistent);
+}
+
+#[test]
+fn check_mid_game_termination() {
+    let (_, check) = replay_and_check(&["e4", "e5", "1-0"]).unwrap();
+    assert_eq!(check.status, Status::Ongoing);
+    assert!(check.is_consistent);
+    assert!(check.is_mid_game_termination);
+
+    let (_, check) = replay_and_check(&["e4", "e5", "*"]).unwrap();
+    assert!(!check.is_mid_game_termination);
+
+    let (_, check) = replay_and_check(&["e4", "e5"]).unwrap();
+    assert_eq!(check.declared, None);
+    assert!(!check.is_mid_game_termination);
+}
+
+#[test]
+fn check_declared_stalemate() {
+    let mut game = Game::from_str("7k/5Q2/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    game.play_move("Qf6").unwrap();
+    assert_eq!(game.status(), Status::Ongoing);
+
+    let mut game = Game::from_str("7k/8/5QK1/8/8/8/8/8 w - - 0 1").unwrap();
+    game.play_move("Qf7").unwrap();
+    assert_eq!(game.status(), Status::Stalemate);
+    assert!(check_result(&game, Some(GameResult::Draw)).is_consistent);
+    assert!(!check_result(&game, Some(GameResult::WhiteWins)).is_consistent);
+}
+
+#[test]
+fn check_inferred_decisive_results() {
+    let inferred = infer_result(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+    assert_eq!(inferred.result, GameResult::BlackWins);
+    assert_eq!(inferred.termination, Termination::Checkmate);
+    assert_eq!(inferred.confidence, Confidence::Certain);
+
+    // A resignation cannot be told apart from a truncated game.
+    let inferred = infer_result(&["e4", "e5", "1-0"]).unwrap();
+    assert_eq!(inferred.result, GameResult::Undecided);
+    assert_eq!(inferred.confidence, Confidence::Unknown);
+    assert!(infer_result(&["e4", "Ke7"]).is_err());
+}
+
+#[test]
+fn check_inferred_draws() {
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let inferred = infer_result(&shuffle.repeat(2)).unwrap();
+    assert_eq!(inferred.termination, Termination::Repetition);
+    assert_eq!(inferred.confidence, Confidence::Likely);
+    let inferred = infer_result(&shuffle.repeat(4)).unwrap();
+    assert_eq!(inferred.confidence, Confidence::Certain);
+
+    let game = Game::from_str("8/8/4k3/8/8/2K5/8/R7 w - - 120 80").unwrap();
+    let inferred = infer_game_result(&game);
+    assert_eq!(inferred.result, GameResult::Draw);
+    assert_eq!(inferred.termination, Termination::FiftyMoves);
+    assert_eq!(inferred.confidence, Confidence::Likely);
+}
+
+#[test]
+fn check_claimable_and_automatic_draws() {
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    let mut game = Game::new();
+    for mv in shuffle.repeat(2) {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(game.draw_rule(), Some(DrawRule::ThreefoldRepetition));
+    assert!(!DrawRule::ThreefoldRepetition.is_automatic());
+    assert_eq!(game.status_with(ClaimPolicy::OverTheBoard), Status::Ongoing);
+    assert_eq!(
+        game.status_with(ClaimPolicy::Online),
+        Status::Draw(DrawRule::ThreefoldRepetition)
+    );
+    let inferred = infer_result_with(&shuffle.repeat(2), ClaimPolicy::Online).unwrap();
+    assert_eq!(inferred.confidence, Confidence::Certain);
+
+    for mv in shuffle.repeat(2) {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        game.status_with(ClaimPolicy::OverTheBoard).to_string(),
+        "fivefold_repetition"
+    );
+
+    // Seventy-five moves end the game, fifty only when claimed, checkmate takes precedence.
+    let game = Game::from_str("8/8/4k3/8/8/2K5/8/R7 w - - 150 100").unwrap();
+    assert_eq!(
+        game.status_with(ClaimPolicy::OverTheBoard),
+        Status::Draw(DrawRule::SeventyFiveMoves)
+    );
+    let game = Game::from_str("8/8/4k3/8/8/2K5/8/R7 w - - 100 80").unwrap();
+    assert_eq!(game.status_with(ClaimPolicy::OverTheBoard), Status::Ongoing);
+    let game = Game::from_str("1k5R/8/1K6/8/8/8/8/8 b - - 150 100").unwrap();
+    assert_eq!(
+        game.status_with(ClaimPolicy::OverTheBoard),
+        Status::Checkmate
+    );
+    assert_eq!(ClaimPolicy::from_str("online"), Ok(ClaimPolicy::Online));
+}
+
+#[test]
+fn check_insufficient_material() {
+    for (fen, expected) in [
+        ("8/8/4k3/8/8/2K5/8/8 w - - 0 1", true),
+        ("8/8/4k3/8/8/2K5/8/6N1 w - - 0 1", true),
+        ("8/8/4k3/2b5/8/2K5/8/6B1 w - - 0 1", true),
+        ("8/8/4k3/3b4/8/2K5/8/6B1 w - - 0 1", false),
+        ("8/8/4k3/8/8/2K5/8/5NN1 w - - 0 1", false),
+        ("8/8/4k3/8/8/2K5/6P1/8 w - - 0 1", false),
+    ] {
+        assert_eq!(
+            is_insufficient_material(&Game::from_str(fen).unwrap()),
+            expected,
+            "{}",
+            fen
+        );
+    }
+    let game = Game::from_str("8/8/4k3/8/8/2K5/8/6B1 b - - 0 1").unwrap();
+    assert_eq!(
+        infer_game_result(&game).termination,
+        Termination::InsufficientMaterial
+    );
+}
@@ -0,0 +1,566 @@
+use crate::utils::draw::is_invisible;
+use crate::utils::error::Error;
+use crate::utils::filter::GameFilter;
+use crate::utils::game::Game;
+use crate::utils::lichess::Eval;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// Tokens that terminate the movetext of a game.
+pub const RESULT_TOKENS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// A single game as found in a PGN file: the tag pairs plus the moves in SAN.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PgnGame {
+    /// Tag pairs in order of appearance, e.g. ("White", "Carlsen, Magnus").
+    pub headers: Vec<(String, String)>,
+
+    /// Moves of the main line in SAN, without move numbers, comments or variations.
+    pub moves: Vec<String>,
+
+    /// Game termination marker of the movetext, if there is one.
+    pub result: Option<String>,
+
+    /// Remaining time in seconds after each move from "[%clk 0:03:00]" comments, aligned with the
+    /// moves. Empty if the game has no clock annotations at all.
+    pub clocks: Vec<Option<u32>>,
+
+    /// Evaluation after each move from "[%eval 0.23]" comments from the point of view of white,
+    /// aligned with the moves. Empty if the game has no evals at all.
+    pub evals: Vec<Option<Eval>>,
+}
+
+impl PgnGame {
+    /// Lookup the value of a tag pair by its name.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn moves(&self) -> Vec<&str> {
+        self.moves.iter().map(|m| m.as_str()).collect()
+    }
+
+    /// The position the game starts from, which is given by the FEN tag unless the SetUp tag is
+    /// "0". Move clocks and castling rights are taken from the FEN, thus move numbers continue
+    /// from there.
+    pub fn start_position(&self) -> Result<Game, Error> {
+        match self.header("FEN") {
+            Some(fen) if self.header("SetUp") != Some("0") => {
+                Game::from_str(fen).map_err(|e| e.located("invalid FEN tag"))
+            }
+            _ => Ok(Game::new()),
+        }
+    }
+}
+
+/// Check whether a line is to be ignored by the escape mechanism of the PGN standard, i.e. it
+/// starts with a "%" in the first column.
+pub(crate) fn is_escape_line(line: &[u8]) -> bool {
+    line.first() == Some(&b'%')
+}
+
+/// Parse a tag pair line like `[Event "Rated Blitz game"]`. Quotes and backslashes within the
+/// value are escaped by a backslash, e.g. `[Event "The \"Immortal\" game"]`.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('[')?;
+    let (key, rest) = inner.split_once(char::is_whitespace)?;
+    let mut chars = rest.trim_start().strip_prefix('"')?.chars();
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '\\' => value.push(chars.next()?),
+            '"' => break,
+            c => value.push(c),
+        }
+    }
+    if chars.as_str().trim() != "]" {
+        return None;
+    }
+
+    Some((key.to_string(), value))
+}
+
+/// The value of a command within a comment, e.g. "0:03:00" for "clk" in "{ [%clk 0:03:00] }".
+fn comment_command<'a>(comment: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = comment.split_once(&format!("[%{}", name))?;
+    let (value, _) = rest.split_once(']')?;
+    Some(value.trim())
+}
+
+/// Parse a clock annotation like "[%clk 1:02:03.4]" within a comment to seconds.
+fn parse_clock_comment(comment: &str) -> Option<u32> {
+    let clock = comment_command(comment, "clk")?.split('.').next()?;
+    clock
+        .split(':')
+        .try_fold(0, |secs, part| Some(secs * 60 + part.parse::<u32>().ok()?))
+}
+
+/// Parse an evaluation like "[%eval 0.23]" (in pawns) or "[%eval #-3]" within a comment. A depth
+/// as in "[%eval 0.23,18]" is ignored.
+fn parse_eval_comment(comment: &str) -> Option<Eval> {
+    let eval = comment_command(comment, "eval")?.split(',').next()?;
+    match eval.strip_prefix('#') {
+        Some(mate) => mate.parse().ok().map(Eval::Mate),
+        None => eval
+            .parse::<f64>()
+            .ok()
+            .filter(|e| e.is_finite())
+            .map(|e| Eval::Cp((e * 100.0).round() as i32)),
+    }
+}
+
+/// Moves, result and annotations of the main line of a movetext.
+struct Movetext {
+    moves: Vec<String>,
+    result: Option<String>,
+    clocks: Vec<Option<u32>>,
+    evals: Vec<Option<Eval>>,
+}
+
+/// Strip the move number from a token, also if it is glued to the move like in "12.Nf3" or
+/// "12...Nf6", and the dots of a black move like "...Nf6" or "…Nf6". A bare move number like "12."
+/// or "12" leaves nothing, while castling like "0-0" is kept.
+fn strip_move_number(token: &str) -> &str {
+    let rest = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    let stripped = rest.trim_start_matches(['.', '…']);
+    if stripped.len() < rest.len() || rest.is_empty() {
+        stripped
+    } else {
+        token
+    }
+}
+
+/// Decompose movetext into the moves of the main line and the result token.
+pub fn parse_movetext(movetext: &str) -> (Vec<String>, Option<String>) {
+    let parsed = parse_annotated_movetext(movetext);
+    (parsed.moves, parsed.result)
+}
+
+/// Decompose movetext like `parse_movetext`, plus the clock and eval annotations of the moves.
+fn parse_annotated_movetext(movetext: &str) -> Movetext {
+    let mut moves = Vec::new();
+    let mut result = None;
+    let mut clocks: Vec<Option<u32>> = Vec::new();
+    let mut evals: Vec<Option<Eval>> = Vec::new();
+
+    // Remove comments and variations first, as those may contain arbitrary content. Braces never
+    // remain in the cleaned movetext, thus "{n}" can refer to the n-th comment of the main line.
+    let mut comments = Vec::new();
+    let mut cleaned = String::with_capacity(movetext.len());
+    let mut variation_depth = 0;
+    let mut is_line_start = true;
+    let mut chars = movetext.chars();
+    while let Some(c) = chars.next() {
+        let is_escape = is_line_start && c == '%';
+        is_line_start = c == '\n';
+        match c {
+            _ if is_escape => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                is_line_start = true;
+                cleaned.push(' ');
+            }
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if variation_depth == 0 && comment.contains("[%") {
+                    cleaned.push_str(&format!(" {{{}}} ", comments.len()));
+                    comments.push(comment);
+                } else {
+                    cleaned.push(' ');
+                }
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                is_line_start = true;
+                cleaned.push(' ');
+            }
+            '(' => variation_depth += 1,
+            ')' => {
+                variation_depth = (variation_depth - 1).max(0);
+                cleaned.push(' ');
+            }
+            _ if variation_depth > 0 => {}
+            c if is_invisible(c) => cleaned.push(' '),
+            c => cleaned.push(c),
+        }
+    }
+
+    for token in cleaned.split_whitespace() {
+        if RESULT_TOKENS.contains(&token) {
+            result = Some(token.to_string());
+        } else if let Some(idx) = token.strip_prefix('{') {
+            let comment = idx
+                .trim_end_matches('}')
+                .parse()
+                .ok()
+                .and_then(|i: usize| comments.get(i));
+            if let (Some(comment), Some(clock), Some(eval)) =
+                (comment, clocks.last_mut(), evals.last_mut())
+            {
+                *clock = clock.or(parse_clock_comment(comment));
+                *eval = eval.or(parse_eval_comment(comment));
+            }
+        } else if token.starts_with('$') {
+            // skip numeric annotation glyphs.
+            continue;
+        } else {
+            // skip move numbers (e.g. "12." or "12..."), also repeated ones after comments.
+            let token = strip_move_number(token);
+            if token.is_empty() {
+                continue;
+            }
+            moves.push(token.to_string());
+            clocks.push(None);
+            evals.push(None);
+        }
+    }
+    if clocks.iter().all(Option::is_none) {
+        clocks.clear();
+    }
+    if evals.iter().all(Option::is_none) {
+        evals.clear();
+    }
+
+    Movetext {
+        moves,
+        result,
+        clocks,
+        evals,
+    }
+}
+
+/// Check whether a brace comment is still open after a line of movetext. A ";" starts a comment
+/// until the end of the line, thus braces after it do not count, just like a ";" within braces.
+pub(crate) fn is_in_comment_after(line: &[u8], mut in_comment: bool) -> bool {
+    for &b in line {
+        match b {
+            b'}' if in_comment => in_comment = false,
+            _ if in_comment => {}
+            b'{' => in_comment = true,
+            b';' => break,
+            _ => {}
+        }
+    }
+    in_comment
+}
+
+/// Streaming reader that yields one game after the other from a buffered source. With a filter,
+/// games with unwanted tag pairs are skipped without parsing their movetext.
+pub struct PgnReader<R: BufRead> {
+    source: R,
+    line: String,
+    done: bool,
+    filter: Option<GameFilter>,
+
+    /// Number of games parsed so far, including filtered ones, which numbers the traced games.
+    n_games: usize,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(source: R) -> Self {
+        PgnReader {
+            source,
+            line: String::new(),
+            done: false,
+            filter: None,
+            n_games: 0,
+        }
+    }
+
+    /// Only yield the games whose tag pairs match the filter.
+    pub fn with_filter(source: R, filter: GameFilter) -> Self {
+        PgnReader {
+            filter: Some(filter),
+            ..PgnReader::new(source)
+        }
+    }
+
+    fn is_wanted(&self, game: &PgnGame) -> bool {
+        self.filter.as_ref().is_none_or(|f| f.matches(game))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = std::io::Result<PgnGame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("parse_pgn_game", game = self.n_games).entered();
+            let mut game = PgnGame::default();
+            let mut movetext = String::new();
+            let mut has_movetext = false;
+            let mut is_wanted = None;
+            let mut in_comment = false;
+
+            loop {
+                // The line buffer may hold the first header of the next game from the previous
+                // call.
+                if self.line.is_empty() {
+                    match self.source.read_line(&mut self.line) {
+                        Ok(0) => {
+                            self.done = true;
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            self.done = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+
+                let trimmed = self.line.trim();
+                if is_escape_line(self.line.as_bytes()) {
+                    // Ignored, like lines of other software per the PGN standard.
+                } else if !in_comment && trimmed.starts_with('[') {
+                    // A header after movetext starts the next game, thus keep the line buffered.
+                    if has_movetext {
+                        break;
+                    }
+                    if let Some(tag) = parse_tag(trimmed) {
+                        game.headers.push(tag);
+                    }
+                } else {
+                    in_comment = is_in_comment_after(self.line.as_bytes(), in_comment);
+                    if !trimmed.is_empty() {
+                        has_movetext = true;
+                        // The headers are complete with the first line of movetext.
+                        if *is_wanted.get_or_insert_with(|| self.is_wanted(&game)) {
+                            movetext.push_str(&self.line);
+                        }
+                    }
+                }
+                self.line.clear();
+            }
+
+            if game.headers.is_empty() && !has_movetext {
+                return None;
+            }
+            self.n_games += 1;
+            if !is_wanted.unwrap_or_else(|| self.is_wanted(&game)) {
+                continue;
+            }
+            let parsed = parse_annotated_movetext(&movetext);
+            game.moves = parsed.moves;
+            game.result = parsed.result;
+            game.clocks = parsed.clocks;
+            game.evals = parsed.evals;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(moves = game.moves.len(), "parsed game");
+
+            return Some(Ok(game));
+        }
+        None
+    }
+}
+
+/// Parse all games of a PGN string.
+pub fn parse_pgn(pgn: &str) -> Vec<PgnGame> {
+    PgnReader::new(pgn.as_bytes())
+        .filter_map(|game| game.ok())
+        .collect()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const TWO_GAMES: &str = r#"[Event "Rated Blitz game"]
+[Site "https://lichess.org/U1N9Qa74"]
+[White "alice"]
+[Black "bob"]
+[Result "1-0"]
+
+1. e4 { [%clk 0:03:00] } 1... e5 2. Nf3 $1 (2. f4 exf4) 2... Nc6 ; a line comment
+3. Bb5 1-0
+
+[Event "Casual game"]
+[Result "*"]
+
+1. d4 d5 *
+"#;
+
+#[test]
+fn check_parse_tag() {
+    assert_eq!(
+        parse_tag("[White \"Carlsen, Magnus\"]"),
+        Some(("White".to_string(), "Carlsen, Magnus".to_string()))
+    );
+    assert_eq!(parse_tag("1. e4 e5"), None);
+    assert_eq!(
+        parse_tag(r#"[Event "The \"Immortal\" game \\ 1851"]"#),
+        Some((
+            "Event".to_string(),
+            r#"The "Immortal" game \ 1851"#.to_string()
+        ))
+    );
+    assert_eq!(
+        parse_tag(r#"[Annotator "a]b"]"#),
+        Some(("Annotator".to_string(), "a]b".to_string()))
+    );
+    assert_eq!(parse_tag(r#"[Event "open]"#), None);
+    assert_eq!(parse_tag(r#"[Event "a" b]"#), None);
+}
+
+#[test]
+fn check_escape_lines() {
+    let pgn = "%TWIC export\n[Event \"Say \\\"hi\\\"\"]\n%[Site \"?\"]\n\n\
+               1. e4 e5\n% 2. d4\n2. Nf3 *\n";
+    let games = parse_pgn(pgn);
+    assert_eq!(games.len(), 1);
+    assert_eq!(
+        games[0].headers,
+        vec![("Event".to_string(), "Say \"hi\"".to_string())]
+    );
+    assert_eq!(games[0].moves, vec!["e4", "e5", "Nf3"]);
+
+    let (moves, _) = parse_movetext("%start\n1. e4 e5 ; comment\n%2. d4\n2. Nf3");
+    assert_eq!(moves, vec!["e4", "e5", "Nf3"]);
+}
+
+#[test]
+fn check_parse_movetext() {
+    let (moves, result) =
+        parse_movetext("1. e4 {comment (with) parens} e5 (1... c5 2. Nf3) 2. Nf3 $5 1/2-1/2");
+    assert_eq!(moves, vec!["e4", "e5", "Nf3"]);
+    assert_eq!(result, Some("1/2-1/2".to_string()));
+}
+
+#[test]
+fn check_parse_glued_move_numbers() {
+    let (moves, result) = parse_movetext(
+        "1.e4 1...c5 2.Nf3 {Sicilian} 2... d6 3 d4 3..cxd4 4.Nxd4 …Nf6 5.Nc3 a6 6.0-0-0 *",
+    );
+    assert_eq!(
+        moves,
+        vec!["e4", "c5", "Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6", "Nc3", "a6", "0-0-0"]
+    );
+    assert_eq!(result, Some("*".to_string()));
+
+    // As found in exports of some GUIs, with the number repeated after every comment.
+    let pgn = "[Result \"1-0\"]\n\n1.e4 {best by test} 1... e5 2.Nf3 {develops} 2...Nc6\n\
+               3.Bb5 {The Spanish.} 3...a6 { } 4.Ba4 1-0\n";
+    let games = parse_pgn(pgn);
+    assert_eq!(
+        games[0].moves,
+        vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4"]
+    );
+}
+
+#[test]
+fn check_parse_movetext_with_odd_characters() {
+    let (moves, _) = parse_movetext("1.\u{200b} e4!\u{a0}e5 \u{feff}2. Nf3?! \u{200b} Nc6");
+    assert_eq!(moves, vec!["e4!", "e5", "Nf3?!", "Nc6"]);
+}
+
+#[test]
+fn check_parse_clocks() {
+    let parsed = parse_annotated_movetext(
+        "1. e4 { [%clk 1:00:05] } e5 {[%clk 0:59:59.9]} (1... c5 { [%clk 0:00:01] }) 2. Nf3 {ok}",
+    );
+    assert_eq!(parsed.moves, vec!["e4", "e5", "Nf3"]);
+    assert_eq!(parsed.clocks, vec![Some(3605), Some(3599), None]);
+    assert!(parsed.evals.is_empty());
+    assert_eq!(parse_clock_comment("[%clk 0:x:01]"), None);
+    assert!(parse_annotated_movetext("1. e4 e5").clocks.is_empty());
+}
+
+#[test]
+fn check_parse_evals() {
+    let parsed = parse_annotated_movetext(
+        "1. e4 { [%eval 0.17] [%clk 0:03:00] } e5 { [%eval -0.3,20] } 2. Qh5 Nc6 3. Bc4 Nf6 \
+         4. Qxf7# { [%eval #0] } 1-0",
+    );
+    assert_eq!(
+        parsed.evals,
+        vec![
+            Some(Eval::Cp(17)),
+            Some(Eval::Cp(-30)),
+            None,
+            None,
+            None,
+            None,
+            Some(Eval::Mate(0))
+        ]
+    );
+    assert_eq!(parsed.clocks[0], Some(180));
+    assert_eq!(parse_eval_comment("[%eval #-3]"), Some(Eval::Mate(-3)));
+    assert_eq!(parse_eval_comment("[%eval NaN]"), None);
+}
+
+#[test]
+fn check_parse_pgn() {
+    let games = parse_pgn(TWO_GAMES);
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].header("White"), Some("alice"));
+    assert_eq!(games[0].moves, vec!["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+    assert_eq!(games[0].result, Some("1-0".to_string()));
+    assert_eq!(games[0].clocks, vec![Some(180), None, None, None, None]);
+    assert_eq!(games[1].header("Event"), Some("Casual game"));
+    assert_eq!(games[1].moves, vec!["d4", "d5"]);
+    assert_eq!(games[1].result, Some("*".to_string()));
+}
+
+#[test]
+fn check_pgn_without_headers() {
+    let games = parse_pgn("1. e4 e5 2. Nf3\n");
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].moves, vec!["e4", "e5", "Nf3"]);
+    assert_eq!(games[0].result, None);
+}
+
+#[test]
+fn check_braces_in_line_comments() {
+    let pgn =
+        "[Event \"A\"]\n\n1. e4 ; not a {comment\n1... e5 { a [bracket]\n[not a header] } *\n\n\
+               [Event \"B\"]\n\n1. d4 *\n";
+    let games = parse_pgn(pgn);
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves, vec!["e4", "e5"]);
+    assert_eq!(games[1].header("Event"), Some("B"));
+}
+
+#[test]
+fn check_start_position() {
+    let pgn =
+        "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K2R b KQ - 3 40\"]\n\n40... Kd7 41. O-O-O+ *\n";
+    let games = parse_pgn(pgn);
+    let mut board = games[0].start_position().unwrap();
+    for mv in games[0].moves() {
+        board.play_move(mv).unwrap();
+    }
+    assert_eq!(board.to_fen(), "8/3k4/8/8/8/8/8/2KR3R b - - 5 41");
+
+    let mut game = PgnGame::default();
+    assert_eq!(game.start_position(), Ok(Game::new()));
+    game.headers.push(("FEN".to_string(), "8/8 w".to_string()));
+    assert!(game.start_position().is_err());
+    game.headers.push(("SetUp".to_string(), "0".to_string()));
+    assert_eq!(game.start_position(), Ok(Game::new()));
+}
+
+#[test]
+fn check_filtered_pgn() {
+    let filter = GameFilter {
+        results: vec![crate::utils::outcome::GameResult::Undecided],
+        ..GameFilter::default()
+    };
+    let games: Vec<PgnGame> = PgnReader::with_filter(TWO_GAMES.as_bytes(), filter)
+        .map(|game| game.unwrap())
+        .collect();
+
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].header("Event"), Some("Casual game"));
+    assert_eq!(games[0].moves, vec!["d4", "d5"]);
+}
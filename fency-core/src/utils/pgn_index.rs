@@ -0,0 +1,327 @@
+use crate::utils::pgn::{is_escape_line, is_in_comment_after, PgnGame, PgnReader};
+use crate::utils::progress::CancellationToken;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Byte ranges of the games of a PGN source, in order. Games are split just like `PgnReader`
+/// does, i.e. a tag pair after movetext starts the next game, unless it is within a comment.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PgnIndex {
+    spans: Vec<Range<u64>>,
+}
+
+impl PgnIndex {
+    /// Scan a source line by line without parsing the games.
+    pub fn build<R: BufRead>(mut source: R) -> std::io::Result<Self> {
+        let mut spans = Vec::new();
+        let mut line = Vec::new();
+        let mut offset = 0;
+        let mut start = 0;
+        let mut has_content = false;
+        let mut has_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            line.clear();
+            let n = source.read_until(b'\n', &mut line)? as u64;
+            if n == 0 {
+                break;
+            }
+            let trimmed = line.trim_ascii();
+            if is_escape_line(&line) {
+                // Ignored like in `PgnReader`.
+            } else if !in_comment && trimmed.starts_with(b"[") {
+                if has_movetext {
+                    spans.push(start..offset);
+                    start = offset;
+                    has_movetext = false;
+                }
+                has_content = true;
+            } else {
+                in_comment = is_in_comment_after(&line, in_comment);
+                if !trimmed.is_empty() {
+                    has_content = true;
+                    has_movetext = true;
+                }
+            }
+            if !has_content {
+                // Skip blank lines in front of the first game.
+                start = offset + n;
+            }
+            offset += n;
+        }
+        if has_content {
+            spans.push(start..offset);
+        }
+
+        Ok(PgnIndex { spans })
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Byte range of the n-th game (counting from 0).
+    pub fn span(&self, n: usize) -> Option<Range<u64>> {
+        self.spans.get(n).cloned()
+    }
+
+    /// Split the games into at most `n` consecutive chunks of about the same number of bytes.
+    pub fn chunks(&self, n: usize) -> Vec<Range<usize>> {
+        let Some(last) = self.spans.last() else {
+            return Vec::new();
+        };
+        let target = last.end.div_ceil(n.max(1) as u64).max(1);
+        let mut chunks = Vec::new();
+        let mut first = 0;
+        for (g, span) in self.spans.iter().enumerate() {
+            if span.end - self.spans[first].start >= target {
+                chunks.push(first..g + 1);
+                first = g + 1;
+            }
+        }
+        if first < self.spans.len() {
+            chunks.push(first..self.spans.len());
+        }
+        chunks
+    }
+}
+
+/// A PGN file with an index of its games, for random access and parallel processing.
+pub struct IndexedPgn {
+    path: PathBuf,
+    file: File,
+    index: PgnIndex,
+}
+
+impl IndexedPgn {
+    /// Open a file and scan it for the boundaries of its games.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let index = PgnIndex::build(BufReader::new(File::open(&path)?))?;
+        Self::with_index(path, index)
+    }
+
+    /// Open a file with an index that has been built before.
+    pub fn with_index<P: AsRef<Path>>(path: P, index: PgnIndex) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        Ok(IndexedPgn { path, file, index })
+    }
+
+    pub fn index(&self) -> &PgnIndex {
+        &self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The unparsed text of the n-th game.
+    pub fn raw_game(&mut self, n: usize) -> std::io::Result<String> {
+        let span = self.index.span(n).ok_or_else(|| out_of_range(n))?;
+        self.file.seek(SeekFrom::Start(span.start))?;
+        let mut text = String::new();
+        (&self.file)
+            .take(span.end - span.start)
+            .read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    /// Parse the n-th game (counting from 0) without reading the games in front of it.
+    pub fn game(&mut self, n: usize) -> std::io::Result<PgnGame> {
+        let text = self.raw_game(n)?;
+        PgnReader::new(text.as_bytes())
+            .next()
+            .unwrap_or_else(|| Err(out_of_range(n)))
+    }
+
+    /// Apply a function to every game, passing its index, on the given number of threads (all
+    /// cores if None). Every thread streams its own chunk of the file, and the results are
+    /// returned in the order of the games.
+    pub fn par_map<T, F>(&self, n_threads: Option<usize>, f: F) -> std::io::Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(usize, PgnGame) -> T + Sync,
+    {
+        self.par_map_cancellable(n_threads, &CancellationToken::new(), f)
+    }
+
+    /// Apply a function to every game like `par_map`, but stop all threads at their next game
+    /// once the token is cancelled, which fails with `ErrorKind::Interrupted`.
+    pub fn par_map_cancellable<T, F>(
+        &self,
+        n_threads: Option<usize>,
+        cancel: &CancellationToken,
+        f: F,
+    ) -> std::io::Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(usize, PgnGame) -> T + Sync,
+    {
+        let n_threads = n_threads
+            .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1);
+        let chunks = self.index.chunks(n_threads);
+        let results: Vec<std::io::Result<Vec<T>>> = thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .into_iter()
+                .map(|chunk| scope.spawn(|| self.map_chunk(chunk, cancel, &f)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("PGN worker thread panicked"))
+                .collect()
+        });
+
+        let mut mapped = Vec::with_capacity(self.len());
+        for result in results {
+            mapped.extend(result?);
+        }
+        Ok(mapped)
+    }
+
+    fn map_chunk<T, F>(
+        &self,
+        chunk: Range<usize>,
+        cancel: &CancellationToken,
+        f: &F,
+    ) -> std::io::Result<Vec<T>>
+    where
+        F: Fn(usize, PgnGame) -> T,
+    {
+        let Range { start, end } = self.spans_of(&chunk);
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let reader = PgnReader::new(BufReader::new(file.take(end - start)));
+
+        chunk
+            .zip(reader)
+            .map(|(g, game)| {
+                cancel
+                    .check()
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Interrupted, e))?;
+                Ok(f(g, game?))
+            })
+            .collect()
+    }
+
+    /// Byte range covered by a range of games.
+    fn spans_of(&self, games: &Range<usize>) -> Range<u64> {
+        self.index.spans[games.start].start..self.index.spans[games.end - 1].end
+    }
+}
+
+fn out_of_range(n: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("no game with index {}", n),
+    )
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const THREE_GAMES: &str = r#"
+[Event "A"]
+[Result "1-0"]
+
+1. e4 { tricky
+[Event "not a game"] } e5 1-0
+
+[Event "B"]
+
+1. d4 ; {
+1... d5 *
+
+[Event "C"]
+
+1. c4 c5 *
+"#;
+
+#[allow(dead_code)]
+fn temp_pgn(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fency-{}-{}.pgn", name, std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn check_pgn_index() {
+    let index = PgnIndex::build(THREE_GAMES.as_bytes()).unwrap();
+
+    assert_eq!(index.len(), 3);
+    let span = index.span(1).unwrap();
+    assert!(THREE_GAMES[span.start as usize..].starts_with("[Event \"B\"]"));
+    assert_eq!(index.span(2).unwrap().end as usize, THREE_GAMES.len());
+    assert_eq!(index.span(3), None);
+    assert!(PgnIndex::build("\n\n".as_bytes()).unwrap().is_empty());
+
+    // Escaped lines are neither tag pairs nor movetext.
+    let pgn = "%TWIC\n[Event \"A\"]\n\n1. e4 *\n%[Event \"X\"]\n% 1. d4\n";
+    let index = PgnIndex::build(pgn.as_bytes()).unwrap();
+    assert_eq!(index.len(), 1);
+    assert_eq!(index.span(0).unwrap().start, 6);
+}
+
+#[test]
+fn check_chunks() {
+    let index = PgnIndex::build(THREE_GAMES.as_bytes()).unwrap();
+
+    assert_eq!(index.chunks(1), vec![0..3]);
+    assert_eq!(index.chunks(3).into_iter().flatten().count(), 3);
+    assert_eq!(index.chunks(100).len(), 3);
+    assert!(PgnIndex::default().chunks(4).is_empty());
+}
+
+#[test]
+fn check_random_access() {
+    let path = temp_pgn("random-access", THREE_GAMES);
+    let mut pgn = IndexedPgn::open(&path).unwrap();
+
+    assert_eq!(pgn.len(), 3);
+    assert_eq!(pgn.game(2).unwrap().moves, vec!["c4", "c5"]);
+    assert_eq!(pgn.game(0).unwrap().moves, vec!["e4", "e5"]);
+    assert_eq!(pgn.game(1).unwrap().header("Event"), Some("B"));
+    assert!(pgn.game(3).is_err());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn check_par_map() {
+    let pgn: String = (0..50)
+        .map(|i| format!("[Round \"{}\"]\n\n1. e4 {{ comment }} e5 *\n\n", i))
+        .collect();
+    let path = temp_pgn("par-map", &pgn);
+    let pgn = IndexedPgn::open(&path).unwrap();
+
+    let rounds = pgn
+        .par_map(Some(4), |g, game| {
+            (g, game.header("Round").unwrap().to_string())
+        })
+        .unwrap();
+    assert_eq!(rounds.len(), 50);
+    assert!(rounds.iter().all(|(g, round)| g.to_string() == *round));
+
+    // Cancelling from within stops all threads.
+    let cancel = CancellationToken::new();
+    let error = pgn
+        .par_map_cancellable(Some(4), &cancel, |g, _| {
+            if g == 3 {
+                cancel.cancel();
+            }
+        })
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,120 @@
+use crate::utils::game::Game;
+use crate::utils::piece::Piece;
+use std::fmt::{Display, Formatter};
+
+/// Coarse stage of a game, e.g. to sample training positions per phase.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl Display for GamePhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GamePhase::Opening => write!(f, "opening"),
+            GamePhase::Middlegame => write!(f, "middlegame"),
+            GamePhase::Endgame => write!(f, "endgame"),
+        }
+    }
+}
+
+/// How to derive the phase from the non-pawn material of both sides. The default weights sum up
+/// to 24 in the initial position.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PhaseScheme {
+    pub knight: u16,
+    pub bishop: u16,
+    pub rook: u16,
+    pub queen: u16,
+
+    /// The opening lasts up to this full move, as long as the material stays at or above
+    /// `opening_material`.
+    pub opening_moves: u16,
+    pub opening_material: u16,
+
+    /// The endgame starts once the material drops to this value or below, regardless of the move.
+    pub endgame_material: u16,
+}
+
+impl Default for PhaseScheme {
+    fn default() -> Self {
+        PhaseScheme {
+            knight: 1,
+            bishop: 1,
+            rook: 2,
+            queen: 4,
+            opening_moves: 10,
+            opening_material: 20,
+            endgame_material: 6,
+        }
+    }
+}
+
+impl PhaseScheme {
+    /// Weight of a piece in the phase material, where pawns and kings do not count.
+    pub fn weight(&self, piece: Piece) -> u16 {
+        match piece {
+            Piece::N => self.knight,
+            Piece::B => self.bishop,
+            Piece::R => self.rook,
+            Piece::Q => self.queen,
+            Piece::P | Piece::K => 0,
+        }
+    }
+
+    /// Sum of the weights of all figures on the board.
+    pub fn material(&self, game: &Game) -> u16 {
+        game.figures.iter().map(|f| self.weight(f.piece())).sum()
+    }
+
+    pub fn phase(&self, game: &Game) -> GamePhase {
+        let material = self.material(game);
+        if material <= self.endgame_material {
+            GamePhase::Endgame
+        } else if game.full_move_clock <= self.opening_moves && material >= self.opening_material {
+            GamePhase::Opening
+        } else {
+            GamePhase::Middlegame
+        }
+    }
+}
+
+impl Game {
+    /// The phase of the game by the default scheme, see `PhaseScheme`.
+    pub fn phase(&self) -> GamePhase {
+        PhaseScheme::default().phase(self)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_phases() {
+    use std::str::FromStr;
+
+    let game = Game::new();
+    assert_eq!(PhaseScheme::default().material(&game), 24);
+    assert_eq!(game.phase(), GamePhase::Opening);
+
+    // Full material, but past the opening moves.
+    let game = Game::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 15")
+        .unwrap();
+    assert_eq!(game.phase(), GamePhase::Middlegame);
+
+    // Queens and a pair of rooks traded early.
+    let game =
+        Game::from_str("r1b1kbn1/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNB1KB2 w q - 0 8").unwrap();
+    assert_eq!(PhaseScheme::default().material(&game), 12);
+    assert_eq!(game.phase(), GamePhase::Middlegame);
+
+    let game = Game::from_str("4k3/pp3r2/8/8/8/8/PP3B2/4K3 w - - 0 40").unwrap();
+    assert_eq!(game.phase(), GamePhase::Endgame);
+
+    let scheme = PhaseScheme {
+        endgame_material: 2,
+        ..PhaseScheme::default()
+    };
+    assert_eq!(scheme.phase(&game), GamePhase::Middlegame);
+    assert_eq!(GamePhase::Endgame.to_string(), "endgame");
+}
@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Counts of the games of a batch job so far.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Progress {
+    /// Games done, including the failed ones.
+    pub processed: usize,
+    pub failed: usize,
+}
+
+/// Reports the progress of a batch job to a callback every given number of games, and once more
+/// when done, e.g. to drive a progress bar. An error of the callback is handed back to the job,
+/// which is thus cancelled cooperatively at the next report.
+pub struct ProgressReporter<F> {
+    progress: Progress,
+    every: usize,
+
+    /// Number of processed games at the last report.
+    reported: Option<usize>,
+    callback: F,
+}
+
+impl<E, F: FnMut(Progress) -> Result<(), E>> ProgressReporter<F> {
+    /// Report every given number of games, where 0 reports every game.
+    pub fn new(every: usize, callback: F) -> Self {
+        ProgressReporter {
+            progress: Progress::default(),
+            every: every.max(1),
+            reported: None,
+            callback,
+        }
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Count a game and report if another `every` games are done.
+    pub fn record(&mut self, is_failed: bool) -> Result<(), E> {
+        self.progress.processed += 1;
+        self.progress.failed += usize::from(is_failed);
+        if self.progress.processed.is_multiple_of(self.every) {
+            self.report()?;
+        }
+        Ok(())
+    }
+
+    /// Report the final counts, unless they were reported already.
+    pub fn finish(&mut self) -> Result<(), E> {
+        if self.reported != Some(self.progress.processed) {
+            self.report()?;
+        }
+        Ok(())
+    }
+
+    fn report(&mut self) -> Result<(), E> {
+        self.reported = Some(self.progress.processed);
+        (self.callback)(self.progress)
+    }
+}
+
+/// Flag to stop a batch job from another thread, e.g. on Ctrl-C. The job checks the flag between
+/// games, thus it stops after the game at hand. Clones share the flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fail once cancelled, which stops a job by `?`.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err("cancelled".to_string());
+        }
+        Ok(())
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_progress_reporter() {
+    let mut reports = Vec::new();
+    let mut reporter = ProgressReporter::new(2, |progress| {
+        reports.push((progress.processed, progress.failed));
+        Ok::<(), String>(())
+    });
+    for is_failed in [false, true, false, false, true] {
+        reporter.record(is_failed).unwrap();
+    }
+    reporter.finish().unwrap();
+    reporter.finish().unwrap();
+    assert_eq!(reports, [(2, 1), (4, 1), (5, 2)]);
+
+    // An empty job reports once, a job that ends at a report does not repeat it.
+    let mut n_reports = 0;
+    let mut reporter = ProgressReporter::new(1, |_| {
+        n_reports += 1;
+        Ok::<(), String>(())
+    });
+    reporter.finish().unwrap();
+    reporter.record(false).unwrap();
+    reporter.finish().unwrap();
+    assert_eq!(n_reports, 2);
+}
+
+#[test]
+fn check_progress_cancels() {
+    use crate::utils::batch::BatchContext;
+
+    let games = [["e4", "e5"], ["d4", "d5"], ["c4", "c5"]];
+    let mut context = BatchContext::default();
+    let mut reporter = ProgressReporter::new(1, |progress| match progress.processed {
+        2 => Err("cancelled".to_string()),
+        _ => Ok(()),
+    });
+    let mut n_replayed = 0;
+    let result = games.iter().try_for_each(|moves| {
+        let is_failed = context.fentasize(moves).is_err();
+        n_replayed += 1;
+        reporter.record(is_failed)
+    });
+    assert_eq!(result, Err("cancelled".to_string()));
+    assert_eq!(n_replayed, 2);
+    assert_eq!(reporter.progress().processed, 2);
+}
+
+#[test]
+fn check_cancellation_token() {
+    let token = CancellationToken::new();
+    let worker = token.clone();
+    assert_eq!(worker.check(), Ok(()));
+    std::thread::spawn(move || token.cancel()).join().unwrap();
+    assert!(worker.is_cancelled());
+    assert_eq!(worker.check(), Err("cancelled".to_string()));
+}
@@ -0,0 +1,150 @@
+//! Property-based tests, which replay random legal games and cross-check every position against
+//! shakmaty as a reference implementation, and feed arbitrary input to the SAN and FEN parsers.
+use crate::utils::draw::{Draw, SanMove};
+use crate::utils::game::Game;
+use crate::utils::piece::Piece;
+use proptest::prelude::*;
+use regex::Regex;
+use shakmaty::fen::Fen;
+use shakmaty::san::SanPlus;
+use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+use std::str::FromStr;
+
+/// A rough shape of SAN, which hits the parser as well as the mover detection.
+const SAN_LIKE: &str = "[NBRQK]?[a-h]?[1-8]?x?[a-h][1-8](=[NBRQK])?[+#]?|O-O(-O)?";
+
+/// Any arrangement of the characters of SAN, e.g. "x=e8-Q", which hits the corner cases of the
+/// SAN parser.
+const SAN_CHARS: &str = "[NBRQK♘♙a-h1-8x=+#-]{0,8}";
+
+/// The regular expression that SAN was parsed with before the hand-rolled parser, which must keep
+/// its semantics.
+const SAN_REGEX: &str = "^(?P<Piece>[NBRQK♔♕♖♗♘♙♚♛♜♝♞♟])?(?P<RemainderFile>[a-h])?(?P<RemainderRank>[1-8])?(?:(?P<Hit>x)|(?P<Dash>-))?(?P<Target>[a-h][1-8])=?(?P<PromotesTo>[NBRQK♔♕♖♗♘♚♛♜♝♞])?(?P<Check>\\+|#)?$";
+
+/// A rough shape of FEN, which mostly passes the syntax but not necessarily the semantics.
+const FEN_LIKE: &str =
+    "[1-8pnbrqkPNBRQK/]{8,40} [wb] (-|K?Q?k?q?) (-|[a-h][1-8]) [0-9]{1,3} [0-9]{1,3}";
+
+/// Drop castling rights of rooks that are no longer on their initial squares. Captured rooks don't
+/// revoke the rights yet, whereas shakmaty never reports rights without a rook.
+fn without_stale_castling(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split(' ').collect();
+    let ranks: Vec<&str> = parts[0].split('/').collect();
+    let (first, last) = (ranks[7], ranks[0]);
+    let rights: String = parts[2]
+        .chars()
+        .filter(|c| match c {
+            'K' => first.ends_with('R'),
+            'Q' => first.starts_with('R'),
+            'k' => last.ends_with('r'),
+            'q' => last.starts_with('r'),
+            _ => true,
+        })
+        .collect();
+    let rights = if rights.is_empty() {
+        "-".to_string()
+    } else {
+        rights
+    };
+    [parts[0], parts[1], &rights, parts[3], parts[4], parts[5]].join(" ")
+}
+
+/// Replay a random game, where every choice picks one of the legal moves (cycling through them),
+/// and compare legal moves, SAN and FEN with shakmaty after every ply.
+fn cross_check(choices: &[usize]) -> Result<(), TestCaseError> {
+    let mut game = Game::new();
+    let mut reference = Chess::default();
+    for &choice in choices {
+        let mut moves = reference.legal_moves().into_iter().collect::<Vec<_>>();
+        if moves.is_empty() {
+            break;
+        }
+        moves.sort_by_key(|m| m.to_uci(CastlingMode::Standard).to_string());
+        let expected: Vec<String> = moves
+            .iter()
+            .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+            .collect();
+        let mut legal_moves = game.legal_moves();
+        legal_moves.sort();
+        prop_assert_eq!(&legal_moves, &expected, "{}", game.clone().to_fen());
+
+        let m = &moves[choice % moves.len()];
+        let san = SanPlus::from_move(reference.clone(), m).to_string();
+        let uci = &expected[choice % moves.len()];
+        prop_assert_eq!(
+            game.uci_to_san(uci)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?,
+            san.clone()
+        );
+
+        game.play_move(&san)
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        reference.play_unchecked(m);
+        let fen = Fen::from_position(reference.clone(), EnPassantMode::Legal).to_string();
+        prop_assert_eq!(
+            without_stale_castling(&game.clone().to_fen()),
+            fen,
+            "after {}",
+            san
+        );
+    }
+    Ok(())
+}
+
+/// Compare the parsed SAN with the groups of the reference regular expression.
+fn cross_check_san(san: &str) -> Result<(), TestCaseError> {
+    let regex = Regex::new(SAN_REGEX).unwrap();
+    let draw = Draw::from_str(san);
+    let Some(captures) = regex.captures(san) else {
+        prop_assert!(draw.is_err_and(|e| e.starts_with("invalid SAN")), "{}", san);
+        return Ok(());
+    };
+    let group = |name: &str| {
+        captures
+            .name(name)
+            .map(|m| m.as_str().chars().next().unwrap())
+    };
+    match draw {
+        Ok(draw) => {
+            prop_assert_eq!(draw.piece, group("Piece").map_or(Piece::P, Piece::from));
+            prop_assert_eq!(draw.remainder_file, group("RemainderFile"));
+            prop_assert_eq!(draw.remainder_rank, group("RemainderRank"));
+            prop_assert_eq!(draw.target.to_string(), &captures["Target"]);
+            prop_assert_eq!(draw.promoted_piece, group("PromotesTo").map(Piece::from));
+        }
+        Err(e) => prop_assert!(!e.starts_with("invalid SAN"), "{}: {}", san, e),
+    }
+    Ok(())
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn check_random_games_against_shakmaty(choices in prop::collection::vec(any::<usize>(), 0..160)) {
+        cross_check(&choices)?;
+    }
+
+    #[test]
+    fn check_san_parser_never_panics(san in "\\PC{0,12}", san_like in SAN_LIKE) {
+        let _ = SanMove::from_str(&san);
+        let _ = Game::new().play_move(&san);
+        let _ = Game::new().play_move(&san_like);
+    }
+
+    #[test]
+    fn check_san_parser_against_regex(san in SAN_CHARS, san_like in SAN_LIKE) {
+        cross_check_san(&san)?;
+        cross_check_san(&san_like)?;
+    }
+
+    #[test]
+    fn check_fen_parser_never_panics(fen in "\\PC{0,60}", fen_like in FEN_LIKE) {
+        let _ = Game::from_str(&fen);
+        // A parsed FEN must survive the round trip through its own output.
+        if let Ok(game) = Game::from_str(&fen_like) {
+            prop_assert_eq!(Game::from_str(&game.clone().to_fen()), Ok(game));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::utils::game::{at_ply, Game};
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// A puzzle of the Lichess puzzle database, i.e. a CSV row with the columns PuzzleId, FEN, Moves,
+/// Rating, RatingDeviation, Popularity, NbPlays, Themes, GameUrl and OpeningTags.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Puzzle {
+    pub id: String,
+
+    /// The position before the move of the opponent that sets up the puzzle.
+    pub fen: String,
+
+    /// The opponent's move followed by the solution, alternating between solver and opponent.
+    pub moves: Vec<String>,
+    pub rating: Option<u16>,
+    pub popularity: Option<i32>,
+    pub plays: Option<u32>,
+    pub themes: Vec<String>,
+    pub game_url: Option<String>,
+    pub opening_tags: Vec<String>,
+}
+
+/// A move of a puzzle line after it has been played.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PuzzleMove {
+    pub uci: String,
+    pub san: String,
+
+    /// The position after the move.
+    pub fen: String,
+
+    /// Whether the move is part of the solution, i.e. played by the solver, not by the opponent.
+    pub is_solution: bool,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_capture: bool,
+    pub is_promotion: bool,
+}
+
+impl FromStr for Puzzle {
+    type Err = String;
+
+    /// Parse a CSV row, where optional trailing columns may be missing.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.trim_end().split(',').collect();
+        let [id, fen, moves, rest @ ..] = &fields[..] else {
+            return Err(format!(
+                "invalid puzzle, expected id, FEN and moves: {}",
+                line
+            ));
+        };
+        let field = |idx: usize| rest.get(idx).copied().filter(|f| !f.is_empty());
+        let words = |idx: usize| field(idx).map_or(Vec::new(), split_words);
+
+        Ok(Puzzle {
+            id: id.to_string(),
+            fen: fen.to_string(),
+            moves: split_words(moves),
+            rating: field(0).and_then(|r| r.parse().ok()),
+            popularity: field(2).and_then(|p| p.parse().ok()),
+            plays: field(3).and_then(|n| n.parse().ok()),
+            themes: words(4),
+            game_url: field(5).map(|u| u.to_string()),
+            opening_tags: words(6),
+        })
+    }
+}
+
+fn split_words(field: &str) -> Vec<String> {
+    field.split_whitespace().map(|w| w.to_string()).collect()
+}
+
+impl Puzzle {
+    /// Replay the opponent's move and the solution line from the FEN. Fails on illegal moves and
+    /// on lines that do not end with a move of the solver.
+    pub fn validate(&self) -> Result<Vec<PuzzleMove>, String> {
+        if self.moves.len() < 2 || !self.moves.len().is_multiple_of(2) {
+            return Err(format!(
+                "puzzle {}: expected the opponent's move and a solution ending with the solver's \
+                 move, got {} moves",
+                self.id,
+                self.moves.len()
+            ));
+        }
+        let mut game =
+            Game::from_str(&self.fen).map_err(|e| format!("puzzle {}: {}", self.id, e))?;
+        let mut line = Vec::with_capacity(self.moves.len());
+        for (i, uci) in self.moves.iter().enumerate() {
+            let san = game
+                .uci_to_san(uci)
+                .map_err(at_ply(i + 1))
+                .map_err(|e| format!("puzzle {}, {}", self.id, e))?;
+            game.play_uci(uci)
+                .map_err(|e| format!("puzzle {}, {}", self.id, e))?;
+            line.push(PuzzleMove {
+                uci: uci.clone(),
+                fen: game.clone().to_fen(),
+                is_solution: i % 2 == 1,
+                is_check: san.contains('+') || san.contains('#'),
+                is_checkmate: san.contains('#'),
+                is_capture: san.contains('x'),
+                is_promotion: san.contains('='),
+                san,
+            });
+        }
+
+        Ok(line)
+    }
+
+    /// The SAN of the solution moves, i.e. of the solver's moves after the opponent's first one.
+    pub fn solution_sans(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .validate()?
+            .into_iter()
+            .filter(|mv| mv.is_solution)
+            .map(|mv| mv.san)
+            .collect())
+    }
+}
+
+/// Read the puzzles of a Lichess puzzle CSV, skipping the header line if present.
+pub struct PuzzleReader<R: BufRead> {
+    lines: std::io::Lines<R>,
+}
+
+impl<R: BufRead> PuzzleReader<R> {
+    pub fn new(source: R) -> Self {
+        PuzzleReader {
+            lines: source.lines(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PuzzleReader<R> {
+    type Item = std::io::Result<Puzzle>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if line.trim().is_empty() || line.starts_with("PuzzleId,") {
+                continue;
+            }
+            return Some(
+                Puzzle::from_str(&line)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            );
+        }
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const PUZZLES: &str = "\
+PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,\
+mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game Italian_Game_Classical_Variation
+00sJ9,r3r1k1/p4ppp/2p2n2/1p6/3P1qb1/2NQR3/PPB2PP1/R1B3K1 w - - 5 18,e3g3 e8e1 g1h2 e1c1 a1c1 f4h6 h2g1 h6c1,\
+2671,105,87,325,advantage attraction fork middlegame sacrifice veryLong,https://lichess.org/gyFeQsOE#35,French_Defense
+";
+
+#[test]
+fn check_read_puzzles() {
+    let puzzles: Vec<Puzzle> = PuzzleReader::new(PUZZLES.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(puzzles.len(), 2);
+    assert_eq!(puzzles[0].id, "00sHx");
+    assert_eq!(puzzles[0].rating, Some(1760));
+    assert_eq!(
+        puzzles[0].themes,
+        vec!["mate", "mateIn2", "middlegame", "short"]
+    );
+    assert_eq!(puzzles[1].moves.len(), 8);
+    assert_eq!(puzzles[1].opening_tags, vec!["French_Defense"]);
+    assert!(Puzzle::from_str("00sHx,8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+}
+
+#[test]
+fn check_validate_puzzle() {
+    let puzzles: Vec<Puzzle> = PuzzleReader::new(PUZZLES.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let line = puzzles[0].validate().unwrap();
+    let sans: Vec<&str> = line.iter().map(|mv| mv.san.as_str()).collect();
+    assert_eq!(sans, vec!["Kd7", "Be6+", "Kd8", "Qf8#"]);
+    assert!(line[3].is_solution && line[3].is_checkmate);
+    assert!(!line[2].is_solution);
+    assert_eq!(
+        puzzles[1].solution_sans().unwrap(),
+        vec!["Re1+", "Rxc1", "Qh6+", "Qxc1+"]
+    );
+
+    let broken = Puzzle {
+        moves: vec!["e8d7".to_string(), "a2e7".to_string()],
+        ..puzzles[0].clone()
+    };
+    assert_eq!(
+        broken.validate(),
+        Err("puzzle 00sHx, ply 2: illegal move: a2e7".to_string())
+    );
+    let odd = Puzzle {
+        moves: vec!["e8d7".to_string()],
+        ..puzzles[0].clone()
+    };
+    assert!(odd.validate().is_err());
+}
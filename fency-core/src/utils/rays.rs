@@ -0,0 +1,103 @@
+use crate::utils::coord::Coord;
+
+/// File and rank deltas of the eight directions, the rook's first and the bishop's last.
+pub const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (-1, 1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+/// Indexes into `DIRECTIONS` of the directions of rooks and bishops.
+pub const ROOK_DIRECTIONS: std::ops::Range<usize> = 0..4;
+pub const BISHOP_DIRECTIONS: std::ops::Range<usize> = 4..8;
+
+/// The squares from a square to the board edge in one direction, nearest first.
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    squares: [i8; 7],
+    len: u8,
+}
+
+impl Ray {
+    pub fn squares(&self) -> &[i8] {
+        &self.squares[..self.len as usize]
+    }
+}
+
+/// Rays of all 64 squares in all eight directions, indexed by square (a8 = 0, as in the FEN) and
+/// direction. The table is built at compile time, thus sliding pieces walk it instead of deriving
+/// and bounds-checking every step.
+pub static RAYS: [[Ray; 8]; 64] = build_rays();
+
+/// The ray from a square in the direction with the given index into `DIRECTIONS`.
+pub fn ray(coord: &Coord, direction: usize) -> &'static [i8] {
+    RAYS[coord.idx as usize][direction].squares()
+}
+
+/// Index into `DIRECTIONS` of the ray from one square that passes the other, if any.
+pub fn direction_to(from: &Coord, to: &Coord) -> Option<usize> {
+    let step = from.direction(to)?;
+    DIRECTIONS.iter().position(|&d| d == step)
+}
+
+const fn build_rays() -> [[Ray; 8]; 64] {
+    let mut rays = [[Ray {
+        squares: [0; 7],
+        len: 0,
+    }; 8]; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let (x, y) = (idx as i8 % 8, 7 - idx as i8 / 8);
+        let mut d = 0;
+        while d < 8 {
+            let (dx, dy) = DIRECTIONS[d];
+            let ray = &mut rays[idx][d];
+            let (mut tx, mut ty) = (x + dx, y + dy);
+            while (0 <= tx) && (tx < 8) && (0 <= ty) && (ty < 8) {
+                ray.squares[ray.len as usize] = Coord::idx_of(tx, ty);
+                ray.len += 1;
+                tx += dx;
+                ty += dy;
+            }
+            d += 1;
+        }
+        idx += 1;
+    }
+    rays
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_rays() {
+    use crate::utils::coord::BOARD;
+
+    let names = |squares: &[i8]| -> Vec<String> {
+        squares
+            .iter()
+            .map(|&i| BOARD[i as usize].to_string())
+            .collect()
+    };
+    let c1 = Coord::from("c1");
+    assert_eq!(names(ray(&c1, 5)), vec!["d2", "e3", "f4", "g5", "h6"]);
+    assert_eq!(names(ray(&c1, 4)), vec!["b2", "a3"]);
+    assert!(ray(&c1, 3).is_empty());
+    assert_eq!(direction_to(&c1, &Coord::from("h6")), Some(5));
+    assert_eq!(direction_to(&c1, &Coord::from("c8")), Some(0));
+    assert_eq!(direction_to(&c1, &Coord::from("d3")), None);
+
+    // Rays agree with stepping square by square.
+    for coord in BOARD {
+        for (d, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+            let steps: Vec<i8> = (1..8)
+                .map_while(|f| coord.shifted(f * dx, f * dy))
+                .map(|c| c.idx)
+                .collect();
+            assert_eq!(ray(&coord, d), &steps[..]);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::utils::game::Game;
+use crate::utils::outcome::is_result_token;
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::replay::replay_plies;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+/// A move that could not be replayed. Games are numbered from 0 and plies from 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ReplayError {
+    pub game: usize,
+    pub ply: usize,
+    pub san: String,
+    pub reason: String,
+
+    /// FEN of the position before the failing move, or the invalid FEN tag.
+    pub fen: String,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "game {}, ply {}: {}", self.game, self.ply, self.reason)
+    }
+}
+
+/// Replay a game from its start position and drop its moves from the first one that fails,
+/// which is returned as error. An invalid FEN tag drops all moves and is reported at ply 0.
+/// Headers and the result are kept as they are.
+pub fn truncate_at_error(game: &mut PgnGame, idx: usize) -> Option<ReplayError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("replay_game", game = idx).entered();
+    let mut board = match game.start_position() {
+        Ok(board) => board,
+        Err(reason) => {
+            let error = ReplayError {
+                game: idx,
+                ply: 0,
+                san: String::new(),
+                reason: reason.to_string(),
+                fen: game.header("FEN").unwrap_or_default().to_string(),
+            };
+            game.moves.clear();
+            game.clocks.clear();
+            game.evals.clear();
+            return Some(error);
+        }
+    };
+    let mut ply = 0;
+    for (i, mv) in game.moves.iter().enumerate() {
+        if is_result_token(mv) {
+            continue;
+        }
+        ply += 1;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("ply", ply, san = mv.as_str()).entered();
+        if let Err(reason) = board.play_move(mv) {
+            let reason = reason.to_string();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(ply, reason, "truncated game");
+            let error = ReplayError {
+                game: idx,
+                ply,
+                san: mv.clone(),
+                reason,
+                fen: board.clone().to_fen(),
+            };
+            game.moves.truncate(i);
+            game.clocks.truncate(i);
+            game.evals.truncate(i);
+            return Some(error);
+        }
+    }
+    None
+}
+
+impl Game {
+    /// Play moves in SAN one after the other, skipping result tokens like "1-0". The first move
+    /// that fails stops the replay, which leaves the game at the position before that move, and is
+    /// returned with its ply and that position as error of game 0.
+    pub fn play_moves(&mut self, moves: &[&str]) -> Result<(), ReplayError> {
+        let mut plies = replay_plies(&mut *self, moves);
+        while let Some((ply, mv, played)) = plies.try_next_ply() {
+            if let Err(reason) = played {
+                return Err(ReplayError {
+                    game: 0,
+                    ply,
+                    san: mv.to_string(),
+                    reason: reason.to_string(),
+                    fen: plies.game().clone().to_fen(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Best effort reader, which truncates games at their first unplayable move instead of failing,
+/// and collects the errors for a report.
+pub struct LenientReader<R: BufRead> {
+    games: PgnReader<R>,
+    idx: usize,
+    errors: Vec<ReplayError>,
+}
+
+impl<R: BufRead> LenientReader<R> {
+    pub fn new(games: PgnReader<R>) -> Self {
+        LenientReader {
+            games,
+            idx: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// The errors of the games read so far.
+    pub fn errors(&self) -> &[ReplayError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<ReplayError> {
+        self.errors
+    }
+}
+
+impl<R: BufRead> Iterator for LenientReader<R> {
+    type Item = std::io::Result<PgnGame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut game = match self.games.next()? {
+            Ok(game) => game,
+            Err(e) => return Some(Err(e)),
+        };
+        if let Some(error) = truncate_at_error(&mut game, self.idx) {
+            self.errors.push(error);
+        }
+        self.idx += 1;
+
+        Some(Ok(game))
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_lenient_reader() {
+    let pgn = "[Event \"a\"]\n\n1. e4 e5 2. Ke3 Nc6 1-0\n\n\
+               [Event \"b\"]\n\n1. d4 d5 1/2-1/2\n\n\
+               [Event \"c\"]\n\n1. Nf3 Nf3 *\n";
+    let mut reader = LenientReader::new(PgnReader::new(pgn.as_bytes()));
+    let games: Vec<PgnGame> = reader.by_ref().map(|g| g.unwrap()).collect();
+
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].moves, vec!["e4", "e5"]);
+    assert_eq!(games[0].result.as_deref(), Some("1-0"));
+    assert_eq!(games[1].moves.len(), 2);
+    assert_eq!(games[2].moves, vec!["Nf3"]);
+
+    let errors = reader.into_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].to_string(), "game 0, ply 3: illegal move: Ke3");
+    assert_eq!(errors[0].san, "Ke3");
+    assert_eq!(
+        errors[0].fen,
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+    assert_eq!((errors[1].game, errors[1].ply), (2, 2));
+}
+
+#[test]
+fn check_truncate_without_error() {
+    let mut game = PgnGame {
+        moves: vec!["e4".to_string(), "1-0".to_string()],
+        ..PgnGame::default()
+    };
+    assert_eq!(truncate_at_error(&mut game, 0), None);
+    assert_eq!(game.moves.len(), 2);
+}
+
+#[test]
+fn check_play_moves() {
+    let mut game = Game::new();
+    assert_eq!(game.play_moves(&["e4", "e5", "1-0"]), Ok(()));
+
+    let error = game.play_moves(&["Nf3", "Nc6", "Bb5", "Bb5"]).unwrap_err();
+    assert_eq!((error.ply, error.san.as_str()), (4, "Bb5"));
+    assert_eq!(error.reason, "illegal move: Bb5");
+    assert_eq!(error.fen, game.clone().to_fen());
+    assert_eq!(game.full_move_clock, 3);
+}
+
+#[test]
+fn check_truncate_at_pinned_figure() {
+    // The knight on c3 is pinned by the bishop on b4, as the pawn left d2.
+    let mut game = PgnGame {
+        moves: ["d4", "e5", "Nc3", "Bb4", "Nd5", "Nc6", "1-0"]
+            .map(String::from)
+            .to_vec(),
+        ..PgnGame::default()
+    };
+    let error = truncate_at_error(&mut game, 0).unwrap();
+    assert_eq!((error.ply, error.san.as_str()), (5, "Nd5"));
+    assert_eq!(error.reason, "illegal move: Nd5");
+    assert_eq!(game.moves, vec!["d4", "e5", "Nc3", "Bb4"]);
+}
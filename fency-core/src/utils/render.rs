@@ -0,0 +1,133 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::is_result_token;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Edge length of a single square in pixels.
+const SQUARE_SIZE: usize = 45;
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+
+/// Render the figures of a position as SVG elements, i.e. without the surrounding document.
+/// Figures are drawn as Unicode chess symbols on top of the squares.
+fn board_elements(game: &Game) -> String {
+    let mut elements = String::new();
+    for (idx, fig) in game.position.iter().enumerate() {
+        let (x, y) = ((idx % 8) * SQUARE_SIZE, (idx / 8) * SQUARE_SIZE);
+        if let Some(fig) = fig {
+            elements.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\">{}</text>",
+                x + SQUARE_SIZE / 2,
+                y + SQUARE_SIZE / 2,
+                fig.piece().to_figurine(fig.color())
+            ));
+        }
+    }
+    elements
+}
+
+/// The static squares of the board, drawn once for all frames.
+fn squares() -> String {
+    let mut squares = String::new();
+    for idx in 0..64 {
+        let (file, rank) = (idx % 8, idx / 8);
+        let color = if (file + rank) % 2 == 0 {
+            LIGHT_SQUARE
+        } else {
+            DARK_SQUARE
+        };
+        squares.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"/>",
+            file * SQUARE_SIZE,
+            rank * SQUARE_SIZE,
+            color,
+            s = SQUARE_SIZE
+        ));
+    }
+    squares
+}
+
+fn svg_document(content: &str) -> String {
+    let size = 8 * SQUARE_SIZE;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" \
+         viewBox=\"0 0 {size} {size}\" font-size=\"{}\" text-anchor=\"middle\" \
+         dominant-baseline=\"central\">{}{}</svg>\n",
+        SQUARE_SIZE * 4 / 5,
+        squares(),
+        content,
+        size = size
+    )
+}
+
+/// Render a single position as a standalone SVG document.
+pub fn board_svg(game: &Game) -> String {
+    svg_document(&board_elements(game))
+}
+
+/// Render a whole game as a single animated SVG, showing one frame per position (starting with the
+/// initial one) for the given delay. The last frame is kept once the animation has finished.
+/// Result tokens like "1-0" are skipped. Returns the number of frames.
+pub fn render_game(moves: &[&str], path: &str, frame_delay_ms: u32) -> std::io::Result<usize> {
+    let mut game = Game::new();
+    let mut frames = vec![board_elements(&game)];
+    for (i, mv) in moves.iter().filter(|mv| !is_result_token(mv)).enumerate() {
+        game.play_move(mv)
+            .map_err(at_ply(i + 1))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        frames.push(board_elements(&game));
+    }
+
+    // Every frame is hidden until its turn, then shown for exactly one delay.
+    let n_frames = frames.len();
+    let content: String = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let duration = if i + 1 == n_frames {
+                "indefinite".to_string()
+            } else {
+                format!("{}ms", frame_delay_ms)
+            };
+            format!(
+                "<g visibility=\"hidden\"><set attributeName=\"visibility\" to=\"visible\" \
+                 begin=\"{}ms\" dur=\"{}\"/>{}</g>",
+                i as u64 * frame_delay_ms as u64,
+                duration,
+                frame
+            )
+        })
+        .collect();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(svg_document(&content).as_bytes())?;
+    writer.flush()?;
+
+    Ok(n_frames)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_board_svg() {
+    let svg = board_svg(&Game::new());
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(svg.matches("<rect").count(), 64);
+    assert_eq!(svg.matches("<text").count(), 32);
+    // The black king on e8 and the white king on e1.
+    assert!(svg.contains("<text x=\"202\" y=\"22\">♚</text>"));
+    assert!(svg.contains("<text x=\"202\" y=\"337\">♔</text>"));
+}
+
+#[test]
+fn check_render_game() {
+    let path = std::env::temp_dir().join("fency_check_render_game.svg");
+    let path = path.to_str().unwrap();
+    let n_frames = render_game(&["e4", "e5", "Nf3", "1-0"], path, 250).unwrap();
+    let svg = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(n_frames, 4);
+    assert_eq!(svg.matches("<set ").count(), 4);
+    assert!(svg.contains("begin=\"750ms\" dur=\"indefinite\""));
+    assert!(svg.contains("begin=\"250ms\" dur=\"250ms\""));
+}
@@ -0,0 +1,233 @@
+use crate::utils::error::Error;
+use crate::utils::game::Game;
+use crate::utils::outcome::is_result_token;
+use std::borrow::BorrowMut;
+
+/// Lazy replay of moves in SAN, which yields the position after every ply. In contrast to
+/// collecting all FENs, only the current position is kept in memory, which allows to stream
+/// arbitrarily long games or datasets. The replay stops after the first invalid move.
+pub struct Replay<I> {
+    plies: PlyReplay<I>,
+    is_failed: bool,
+}
+
+impl<I, S> Iterator for Replay<I>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = Result<Game, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_failed {
+            return None;
+        }
+        match self.plies.next_ply() {
+            Ok(ply) => ply.map(|(_, _, game)| Ok(game.clone())),
+            Err(e) => {
+                self.is_failed = true;
+                Some(Err(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Replay moves starting from the given position.
+pub fn replay<I, S>(game: Game, moves: I) -> Replay<I::IntoIter>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    Replay {
+        plies: replay_plies(game, moves),
+        is_failed: false,
+    }
+}
+
+/// Replay of moves in SAN in place, which plays one ply per call of `next_ply` and lends the
+/// position after it, thus unlike `replay` no position is copied. The game is either owned or
+/// borrowed, e.g. to reuse its allocations for the next game. Result tokens like "1-0" are
+/// skipped, as those are no moves, and errors tell the failing ply.
+pub struct PlyReplay<I, G = Game> {
+    game: G,
+    before: Option<Game>,
+    moves: I,
+    ply: usize,
+    is_lenient: bool,
+    max_plies: Option<usize>,
+}
+
+/// Replay moves in place starting from the given position.
+pub fn replay_plies<G, I, S>(game: G, moves: I) -> PlyReplay<I::IntoIter, G>
+where
+    G: BorrowMut<Game>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    PlyReplay {
+        game,
+        before: None,
+        moves: moves.into_iter(),
+        ply: 0,
+        is_lenient: false,
+        max_plies: None,
+    }
+}
+
+impl<I, S, G> PlyReplay<I, G>
+where
+    G: BorrowMut<Game>,
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    /// Play the moves by `Game::play_move_lenient` instead of `Game::play_move`.
+    pub fn lenient(mut self, is_lenient: bool) -> Self {
+        self.is_lenient = is_lenient;
+        self
+    }
+
+    /// Fail on a move beyond the given number of plies, before that move is played.
+    pub fn max_plies(mut self, max_plies: Option<usize>) -> Self {
+        self.max_plies = max_plies;
+        self
+    }
+
+    /// Play the next move and return its ply (counting from 1), the move and the position after
+    /// it, or None after the last move.
+    pub fn next_ply(&mut self) -> Result<Option<(usize, S, &Game)>, Error> {
+        Ok(self
+            .advance()?
+            .map(|(ply, mv)| (ply, mv, self.game.borrow())))
+    }
+
+    /// Play the next move like `next_ply`, but also lend the position before the move, which
+    /// costs a copy of the position per ply.
+    pub fn next_pair(&mut self) -> Result<Option<(usize, S, &Game, &Game)>, Error> {
+        let before = self.game.borrow().clone();
+        Ok(self
+            .advance()?
+            .map(|(ply, mv)| (ply, mv, &*self.before.insert(before), self.game.borrow())))
+    }
+
+    /// Number of plies played so far.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The position after the last ply played.
+    pub fn game(&self) -> &Game {
+        self.game.borrow()
+    }
+
+    pub fn into_game(self) -> G {
+        self.game
+    }
+
+    /// Play all remaining moves and return the final position.
+    pub fn finish(mut self) -> Result<G, Error> {
+        while self.advance()?.is_some() {}
+        Ok(self.game)
+    }
+
+    /// Play the next move like `next_ply`, but also return a move that fails, together with the
+    /// error as the game reports it, i.e. without its ply. The position is then left as it was
+    /// before that move.
+    pub fn try_next_ply(&mut self) -> Option<(usize, S, Result<&Game, Error>)> {
+        let mv = self.moves.find(|mv| !is_result_token(mv.as_ref()))?;
+        self.ply += 1;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("ply", ply = self.ply, san = mv.as_ref()).entered();
+        let game = self.game.borrow_mut();
+        let played = match self.max_plies {
+            Some(max_plies) if self.ply > max_plies => Err(Error::TooManyPlies(format!(
+                "game exceeds {} plies",
+                max_plies
+            ))),
+            _ if self.is_lenient => game.play_move_lenient(mv.as_ref()),
+            _ => game.play_move(mv.as_ref()),
+        };
+
+        Some((self.ply, mv, played.map(|()| &*game)))
+    }
+
+    fn advance(&mut self) -> Result<Option<(usize, S)>, Error> {
+        match self.try_next_ply() {
+            None => Ok(None),
+            Some((ply, mv, played)) => {
+                played.map_err(|e| e.located(format!("ply {}", ply)))?;
+                Ok(Some((ply, mv)))
+            }
+        }
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_replay() {
+    let mut positions = replay(Game::new(), ["e4", "e5", "1-0"]);
+
+    assert_eq!(positions.next().unwrap().unwrap().uci, "e2e4");
+    let last = positions.next().unwrap().unwrap();
+    assert_eq!(
+        last.to_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+    assert_eq!(positions.next(), None);
+}
+
+#[test]
+fn check_replay_is_lazy() {
+    // An endless source of moves is fine, as long as only a few positions are taken.
+    let knights = ["Nf3", "Nf6", "Ng1", "Ng8"].into_iter().cycle();
+    let positions: Vec<Game> = replay(Game::new(), knights)
+        .take(400)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        positions[399].clone().to_fen_list()[0],
+        Game::new().to_fen_list()[0]
+    );
+}
+
+#[test]
+fn check_replay_stops_at_invalid_moves() {
+    let mut positions = replay(Game::new(), ["e4", "e5", "Ke3", "Nf3"]);
+    assert!(positions.next().unwrap().is_ok());
+    assert!(positions.next().unwrap().is_ok());
+    assert_eq!(
+        positions.next().unwrap(),
+        Err("ply 3: illegal move: Ke3".to_string())
+    );
+    assert!(positions.next().is_none());
+}
+
+#[test]
+fn check_ply_replay() {
+    let mut plies = replay_plies(Game::new(), ["e4", "e5", "Nf3", "1-0"]);
+    let (ply, mv, game) = plies.next_ply().unwrap().unwrap();
+    assert_eq!((ply, mv, game.uci.as_str()), (1, "e4", "e2e4"));
+
+    let (ply, _, before, after) = plies.next_pair().unwrap().unwrap();
+    assert_eq!(ply, 2);
+    assert_eq!(before.uci, "e2e4");
+    assert_eq!(after.uci, "e7e5");
+
+    assert_eq!(plies.finish().unwrap().uci, "g1f3");
+}
+
+#[test]
+fn check_ply_replay_errors() {
+    let mut plies = replay_plies(Game::new(), ["e4", "e5", "Ke3"]).max_plies(Some(3));
+    while let Ok(Some(_)) = plies.next_ply() {}
+    assert_eq!(plies.game().uci, "e7e5");
+
+    let mut plies = replay_plies(Game::new(), ["e4", "e5", "Ke3"]).max_plies(Some(2));
+    assert!(plies.next_ply().is_ok());
+    assert!(plies.next_ply().is_ok());
+    assert_eq!(
+        plies.next_ply().err(),
+        Some(Error::TooManyPlies(
+            "ply 3: game exceeds 2 plies".to_string()
+        ))
+    );
+}
@@ -0,0 +1,324 @@
+use crate::utils::game::Game;
+use crate::utils::json::json_str;
+use crate::utils::outcome::{check_result, GameResult, Status};
+use crate::utils::pgn::{PgnGame, PgnReader};
+use crate::utils::writer::{play_corrected, CheckMarker};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Something suspicious about a game, as found while replaying it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Anomaly {
+    /// A move that cannot be played, which ends the replay.
+    IllegalMove {
+        ply: usize,
+        san: String,
+        reason: String,
+    },
+    /// The check or checkmate marker of a move does not match the position.
+    CheckMarker {
+        ply: usize,
+        san: String,
+        corrected: String,
+    },
+    /// The Result tag and the termination marker of the movetext differ.
+    ResultTagMismatch { tag: String, marker: String },
+    /// The declared result is impossible given the final position, e.g. "1-0" after a stalemate.
+    ResultMismatch {
+        declared: GameResult,
+        status: Status,
+    },
+    /// The FEN tag of a game set up from a position is invalid, thus no move is replayed.
+    InvalidSetUp { fen: String, reason: String },
+    /// A move without clock annotation in a game that has them.
+    MissingClock { ply: usize },
+    /// The clock of a side grew by more than the time control adds after a move.
+    ClockIncrease { ply: usize, before: u32, after: u32 },
+}
+
+impl Anomaly {
+    /// Short name of the kind of anomaly, e.g. "illegal_move".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Anomaly::IllegalMove { .. } => "illegal_move",
+            Anomaly::CheckMarker { .. } => "check_marker",
+            Anomaly::ResultTagMismatch { .. } => "result_tag_mismatch",
+            Anomaly::ResultMismatch { .. } => "result_mismatch",
+            Anomaly::InvalidSetUp { .. } => "invalid_setup",
+            Anomaly::MissingClock { .. } => "missing_clock",
+            Anomaly::ClockIncrease { .. } => "clock_increase",
+        }
+    }
+
+    /// The ply the anomaly refers to, if any.
+    pub fn ply(&self) -> Option<usize> {
+        match self {
+            Anomaly::IllegalMove { ply, .. }
+            | Anomaly::CheckMarker { ply, .. }
+            | Anomaly::MissingClock { ply }
+            | Anomaly::ClockIncrease { ply, .. } => Some(*ply),
+            Anomaly::ResultTagMismatch { .. }
+            | Anomaly::ResultMismatch { .. }
+            | Anomaly::InvalidSetUp { .. } => None,
+        }
+    }
+}
+
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Anomaly::IllegalMove { reason, .. } => write!(f, "{}", reason),
+            Anomaly::CheckMarker { san, corrected, .. } => {
+                write!(f, "{} should be {}", san, corrected)
+            }
+            Anomaly::ResultTagMismatch { tag, marker } => {
+                write!(f, "Result tag {} but movetext ends with {}", tag, marker)
+            }
+            Anomaly::ResultMismatch { declared, status } => {
+                write!(f, "result {} after {}", declared, status)
+            }
+            Anomaly::InvalidSetUp { reason, .. } => write!(f, "{}", reason),
+            Anomaly::MissingClock { .. } => write!(f, "missing clock"),
+            Anomaly::ClockIncrease { before, after, .. } => {
+                write!(f, "clock increased from {}s to {}s", before, after)
+            }
+        }
+    }
+}
+
+/// Everything that is known about a game after replaying it, e.g. to lint PGN files.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameReport {
+    pub headers: Vec<(String, String)>,
+
+    /// The moves that could be replayed, in SAN as derived from the position.
+    pub moves: Vec<String>,
+
+    /// FENs from the initial position to the final one, i.e. one more than there are moves.
+    pub fens: Vec<String>,
+    pub status: Status,
+    pub declared: Option<GameResult>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl GameReport {
+    /// Replay a game from its start position and collect its anomalies. Replay stops at the first
+    /// illegal move.
+    pub fn of(game: &PgnGame) -> Self {
+        let mut anomalies = Vec::new();
+        let (mut board, replayed) = match game.start_position() {
+            Ok(board) => (board, game.moves.as_slice()),
+            Err(reason) => {
+                let fen = game.header("FEN").unwrap_or_default().to_string();
+                let reason = reason.to_string();
+                anomalies.push(Anomaly::InvalidSetUp { fen, reason });
+                (Game::new(), &[][..])
+            }
+        };
+        let mut moves = Vec::with_capacity(game.moves.len());
+        let mut fens = vec![board.clone().to_fen()];
+        for (i, mv) in replayed.iter().enumerate() {
+            let ply = i + 1;
+            match play_corrected(&mut board, mv) {
+                Ok(corrected) => {
+                    if CheckMarker::of(mv) != CheckMarker::of(&corrected) {
+                        anomalies.push(Anomaly::CheckMarker {
+                            ply,
+                            san: mv.clone(),
+                            corrected: corrected.clone(),
+                        });
+                    }
+                    moves.push(corrected);
+                    fens.push(board.clone().to_fen());
+                }
+                Err(reason) => {
+                    anomalies.push(Anomaly::IllegalMove {
+                        ply,
+                        san: mv.clone(),
+                        reason,
+                    });
+                    break;
+                }
+            }
+        }
+        anomalies.extend(clock_anomalies(game, moves.len()));
+
+        let tag = game.header("Result");
+        if let (Some(tag), Some(marker)) = (tag, game.result.as_deref()) {
+            if tag != marker {
+                anomalies.push(Anomaly::ResultTagMismatch {
+                    tag: tag.to_string(),
+                    marker: marker.to_string(),
+                });
+            }
+        }
+        let declared = tag
+            .or(game.result.as_deref())
+            .and_then(|r| GameResult::from_str(r).ok());
+        let check = check_result(&board, declared);
+        if !check.is_consistent {
+            anomalies.push(Anomaly::ResultMismatch {
+                declared: declared.unwrap_or(GameResult::Undecided),
+                status: check.status,
+            });
+        }
+
+        GameReport {
+            headers: game.headers.clone(),
+            moves,
+            fens,
+            status: check.status,
+            declared,
+            anomalies,
+        }
+    }
+
+    /// Whether the game was replayed completely without anything suspicious.
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+
+    /// Render the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let strings = |values: &[String]| {
+            let quoted: Vec<String> = values.iter().map(|v| json_str(v)).collect();
+            format!("[{}]", quoted.join(","))
+        };
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}", json_str(k), json_str(v)))
+            .collect();
+        let anomalies: Vec<String> = self
+            .anomalies
+            .iter()
+            .map(|a| {
+                let ply = a.ply().map_or("null".to_string(), |p| p.to_string());
+                format!(
+                    "{{\"kind\":{},\"ply\":{},\"message\":{}}}",
+                    json_str(a.kind()),
+                    ply,
+                    json_str(&a.to_string())
+                )
+            })
+            .collect();
+        let declared = self
+            .declared
+            .map_or("null".to_string(), |r| json_str(&r.to_string()));
+
+        format!(
+            "{{\"headers\":{{{}}},\"moves\":{},\"fens\":{},\"status\":{},\"declared\":{},\
+             \"anomalies\":[{}]}}",
+            headers.join(","),
+            strings(&self.moves),
+            strings(&self.fens),
+            json_str(&self.status.to_string()),
+            declared,
+            anomalies.join(",")
+        )
+    }
+}
+
+/// Check the clock annotations of the first plies, if the game has any.
+fn clock_anomalies(game: &PgnGame, plies: usize) -> Vec<Anomaly> {
+    let time_control = game.time_control();
+    let clocks = &game.clocks[..game.clocks.len().min(plies)];
+    let mut anomalies = Vec::new();
+    for (i, clock) in clocks.iter().enumerate() {
+        let ply = i + 1;
+        match (i.checked_sub(2).and_then(|j| clocks[j]), *clock) {
+            (_, None) => anomalies.push(Anomaly::MissingClock { ply }),
+            (Some(before), Some(after))
+                if after > before + time_control.added_after(i as u32 / 2).unwrap_or(0) =>
+            {
+                anomalies.push(Anomaly::ClockIncrease { ply, before, after })
+            }
+            _ => {}
+        }
+    }
+    anomalies
+}
+
+/// Report every game of a PGN string.
+pub fn report_pgn(pgn: &str) -> Result<Vec<GameReport>, String> {
+    PgnReader::new(pgn.as_bytes())
+        .map(|game| Ok(GameReport::of(&game.map_err(|e| e.to_string())?)))
+        .collect()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_clean_report() {
+    let reports = report_pgn("[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n").unwrap();
+    let report = &reports[0];
+
+    assert!(report.is_clean());
+    assert_eq!(report.moves.len(), 4);
+    assert_eq!(report.fens.len(), 5);
+    assert_eq!(report.status, Status::Checkmate);
+    assert_eq!(report.declared, Some(GameResult::BlackWins));
+    assert!(report.to_json().starts_with(
+        "{\"headers\":{\"Result\":\"0-1\"},\"moves\":[\"f3\",\"e5\",\"g4\",\"Qh4#\"],\"fens\":["
+    ));
+    assert!(report
+        .to_json()
+        .ends_with("\"status\":\"checkmate\",\"declared\":\"0-1\",\"anomalies\":[]}"));
+}
+
+#[test]
+fn check_report_anomalies() {
+    let pgn = "[Result \"1-0\"]\n[TimeControl \"60+1\"]\n\n\
+               1. e4 {[%clk 0:01:00]} f5 {[%clk 0:01:00]} 2. Qh5 {[%clk 0:01:05]} g6+ \
+               3. Ke3 {[%clk 0:00:50]} 0-1\n";
+    let report = &report_pgn(pgn).unwrap()[0];
+    let kinds: Vec<&str> = report.anomalies.iter().map(|a| a.kind()).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            "check_marker",
+            "check_marker",
+            "illegal_move",
+            "clock_increase",
+            "missing_clock",
+            "result_tag_mismatch"
+        ]
+    );
+    assert_eq!(report.anomalies[0].to_string(), "Qh5 should be Qh5+");
+    assert_eq!(report.anomalies[2].ply(), Some(5));
+    assert_eq!(report.anomalies[3].ply(), Some(3));
+    assert_eq!(report.moves, vec!["e4", "f5", "Qh5+", "g6"]);
+    assert!(report.to_json().contains(
+        "{\"kind\":\"clock_increase\",\"ply\":3,\"message\":\"clock increased from 60s to 65s\"}"
+    ));
+}
+
+#[test]
+fn check_result_mismatch_report() {
+    let report = &report_pgn("1. f3 e5 2. g4 Qh4# 1-0\n").unwrap()[0];
+    assert_eq!(
+        report.anomalies,
+        vec![Anomaly::ResultMismatch {
+            declared: GameResult::WhiteWins,
+            status: Status::Checkmate
+        }]
+    );
+    assert_eq!(
+        report.anomalies[0].to_string(),
+        "result 1-0 after checkmate"
+    );
+}
+
+#[test]
+fn check_set_up_report() {
+    let pgn =
+        "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 b Q - 0 40\"]\n\n40... Kd7 41. O-O-O+ *\n";
+    let report = &report_pgn(pgn).unwrap()[0];
+    assert!(report.is_clean());
+    assert_eq!(report.fens[0], "4k3/8/8/8/8/8/8/R3K3 b Q - 0 40");
+
+    let report = &report_pgn("[FEN \"8/8 w\"]\n\n1. e4 *\n").unwrap()[0];
+    assert_eq!(report.anomalies[0].kind(), "invalid_setup");
+    assert!(report.moves.is_empty());
+}
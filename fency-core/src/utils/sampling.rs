@@ -0,0 +1,158 @@
+use crate::utils::export::PlyRecord;
+use crate::utils::game::Game;
+use crate::utils::replay::replay_plies;
+
+/// Criteria for the positions to extract from a game, e.g. for training data. Positions are
+/// identified by the ply after which they occur. All given criteria must be met.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PlySelection {
+    /// Keep every k-th ply only, i.e. plies k, 2k, 3k and so on.
+    pub every: Option<usize>,
+
+    /// First and last ply to keep (inclusive).
+    pub min_ply: Option<usize>,
+    pub max_ply: Option<usize>,
+
+    /// Keep positions right after a capture only.
+    pub after_capture: bool,
+
+    /// Keep positions where the side to move is in check only.
+    pub in_check: bool,
+}
+
+impl PlySelection {
+    /// Check the criteria that only depend on the ply, which are known before playing it.
+    fn is_selected_ply(&self, ply: usize) -> bool {
+        self.every.is_none_or(|k| ply.is_multiple_of(k))
+            && self.min_ply.is_none_or(|min| ply >= min)
+            && self.max_ply.is_none_or(|max| ply <= max)
+    }
+
+    /// Check all criteria for the position after a ply.
+    fn is_selected(&self, ply: usize, game: &Game, is_capture: bool) -> bool {
+        self.is_selected_ply(ply)
+            && (!self.after_capture || is_capture)
+            && (!self.in_check || game.in_check())
+    }
+}
+
+/// Replay a game and return the records of the selected plies only. The FEN is derived for those
+/// plies alone and replay stops after the last ply that can be selected. Result tokens are
+/// skipped and evals are matched by ply, just like in `ply_records`.
+pub fn sample_plies(
+    moves: &[&str],
+    evals: Option<&[Option<f64>]>,
+    selection: &PlySelection,
+) -> Result<Vec<PlyRecord>, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut records = Vec::new();
+    while selection.max_ply.is_none_or(|max| plies.ply() < max) {
+        let n_figures = plies.game().figures.len();
+        let Some((ply, mv, game)) = plies.next_ply()? else {
+            break;
+        };
+
+        let is_capture = game.figures.len() < n_figures;
+        if selection.is_selected(ply, game, is_capture) {
+            let eval = evals.and_then(|e| e.get(ply - 1).copied().flatten());
+            records.push(PlyRecord::new(ply, mv, game, is_capture, eval));
+        }
+    }
+
+    Ok(records)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const MOVES: [&str; 12] = [
+    "e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+", "Be2", "Bg4", "d4", "Bxe2", "Ngxe2", "Qxe2#",
+];
+
+#[allow(dead_code)]
+fn sampled_plies(selection: PlySelection) -> Vec<usize> {
+    sample_plies(&MOVES, None, &selection)
+        .unwrap()
+        .iter()
+        .map(|r| r.ply)
+        .collect()
+}
+
+#[test]
+fn check_ply_based_sampling() {
+    assert_eq!(sampled_plies(PlySelection::default()).len(), 12);
+    let every_third = PlySelection {
+        every: Some(3),
+        ..PlySelection::default()
+    };
+    assert_eq!(sampled_plies(every_third.clone()), vec![3, 6, 9, 12]);
+    assert_eq!(
+        sampled_plies(PlySelection {
+            min_ply: Some(4),
+            max_ply: Some(9),
+            ..every_third
+        }),
+        vec![6, 9]
+    );
+}
+
+#[test]
+fn check_position_based_sampling() {
+    let captures = PlySelection {
+        after_capture: true,
+        ..PlySelection::default()
+    };
+    assert_eq!(sampled_plies(captures), vec![3, 4, 10, 11, 12]);
+
+    let checks = PlySelection {
+        in_check: true,
+        ..PlySelection::default()
+    };
+    assert_eq!(sampled_plies(checks.clone()), vec![6, 12]);
+
+    let records = sample_plies(&MOVES, Some(&[Some(0.1); 12]), &checks).unwrap();
+    assert_eq!(records[0].san, "Qe5+");
+    assert_eq!(records[0].eval, Some(0.1));
+    assert_eq!(
+        records[0].fen,
+        "rnb1kbnr/ppp1pppp/8/4q3/8/2N5/PPPP1PPP/R1BQKBNR w KQkq - 2 4"
+    );
+}
+
+#[test]
+fn check_sampled_flags_match_selection() {
+    // Plies selected by the board keep their flags, even if the SAN lacks the markers.
+    let moves = ["e4", "d5", "ed5", "Qd5", "Nc3", "Qe5"];
+    let checks = PlySelection {
+        in_check: true,
+        ..PlySelection::default()
+    };
+    let records = sample_plies(&moves, None, &checks).unwrap();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].is_check);
+
+    let captures = PlySelection {
+        after_capture: true,
+        ..PlySelection::default()
+    };
+    let records = sample_plies(&moves, None, &captures).unwrap();
+    assert_eq!(
+        records.iter().map(|r| r.ply).collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+    assert!(records.iter().all(|r| r.is_hit));
+}
+
+#[test]
+fn check_sampling_stops_after_max_ply() {
+    // The illegal move after the last selected ply is never played.
+    let selection = PlySelection {
+        max_ply: Some(2),
+        ..PlySelection::default()
+    };
+    assert_eq!(
+        sample_plies(&["e4", "e5", "Ke3"], None, &selection)
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,166 @@
+use crate::utils::castling::Castling;
+use crate::utils::coord::Coord;
+use crate::utils::draw::Draw;
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+// All types are serialized into their textual chess notation, which keeps the representation
+// compact and readable, e.g. a Coord becomes "e4" and a Figure becomes "Ne4".
+
+impl Serialize for Coord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if !Coord::is_valid(&s) {
+            return Err(D::Error::custom(format!("invalid square: {}", s)));
+        }
+        Ok(Coord::from(&s[..]))
+    }
+}
+
+impl Serialize for Figure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Figure {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if !Figure::is_valid(&s) {
+            return Err(D::Error::custom(format!("invalid figure: {}", s)));
+        }
+        Ok(Figure::from(&s[..]))
+    }
+}
+
+impl Serialize for Castling {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Castling {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s != "-" && !s.chars().all(|c| "KQkq".contains(c)) {
+            return Err(D::Error::custom(format!("invalid castling rights: {}", s)));
+        }
+        Ok(Castling::from(&s[..]))
+    }
+}
+
+/// A draw is fully described by its SAN (plus annotations), so only the SAN is stored.
+impl Serialize for Draw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ep = if self.is_marked_en_passant {
+            "e.p."
+        } else {
+            ""
+        };
+        let annotation = self.annotation.as_deref().unwrap_or_default();
+        serializer.collect_str(&format_args!("{}{}{}", self.san, ep, annotation))
+    }
+}
+
+impl<'de> Deserialize<'de> for Draw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Draw::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+/// Intermediate representation of a game: the FEN carries the whole state, except for the last
+/// move, which is kept in UCI notation.
+#[derive(Serialize, Deserialize)]
+struct GameRepr {
+    fen: String,
+    uci: String,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameRepr {
+            fen: self.clone().to_fen(),
+            uci: self.uci.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GameRepr::deserialize(deserializer)?;
+        let mut game = Game::from_str(&repr.fen).map_err(D::Error::custom)?;
+        game.uci = repr.uci;
+        Ok(game)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_coord_roundtrip() {
+    let json = serde_json::to_string(&Coord::from("e4")).unwrap();
+    assert_eq!(json, "\"e4\"");
+    assert_eq!(
+        serde_json::from_str::<Coord>(&json).unwrap(),
+        Coord::from("e4")
+    );
+    assert!(serde_json::from_str::<Coord>("\"i9\"").is_err());
+}
+
+#[test]
+fn check_figure_roundtrip() {
+    let json = serde_json::to_string(&Figure::from("ng8")).unwrap();
+    assert_eq!(json, "\"ng8\"");
+    assert_eq!(
+        serde_json::from_str::<Figure>(&json).unwrap(),
+        Figure::from("ng8")
+    );
+    assert!(serde_json::from_str::<Figure>("\"Xa1\"").is_err());
+}
+
+#[test]
+fn check_castling_roundtrip() {
+    let castling = Castling::from("Kq");
+    let json = serde_json::to_string(&castling).unwrap();
+    assert_eq!(json, "\"Kq\"");
+    assert_eq!(serde_json::from_str::<Castling>(&json).unwrap(), castling);
+}
+
+#[test]
+fn check_draw_roundtrip() {
+    let draw = Draw::from_str("exd1=Q#").unwrap();
+    let json = serde_json::to_string(&draw).unwrap();
+    assert_eq!(json, "\"exd1=Q#\"");
+    assert_eq!(serde_json::from_str::<Draw>(&json).unwrap(), draw);
+}
+
+#[test]
+fn check_annotated_draw_roundtrip() {
+    let draw = Draw::from_str("exd6e.p.!?").unwrap();
+    let json = serde_json::to_string(&draw).unwrap();
+    assert_eq!(serde_json::from_str::<Draw>(&json).unwrap(), draw);
+}
+
+#[test]
+fn check_game_roundtrip() {
+    let mut game = Game::new();
+    for mv in ["e4", "c5", "Nf3"] {
+        game.play_move(mv).unwrap();
+    }
+    let json = serde_json::to_string(&game).unwrap();
+    assert_eq!(
+        json,
+        "{\"fen\":\"rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2\",\"uci\":\"g1f3\"}"
+    );
+    assert_eq!(serde_json::from_str::<Game>(&json).unwrap(), game);
+}
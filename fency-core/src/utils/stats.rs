@@ -0,0 +1,240 @@
+use crate::utils::coord::Coord;
+use crate::utils::game::Game;
+use crate::utils::labels::declared_result;
+use crate::utils::outcome::{is_result_token, GameResult, Status};
+use crate::utils::pgn::PgnGame;
+use crate::utils::piece::Piece;
+use crate::utils::replay::replay_plies;
+use std::collections::{BTreeMap, HashMap};
+
+/// Statistics of the games within a rating bucket.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BucketStats {
+    pub games: usize,
+    pub plies: usize,
+    pub captures: usize,
+    pub white_wins: usize,
+    pub black_wins: usize,
+    pub draws: usize,
+    pub undecided: usize,
+
+    /// Number of games per ECO code, where games without ECO tag count as "?".
+    pub openings: HashMap<String, usize>,
+}
+
+impl BucketStats {
+    /// Count a game. Plies and captures are taken from the SAN, thus the moves are not replayed.
+    pub fn add(&mut self, game: &PgnGame) {
+        let moves = game.moves.iter().filter(|mv| !is_result_token(mv));
+        let (plies, captures) =
+            moves.fold((0, 0), |(p, c), mv| (p + 1, c + mv.contains('x') as usize));
+        self.games += 1;
+        self.plies += plies;
+        self.captures += captures;
+        match declared_result(game) {
+            Some(GameResult::WhiteWins) => self.white_wins += 1,
+            Some(GameResult::BlackWins) => self.black_wins += 1,
+            Some(GameResult::Draw) => self.draws += 1,
+            Some(GameResult::Undecided) | None => self.undecided += 1,
+        }
+        let eco = game.header("ECO").unwrap_or("?");
+        *self.openings.entry(eco.to_string()).or_default() += 1;
+    }
+
+    pub fn average_plies(&self) -> f64 {
+        self.plies as f64 / self.games.max(1) as f64
+    }
+
+    /// Share of plies that are captures.
+    pub fn capture_rate(&self) -> f64 {
+        self.captures as f64 / self.plies.max(1) as f64
+    }
+
+    /// The most frequent openings, ties ordered by ECO code.
+    pub fn top_openings(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut openings: Vec<(&str, usize)> = self
+            .openings
+            .iter()
+            .map(|(eco, &count)| (eco.as_str(), count))
+            .collect();
+        openings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        openings.truncate(n);
+        openings
+    }
+}
+
+/// Statistics of a stream of games grouped by the average rating of both players, rounded down to
+/// a multiple of the bucket width. Games without valid ratings are grouped in the None bucket.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EloStats {
+    pub bucket_width: u16,
+    pub buckets: BTreeMap<Option<u16>, BucketStats>,
+}
+
+/// Columns of the summary table.
+pub const SUMMARY_COLUMNS: [&str; 10] = [
+    "bucket",
+    "games",
+    "average_plies",
+    "capture_rate",
+    "white_wins",
+    "draws",
+    "black_wins",
+    "undecided",
+    "top_opening",
+    "top_opening_games",
+];
+
+impl EloStats {
+    pub fn new(bucket_width: u16) -> Self {
+        EloStats {
+            bucket_width: bucket_width.max(1),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// The bucket of a game, i.e. the lower bound of the rating range of its average rating.
+    pub fn bucket_of(&self, game: &PgnGame) -> Option<u16> {
+        let elo = |key| game.header(key)?.parse::<u16>().ok();
+        let average = (u32::from(elo("WhiteElo")?) + u32::from(elo("BlackElo")?)) / 2;
+        Some(average as u16 / self.bucket_width * self.bucket_width)
+    }
+
+    pub fn add(&mut self, game: &PgnGame) {
+        let bucket = self.bucket_of(game);
+        self.buckets.entry(bucket).or_default().add(game);
+    }
+
+    /// The summary as CSV with a header line, one row per bucket in ascending order, where the
+    /// bucket without ratings comes first as "?".
+    pub fn to_csv(&self) -> String {
+        let mut csv = SUMMARY_COLUMNS.join(",") + "\n";
+        for (bucket, stats) in &self.buckets {
+            let (top, top_games) = stats.top_openings(1).first().copied().unwrap_or(("", 0));
+            let bucket = bucket.map_or("?".to_string(), |b| b.to_string());
+            csv.push_str(&format!(
+                "{},{},{:.2},{:.4},{},{},{},{},{},{}\n",
+                bucket,
+                stats.games,
+                stats.average_plies(),
+                stats.capture_rate(),
+                stats.white_wins,
+                stats.draws,
+                stats.black_wins,
+                stats.undecided,
+                top,
+                top_games
+            ));
+        }
+        csv
+    }
+}
+
+impl Extend<PgnGame> for EloStats {
+    fn extend<T: IntoIterator<Item = PgnGame>>(&mut self, games: T) {
+        for game in games {
+            self.add(&game);
+        }
+    }
+}
+
+/// Counts of a replayed game, e.g. to profile a dataset. Captures, castlings and promotions are
+/// derived from the board rather than from the SAN, thus they do not depend on its markers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GameSummary {
+    pub plies: usize,
+    pub captures: usize,
+    pub castlings: usize,
+    pub promotions: usize,
+
+    /// Plies that give check, including checkmate.
+    pub checks: usize,
+    pub status: Status,
+}
+
+/// Replay moves in SAN and count what happened without deriving any FEN. Result tokens like "1-0"
+/// are skipped.
+pub fn summarize(moves: &[&str]) -> Result<GameSummary, String> {
+    let mut plies = replay_plies(Game::new(), moves);
+    let mut summary = GameSummary {
+        plies: 0,
+        captures: 0,
+        castlings: 0,
+        promotions: 0,
+        checks: 0,
+        status: Status::Ongoing,
+    };
+    loop {
+        let pieces_before = plies.game().figures.len();
+        let Some((ply, _, game)) = plies.next_ply()? else {
+            break;
+        };
+        summary.plies = ply;
+        summary.captures += (game.figures.len() < pieces_before) as usize;
+        summary.promotions += (game.uci.len() == 5) as usize;
+        summary.checks += game.in_check() as usize;
+
+        // Only castling moves a king by two files.
+        let (src, tgt) = (Coord::from(&game.uci[..2]), Coord::from(&game.uci[2..4]));
+        let is_king = game.position[tgt.idx as usize].is_some_and(|f| f.piece() == Piece::K);
+        summary.castlings += (is_king && (src.x - tgt.x).abs() == 2) as usize;
+    }
+    summary.status = plies.game().status();
+    Ok(summary)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const RATED_GAMES: &str = "\
+[WhiteElo \"1510\"]\n[BlackElo \"1590\"]\n[ECO \"C20\"]\n[Result \"1-0\"]\n\n\
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n\n\
+[WhiteElo \"1450\"]\n[BlackElo \"1700\"]\n[ECO \"C20\"]\n[Result \"1/2-1/2\"]\n\n\
+1. e4 e5 1/2-1/2\n\n\
+[WhiteElo \"2100\"]\n[BlackElo \"2050\"]\n[ECO \"D00\"]\n[Result \"0-1\"]\n\n\
+1. d4 d5 2. Bf4 c5 3. e3 cxd4 4. exd4 0-1\n\n\
+[White \"anonymous\"]\n\n1. e4 *\n";
+
+#[test]
+fn check_elo_stats() {
+    let mut stats = EloStats::new(200);
+    stats.extend(crate::utils::pgn::parse_pgn(RATED_GAMES));
+
+    assert_eq!(
+        stats.buckets.keys().collect::<Vec<_>>(),
+        vec![&None, &Some(1400), &Some(2000)]
+    );
+    let low = &stats.buckets[&Some(1400)];
+    assert_eq!((low.games, low.plies, low.captures), (2, 9, 1));
+    assert_eq!((low.white_wins, low.draws, low.black_wins), (1, 1, 0));
+    assert_eq!(low.top_openings(2), vec![("C20", 2)]);
+    assert_eq!(stats.buckets[&None].undecided, 1);
+
+    assert_eq!(
+        stats.to_csv(),
+        "bucket,games,average_plies,capture_rate,white_wins,draws,black_wins,undecided,\
+         top_opening,top_opening_games\n\
+         ?,1,1.00,0.0000,0,0,0,1,?,1\n\
+         1400,2,4.50,0.1111,1,1,0,0,C20,2\n\
+         2000,1,7.00,0.2857,0,0,1,0,D00,1\n"
+    );
+}
+
+#[test]
+fn check_summarize() {
+    let summary = summarize(&[
+        "e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5", "Nf3", "Nf6", "Bc4", "Bf5", "O-O", "e6", "1-0",
+    ])
+    .unwrap();
+    assert_eq!(
+        (summary.plies, summary.captures, summary.castlings),
+        (12, 2, 1)
+    );
+    assert_eq!((summary.checks, summary.status), (0, Status::Ongoing));
+
+    let summary = summarize(&["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]).unwrap();
+    assert_eq!((summary.checks, summary.status), (1, Status::Checkmate));
+    assert_eq!(
+        summarize(&["e4", "e5", "Ke3"]).unwrap_err(),
+        "ply 3: illegal move: Ke3"
+    );
+}
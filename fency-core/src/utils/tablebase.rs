@@ -0,0 +1,222 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::pgn::{PgnGame, PgnReader};
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess};
+use shakmaty_syzygy::{Metric, SyzygyError};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Theoretical outcome from the point of view of the side to move, considering the fifty-move rule
+/// as if the position was reached by a capture or pawn move.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Wdl {
+    Loss,
+    /// A loss that is saved by the fifty-move rule.
+    BlessedLoss,
+    Draw,
+    /// A win that is spoiled by the fifty-move rule.
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    /// 1 for a win, 0 for a draw (including cursed wins and blessed losses), -1 for a loss.
+    pub fn score(self) -> i8 {
+        match self {
+            Wdl::Loss => -1,
+            Wdl::BlessedLoss | Wdl::Draw | Wdl::CursedWin => 0,
+            Wdl::Win => 1,
+        }
+    }
+}
+
+impl From<shakmaty_syzygy::Wdl> for Wdl {
+    fn from(wdl: shakmaty_syzygy::Wdl) -> Self {
+        match wdl {
+            shakmaty_syzygy::Wdl::Loss => Wdl::Loss,
+            shakmaty_syzygy::Wdl::BlessedLoss => Wdl::BlessedLoss,
+            shakmaty_syzygy::Wdl::Draw => Wdl::Draw,
+            shakmaty_syzygy::Wdl::CursedWin => Wdl::CursedWin,
+            shakmaty_syzygy::Wdl::Win => Wdl::Win,
+        }
+    }
+}
+
+impl Display for Wdl {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let wdl = match self {
+            Wdl::Loss => "loss",
+            Wdl::BlessedLoss => "blessed_loss",
+            Wdl::Draw => "draw",
+            Wdl::CursedWin => "cursed_win",
+            Wdl::Win => "win",
+        };
+        write!(f, "{}", wdl)
+    }
+}
+
+/// Syzygy endgame tablebases, e.g. the 3-4-5 or 6-man tables. Files are opened lazily on probing.
+pub struct Tablebase {
+    tables: shakmaty_syzygy::Tablebase<Chess>,
+}
+
+impl Tablebase {
+    /// Add the WDL (.rtbw) and DTZ (.rtbz) tables of the given directories.
+    pub fn open<P: AsRef<Path>>(dirs: &[P]) -> Result<Self, String> {
+        let mut tables = shakmaty_syzygy::Tablebase::new();
+        for dir in dirs {
+            tables
+                .add_directory(dir)
+                .map_err(|e| format!("{}: {}", dir.as_ref().display(), e))?;
+        }
+        Ok(Tablebase { tables })
+    }
+
+    /// Maximum number of pieces (including kings) of the added tables.
+    pub fn max_pieces(&self) -> usize {
+        self.tables.max_pieces()
+    }
+
+    /// Whether the tables can contain the position, i.e. it has few enough pieces and no castling
+    /// rights.
+    pub fn covers(&self, game: &Game) -> bool {
+        let pieces = game.position.iter().filter(|f| f.is_some()).count();
+        let castling = &game.castling;
+        let can_castle = castling.white_kingside
+            || castling.white_queenside
+            || castling.black_kingside
+            || castling.black_queenside;
+        pieces <= self.max_pieces().max(2) && !can_castle
+    }
+
+    fn position(game: &Game) -> Result<Chess, String> {
+        let fen = game.clone().to_fen();
+        Fen::from_ascii(fen.as_bytes())
+            .map_err(|e| format!("{}: {}", fen, e))?
+            .into_position(CastlingMode::Standard)
+            .map_err(|e| format!("{}: {}", fen, e))
+    }
+
+    /// The theoretical outcome for the side to move, or None if the position is not covered.
+    pub fn probe_wdl(&self, game: &Game) -> Result<Option<Wdl>, String> {
+        if !self.covers(game) {
+            return Ok(None);
+        }
+        let wdl = self
+            .tables
+            .probe_wdl_after_zeroing(&Self::position(game)?)
+            .map_err(|e| e.to_string())?;
+        Ok(Some(wdl.into()))
+    }
+
+    /// The distance to zeroing in plies, i.e. to the next capture or pawn move that keeps the
+    /// outcome, negative if the side to move loses. None if the position is not covered or the DTZ
+    /// tables are missing. The value may be one ply too short, as some tables store rounded values.
+    pub fn probe_dtz(&self, game: &Game) -> Result<Option<i32>, String> {
+        if !self.covers(game) {
+            return Ok(None);
+        }
+        match self.tables.probe_dtz(&Self::position(game)?) {
+            Ok(dtz) => Ok(Some(dtz.ignore_rounding().0)),
+            Err(SyzygyError::MissingTable {
+                metric: Metric::Dtz,
+                ..
+            }) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl Game {
+    /// The theoretical outcome of the position for the side to move, see `Tablebase::probe_wdl`.
+    pub fn tablebase_wdl(&self, tablebase: &Tablebase) -> Result<Option<Wdl>, String> {
+        tablebase.probe_wdl(self)
+    }
+}
+
+/// An endgame position of a game labeled with its theoretical outcome.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndgameLabel {
+    /// Index of the game in the dataset.
+    pub game: usize,
+
+    /// Number of plies played before the position.
+    pub ply: usize,
+    pub fen: String,
+    pub wdl: Wdl,
+    pub dtz: Option<i32>,
+}
+
+/// Label every position of a game that is covered by the tablebase.
+pub fn label_endgame(
+    game: &PgnGame,
+    idx: usize,
+    tablebase: &Tablebase,
+) -> Result<Vec<EndgameLabel>, String> {
+    let mut board = game.start_position()?;
+    let mut labels = Vec::new();
+    for ply in 0..=game.moves.len() {
+        if ply > 0 {
+            board.play_move(&game.moves[ply - 1]).map_err(at_ply(ply))?;
+        }
+        if let Some(wdl) = tablebase.probe_wdl(&board)? {
+            labels.push(EndgameLabel {
+                game: idx,
+                ply,
+                fen: board.clone().to_fen(),
+                wdl,
+                dtz: tablebase.probe_dtz(&board)?,
+            });
+        }
+    }
+
+    Ok(labels)
+}
+
+/// Label the endgame positions of all games of a dataset, see `label_endgame`.
+pub fn label_endgames<R: BufRead>(
+    games: PgnReader<R>,
+    tablebase: &Tablebase,
+) -> Result<Vec<EndgameLabel>, String> {
+    let mut labels = Vec::new();
+    for (g, game) in games.enumerate() {
+        let game = game.map_err(|e| e.to_string())?;
+        labels
+            .extend(label_endgame(&game, g, tablebase).map_err(|e| format!("game {}, {}", g, e))?);
+    }
+
+    Ok(labels)
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_probe_without_tables() {
+    use std::str::FromStr;
+
+    let tablebase = Tablebase::open::<&str>(&[]).unwrap();
+    assert_eq!(tablebase.max_pieces(), 0);
+
+    // Bare kings are a draw without any tables.
+    let kings = Game::from_str("8/8/4k3/8/8/3K4/8/8 w - - 0 60").unwrap();
+    assert_eq!(kings.tablebase_wdl(&tablebase), Ok(Some(Wdl::Draw)));
+    assert_eq!(tablebase.probe_dtz(&kings), Ok(Some(0)));
+
+    let rook = Game::from_str("8/8/4k3/8/8/3K4/8/7R w - - 0 60").unwrap();
+    assert_eq!(rook.tablebase_wdl(&tablebase), Ok(None));
+    assert_eq!(Game::new().tablebase_wdl(&tablebase), Ok(None));
+    assert!(Tablebase::open(&["/nonexistent/syzygy"]).is_err());
+}
+
+#[test]
+fn check_label_endgames() {
+    let tablebase = Tablebase::open::<&str>(&[]).unwrap();
+    let pgn = "1. e4 e5 *\n\n1. d4 *\n";
+    let labels = label_endgames(PgnReader::new(pgn.as_bytes()), &tablebase).unwrap();
+    assert!(labels.is_empty());
+
+    assert_eq!(Wdl::CursedWin.score(), 0);
+    assert_eq!(Wdl::Loss.score(), -1);
+    assert_eq!(Wdl::BlessedLoss.to_string(), "blessed_loss");
+    assert_eq!(Wdl::from(shakmaty_syzygy::Wdl::Win), Wdl::Win);
+}
@@ -0,0 +1,235 @@
+use crate::utils::pgn::PgnGame;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A period of a time control, e.g. 40 moves in 90 minutes with 30 seconds increment per move.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Period {
+    /// Number of moves to play within the period, or None for the rest of the game.
+    pub moves: Option<u32>,
+    pub seconds: u32,
+    pub increment: u32,
+}
+
+/// The TimeControl tag of a PGN, e.g. "300+3" or "40/5400+30:1800+30".
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TimeControl {
+    /// "?", the time control is not known.
+    Unknown,
+    /// "-", there was no time control.
+    Unlimited,
+    /// "*180", an hourglass where the time used by one side is added to the other.
+    Sandclock(u32),
+    /// Consecutive periods, where a last period with a number of moves is repeated.
+    Periods(Vec<Period>),
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(tc: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid time control: {}", tc);
+        let number = |s: &str| s.parse::<u32>().map_err(|_| err());
+        match tc.trim() {
+            "?" => Ok(TimeControl::Unknown),
+            "-" => Ok(TimeControl::Unlimited),
+            t if t.starts_with('*') => Ok(TimeControl::Sandclock(number(&t[1..])?)),
+            t => t
+                .split(':')
+                .map(|field| {
+                    let (moves, rest) = match field.split_once('/') {
+                        Some((moves, rest)) => (Some(number(moves)?), rest),
+                        None => (None, field),
+                    };
+                    let (seconds, increment) = match rest.split_once('+') {
+                        Some((seconds, increment)) => (number(seconds)?, number(increment)?),
+                        None => (number(rest)?, 0),
+                    };
+                    Ok(Period {
+                        moves,
+                        seconds,
+                        increment,
+                    })
+                })
+                .collect::<Result<_, _>>()
+                .map(TimeControl::Periods),
+        }
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TimeControl::Unknown => write!(f, "?"),
+            TimeControl::Unlimited => write!(f, "-"),
+            TimeControl::Sandclock(seconds) => write!(f, "*{}", seconds),
+            TimeControl::Periods(periods) => {
+                let fields: Vec<String> = periods
+                    .iter()
+                    .map(|p| {
+                        let moves = p.moves.map(|m| format!("{}/", m)).unwrap_or_default();
+                        let increment = match p.increment {
+                            0 => String::new(),
+                            inc => format!("+{}", inc),
+                        };
+                        format!("{}{}{}", moves, p.seconds, increment)
+                    })
+                    .collect();
+                write!(f, "{}", fields.join(":"))
+            }
+        }
+    }
+}
+
+impl TimeControl {
+    /// Time on the clock before the first move, if known.
+    pub fn initial_seconds(&self) -> Option<u32> {
+        match self {
+            TimeControl::Sandclock(seconds) => Some(*seconds),
+            TimeControl::Periods(periods) => periods.first().map(|p| p.seconds),
+            TimeControl::Unknown | TimeControl::Unlimited => None,
+        }
+    }
+
+    /// Time added to the clock of a side after its n-th move (counting from 1), i.e. the
+    /// increment plus the time of the next period if the move completes the current one.
+    /// None if unknown or if the clock does not work that way.
+    pub fn added_after(&self, n: u32) -> Option<u32> {
+        let TimeControl::Periods(periods) = self else {
+            return None;
+        };
+        let mut completed = 0;
+        let mut idx = 0;
+        loop {
+            let period = periods.get(idx)?;
+            let Some(moves) = period.moves.filter(|&m| m > 0) else {
+                return Some(period.increment);
+            };
+            if n <= completed + moves {
+                let bonus = if n == completed + moves {
+                    // A last period with a number of moves is repeated.
+                    periods.get(idx + 1).unwrap_or(period).seconds
+                } else {
+                    0
+                };
+                return Some(period.increment + bonus);
+            }
+            completed += moves;
+            if idx + 1 < periods.len() {
+                idx += 1;
+            }
+        }
+    }
+}
+
+/// Time spent on each ply in seconds, derived from the remaining time after the plies as given by
+/// "[%clk]" comments. The time spent is the difference to the previous clock of the same side plus
+/// the time added after the move. Plies without clocks, before the first clock of a side with an
+/// unknown time control, or with inconsistent clocks have no time spent.
+pub fn time_spent(clocks: &[Option<u32>], time_control: &TimeControl) -> Vec<Option<u32>> {
+    if matches!(
+        time_control,
+        TimeControl::Unlimited | TimeControl::Sandclock(_)
+    ) {
+        return vec![None; clocks.len()];
+    }
+    let added = |i: usize| match time_control {
+        TimeControl::Unknown => Some(0),
+        tc => tc.added_after((i / 2 + 1) as u32),
+    };
+    (0..clocks.len())
+        .map(|i| {
+            let before = match i.checked_sub(2) {
+                Some(j) => clocks[j]? + added(j)?,
+                None => time_control.initial_seconds()?,
+            };
+            before.checked_sub(clocks[i]?)
+        })
+        .collect()
+}
+
+impl PgnGame {
+    /// The time control given by the TimeControl tag, which is unknown if missing or invalid.
+    pub fn time_control(&self) -> TimeControl {
+        self.header("TimeControl")
+            .and_then(|tc| TimeControl::from_str(tc).ok())
+            .unwrap_or(TimeControl::Unknown)
+    }
+
+    /// Time spent on each move according to the clock annotations, see `time_spent`.
+    pub fn time_spent(&self) -> Vec<Option<u32>> {
+        time_spent(&self.clocks, &self.time_control())
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_parse_time_control() {
+    for tc in [
+        "?",
+        "-",
+        "*180",
+        "300+3",
+        "40/5400+30:1800+30",
+        "40/9000",
+        "4500",
+    ] {
+        assert_eq!(TimeControl::from_str(tc).unwrap().to_string(), tc);
+    }
+    assert_eq!(
+        TimeControl::from_str("40/5400:1800").unwrap(),
+        TimeControl::Periods(vec![
+            Period {
+                moves: Some(40),
+                seconds: 5400,
+                increment: 0
+            },
+            Period {
+                moves: None,
+                seconds: 1800,
+                increment: 0
+            },
+        ])
+    );
+    assert!(TimeControl::from_str("5 min").is_err());
+    assert!(TimeControl::from_str("300+").is_err());
+}
+
+#[test]
+fn check_added_time() {
+    let blitz = TimeControl::from_str("180+2").unwrap();
+    assert_eq!(blitz.initial_seconds(), Some(180));
+    assert_eq!(blitz.added_after(1), Some(2));
+
+    let classical = TimeControl::from_str("40/5400+30:1800+30").unwrap();
+    assert_eq!(classical.added_after(39), Some(30));
+    assert_eq!(classical.added_after(40), Some(1830));
+    assert_eq!(classical.added_after(41), Some(30));
+
+    let repeated = TimeControl::from_str("40/7200").unwrap();
+    assert_eq!(repeated.added_after(40), Some(7200));
+    assert_eq!(repeated.added_after(80), Some(7200));
+    assert_eq!(repeated.added_after(81), Some(0));
+    assert_eq!(TimeControl::Unknown.added_after(1), None);
+}
+
+#[test]
+fn check_time_spent() {
+    let blitz = TimeControl::from_str("180+2").unwrap();
+    let clocks = [Some(178), Some(175), Some(170), None, Some(180), Some(160)];
+    assert_eq!(
+        time_spent(&clocks, &blitz),
+        vec![Some(2), Some(5), Some(10), None, None, None]
+    );
+    assert_eq!(
+        time_spent(&[Some(178), Some(175), Some(170)], &TimeControl::Unknown),
+        vec![None, None, Some(8)]
+    );
+
+    let game = PgnGame {
+        headers: vec![("TimeControl".to_string(), "60+1".to_string())],
+        clocks: vec![Some(55), Some(60)],
+        ..PgnGame::default()
+    };
+    assert_eq!(game.time_spent(), vec![Some(5), Some(0)]);
+}
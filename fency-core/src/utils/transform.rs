@@ -0,0 +1,141 @@
+use crate::utils::castling::Castling;
+use crate::utils::coord::Coord;
+use crate::utils::figure::Figure;
+use crate::utils::game::Game;
+use std::str::FromStr;
+
+/// Symmetries of the board, e.g. to augment training data or to compare endgames.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Transform {
+    /// Swap the a- and h-file side. As castling requires the king on the e-file, the castling
+    /// rights are lost, while the En-Passant square moves along with the pawn.
+    MirrorHorizontal,
+    /// Swap the first and the eighth rank, keeping the colors of the figures. Pawns then move
+    /// backwards, thus neither castling nor En-Passant remain meaningful and both are dropped.
+    MirrorVertical,
+    /// The same position seen from the other side: ranks are mirrored, colors are swapped, as are
+    /// the side to move and the castling rights. The result is equivalent to the original.
+    SwapColors,
+}
+
+impl Transform {
+    fn square(self, coord: Coord) -> Coord {
+        match self {
+            Transform::MirrorHorizontal => Coord::from_xy(7 - coord.x, coord.y),
+            Transform::MirrorVertical | Transform::SwapColors => {
+                Coord::from_xy(coord.x, 7 - coord.y)
+            }
+        }
+    }
+}
+
+impl Game {
+    /// Apply a transform to the position, which yields a new game without history.
+    pub fn transformed(&self, transform: Transform) -> Game {
+        let mut position = [None; 64];
+        for fig in self.position.iter().flatten() {
+            let coord = transform.square(fig.coord());
+            let color = match transform {
+                Transform::SwapColors => fig.color().next(),
+                _ => fig.color(),
+            };
+            position[coord.idx as usize] = Some(Figure::new(color, coord, fig.piece()));
+        }
+        let (color, castling, en_passant) = match transform {
+            Transform::MirrorHorizontal => (
+                self.color,
+                Castling::from("-"),
+                self.en_passant.map(|ep| transform.square(ep)),
+            ),
+            Transform::MirrorVertical => (self.color, Castling::from("-"), None),
+            Transform::SwapColors => (
+                self.color.next(),
+                Castling {
+                    white_kingside: self.castling.black_kingside,
+                    white_queenside: self.castling.black_queenside,
+                    black_kingside: self.castling.white_kingside,
+                    black_queenside: self.castling.white_queenside,
+                },
+                self.en_passant.map(|ep| transform.square(ep)),
+            ),
+        };
+
+        Game::from_parts(
+            position,
+            color,
+            castling,
+            en_passant,
+            self.half_move_clock,
+            self.full_move_clock,
+        )
+    }
+
+    pub fn mirror_horizontal(&self) -> Game {
+        self.transformed(Transform::MirrorHorizontal)
+    }
+
+    pub fn mirror_vertical(&self) -> Game {
+        self.transformed(Transform::MirrorVertical)
+    }
+
+    pub fn swap_colors(&self) -> Game {
+        self.transformed(Transform::SwapColors)
+    }
+}
+
+/// Apply a transform to a FEN.
+pub fn transform_fen(fen: &str, transform: Transform) -> Result<String, String> {
+    Ok(Game::from_str(fen)?.transformed(transform).to_fen())
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+const FEN: &str = "rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+
+#[test]
+fn check_mirror_horizontal() {
+    assert_eq!(
+        transform_fen(FEN, Transform::MirrorHorizontal).unwrap(),
+        "r1bkqbnr/pppp1ppp/2n5/3Pp3/8/8/PPP1PPPP/RNBKQBNR w - e6 0 3"
+    );
+}
+
+#[test]
+fn check_mirror_vertical() {
+    assert_eq!(
+        transform_fen(FEN, Transform::MirrorVertical).unwrap(),
+        "RNBQKBNR/PPPP1PPP/8/8/3pP3/5n2/ppp1pppp/rnbqkb1r w - - 0 3"
+    );
+}
+
+#[test]
+fn check_swap_colors() {
+    let game = Game::from_str("r3k2r/8/8/8/4Pp2/8/8/4K2R b Kkq e3 0 1").unwrap();
+    let swapped = game.swap_colors();
+    assert_eq!(
+        swapped.clone().to_fen(),
+        "4k2r/8/8/4pP2/8/8/8/R3K2R w KQk e6 0 1"
+    );
+
+    // The swapped game plays the same, and swapping twice restores the original.
+    assert_eq!(swapped.legal_moves().len(), game.legal_moves().len());
+    assert_eq!(swapped.swap_colors(), game);
+    assert_eq!(
+        Game::new().swap_colors().to_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"
+    );
+}
+
+#[test]
+fn check_transforms_are_involutions() {
+    let game = Game::from_str(FEN).unwrap();
+    for transform in [
+        Transform::MirrorHorizontal,
+        Transform::MirrorVertical,
+        Transform::SwapColors,
+    ] {
+        let twice = game.transformed(transform).transformed(transform);
+        assert_eq!(twice.position, game.position);
+        assert_eq!(twice.color, game.color);
+    }
+}
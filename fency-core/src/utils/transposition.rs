@@ -0,0 +1,122 @@
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::is_result_token;
+use crate::utils::zobrist::{reduced_fen, zobrist_hash};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Where a position occurs: the index of the game and the ply after which it is on the board.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Occurrence {
+    pub game: usize,
+    pub ply: usize,
+}
+
+/// A position that occurs more than once within the given games.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transposition {
+    pub key: u64,
+    pub fen: String,
+    pub occurrences: Vec<Occurrence>,
+
+    /// Number of distinct move orders that lead to the position. A position that is only reached
+    /// by a single move order (e.g. a shared opening line) is no transposition in the narrow sense.
+    pub n_move_orders: usize,
+}
+
+impl Transposition {
+    pub fn is_transposition(&self) -> bool {
+        self.n_move_orders > 1
+    }
+}
+
+/// Replay all games and report the positions that occur more than once, ordered by their first
+/// occurrence. The initial position (ply 0) is not considered. Result tokens are skipped.
+pub fn find_transpositions(games: &[Vec<&str>]) -> Result<Vec<Transposition>, String> {
+    let mut order: Vec<u64> = Vec::new();
+    let mut found: HashMap<u64, (Transposition, Vec<u64>)> = HashMap::new();
+
+    for (g, moves) in games.iter().enumerate() {
+        let mut game = Game::new();
+        let mut move_order = DefaultHasher::new();
+        for (i, mv) in moves.iter().filter(|mv| !is_result_token(mv)).enumerate() {
+            game.play_move(mv)
+                .map_err(|e| format!("game {}, {}", g, at_ply(i + 1)(e)))?;
+            game.uci.hash(&mut move_order);
+
+            let key = zobrist_hash(&game);
+            let (entry, move_orders) = found.entry(key).or_insert_with(|| {
+                order.push(key);
+                let transposition = Transposition {
+                    key,
+                    fen: reduced_fen(&game),
+                    occurrences: Vec::new(),
+                    n_move_orders: 0,
+                };
+                (transposition, Vec::new())
+            });
+            entry.occurrences.push(Occurrence {
+                game: g,
+                ply: i + 1,
+            });
+            let move_order = move_order.finish();
+            if !move_orders.contains(&move_order) {
+                move_orders.push(move_order);
+                entry.n_move_orders += 1;
+            }
+        }
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|key| found.remove(&key))
+        .map(|(transposition, _)| transposition)
+        .filter(|t| t.occurrences.len() > 1)
+        .collect())
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_find_transpositions() {
+    let games = vec![
+        vec!["Nf3", "Nf6", "d4", "d5", "1-0"],
+        vec!["d4", "d5", "Nf3", "Nf6", "c4"],
+        vec!["d4", "e5"],
+    ];
+    let transpositions = find_transpositions(&games).unwrap();
+
+    assert_eq!(transpositions.len(), 2);
+    let transposition = &transpositions[0];
+    assert!(transposition.is_transposition());
+    assert_eq!(
+        transposition.fen,
+        "rnbqkb1r/ppp1pppp/5n2/3p4/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq -"
+    );
+    assert_eq!(
+        transposition.occurrences,
+        vec![
+            Occurrence { game: 0, ply: 4 },
+            Occurrence { game: 1, ply: 4 }
+        ]
+    );
+
+    // "d4" is shared by the second and third game, but without a transposition.
+    assert!(!transpositions[1].is_transposition());
+    assert_eq!(
+        transpositions[1].occurrences,
+        vec![
+            Occurrence { game: 1, ply: 1 },
+            Occurrence { game: 2, ply: 1 }
+        ]
+    );
+}
+
+#[test]
+fn check_repetitions_within_a_game() {
+    let games = vec![vec!["Nf3", "Nf6", "Ng1", "Ng8", "Nf3"]];
+    let transpositions = find_transpositions(&games).unwrap();
+
+    assert_eq!(transpositions.len(), 1);
+    assert_eq!(transpositions[0].occurrences.len(), 2);
+    assert!(transpositions[0].is_transposition());
+}
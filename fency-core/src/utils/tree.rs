@@ -0,0 +1,358 @@
+use crate::utils::draw::clean_san;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::outcome::{is_result_token, GameResult};
+use crate::utils::pgn::PgnGame;
+use crate::utils::zobrist::zobrist_hash;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::str::FromStr;
+
+/// Header line of the file format, to recognize tree files and their version.
+const FILE_HEADER: &str = "fency-tree 1";
+
+/// Number of games and their results, as seen from white.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Stats {
+    pub games: u32,
+    pub white_wins: u32,
+    pub draws: u32,
+    pub black_wins: u32,
+}
+
+impl Stats {
+    fn add(&mut self, result: Option<GameResult>) {
+        self.games += 1;
+        match result {
+            Some(GameResult::WhiteWins) => self.white_wins += 1,
+            Some(GameResult::Draw) => self.draws += 1,
+            Some(GameResult::BlackWins) => self.black_wins += 1,
+            Some(GameResult::Undecided) | None => {}
+        }
+    }
+
+    fn to_fields(self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.games, self.white_wins, self.draws, self.black_wins
+        )
+    }
+
+    fn from_fields(fields: &[&str]) -> Result<Self, String> {
+        let values = fields
+            .iter()
+            .map(|f| f.parse::<u32>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<u32>, String>>()?;
+        match values[..] {
+            [games, white_wins, draws, black_wins] => Ok(Stats {
+                games,
+                white_wins,
+                draws,
+                black_wins,
+            }),
+            _ => Err("expected four counts".to_string()),
+        }
+    }
+}
+
+/// A move that has been played from a position, with the stats of the games that played it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveStats {
+    pub san: String,
+    pub uci: String,
+    /// Zobrist hash of the position after the move.
+    pub key: u64,
+    pub stats: Stats,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Node {
+    stats: Stats,
+    children: Vec<MoveStats>,
+}
+
+/// Opening tree over many games, where positions are keyed by their Zobrist hash. Transpositions
+/// thus end up in the same node, no matter by which move order they were reached.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PositionTree {
+    nodes: HashMap<u64, Node>,
+
+    /// Only the first plies of every game are added, if given.
+    pub max_plies: Option<usize>,
+}
+
+impl PositionTree {
+    pub fn new() -> Self {
+        PositionTree::default()
+    }
+
+    pub fn with_max_plies(max_plies: usize) -> Self {
+        PositionTree {
+            max_plies: Some(max_plies),
+            ..PositionTree::default()
+        }
+    }
+
+    /// Number of distinct positions in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Add the moves of a game (in SAN) with its result. Result tokens are skipped. A game with an
+    /// invalid move is not added at all. A game counts at most once per position and per move,
+    /// even if it repeats them.
+    pub fn add_game(&mut self, moves: &[&str], result: Option<GameResult>) -> Result<(), String> {
+        let mut game = Game::new();
+        let root_key = zobrist_hash(&game);
+
+        // Replay the whole game first, so that an invalid move leaves the tree untouched.
+        let max_plies = self.max_plies.unwrap_or(usize::MAX);
+        let mut plies = Vec::new();
+        for (i, mv) in moves
+            .iter()
+            .filter(|mv| !is_result_token(mv))
+            .take(max_plies)
+            .enumerate()
+        {
+            game.play_move(mv).map_err(at_ply(i + 1))?;
+            plies.push((clean_san(mv).0, game.uci.clone(), zobrist_hash(&game)));
+        }
+
+        let mut key = root_key;
+        let mut visited_nodes = HashSet::from([key]);
+        let mut visited_moves = HashSet::new();
+        self.nodes.entry(key).or_default().stats.add(result);
+        for (san, uci, child_key) in plies {
+            let node = self.nodes.get_mut(&key).unwrap();
+            let is_new_move = visited_moves.insert((key, child_key));
+            match node.children.iter_mut().find(|c| c.key == child_key) {
+                Some(child) if is_new_move => child.stats.add(result),
+                Some(_) => {}
+                None => {
+                    let mut stats = Stats::default();
+                    stats.add(result);
+                    node.children.push(MoveStats {
+                        san,
+                        uci,
+                        key: child_key,
+                        stats,
+                    });
+                }
+            }
+            let child = self.nodes.entry(child_key).or_default();
+            if visited_nodes.insert(child_key) {
+                child.stats.add(result);
+            }
+            key = child_key;
+        }
+        Ok(())
+    }
+
+    /// Add a parsed PGN game, taking the result from its termination marker or Result tag.
+    pub fn add_pgn_game(&mut self, pgn_game: &PgnGame) -> Result<(), String> {
+        let result = pgn_game
+            .result
+            .as_deref()
+            .or(pgn_game.header("Result"))
+            .and_then(|r| GameResult::from_str(r).ok());
+        self.add_game(&pgn_game.moves(), result)
+    }
+
+    /// Stats of all games that reached the position.
+    pub fn stats(&self, game: &Game) -> Option<Stats> {
+        self.nodes.get(&zobrist_hash(game)).map(|n| n.stats)
+    }
+
+    /// Moves played from the position, the most popular first.
+    pub fn children(&self, game: &Game) -> Vec<MoveStats> {
+        let mut children = self
+            .nodes
+            .get(&zobrist_hash(game))
+            .map(|n| n.children.clone())
+            .unwrap_or_default();
+        children.sort_by_key(|c| std::cmp::Reverse(c.stats.games));
+        children
+    }
+
+    /// Write the tree to a line based text file, one line per position and per move.
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", FILE_HEADER)?;
+        // Sort by key, so equal trees produce equal files.
+        let mut keys: Vec<&u64> = self.nodes.keys().collect();
+        keys.sort();
+        for key in keys {
+            let node = &self.nodes[key];
+            writeln!(writer, "node {:016x} {}", key, node.stats.to_fields())?;
+            for child in &node.children {
+                writeln!(
+                    writer,
+                    "move {:016x} {} {} {:016x} {}",
+                    key,
+                    child.san,
+                    child.uci,
+                    child.key,
+                    child.stats.to_fields()
+                )?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read a tree that has been written by `save`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let reader = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+        let mut lines = reader.lines();
+        match lines.next() {
+            Some(Ok(header)) if header == FILE_HEADER => {}
+            _ => return Err(format!("not a position tree file: {}", path)),
+        }
+
+        let mut tree = PositionTree::new();
+        for (n, line) in lines.enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            let invalid = |reason: String| format!("invalid line {}: {}", n + 2, reason);
+            let key = |hex: &str| u64::from_str_radix(hex, 16).map_err(|e| invalid(e.to_string()));
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["node", k, ref stats @ ..] => {
+                    let stats = Stats::from_fields(stats).map_err(invalid)?;
+                    tree.nodes.entry(key(k)?).or_default().stats = stats;
+                }
+                ["move", k, san, uci, child, ref stats @ ..] => {
+                    let child = MoveStats {
+                        san: san.to_string(),
+                        uci: uci.to_string(),
+                        key: key(child)?,
+                        stats: Stats::from_fields(stats).map_err(invalid)?,
+                    };
+                    tree.nodes.entry(key(k)?).or_default().children.push(child);
+                }
+                [] => {}
+                _ => return Err(invalid("unknown record".to_string())),
+            }
+        }
+
+        Ok(tree)
+    }
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[allow(dead_code)]
+fn sample_tree() -> PositionTree {
+    let mut tree = PositionTree::new();
+    tree.add_game(&["e4", "e5", "Nf3", "1-0"], Some(GameResult::WhiteWins))
+        .unwrap();
+    tree.add_game(&["e4", "c5", "Nf3"], Some(GameResult::BlackWins))
+        .unwrap();
+    tree.add_game(&["d4", "d5"], Some(GameResult::Draw))
+        .unwrap();
+    tree.add_game(&["e4!", "e5"], None).unwrap();
+    tree
+}
+
+#[test]
+fn check_position_tree() {
+    let tree = sample_tree();
+    let root = Game::new();
+
+    assert_eq!(
+        tree.stats(&root),
+        Some(Stats {
+            games: 4,
+            white_wins: 1,
+            draws: 1,
+            black_wins: 1
+        })
+    );
+    let children = tree.children(&root);
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].san, "e4");
+    assert_eq!(children[0].uci, "e2e4");
+    assert_eq!(children[0].stats.games, 3);
+    assert_eq!(children[1].san, "d4");
+    assert_eq!(children[1].stats.draws, 1);
+
+    let mut game = Game::new();
+    game.play_move("e4").unwrap();
+    let children = tree.children(&game);
+    assert_eq!(children[0].san, "e5");
+    assert_eq!(children[0].stats.games, 2);
+    assert_eq!(
+        tree.children(&Game::from_str("8/8/8/8/8/8/8/K6k w - - 0 1").unwrap()),
+        vec![]
+    );
+}
+
+#[test]
+fn check_position_tree_merges_transpositions() {
+    let mut tree = PositionTree::with_max_plies(4);
+    tree.add_game(&["Nf3", "Nf6", "d4", "d5", "c4"], None)
+        .unwrap();
+    tree.add_game(&["d4", "d5", "Nf3", "Nf6", "c4"], None)
+        .unwrap();
+
+    let game = Game::from_str("rnbqkb1r/ppp1pppp/5n2/3p4/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq - 2 3");
+    assert_eq!(tree.stats(&game.unwrap()).unwrap().games, 2);
+    // c4 is beyond the maximal number of plies.
+    assert_eq!(tree.len(), 8);
+}
+
+#[test]
+fn check_position_tree_counts_repetitions_once() {
+    let mut tree = PositionTree::new();
+    let moves = ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8", "e4"];
+    tree.add_game(&moves, Some(GameResult::Draw)).unwrap();
+    tree.add_game(&["Nf3", "d5"], Some(GameResult::WhiteWins))
+        .unwrap();
+
+    let root = Game::new();
+    assert_eq!(
+        tree.stats(&root),
+        Some(Stats {
+            games: 2,
+            white_wins: 1,
+            draws: 1,
+            black_wins: 0
+        })
+    );
+    let children = tree.children(&root);
+    assert_eq!(children.len(), 2);
+    assert_eq!(
+        (children[0].san.as_str(), children[0].stats.games),
+        ("Nf3", 2)
+    );
+    assert_eq!(
+        (children[1].san.as_str(), children[1].stats.games),
+        ("e4", 1)
+    );
+
+    let mut game = Game::new();
+    game.play_move("Nf3").unwrap();
+    assert_eq!(tree.stats(&game).unwrap().games, 2);
+    let children = tree.children(&game);
+    assert_eq!(
+        (children[0].san.as_str(), children[0].stats.draws),
+        ("Nf6", 1)
+    );
+}
+
+#[test]
+fn check_position_tree_save_and_load() {
+    let tree = sample_tree();
+    let path = std::env::temp_dir().join("fency_check_position_tree.txt");
+    let path = path.to_str().unwrap();
+    tree.save(path).unwrap();
+    let loaded = PositionTree::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(loaded.len(), tree.len());
+    let root = Game::new();
+    assert_eq!(loaded.stats(&root), tree.stats(&root));
+    assert_eq!(loaded.children(&root), tree.children(&root));
+    assert!(PositionTree::load("/nonexistent/tree.txt").is_err());
+}
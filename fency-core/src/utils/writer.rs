@@ -0,0 +1,337 @@
+use crate::utils::color::Color;
+use crate::utils::game::{at_ply, Game};
+use crate::utils::pgn::PgnGame;
+use crate::utils::replay::replay_plies;
+
+/// Maximum length of movetext lines, as recommended by the PGN standard.
+const LINE_WIDTH: usize = 80;
+
+/// Check or checkmate marker at the end of a SAN.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CheckMarker {
+    None,
+    Check,
+    Checkmate,
+}
+
+impl CheckMarker {
+    /// The marker as written in the given SAN, ignoring annotations like "!?".
+    pub fn of(san: &str) -> Self {
+        if san.contains('#') {
+            CheckMarker::Checkmate
+        } else if san.contains('+') {
+            CheckMarker::Check
+        } else {
+            CheckMarker::None
+        }
+    }
+}
+
+/// A move whose check marker does not match the position, e.g. "Qh5" that actually gives check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarkerMismatch {
+    pub ply: usize,
+    pub san: String,
+    pub corrected: String,
+    pub claimed: CheckMarker,
+    pub actual: CheckMarker,
+}
+
+/// Play a move and return its SAN as derived from the position, keeping annotations like "!?".
+pub(crate) fn play_corrected(game: &mut Game, mv: &str) -> Result<String, String> {
+    let uci = game.san_to_uci(mv)?;
+    let san = game.uci_to_san(&uci)?;
+    game.play_uci(&uci)?;
+
+    Ok(with_glyphs(san, mv))
+}
+
+/// The SAN followed by the annotations like "!?" of the move as given.
+fn with_glyphs(san: String, mv: &str) -> String {
+    san + &mv[mv.trim_end_matches(['!', '?']).len()..]
+}
+
+/// Replay the moves and re-emit them as SAN derived from the position, i.e. with the true check
+/// and checkmate markers and minimal disambiguation. Annotations like "!?" are kept. Result
+/// tokens are skipped. Mismatches between the claimed and the actual markers are reported.
+pub fn correct_sans(moves: &[&str]) -> Result<(Vec<String>, Vec<MarkerMismatch>), String> {
+    correct_sans_from(Game::new(), moves)
+}
+
+/// Correct the moves like `correct_sans`, but replay from the given position.
+fn correct_sans_from(
+    game: Game,
+    moves: &[&str],
+) -> Result<(Vec<String>, Vec<MarkerMismatch>), String> {
+    let mut sans = Vec::with_capacity(moves.len());
+    let mut mismatches = Vec::new();
+    let mut plies = replay_plies(game, moves);
+    while let Some((ply, &mv, before, after)) = plies.next_pair()? {
+        let san = before.uci_to_san(&after.uci).map_err(at_ply(ply))?;
+        let corrected = with_glyphs(san, mv);
+        let (claimed, actual) = (CheckMarker::of(mv), CheckMarker::of(&corrected));
+        if claimed != actual {
+            mismatches.push(MarkerMismatch {
+                ply,
+                san: mv.to_string(),
+                corrected: corrected.clone(),
+                claimed,
+                actual,
+            });
+        }
+        sans.push(corrected);
+    }
+
+    Ok((sans, mismatches))
+}
+
+/// Annotations of a ply to be written after its move, e.g. the results of an engine analysis.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PlyAnnotation {
+    /// Numeric annotation glyphs, written as "$n", e.g. 2 for a mistake or 14 for a slight
+    /// advantage of white.
+    pub nags: Vec<u8>,
+
+    /// Text of a brace comment, e.g. "[%eval 0.35] The main line.". Line breaks are written as
+    /// spaces.
+    pub comment: Option<String>,
+}
+
+/// A move in display form with its move number, e.g. "12. Nf3" for white and "12... Nf6" for
+/// black, where the number is the full-move clock before the move.
+pub fn numbered_san(full_move_clock: u16, color: Color, san: &str) -> String {
+    match color {
+        Color::W => format!("{}. {}", full_move_clock, san),
+        Color::B => format!("{}... {}", full_move_clock, san),
+    }
+}
+
+/// Escape a tag value, in which quotes and backslashes must be preceded by a backslash.
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Write a game as PGN with corrected SAN, wrapping the movetext at 80 characters. The result
+/// is taken from the Result tag, the termination marker or "*" otherwise. Games set up from a FEN
+/// continue its move numbers, e.g. "40... Kd7 41. Rd1+".
+pub fn write_pgn(game: &PgnGame) -> Result<String, String> {
+    write_annotated_pgn(game, &[])
+}
+
+/// Write a game as PGN like `write_pgn`, with the NAGs and comments of the plies after their
+/// moves, e.g. "4. Qxf7# $1 {Scholar's mate}". A black move after a comment repeats its move
+/// number, as the PGN standard demands. There may be fewer annotations than moves, but not more,
+/// and comments must not contain "}", as brace comments cannot be nested.
+pub fn write_annotated_pgn(
+    game: &PgnGame,
+    annotations: &[PlyAnnotation],
+) -> Result<String, String> {
+    let start = game.start_position()?;
+    let first_move = start.full_move_clock as usize;
+    let black_first = usize::from(start.color.is_black());
+    let (sans, _) = correct_sans_from(start, &game.moves())?;
+    if annotations.len() > sans.len() {
+        return Err(format!(
+            "{} annotations for {} moves",
+            annotations.len(),
+            sans.len()
+        ));
+    }
+    if let Some(i) = annotations
+        .iter()
+        .position(|a| a.comment.as_ref().is_some_and(|c| c.contains('}')))
+    {
+        return Err(at_ply(i + 1)("comment contains \"}\"".to_string()));
+    }
+    let result = game
+        .header("Result")
+        .or(game.result.as_deref())
+        .unwrap_or("*");
+
+    let mut pgn = String::new();
+    write_tags(&mut pgn, &game.headers);
+
+    let mut tokens = Vec::with_capacity(sans.len() * 3 / 2 + 1);
+    let mut is_commented = false;
+    for (i, san) in sans.into_iter().enumerate() {
+        let half = i + black_first;
+        if half % 2 == 0 {
+            tokens.push(format!("{}.", first_move + half / 2));
+        } else if i == 0 || is_commented {
+            tokens.push(format!("{}...", first_move + half / 2));
+        }
+        tokens.push(san);
+
+        let annotation = annotations.get(i).cloned().unwrap_or_default();
+        tokens.extend(annotation.nags.iter().map(|nag| format!("${}", nag)));
+        is_commented = annotation.comment.is_some();
+        if let Some(comment) = annotation.comment {
+            // Split the comment into words, so that long comments are wrapped as well.
+            let words: Vec<&str> = comment.split_whitespace().collect();
+            match words.as_slice() {
+                [] => tokens.push("{}".to_string()),
+                [word] => tokens.push(format!("{{{}}}", word)),
+                [first, middle @ .., last] => {
+                    tokens.push(format!("{{{}", first));
+                    tokens.extend(middle.iter().map(|w| w.to_string()));
+                    tokens.push(format!("{}}}", last));
+                }
+            }
+        }
+    }
+    tokens.push(result.to_string());
+    write_movetext(&mut pgn, tokens);
+
+    Ok(pgn)
+}
+
+/// Write the tags of a game, followed by the empty line that separates them from the movetext.
+pub(crate) fn write_tags(pgn: &mut String, headers: &[(String, String)]) {
+    for (key, value) in headers {
+        pgn.push_str(&format!("[{} \"{}\"]\n", key, escape_tag(value)));
+    }
+    if !headers.is_empty() {
+        pgn.push('\n');
+    }
+}
+
+/// Join the movetext tokens by spaces and wrap the lines at 80 characters.
+pub(crate) fn write_movetext(pgn: &mut String, tokens: Vec<String>) {
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && (line.len() + 1 + token.len() > LINE_WIDTH) {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_corrected_markers() {
+    let (sans, mismatches) =
+        correct_sans(&["e4", "e5", "Bc4", "Nc6", "Qh5+", "Nf6?", "Qxf7+!", "1-0"]).unwrap();
+
+    assert_eq!(
+        sans,
+        vec!["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6?", "Qxf7#!"]
+    );
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(mismatches[0].ply, 5);
+    assert_eq!(mismatches[0].claimed, CheckMarker::Check);
+    assert_eq!(mismatches[0].actual, CheckMarker::None);
+    assert_eq!(mismatches[1].san, "Qxf7+!");
+    assert_eq!(mismatches[1].corrected, "Qxf7#!");
+    assert_eq!(mismatches[1].actual, CheckMarker::Checkmate);
+}
+
+#[test]
+fn check_missing_check_marker() {
+    let (sans, mismatches) = correct_sans(&["e4", "f5", "Qh5"]).unwrap();
+    assert_eq!(sans[2], "Qh5+");
+    assert_eq!(mismatches[0].claimed, CheckMarker::None);
+    assert!(correct_sans(&["e4", "Ke7"]).is_err());
+}
+
+#[test]
+fn check_write_pgn() {
+    let game = PgnGame {
+        headers: vec![
+            ("Event".to_string(), "Say \"hi\"".to_string()),
+            ("Result".to_string(), "1-0".to_string()),
+        ],
+        moves: ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7"]
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+        ..PgnGame::default()
+    };
+    assert_eq!(
+        write_pgn(&game).unwrap(),
+        "[Event \"Say \\\"hi\\\"\"]\n[Result \"1-0\"]\n\n\
+         1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+    );
+
+    // Long movetext is wrapped.
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"].repeat(10);
+    let game = PgnGame {
+        moves: shuffle.iter().map(|m| m.to_string()).collect(),
+        ..PgnGame::default()
+    };
+    let pgn = write_pgn(&game).unwrap();
+    assert!(pgn.lines().all(|line| line.len() <= LINE_WIDTH));
+    assert!(pgn.ends_with(" Ng8 *\n"));
+
+    // Games set up from a FEN continue its move numbers.
+    let game = PgnGame {
+        headers: vec![
+            ("SetUp".to_string(), "1".to_string()),
+            (
+                "FEN".to_string(),
+                "4k3/8/8/8/8/8/8/R3K3 b Q - 0 40".to_string(),
+            ),
+        ],
+        moves: vec!["Kd7".to_string(), "O-O-O".to_string()],
+        ..PgnGame::default()
+    };
+    assert!(write_pgn(&game)
+        .unwrap()
+        .ends_with("\n\n40... Kd7 41. O-O-O+ *\n"));
+}
+
+#[test]
+fn check_numbered_san() {
+    assert_eq!(numbered_san(12, Color::W, "Nf3"), "12. Nf3");
+    assert_eq!(numbered_san(12, Color::B, "Nf6"), "12... Nf6");
+}
+
+#[test]
+fn check_write_annotated_pgn() {
+    use crate::utils::pgn::parse_pgn;
+
+    let game = PgnGame {
+        moves: ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7"]
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+        ..PgnGame::default()
+    };
+    let comment = |c: &str| PlyAnnotation {
+        comment: Some(c.to_string()),
+        ..PlyAnnotation::default()
+    };
+    let mut annotations = vec![PlyAnnotation::default(); 7];
+    annotations[0] = comment("[%eval 0.3]");
+    annotations[5] = PlyAnnotation {
+        nags: vec![4],
+        comment: Some("Allows\nmate.".to_string()),
+    };
+    annotations[6].nags = vec![1, 18];
+    let pgn = write_annotated_pgn(&game, &annotations).unwrap();
+    assert_eq!(
+        pgn,
+        "1. e4 {[%eval 0.3]} 1... e5 2. Bc4 Nc6 3. Qh5 Nf6 $4 {Allows mate.} 4. Qxf7# $1\n\
+         $18 *\n"
+    );
+    // The NAGs and comments are skipped when read back, except for the evals.
+    let parsed = &parse_pgn(&pgn)[0];
+    assert_eq!(parsed.moves[..6], game.moves[..6]);
+    assert_eq!(parsed.evals.len(), 7);
+
+    // Long comments are wrapped, while too many annotations and nested braces are rejected.
+    let long = vec![comment(&"word ".repeat(40)); 2];
+    let pgn = write_annotated_pgn(&game, &long).unwrap();
+    assert!(pgn.lines().all(|line| line.len() <= LINE_WIDTH));
+    assert!(write_annotated_pgn(&game, &vec![PlyAnnotation::default(); 8]).is_err());
+    assert_eq!(
+        write_annotated_pgn(&game, &[comment("a"), comment("}")]).unwrap_err(),
+        "ply 2: comment contains \"}\""
+    );
+}
@@ -0,0 +1,143 @@
+use crate::utils::color::Color;
+use crate::utils::game::Game;
+use std::sync::OnceLock;
+
+// Offsets into the table of random keys: 12 figures on 64 squares, the side to move, four
+// castling rights and eight en passant files.
+const SIDE_TO_MOVE: usize = 12 * 64;
+const CASTLING: usize = SIDE_TO_MOVE + 1;
+const EN_PASSANT: usize = CASTLING + 4;
+const N_KEYS: usize = EN_PASSANT + 8;
+
+/// Fixed seed, so hashes are stable across runs and can be persisted.
+const SEED: u64 = 0x5eed_f3c1_2b4d_9a07;
+
+/// Random keys generated by SplitMix64, which is good enough for hashing positions.
+fn keys() -> &'static [u64; N_KEYS] {
+    static KEYS: OnceLock<[u64; N_KEYS]> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let mut state = SEED;
+        [0; N_KEYS].map(|_| {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        })
+    })
+}
+
+/// Zobrist hash of a position, i.e. of everything in the FEN except for the move clocks. Equal
+/// positions thus have equal hashes, no matter by which move order they were reached. Like
+/// `reduced_fen`, only castling rights that king and rook can still use and En-Passant squares
+/// with a legal capture count, thus FENs that set these fields liberally hash alike.
+pub fn zobrist_hash(game: &Game) -> u64 {
+    let keys = keys();
+    let mut hash = 0;
+    for fig in game.position.iter().flatten() {
+        hash ^= keys[fig.index() * 64 + fig.square()];
+    }
+    if game.color == Color::B {
+        hash ^= keys[SIDE_TO_MOVE];
+    }
+    let castling = game.possible_castling();
+    for (i, &right) in [
+        castling.white_kingside,
+        castling.white_queenside,
+        castling.black_kingside,
+        castling.black_queenside,
+    ]
+    .iter()
+    .enumerate()
+    {
+        if right {
+            hash ^= keys[CASTLING + i];
+        }
+    }
+    if let Some(ep) = game.en_passant.filter(|_| game.is_en_passant_capturable()) {
+        hash ^= keys[EN_PASSANT + ep.x as usize];
+    }
+
+    hash
+}
+
+/// The FEN without the move clocks, which identifies a position just like its Zobrist hash. It is
+/// normalized like the keys of opening books, see `Game::normalized_fen`.
+pub fn reduced_fen(game: &Game) -> String {
+    game.normalized_fen()
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_zobrist_hash_of_transposition() {
+    let mut game_a = Game::new();
+    let mut game_b = Game::new();
+    for mv in ["Nf3", "Nf6", "d4", "d5"] {
+        game_a.play_move(mv).unwrap();
+    }
+    for mv in ["d4", "d5", "Nf3", "Nf6"] {
+        game_b.play_move(mv).unwrap();
+    }
+    assert_eq!(zobrist_hash(&game_a), zobrist_hash(&game_b));
+    assert_eq!(reduced_fen(&game_a), reduced_fen(&game_b));
+    assert_ne!(zobrist_hash(&game_a), zobrist_hash(&Game::new()));
+}
+
+#[test]
+fn check_zobrist_hash_covers_side_and_rights() {
+    let mut game = Game::new();
+    let initial = zobrist_hash(&game);
+    // The knights return, but the side to move is the same only after both sides moved twice.
+    for mv in ["Nf3", "Nf6", "Ng1"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_ne!(zobrist_hash(&game), initial);
+    game.play_move("Ng8").unwrap();
+    assert_eq!(zobrist_hash(&game), initial);
+
+    // Moving the rook there and back loses the castling right.
+    for mv in ["Nf3", "Nf6", "Rg1", "Ng8", "Rh1", "Nf6", "Ng1", "Ng8"] {
+        game.play_move(mv).unwrap();
+    }
+    assert_eq!(
+        reduced_fen(&game),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Qkq -"
+    );
+    assert_ne!(zobrist_hash(&game), initial);
+}
+
+#[test]
+fn check_zobrist_hash_matches_reduced_fen() {
+    use std::str::FromStr;
+
+    // Rights without rook or king and En-Passant squares without a capturing pawn are dropped.
+    let fens = [
+        "4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K2R w KQkq - 0 1",
+        "4k3/8/8/8/8/8/8/4K2R w K - 3 9",
+        "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+        "4k3/8/8/8/4Pp2/8/8/4K3 b - e3 0 1",
+        "4k3/8/8/8/4Pp2/8/8/4K3 b - - 0 1",
+        "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1",
+        "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1",
+        "4k3/8/8/8/4P3/8/8/4K3 w - - 0 1",
+    ];
+    let games: Vec<Game> = fens.iter().map(|f| Game::from_str(f).unwrap()).collect();
+    for a in &games {
+        for b in &games {
+            assert_eq!(
+                zobrist_hash(a) == zobrist_hash(b),
+                reduced_fen(a) == reduced_fen(b),
+                "{} vs {}",
+                reduced_fen(a),
+                reduced_fen(b)
+            );
+        }
+    }
+    assert_eq!(zobrist_hash(&games[0]), zobrist_hash(&games[1]));
+    assert_eq!(zobrist_hash(&games[2]), zobrist_hash(&games[3]));
+    assert_ne!(zobrist_hash(&games[3]), zobrist_hash(&games[4]));
+    assert_ne!(zobrist_hash(&games[5]), zobrist_hash(&games[6]));
+    assert_eq!(zobrist_hash(&games[7]), zobrist_hash(&games[8]));
+}
@@ -0,0 +1,120 @@
+use fency_core::utils::diff;
+use fency_core::utils::figure::Figure;
+use fency_core::utils::game::{at_ply, Game};
+use fency_core::utils::json::json_str;
+use fency_core::utils::outcome::is_result_token;
+use fency_core::utils::pgn;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// Replay SAN moves from the starting position and return the FEN after every ply.
+#[wasm_bindgen]
+pub fn fentasize(moves: Vec<String>) -> Result<Vec<String>, JsError> {
+    replay_fens(Game::new(), &moves).map_err(|e| JsError::new(&e))
+}
+
+/// Replay SAN moves and collect the FEN after every ply, where result tokens like "1-0" are no
+/// plies and thus skipped.
+fn replay_fens(mut game: Game, moves: &[String]) -> Result<Vec<String>, String> {
+    moves
+        .iter()
+        .filter(|mv| !is_result_token(mv))
+        .enumerate()
+        .map(|(i, mv)| {
+            game.play_move(mv).map_err(at_ply(i + 1))?;
+            Ok(game.clone().to_fen())
+        })
+        .collect()
+}
+
+/// List the legal moves of the side to move in the given FEN, in UCI notation.
+#[wasm_bindgen(js_name = legalMoves)]
+pub fn legal_moves(fen: &str) -> Result<Vec<String>, JsError> {
+    let game = Game::from_str(fen).map_err(|e| JsError::new(e.message()))?;
+    Ok(game.legal_moves())
+}
+
+/// Parse all games of a PGN string. Returns a JSON array (to be used with `JSON.parse`) holding
+/// per game an object with `headers`, `moves`, `result` and the `fens` after every ply, starting
+/// from the position of the FEN tag if there is one.
+#[wasm_bindgen(js_name = parsePgn)]
+pub fn parse_pgn(pgn: &str) -> Result<String, JsError> {
+    let games: Vec<String> = pgn::parse_pgn(pgn)
+        .iter()
+        .map(|game| {
+            let headers: Vec<String> = game
+                .headers
+                .iter()
+                .map(|(k, v)| format!("{}:{}", json_str(k), json_str(v)))
+                .collect();
+            let moves: Vec<String> = game.moves.iter().map(|m| json_str(m)).collect();
+            let fens: Vec<String> = replay_fens(game.start_position()?, &game.moves)?
+                .iter()
+                .map(|f| json_str(f))
+                .collect();
+            let result = game.result.as_deref().map_or("null".to_string(), json_str);
+
+            Ok(format!(
+                "{{\"headers\":{{{}}},\"moves\":[{}],\"result\":{},\"fens\":[{}]}}",
+                headers.join(","),
+                moves.join(","),
+                result,
+                fens.join(",")
+            ))
+        })
+        .collect::<Result<_, String>>()
+        .map_err(|e| JsError::new(&e))?;
+
+    Ok(format!("[{}]", games.join(",")))
+}
+
+/// Replay SAN moves and return the changed squares of every ply as a JSON array (to be used with
+/// `JSON.parse`) holding per ply an array of `{"square", "before", "after"}` objects, where
+/// figures are given as FEN letters (e.g. "N") or null for empty squares.
+#[wasm_bindgen(js_name = plyDiffs)]
+pub fn ply_diffs(moves: Vec<String>) -> Result<String, JsError> {
+    let moves: Vec<&str> = moves.iter().map(|m| m.as_str()).collect();
+    let figure = |fig: Option<Figure>| {
+        fig.map_or("null".to_string(), |f| json_str(&f.to_char().to_string()))
+    };
+    let plies: Vec<String> = diff::ply_diffs(&moves)
+        .map_err(|e| JsError::new(&e))?
+        .iter()
+        .map(|ply| {
+            let changes: Vec<String> = ply
+                .changes
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{{\"square\":{},\"before\":{},\"after\":{}}}",
+                        json_str(&c.square.to_string()),
+                        figure(c.before),
+                        figure(c.after)
+                    )
+                })
+                .collect();
+            format!("[{}]", changes.join(","))
+        })
+        .collect();
+
+    Ok(format!("[{}]", plies.join(",")))
+}
+
+//- - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
+#[test]
+fn check_fentasize_skips_result_tokens() {
+    let moves = ["e4", "e5", "1-0"].map(String::from).to_vec();
+    let fens = fentasize(moves).unwrap();
+    assert_eq!(fens.len(), 2);
+    assert_eq!(
+        fens[1],
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+
+    // Plies are counted without the result token.
+    let moves = ["1/2-1/2", "e4", "e4"].map(String::from).to_vec();
+    assert_eq!(
+        replay_fens(Game::new(), &moves),
+        Err("ply 2: illegal move: e4".to_string())
+    );
+}
@@ -0,0 +1,15 @@
+#![no_main]
+use fency_core::utils::game::Game;
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+// Parse arbitrary input as FEN, which must survive the round trip through its own output, and
+// generate the moves of every position parsed, which must not panic on odd boards.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(fen) = std::str::from_utf8(data) {
+        if let Ok(game) = Game::from_str(fen) {
+            let _ = game.legal_moves();
+            assert_eq!(Game::from_str(&game.clone().to_fen()), Ok(game));
+        }
+    }
+});
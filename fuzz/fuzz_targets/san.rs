@@ -0,0 +1,18 @@
+#![no_main]
+use fency_core::utils::draw::SanMove;
+use fency_core::utils::game::Game;
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+// Parse arbitrary input as SAN and play whitespace separated tokens from the initial position.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(moves) = std::str::from_utf8(data) {
+        let _ = SanMove::from_str(moves);
+        let mut game = Game::new();
+        for mv in moves.split_whitespace() {
+            if game.play_move(mv).is_err() {
+                break;
+            }
+        }
+    }
+});
@@ -1,23 +1,2332 @@
-pub mod utils;
+//! Python bindings of fency-core. The core types are re-exported, such that Rust code can depend
+//! on this crate as well, see the `fency_core` crate documentation for examples.
 
-use crate::utils::game::Game;
+// The PyO3 0.19 macros define trait impls within const blocks, which recent compilers lint.
+#![allow(non_local_definitions)]
+
+use fency_core::utils::batch::{BatchContext, FenBuffer};
+use fency_core::utils::blunders::{self, BlunderCriteria};
+use fency_core::utils::book::OpeningBook;
+use fency_core::utils::cache::PositionCache;
+use fency_core::utils::compression;
+use fency_core::utils::conversion;
+use fency_core::utils::coord::{Coord, FromIndex};
+use fency_core::utils::diff;
+use fency_core::utils::draw::SanMove;
+use fency_core::utils::endgame;
+use fency_core::utils::engine::{Engine, EngineConfig, SearchLimit};
+use fency_core::utils::evaluation;
+use fency_core::utils::explorer::{self, OpeningKey};
+use fency_core::utils::export;
+use fency_core::utils::fentasize::{split_games, FenRecord, FentasizeOptions};
+use fency_core::utils::figure::Figure;
+use fency_core::utils::filter::GameFilter;
+use fency_core::utils::game::at_ply;
+use fency_core::utils::king_safety::KingSafety;
+use fency_core::utils::labels;
+use fency_core::utils::lichess;
+use fency_core::utils::material::{self, MaterialEvaluator};
+use fency_core::utils::motifs;
+use fency_core::utils::notation::NotationConfig;
+use fency_core::utils::occupancy::{self, Occupancy};
+use fency_core::utils::outcome::{self, ClaimPolicy};
+use fency_core::utils::pgn;
+use fency_core::utils::phase::PhaseScheme;
+use fency_core::utils::progress::{CancellationToken, Progress, ProgressReporter};
+use fency_core::utils::puzzle::{Puzzle, PuzzleReader};
+use fency_core::utils::recovery::LenientReader;
+use fency_core::utils::render;
+use fency_core::utils::report::GameReport;
+use fency_core::utils::sampling::{self, PlySelection};
+use fency_core::utils::stats::{self, EloStats};
+use fency_core::utils::tablebase::{self, Tablebase};
+use fency_core::utils::transposition;
+use fency_core::utils::tree::{self, PositionTree};
+use fency_core::utils::writer::{self, PlyAnnotation};
+pub use fency_core::{Castling, Color, Error, Game, Move, Piece, Square};
+use numpy::{PyArray1, PyArray3};
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyKeyboardInterrupt, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyIterator};
 use pyo3::wrap_pyfunction;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+mod logging;
+mod source;
+
+use source::PgnSource;
 
 #[pymodule]
 fn fency_pgn(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(fentasize, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_games, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_uci, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_from_fen, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_san, m)?)?;
+    m.add_function(wrap_pyfunction!(final_fen, m)?)?;
+    m.add_function(wrap_pyfunction!(final_fen_from_fen, m)?)?;
+    m.add_function(wrap_pyfunction!(summarize, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_records, m)?)?;
+    m.add_function(wrap_pyfunction!(san_to_uci, m)?)?;
+    m.add_function(wrap_pyfunction!(uci_to_san, m)?)?;
+    m.add_function(wrap_pyfunction!(to_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(fentasize_ndjson, m)?)?;
+    m.add_function(wrap_pyfunction!(lichess_tags, m)?)?;
+    m.add_function(wrap_pyfunction!(check_terminations, m)?)?;
+    m.add_function(wrap_pyfunction!(check_result, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_result, m)?)?;
+    m.add_function(wrap_pyfunction!(render_game, m)?)?;
+    m.add_function(wrap_pyfunction!(ply_diffs, m)?)?;
+    m.add_function(wrap_pyfunction!(material_balances, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluations, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_motifs, m)?)?;
+    m.add_function(wrap_pyfunction!(find_transpositions, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_pgns, m)?)?;
+    m.add_function(wrap_pyfunction!(piece_heatmaps, m)?)?;
+    m.add_function(wrap_pyfunction!(logging::enable_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(read_pgn, m)?)?;
+    m.add_function(wrap_pyfunction!(read_pgn_lenient, m)?)?;
+    m.add_function(wrap_pyfunction!(report_pgn, m)?)?;
+    m.add_function(wrap_pyfunction!(time_usage, m)?)?;
+    m.add_function(wrap_pyfunction!(clock_readings, m)?)?;
+    m.add_function(wrap_pyfunction!(elo_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(opening_report, m)?)?;
+    m.add_function(wrap_pyfunction!(find_blunders, m)?)?;
+    m.add_function(wrap_pyfunction!(find_material_blunders, m)?)?;
+    m.add_function(wrap_pyfunction!(analyse_game, m)?)?;
+    m.add_function(wrap_pyfunction!(label_endgames, m)?)?;
+    m.add_function(wrap_pyfunction!(positions_of_class, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_puzzle, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_puzzles, m)?)?;
+    m.add_function(wrap_pyfunction!(sample_positions, m)?)?;
+    m.add_function(wrap_pyfunction!(positions_with_outcome, m)?)?;
+    m.add_function(wrap_pyfunction!(policy_pairs, m)?)?;
+    m.add_function(wrap_pyfunction!(correct_sans, m)?)?;
+    m.add_function(wrap_pyfunction!(write_pgn, m)?)?;
+    m.add_function(wrap_pyfunction!(position_cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_position_cache, m)?)?;
+    m.add_class::<PyGame>()?;
+    m.add_class::<PyFentasizeOptions>()?;
+    m.add_class::<PyPhaseScheme>()?;
+    m.add_class::<PyMaterialEvaluator>()?;
+    m.add_class::<PyPositionTree>()?;
+    m.add_class::<PyMove>()?;
+    m.add_class::<PySquare>()?;
+    m.add_class::<PyPiece>()?;
+    m.add_class::<PyColor>()?;
+    m.add_class::<PyPosition>()?;
+    m.add_class::<PositionIterator>()?;
     Ok(())
 }
 
+/// A game that can be replayed move by move, e.g. to inspect positions while debugging.
+#[pyclass(name = "Game")]
+struct PyGame {
+    game: Game,
+}
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    #[pyo3(signature = (fen=None))]
+    fn new(fen: Option<&str>) -> PyResult<Self> {
+        let game = match fen {
+            None => Game::new(),
+            Some(fen) => Game::from_str(fen).map_err(value_error)?,
+        };
+        Ok(PyGame { game })
+    }
+
+    /// Play a move in SAN. In lenient mode, castling is not checked for rights and attacks.
+    #[pyo3(signature = (mv, lenient=false))]
+    fn play_move(&mut self, mv: &str, lenient: bool) -> PyResult<()> {
+        let played = if lenient {
+            self.game.play_move_lenient(mv)
+        } else {
+            self.game.play_move(mv)
+        };
+        played.map_err(value_error)
+    }
+
+    /// Play moves in SAN in one call. The first failing move raises a ValueError with its ply,
+    /// SAN and the FEN before it, and leaves the game at that position.
+    fn play_moves(&mut self, moves: Vec<&str>) -> PyResult<()> {
+        self.game.play_moves(&moves).map_err(|e| {
+            PyValueError::new_err(format!(
+                "ply {} ({}): {} at {}",
+                e.ply, e.san, e.reason, e.fen
+            ))
+        })
+    }
+
+    fn play_uci(&mut self, uci: &str) -> PyResult<()> {
+        self.game.play_uci(uci).map_err(value_error)
+    }
+
+    fn fen(&self) -> String {
+        self.game.clone().to_fen()
+    }
+
+    #[getter]
+    fn turn(&self) -> PyColor {
+        PyColor::from(self.game.color)
+    }
+
+    /// The move number of the FEN, which starts at 1 and grows after every move of black.
+    #[getter]
+    fn full_move_number(&self) -> u16 {
+        self.game.full_move_clock
+    }
+
+    /// Plies since the last capture or pawn move, as counted for the fifty-move rule.
+    #[getter]
+    fn half_move_clock(&self) -> u16 {
+        self.game.half_move_clock
+    }
+
+    /// Plies since the initial position of a standard game as implied by the move number and the
+    /// side to move, e.g. 3 after "1. e4 e5 2. Nf3", also for games set up from a FEN.
+    #[getter]
+    fn ply(&self) -> usize {
+        self.game.ply()
+    }
+
+    /// The castling rights of the FEN as (white_kingside, white_queenside, black_kingside,
+    /// black_queenside). A right does not imply that castling is legal right now.
+    #[getter]
+    fn castling_rights(&self) -> (bool, bool, bool, bool) {
+        let castling = &self.game.castling;
+        (
+            castling.white_kingside,
+            castling.white_queenside,
+            castling.black_kingside,
+            castling.black_queenside,
+        )
+    }
+
+    /// The square to which a pawn can capture En-Passant, if any.
+    #[getter]
+    fn en_passant(&self) -> Option<PySquare> {
+        self.game.en_passant.map(|coord| PySquare { coord })
+    }
+
+    /// The FEN without clocks and with legal castling and En-Passant fields only, as used as key
+    /// by opening books and the Lichess opening explorer.
+    fn normalized_fen(&self) -> String {
+        self.game.normalized_fen()
+    }
+
+    /// Compare positions with == and !=, ignoring the move clocks and the last move.
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python<'_>) -> PyObject {
+        match op {
+            CompareOp::Eq => self.game.eq_position(&other.game).into_py(py),
+            CompareOp::Ne => (!self.game.eq_position(&other.game)).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    /// The squares whose content differs in the other game as (square, before, after), where
+    /// figures are given as FEN letters (e.g. "N") or None for empty squares.
+    fn diff(&self, other: &Self) -> Vec<SquareChange> {
+        square_changes(&self.game.diff(&other.game))
+    }
+
+    /// "ongoing", "checkmate", "stalemate" or the draw rule that ends the game under the claim
+    /// policy, e.g. "fivefold_repetition". Under "otb" (over the board) only automatic draws end
+    /// it, under "online" claimable ones like "threefold_repetition" as well.
+    #[pyo3(signature = (claim_policy="otb"))]
+    fn status(&self, claim_policy: &str) -> PyResult<String> {
+        let policy = ClaimPolicy::from_str(claim_policy).map_err(PyValueError::new_err)?;
+        Ok(self.game.status_with(policy).to_string())
+    }
+
+    /// The rule by which the position is drawn, e.g. "threefold_repetition", or None. Automatic
+    /// draws take precedence over claimable ones.
+    fn draw_rule(&self) -> Option<String> {
+        self.game.draw_rule().map(|rule| rule.to_string())
+    }
+
+    /// Legal moves in UCI notation. With cached=True, they are looked up in the process-wide
+    /// position cache, which pays off for datasets with many shared positions, see
+    /// position_cache_stats.
+    #[pyo3(signature = (cached=false))]
+    fn legal_moves(&self, cached: bool) -> Vec<String> {
+        if cached {
+            self.game.cached_info().legal_moves.clone()
+        } else {
+            self.game.legal_moves()
+        }
+    }
+
+    /// The UCI command that sets up the game including its moves, e.g. "position startpos moves
+    /// e2e4", such that engines can detect repetitions.
+    fn uci_position(&self) -> String {
+        self.game.uci_position()
+    }
+
+    /// The theoretical outcome for the side to move according to the Syzygy tables in the given
+    /// directories, e.g. "win" or "blessed_loss". None if the tables do not cover the position.
+    fn tablebase_wdl(&self, tablebase_dirs: Vec<&str>) -> PyResult<Option<String>> {
+        let tablebase = Tablebase::open(&tablebase_dirs).map_err(PyValueError::new_err)?;
+        Ok(self
+            .game
+            .tablebase_wdl(&tablebase)
+            .map_err(PyValueError::new_err)?
+            .map(|wdl| wdl.to_string()))
+    }
+
+    /// (ply, uci, hash, reduced FEN) of the initial position and of every ply played since.
+    fn history(&self) -> Vec<(usize, String, u64, String)> {
+        self.game
+            .history()
+            .iter()
+            .map(|s| (s.ply, s.uci.clone(), s.hash, s.reduced_fen()))
+            .collect()
+    }
+
+    /// The game after the given ply (0 for the initial position), if it has been played.
+    fn position_after(&self, ply: usize) -> Option<PyGame> {
+        self.game.position_after(ply).map(|game| PyGame { game })
+    }
+
+    /// An independent copy to continue with other moves, e.g. after truncate.
+    fn fork(&self) -> PyGame {
+        PyGame {
+            game: self.game.fork(),
+        }
+    }
+
+    /// Take back all plies after the given one (0 for the initial position), restoring castling
+    /// rights, En-Passant and move clocks of that ply.
+    fn truncate(&mut self, ply: usize) -> PyResult<()> {
+        self.game.truncate(ply).map_err(value_error)
+    }
+
+    /// Explain why a move in SAN or UCI cannot be played as pair of kind and message, e.g.
+    /// ("blocked_path", "path is blocked"), or None if it is legal.
+    fn why_illegal(&self, mv: &str) -> Option<(&'static str, String)> {
+        self.game
+            .why_illegal(mv)
+            .map(|reason| (reason.kind(), reason.to_string()))
+    }
+
+    /// Put a figure like "Nf3" or "ke8" on the board and return the replaced one, if any.
+    /// Castling rights and En-Passant are dropped when the position does no longer allow them.
+    fn put(&mut self, figure: &str) -> PyResult<Option<String>> {
+        let replaced = self.game.put(figure).map_err(value_error)?;
+        Ok(replaced.map(|f| f.to_string()))
+    }
+
+    /// Remove the figure on a square like "e4" and return it, if any.
+    fn remove(&mut self, square: &str) -> PyResult<Option<String>> {
+        let removed = self.game.remove(square).map_err(value_error)?;
+        Ok(removed.map(|f| f.to_string()))
+    }
+
+    /// Remove all figures, e.g. to compose a position from scratch.
+    fn clear(&mut self) {
+        self.game.clear()
+    }
+
+    /// The position with files mirrored (a <-> h). Castling rights are dropped.
+    fn mirror_horizontal(&self) -> PyGame {
+        PyGame {
+            game: self.game.mirror_horizontal(),
+        }
+    }
+
+    /// The position with ranks mirrored (1 <-> 8) and unchanged colors, which drops castling
+    /// rights and En-Passant.
+    fn mirror_vertical(&self) -> PyGame {
+        PyGame {
+            game: self.game.mirror_vertical(),
+        }
+    }
+
+    /// The equivalent position with ranks mirrored, colors swapped and the other side to move.
+    fn swap_colors(&self) -> PyGame {
+        PyGame {
+            game: self.game.swap_colors(),
+        }
+    }
+
+    /// Number of legal moves of a color as if it were its turn, by default of the side to move.
+    #[pyo3(signature = (color=None))]
+    fn mobility(&self, color: Option<PyColor>) -> usize {
+        self.game
+            .mobility(color.map_or(self.game.color, Color::from))
+    }
+
+    /// Index of a move in UCI notation in the AlphaZero action space of 8 x 8 x 73 moves, as seen
+    /// by the side to move. See `action_index` of the Rust crate for the layout.
+    fn action_index(&self, uci: &str) -> PyResult<usize> {
+        self.game.action_index(uci).map_err(PyValueError::new_err)
+    }
+
+    /// The move in UCI notation of an index of the AlphaZero action space, which may be illegal.
+    fn action_move(&self, index: usize) -> PyResult<String> {
+        self.game.action_move(index).map_err(PyValueError::new_err)
+    }
+
+    /// Indices of the legal moves in the AlphaZero action space, in the order of `legal_moves`.
+    fn legal_actions(&self) -> Vec<usize> {
+        self.game.legal_actions()
+    }
+
+    /// The material signature like "KRPvKR", the stronger side first, once the material of both
+    /// sides is at most the given or the default number of pawns, None before.
+    #[pyo3(signature = (max_material=None))]
+    fn endgame_class(&self, max_material: Option<i16>) -> Option<String> {
+        self.game
+            .endgame_class_with(max_material.unwrap_or(endgame::ENDGAME_MATERIAL))
+    }
+
+    /// Rough engine-free evaluation in centipawns from the point of view of white, made of
+    /// material, piece-square tables and mobility, e.g. to skip clearly lost positions.
+    fn evaluate(&self) -> i32 {
+        self.game.evaluate()
+    }
+
+    /// King-safety features of a color as a dict, by default of the side to move, or None if it has
+    /// no king. See fentasize_records for the keys.
+    #[pyo3(signature = (color=None))]
+    fn king_safety<'py>(
+        &self,
+        py: Python<'py>,
+        color: Option<PyColor>,
+    ) -> PyResult<Option<&'py PyDict>> {
+        self.game
+            .king_safety(color.map_or(self.game.color, Color::from))
+            .map(|safety| king_safety_to_dict(py, safety))
+            .transpose()
+    }
+
+    /// The phase of the game, i.e. "opening", "middlegame" or "endgame", by the given or the
+    /// default scheme.
+    #[pyo3(signature = (scheme=None))]
+    fn phase(&self, scheme: Option<PyPhaseScheme>) -> String {
+        scheme
+            .map_or_else(PhaseScheme::default, |s| s.scheme)
+            .phase(&self.game)
+            .to_string()
+    }
+
+    /// Pieces of the color captured so far, in order of capture, by default of the side to move.
+    #[pyo3(signature = (color=None))]
+    fn captured_pieces(&self, color: Option<PyColor>) -> Vec<PyPiece> {
+        self.game
+            .captured_pieces(color.map_or(self.game.color, Color::from))
+            .into_iter()
+            .map(PyPiece::from)
+            .collect()
+    }
+
+    /// Material of white minus the material of black in pawns (1, 3, 3, 5 and 9).
+    fn material_balance(&self) -> i16 {
+        self.game.material_balance()
+    }
+
+    /// The piece and its color on the square, if any.
+    fn piece_at(&self, square: &PySquare) -> Option<(PyPiece, PyColor)> {
+        self.game.position[square.coord.idx as usize]
+            .map(|fig| (PyPiece::from(fig.piece()), PyColor::from(fig.color())))
+    }
+
+    /// Squares of the figures that attack the square, by default those of the side to move.
+    #[pyo3(signature = (square, color=None))]
+    fn attackers_of(&self, square: &PySquare, color: Option<PyColor>) -> Vec<PySquare> {
+        let color = color.map_or(self.game.color, Color::from);
+        self.game
+            .attackers_of(&square.coord, color)
+            .iter()
+            .map(|fig| PySquare { coord: fig.coord() })
+            .collect()
+    }
+
+    /// Lazily replay the moves (any iterable of SAN strings) from the current position, yielding
+    /// a position per ply. The game itself is not changed.
+    fn positions(&self, moves: &PyAny) -> PyResult<PositionIterator> {
+        Ok(PositionIterator {
+            game: self.game.clone(),
+            moves: moves.iter()?.into(),
+            ply: 0,
+        })
+    }
+
+    fn to_ascii(&self) -> String {
+        self.game.to_ascii()
+    }
+
+    fn to_unicode(&self) -> String {
+        self.game.to_unicode()
+    }
+
+    fn __str__(&self) -> String {
+        self.game.to_unicode()
+    }
+
+    fn __repr__(&self) -> String {
+        self.game.to_ascii()
+    }
+}
+
+/// Replay the moves and return the FEN after every ply. Moves in a localized SAN can be read by
+/// passing the notation's language code, e.g. notation="de" for "Sf3" instead of "Nf3".
+#[pyfunction]
+#[pyo3(signature = (moves, notation=None))]
+fn fentasize(moves: Vec<&str>, notation: Option<&str>) -> PyResult<Vec<String>> {
+    replay_localized(&moves, &notation_config(notation)?).map_err(PyValueError::new_err)
+}
+
+/// Replay a flat stream of several games like fentasize and return the FENs per game. Games are
+/// separated by result tokens like "1-0" or by empty strings.
+#[pyfunction]
+#[pyo3(signature = (moves, notation=None))]
+fn fentasize_games(moves: Vec<&str>, notation: Option<&str>) -> PyResult<Vec<Vec<String>>> {
+    let notation = notation_config(notation)?;
+    split_games(&moves)
+        .iter()
+        .enumerate()
+        .map(|(i, game)| {
+            let _span = tracing::debug_span!("replay_game", game = i).entered();
+            replay_localized(game, &notation)
+                .map_err(|e| PyValueError::new_err(format!("game {}: {}", i, e)))
+        })
+        .collect()
+}
+
+/// The reporter of the progress of a batch job, which calls the optional callback with
+/// (processed, failed) every given number of games and when done. An exception of the callback
+/// cancels the job.
+fn progress_reporter(
+    callback: Option<PyObject>,
+    every: usize,
+) -> ProgressReporter<impl FnMut(Progress) -> PyResult<()>> {
+    ProgressReporter::new(every, move |progress: Progress| {
+        let Some(callback) = &callback else {
+            return Ok(());
+        };
+        Python::with_gil(|py| {
+            callback.call1(py, (progress.processed, progress.failed))?;
+            Ok(())
+        })
+    })
+}
+
+/// Interval at which signals like Ctrl-C are handled while a batch job runs.
+const SIGNAL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run a batch job on a worker thread with the GIL released, while this thread handles signals
+/// every `SIGNAL_INTERVAL`. An exception of a signal handler, e.g. KeyboardInterrupt, cancels the
+/// token, which the job checks between games, and is raised once the job has stopped.
+fn run_cancellable<T, F>(py: Python<'_>, job: F) -> PyResult<T>
+where
+    T: Send,
+    F: FnOnce(&CancellationToken) -> PyResult<T> + Send,
+{
+    let cancel = &CancellationToken::new();
+    py.allow_threads(|| {
+        thread::scope(|scope| {
+            let (done, finished) = mpsc::channel();
+            let worker = scope.spawn(move || {
+                let result = job(cancel);
+                let _ = done.send(());
+                result
+            });
+            let mut signal = Ok(());
+            while let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(SIGNAL_INTERVAL) {
+                signal = Python::with_gil(|py| py.check_signals());
+                if signal.is_err() {
+                    cancel.cancel();
+                    break;
+                }
+            }
+            let result = worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            signal.and(result)
+        })
+    })
+}
+
+/// Stop a job once cancelled, see `run_cancellable`.
+fn check_cancelled(cancel: &CancellationToken) -> PyResult<()> {
+    cancel.check().map_err(PyKeyboardInterrupt::new_err)
+}
+
+/// Replay many games given as lists of moves in SAN and return the FENs per game. The FENs are
+/// rendered into buffers that one game reuses from the previous and copied once into the result,
+/// which saves the temporary strings of rendering every FEN on its own. The progress
+/// callback is called with (processed, failed) every progress_every games, e.g. to update a tqdm
+/// bar, and cancels the batch by raising. The GIL is released meanwhile, and Ctrl-C stops the
+/// batch after the game at hand.
 #[pyfunction]
-fn fentasize(moves: Vec<&str>) -> PyResult<Vec<String>> {
+#[pyo3(signature = (games, progress=None, progress_every=1000))]
+fn fentasize_batch(
+    py: Python<'_>,
+    games: Vec<Vec<&str>>,
+    progress: Option<PyObject>,
+    progress_every: usize,
+) -> PyResult<Vec<Vec<String>>> {
+    run_cancellable(py, |cancel| {
+        let mut context = BatchContext::default();
+        let mut reporter = progress_reporter(progress, progress_every);
+        let fens = games
+            .iter()
+            .enumerate()
+            .map(|(i, moves)| {
+                check_cancelled(cancel)?;
+                let _span = tracing::debug_span!("replay_game", game = i).entered();
+                let fens = context
+                    .fentasize(moves)
+                    .map(|fens| fens.to_vec())
+                    .map_err(|e| PyValueError::new_err(format!("game {}: {}", i, e)))?;
+                reporter.record(false)?;
+                Ok(fens)
+            })
+            .collect::<PyResult<_>>()?;
+        reporter.finish()?;
+        Ok(fens)
+    })
+}
+
+type FenArrays<'py> = (&'py PyArray1<u8>, &'py PyArray1<i64>, &'py PyArray1<i64>);
+
+/// Replay many games like fentasize_batch, but return all FENs as numpy arrays (data, offsets,
+/// game_offsets) instead of strings. The FENs are ASCII bytes in data, where FEN i spans
+/// data[offsets[i]:offsets[i + 1]], and the FENs of game g are those from game_offsets[g] to
+/// game_offsets[g + 1]. This is the layout of Arrow (large) string arrays, e.g.
+/// pyarrow.LargeStringArray.from_buffers(len(offsets) - 1, pa.py_buffer(offsets), pa.py_buffer(data)).
+/// Progress and Ctrl-C are handled like by fentasize_batch.
+#[pyfunction]
+#[pyo3(signature = (games, progress=None, progress_every=1000))]
+fn fentasize_buffer<'py>(
+    py: Python<'py>,
+    games: Vec<Vec<&str>>,
+    progress: Option<PyObject>,
+    progress_every: usize,
+) -> PyResult<FenArrays<'py>> {
+    let (buffer, game_offsets) = run_cancellable(py, |cancel| {
+        let mut context = BatchContext::default();
+        let mut reporter = progress_reporter(progress, progress_every);
+        let mut buffer = FenBuffer::new();
+        let mut game_offsets = Vec::with_capacity(games.len() + 1);
+        game_offsets.push(0);
+        for (i, moves) in games.iter().enumerate() {
+            check_cancelled(cancel)?;
+            let _span = tracing::debug_span!("replay_game", game = i).entered();
+            context
+                .fentasize_into(moves, &mut buffer)
+                .map_err(|e| PyValueError::new_err(format!("game {}: {}", i, e)))?;
+            game_offsets.push(buffer.len() as i64);
+            reporter.record(false)?;
+        }
+        reporter.finish()?;
+        Ok((buffer, game_offsets))
+    })?;
+
+    Ok((
+        PyArray1::from_vec(py, buffer.data.into_bytes()),
+        PyArray1::from_vec(py, buffer.offsets),
+        PyArray1::from_vec(py, game_offsets),
+    ))
+}
+
+fn notation_config(notation: Option<&str>) -> PyResult<NotationConfig> {
+    match notation {
+        None => Ok(NotationConfig::english()),
+        Some(code) => NotationConfig::from_str(code).map_err(PyValueError::new_err),
+    }
+}
+
+fn replay_localized(moves: &[&str], notation: &NotationConfig) -> Result<Vec<String>, String> {
     let mut game = Game::new();
-    let fens: Vec<String> = moves.iter().fold(Vec::new(), |mut acc, &mv| {
-        game.play_move(mv);
-        acc.push(game.clone().to_fen());
-        acc
-    });
+    let mut fens = Vec::new();
+    for &mv in moves {
+        // Result tokens like "1-0" terminate the game, but are no moves.
+        if outcome::is_result_token(mv) {
+            continue;
+        }
+        let _span = tracing::trace_span!("ply", ply = fens.len() + 1, san = mv).entered();
+        game.play_localized_move(mv, notation)
+            .map_err(at_ply(fens.len() + 1))?;
+        fens.push(game.clone().to_fen());
+    }
 
     Ok(fens)
 }
+
+/// Replay the moves starting from the given FEN, e.g. the FEN tag of a PGN set up from a
+/// position, and return the FEN after every ply. Move numbers continue from the FEN.
+#[pyfunction]
+fn fentasize_from_fen(fen: &str, moves: Vec<&str>) -> PyResult<Vec<String>> {
+    let options = FentasizeOptions::new().start_fen(fen);
+    Ok(options
+        .fentasize(&moves)
+        .map_err(value_error)?
+        .into_iter()
+        .map(|record| record.fen)
+        .collect())
+}
+
+/// Replay the moves and count what happened without deriving any FEN. Returns a dict with the keys
+/// "plies", "captures", "castlings", "promotions", "checks" and "status", e.g. "checkmate".
+#[pyfunction]
+fn summarize<'py>(py: Python<'py>, moves: Vec<&str>) -> PyResult<&'py PyDict> {
+    let summary = stats::summarize(&moves).map_err(PyValueError::new_err)?;
+    let dict = PyDict::new(py);
+    dict.set_item("plies", summary.plies)?;
+    dict.set_item("captures", summary.captures)?;
+    dict.set_item("castlings", summary.castlings)?;
+    dict.set_item("promotions", summary.promotions)?;
+    dict.set_item("checks", summary.checks)?;
+    dict.set_item("status", summary.status.to_string())?;
+    Ok(dict)
+}
+
+/// Decompose a SAN without a position into a dict with the keys "kind" ("normal", "castle", "drop"
+/// or "null"), "piece", "target", "promotion", "is_capture", "is_check", "is_checkmate" and
+/// "is_long", where values that do not apply to the kind are None or False.
+#[pyfunction]
+fn parse_san<'py>(py: Python<'py>, san: &str) -> PyResult<&'py PyDict> {
+    let san_move = SanMove::from_str(san).map_err(PyValueError::new_err)?;
+    let dict = PyDict::new(py);
+    let (kind, piece, target) = match &san_move {
+        SanMove::Normal(draw) => ("normal", Some(draw.piece), Some(draw.target)),
+        SanMove::Castle(_) => ("castle", Some(Piece::K), None),
+        SanMove::Drop(drop) => ("drop", Some(drop.piece), Some(drop.target)),
+        SanMove::Null => ("null", None, None),
+    };
+    dict.set_item("kind", kind)?;
+    dict.set_item("piece", piece.map(PyPiece::from).into_py(py))?;
+    dict.set_item("target", target.map(|coord| PySquare { coord }).into_py(py))?;
+    let (promotion, is_capture) = match &san_move {
+        SanMove::Normal(draw) => (draw.promoted_piece.map(PyPiece::from), draw.is_hit),
+        _ => (None, false),
+    };
+    dict.set_item("promotion", promotion.into_py(py))?;
+    dict.set_item("is_capture", is_capture)?;
+    dict.set_item("is_check", san_move.is_check())?;
+    dict.set_item("is_checkmate", san_move.is_checkmate())?;
+    dict.set_item(
+        "is_long",
+        matches!(san_move, SanMove::Castle(castling) if castling.is_long),
+    )?;
+    Ok(dict)
+}
+
+/// Replay the moves and return only the FEN of the final position, which skips the FENs of all
+/// other plies, e.g. to verify results or to deduplicate games.
+#[pyfunction]
+fn final_fen(moves: Vec<&str>) -> PyResult<String> {
+    FentasizeOptions::new()
+        .final_fen(&moves)
+        .map_err(value_error)
+}
+
+/// Replay the moves starting from the given FEN like fentasize_from_fen, but return only the FEN
+/// of the final position.
+#[pyfunction]
+fn final_fen_from_fen(fen: &str, moves: Vec<&str>) -> PyResult<String> {
+    FentasizeOptions::new()
+        .start_fen(fen)
+        .final_fen(&moves)
+        .map_err(value_error)
+}
+
+/// Replay the moves and return (fen, uci_position) after every ply, where uci_position is the UCI
+/// command with all moves so far, e.g. "position startpos moves e2e4 e7e5". Feeding engines the
+/// moves instead of isolated FENs lets them detect repetitions.
+#[pyfunction]
+fn fentasize_uci(moves: Vec<&str>) -> PyResult<Vec<(String, String)>> {
+    let mut game = Game::new();
+    let mut positions = Vec::new();
+    for &mv in moves.iter().filter(|mv| !outcome::is_result_token(mv)) {
+        game.play_move(mv)
+            .map_err(at_ply(positions.len() + 1))
+            .map_err(PyValueError::new_err)?;
+        positions.push((game.clone().to_fen(), game.uci_position()));
+    }
+
+    Ok(positions)
+}
+
+/// Replay the moves and return a dict per ply with the keys "ply", "san", "color" ("w" or "b" for
+/// the side that moved) and "fen", plus "uci", "flags", "hash", "king_safety", "phase" and
+/// "move_label" if requested by the options, thus `pd.DataFrame(records)` makes a table of the
+/// game. Without options, "uci" and "flags" are included. Flags are a dict with the keys
+/// "is_check", "is_checkmate", "is_capture", "is_promotion" and "is_castling". King safety is a
+/// list with a dict per king, white first, with the keys "color", "square", "pawn_shield",
+/// "open_files", "half_open_files", "attackers", "attack_weight" and "castled" ("kingside",
+/// "queenside" or None). The phase is "opening", "middlegame" or "endgame". The move label is the
+/// move that led to the position with its number, e.g. "12. Nf3" or "12... Nf6".
+#[pyfunction]
+#[pyo3(signature = (moves, options=None))]
+fn fentasize_records<'py>(
+    py: Python<'py>,
+    moves: Vec<&str>,
+    options: Option<PyRef<PyFentasizeOptions>>,
+) -> PyResult<Vec<&'py PyDict>> {
+    let options = options.map_or_else(
+        || {
+            FentasizeOptions::new()
+                .include_uci(true)
+                .include_flags(true)
+        },
+        |o| o.options.clone(),
+    );
+    options
+        .fentasize(&moves)
+        .map_err(value_error)?
+        .into_iter()
+        .map(|record| record_to_dict(py, record))
+        .collect()
+}
+
+/// Convert moves in SAN into UCI notation, starting from the given FEN or the initial position if
+/// None. Result tokens like "1-0" are skipped.
+#[pyfunction]
+#[pyo3(signature = (start_fen, sans))]
+fn san_to_uci(start_fen: Option<&str>, sans: Vec<&str>) -> PyResult<Vec<String>> {
+    conversion::san_to_uci(start_fen, &sans).map_err(PyValueError::new_err)
+}
+
+/// Convert moves in UCI notation into SAN with check and checkmate markers, starting from the given
+/// FEN or the initial position if None.
+#[pyfunction]
+#[pyo3(signature = (start_fen, ucis))]
+fn uci_to_san(start_fen: Option<&str>, ucis: Vec<&str>) -> PyResult<Vec<String>> {
+    conversion::uci_to_san(start_fen, &ucis).map_err(PyValueError::new_err)
+}
+
+/// Write games as JSON Lines to the given path, one object per ply or, if requested, per game.
+#[pyfunction]
+#[pyo3(signature = (games, path, evals=None, per_game=false))]
+fn to_jsonl(
+    games: Vec<Vec<&str>>,
+    path: &str,
+    evals: Option<Vec<Vec<Option<f64>>>>,
+    per_game: bool,
+) -> PyResult<usize> {
+    Ok(export::to_jsonl(path, &games, evals.as_deref(), per_game)?)
+}
+
+/// Read the Lichess-specific tags of every game of a PGN string (or bytes, pathlib.Path or file
+/// object) as dicts with the keys
+/// "white_elo", "black_elo", "white_rating_diff", "black_rating_diff", "opening", "termination",
+/// "utc" and "variant". Ratings are ints, "utc" is a timezone-aware datetime, termination and
+/// variant are strings like "time_forfeit" and "kingOfTheHill". Missing values are None.
+#[pyfunction]
+fn lichess_tags<'py>(py: Python<'py>, pgn: &PyAny) -> PyResult<Vec<&'py PyDict>> {
+    let pgn = PgnSource::extract(pgn, false)?.read_to_string()?;
+    let datetime = py.import("datetime")?;
+    let utc = datetime.getattr("timezone")?.getattr("utc")?;
+    pgn::parse_pgn(&pgn)
+        .iter()
+        .map(|game| {
+            let tags = game.lichess_tags();
+            let dict = PyDict::new(py);
+            dict.set_item("white_elo", tags.white_elo)?;
+            dict.set_item("black_elo", tags.black_elo)?;
+            dict.set_item("white_rating_diff", tags.white_rating_diff)?;
+            dict.set_item("black_rating_diff", tags.black_rating_diff)?;
+            dict.set_item("opening", tags.opening)?;
+            dict.set_item("termination", tags.termination.map(|t| t.to_string()))?;
+            let time = match tags.utc {
+                Some(t) => Some(
+                    datetime
+                        .getattr("datetime")?
+                        .call1((t.year, t.month, t.day, t.hour, t.minute, t.second, 0, utc))?,
+                ),
+                None => None,
+            };
+            dict.set_item("utc", time)?;
+            dict.set_item("variant", tags.variant.map(|v| v.to_string()))?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+type TerminationRow = (Option<String>, String, &'static str, Option<String>);
+
+/// Replay the games of a PGN (content, path or file object) and reconcile their Termination tags
+/// with the final positions, e.g. to find mislabeled games in Lichess dumps. Returns per game a
+/// tuple (termination, status, verdict, reason), where termination is e.g. "time_forfeit" or None,
+/// status e.g. "checkmate", verdict "consistent", "mismatch" or "unverifiable" and reason explains
+/// a mismatch, e.g. "time_forfeit after checkmate". A game that cannot be replayed raises.
+#[pyfunction]
+fn check_terminations(pgn: &PyAny) -> PyResult<Vec<TerminationRow>> {
+    let source = PgnSource::extract(pgn, true)?.open()?;
+    lichess::check_terminations(source)
+        .map(|check| {
+            let check = check.map_err(PyValueError::new_err)?;
+            let reason = match &check.verdict {
+                lichess::TerminationVerdict::Mismatch(reason) => Some(reason.clone()),
+                _ => None,
+            };
+            Ok((
+                check.termination.map(|t| t.to_string()),
+                check.status.to_string(),
+                check.verdict.kind(),
+                reason,
+            ))
+        })
+        .collect()
+}
+
+/// Replay all games of a Lichess API export in NDJSON format and return their FENs per game.
+#[pyfunction]
+fn fentasize_ndjson(ndjson: &str) -> PyResult<Vec<Vec<String>>> {
+    lichess::read_ndjson(ndjson.as_bytes())
+        .map(|game| game.and_then(|g| g.fens()))
+        .collect::<Result<_, _>>()
+        .map_err(PyValueError::new_err)
+}
+
+/// Replay the moves and compare a trailing result token (e.g. "1-0") with the final position.
+/// Returns the declared result (if any), the board status ("ongoing", "checkmate" or
+/// "stalemate"), whether both are consistent, which includes a mate marker "#" on the last move,
+/// and whether the game was terminated mid-game.
+#[pyfunction]
+fn check_result(moves: Vec<&str>) -> PyResult<(Option<String>, String, bool, bool)> {
+    let (_, check) = outcome::replay_and_check(&moves).map_err(PyValueError::new_err)?;
+    Ok((
+        check.declared.map(|r| r.to_string()),
+        check.status.to_string(),
+        check.is_consistent,
+        check.is_mid_game_termination,
+    ))
+}
+
+/// Replay the moves and infer the result from the final position, e.g. for games without a
+/// termination marker. Returns the result token, the termination ("checkmate", "stalemate",
+/// "insufficient_material", "fifty_moves", "repetition" or "unknown") and the confidence
+/// ("certain", "likely" or "unknown"). Claimable draws are likely under the claim policy "otb"
+/// (over the board) and certain under "online", where they end the game right away.
+#[pyfunction]
+#[pyo3(signature = (moves, claim_policy="otb"))]
+fn infer_result(moves: Vec<&str>, claim_policy: &str) -> PyResult<(String, String, String)> {
+    let policy = ClaimPolicy::from_str(claim_policy).map_err(PyValueError::new_err)?;
+    let inferred = outcome::infer_result_with(&moves, policy).map_err(PyValueError::new_err)?;
+    Ok((
+        inferred.result.to_string(),
+        inferred.termination.to_string(),
+        inferred.confidence.to_string(),
+    ))
+}
+
+/// Render the game as an animated SVG to the given path, showing every position for the given
+/// number of milliseconds. Returns the number of frames.
+#[pyfunction]
+#[pyo3(signature = (moves, path, frame_delay=500))]
+fn render_game(moves: Vec<&str>, path: &str, frame_delay: u32) -> PyResult<usize> {
+    Ok(render::render_game(&moves, path, frame_delay)?)
+}
+
+type SampledPosition = (usize, String, String, String, Option<f64>);
+
+/// Replay the moves and return only the selected positions as (ply, san, uci, fen, eval): every
+/// k-th ply, plies within [min_ply, max_ply], after captures or with the side to move in check.
+/// All given criteria must be met.
+#[pyfunction]
+#[pyo3(signature = (
+    moves, evals=None, every=None, min_ply=None, max_ply=None, after_capture=false, in_check=false
+))]
+fn sample_positions(
+    moves: Vec<&str>,
+    evals: Option<Vec<Option<f64>>>,
+    every: Option<usize>,
+    min_ply: Option<usize>,
+    max_ply: Option<usize>,
+    after_capture: bool,
+    in_check: bool,
+) -> PyResult<Vec<SampledPosition>> {
+    let selection = PlySelection {
+        every,
+        min_ply,
+        max_ply,
+        after_capture,
+        in_check,
+    };
+    Ok(sampling::sample_plies(&moves, evals.as_deref(), &selection)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|r| (r.ply, r.san, r.uci, r.fen, r.eval))
+        .collect())
+}
+
+type LabeledPosition = (String, String, i8, usize, usize);
+
+/// Label every position of the games in a PGN string (or bytes, pathlib.Path or file object) with
+/// the game's result as (fen,
+/// side_to_move, outcome, ply, total_plies), where the outcome is 1, 0 or -1 from the point of view
+/// of the side to move ("w" or "b"). Games without a decided result are skipped.
+#[pyfunction]
+fn positions_with_outcome(pgn: &PyAny) -> PyResult<Vec<LabeledPosition>> {
+    let pgn = PgnSource::extract(pgn, false)?.read_to_string()?;
+    Ok(labels::positions_with_outcome(&pgn)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|p| {
+            (
+                p.fen,
+                p.side_to_move.to_string(),
+                p.outcome,
+                p.ply,
+                p.total_plies,
+            )
+        })
+        .collect())
+}
+
+/// Pair every position with the move played in it as (fen, uci, index), where the index points
+/// into the legal moves of the position in the stable order of `Game.legal_moves`. Takes a PGN
+/// string, whose games may start from a FEN tag, or a list of SAN moves from the initial position.
+#[pyfunction]
+fn policy_pairs(pgn_or_moves: &PyAny) -> PyResult<Vec<(String, String, usize)>> {
+    let pairs = match pgn_or_moves.extract::<&str>() {
+        Ok(pgn) => labels::policy_pairs_pgn(pgn),
+        Err(_) => labels::policy_pairs(&Game::new(), &pgn_or_moves.extract::<Vec<&str>>()?),
+    };
+    Ok(pairs
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|p| (p.fen, p.uci, p.index))
+        .collect())
+}
+
+type MarkerMismatch = (usize, String, String);
+
+/// Replay the moves and re-emit them as SAN with the check ("+") and checkmate ("#") markers
+/// derived from the position. Also returns the moves whose markers were missing or spurious as
+/// (ply, san, corrected).
+#[pyfunction]
+fn correct_sans(moves: Vec<&str>) -> PyResult<(Vec<String>, Vec<MarkerMismatch>)> {
+    let (sans, mismatches) = writer::correct_sans(&moves).map_err(PyValueError::new_err)?;
+    Ok((
+        sans,
+        mismatches
+            .into_iter()
+            .map(|m| (m.ply, m.san, m.corrected))
+            .collect(),
+    ))
+}
+
+/// Write a game as PGN with SAN derived from the position, wrapped at 80 characters. The headers
+/// are (key, value) pairs as returned by read_pgn, where the Result tag gives the result. Comments
+/// (str or None) and NAGs (lists of ints, e.g. [2] for "$2") are written after the moves of their
+/// plies, e.g. to write back evals computed elsewhere as "{[%eval 0.35]}".
+#[pyfunction]
+#[pyo3(signature = (moves, headers=None, comments=None, nags=None))]
+fn write_pgn(
+    moves: Vec<String>,
+    headers: Option<Vec<(String, String)>>,
+    comments: Option<Vec<Option<String>>>,
+    nags: Option<Vec<Vec<u8>>>,
+) -> PyResult<String> {
+    let (comments, nags) = (comments.unwrap_or_default(), nags.unwrap_or_default());
+    let annotations: Vec<PlyAnnotation> = (0..comments.len().max(nags.len()))
+        .map(|i| PlyAnnotation {
+            nags: nags.get(i).cloned().unwrap_or_default(),
+            comment: comments.get(i).cloned().flatten(),
+        })
+        .collect();
+    let game = pgn::PgnGame {
+        headers: headers.unwrap_or_default(),
+        moves,
+        ..pgn::PgnGame::default()
+    };
+    writer::write_annotated_pgn(&game, &annotations).map_err(PyValueError::new_err)
+}
+
+/// Counters of the process-wide position cache as a dict with the keys "hits", "misses",
+/// "evictions", "entries", "capacity" and "hit_rate".
+#[pyfunction]
+fn position_cache_stats(py: Python<'_>) -> PyResult<&PyDict> {
+    let stats = PositionCache::global().stats();
+    let dict = PyDict::new(py);
+    dict.set_item("hits", stats.hits)?;
+    dict.set_item("misses", stats.misses)?;
+    dict.set_item("evictions", stats.evictions)?;
+    dict.set_item("entries", stats.entries)?;
+    dict.set_item("capacity", stats.capacity)?;
+    dict.set_item("hit_rate", stats.hit_rate())?;
+    Ok(dict)
+}
+
+/// Drop all positions of the process-wide position cache and reset its counters.
+#[pyfunction]
+fn clear_position_cache() {
+    PositionCache::global().clear();
+}
+
+type SquareChange = (String, Option<String>, Option<String>);
+
+/// Replay the moves and return the changed squares of every ply as (square, before, after), where
+/// figures are given as FEN letters (e.g. "N") or None for empty squares.
+#[pyfunction]
+fn ply_diffs(moves: Vec<&str>) -> PyResult<Vec<Vec<SquareChange>>> {
+    Ok(diff::ply_diffs(&moves)
+        .map_err(PyValueError::new_err)?
+        .iter()
+        .map(|ply| square_changes(&ply.changes))
+        .collect())
+}
+
+/// Replay the moves and return the material balance after every ply, i.e. the material of white
+/// minus the material of black in pawns.
+#[pyfunction]
+fn material_balances(moves: Vec<&str>) -> PyResult<Vec<i16>> {
+    material::material_balances(&moves).map_err(PyValueError::new_err)
+}
+
+/// Replay the moves and return the rough evaluation after every ply in centipawns from the point of
+/// view of white, see Game.evaluate.
+#[pyfunction]
+fn evaluations(moves: Vec<&str>) -> PyResult<Vec<i32>> {
+    evaluation::evaluations(&moves).map_err(PyValueError::new_err)
+}
+
+/// Replay the moves and return the tactical motifs of every ply, e.g. ["fork"]. The labels are
+/// "fork", "pin", "skewer", "discovered_attack" and "back_rank_threat".
+#[pyfunction]
+fn detect_motifs(moves: Vec<&str>) -> PyResult<Vec<Vec<String>>> {
+    Ok(motifs::detect_motifs(&moves)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|ply| ply.motifs.iter().map(|m| m.to_string()).collect())
+        .collect())
+}
+
+fn square_changes(changes: &[diff::SquareChange]) -> Vec<SquareChange> {
+    let figure = |fig: Option<Figure>| fig.map(|f| f.to_char().to_string());
+    changes
+        .iter()
+        .map(|c| (c.square.to_string(), figure(c.before), figure(c.after)))
+        .collect()
+}
+
+/// Merge the games of many PGN strings into a single PGN game of variations, where the most
+/// popular move is the main line. Lines that transpose into the same position are merged if
+/// `transpositions` is set, moves played by fewer than `min_games` games are pruned, and only the
+/// first `max_plies` plies of every game are added if given.
+#[pyfunction]
+#[pyo3(signature = (pgns, transpositions=false, min_games=1, max_plies=None))]
+fn merge_pgns(
+    pgns: Vec<&str>,
+    transpositions: bool,
+    min_games: u32,
+    max_plies: Option<usize>,
+) -> PyResult<String> {
+    let mut book = OpeningBook::default();
+    if transpositions {
+        book = book.merge_transpositions();
+    }
+    if let Some(max_plies) = max_plies {
+        book = book.max_plies(max_plies);
+    }
+    for (i, pgn) in pgns.iter().enumerate() {
+        book.add_pgn(pgn)
+            .map_err(|e| PyValueError::new_err(format!("pgn {}, {}", i, e)))?;
+    }
+    book.prune(min_games);
+    Ok(book.to_pgn())
+}
+
+type Transposition = (u64, String, Vec<(usize, usize)>, bool);
+
+/// Report positions that occur more than once within the games as (zobrist key, reduced FEN,
+/// [(game, ply)], whether reached by different move orders).
+#[pyfunction]
+fn find_transpositions(games: Vec<Vec<&str>>) -> PyResult<Vec<Transposition>> {
+    Ok(transposition::find_transpositions(&games)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|t| {
+            let occurrences = t.occurrences.iter().map(|o| (o.game, o.ply)).collect();
+            (t.key, t.fen.clone(), occurrences, t.is_transposition())
+        })
+        .collect())
+}
+
+type PgnRecord = (Vec<(String, String)>, Vec<String>, Option<String>);
+
+/// Read the games of a (possibly compressed) PGN file as (headers, moves, result). The path may
+/// also be a pathlib.Path, an open file object in text or binary mode, or the PGN itself as bytes.
+/// Files are streamed, while bytes are parsed in memory. Games whose headers do not match the
+/// criteria are skipped without parsing their moves. Ratings bound both
+/// players, ECO codes are matched by prefix, dates are given as "YYYY.MM.DD" and results as tokens
+/// like "1-0". Progress and Ctrl-C are handled like by fentasize_batch, counting the matching
+/// games.
+#[pyfunction]
+#[pyo3(signature = (
+    path, min_elo=None, max_elo=None, time_controls=None, variants=None, results=None, eco=None,
+    date_from=None, date_to=None, player=None, progress=None, progress_every=1000
+))]
+#[allow(clippy::too_many_arguments)]
+fn read_pgn(
+    py: Python<'_>,
+    path: &PyAny,
+    min_elo: Option<u16>,
+    max_elo: Option<u16>,
+    time_controls: Option<Vec<String>>,
+    variants: Option<Vec<String>>,
+    results: Option<Vec<&str>>,
+    eco: Option<Vec<String>>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    player: Option<String>,
+    progress: Option<PyObject>,
+    progress_every: usize,
+) -> PyResult<Vec<PgnRecord>> {
+    let filter = GameFilter {
+        min_elo,
+        max_elo,
+        time_controls: time_controls.unwrap_or_default(),
+        variants: variants.unwrap_or_default(),
+        results: results
+            .unwrap_or_default()
+            .iter()
+            .map(|r| outcome::GameResult::from_str(r))
+            .collect::<Result<_, _>>()
+            .map_err(value_error)?,
+        eco: eco.unwrap_or_default(),
+        date_from,
+        date_to,
+        player,
+    };
+    let source = PgnSource::extract(path, true)?;
+    run_cancellable(py, |cancel| {
+        let mut reporter = progress_reporter(progress, progress_every);
+        let games = pgn::PgnReader::with_filter(source.open()?, filter)
+            .map(|game| {
+                check_cancelled(cancel)?;
+                let g = game?;
+                reporter.record(false)?;
+                Ok((g.headers, g.moves, g.result))
+            })
+            .collect::<PyResult<_>>()?;
+        reporter.finish()?;
+        Ok(games)
+    })
+}
+
+type ReplayError = (usize, usize, String, String);
+
+/// Read the games of a (possibly compressed) PGN file or other source like `read_pgn`, but truncate games at their
+/// first move that cannot be replayed instead of failing. Returns the games and the errors as
+/// (game, ply, san, reason), where games are numbered from 0. Progress and Ctrl-C are handled like
+/// by fentasize_batch, where truncated games count as failed.
+#[pyfunction]
+#[pyo3(signature = (path, progress=None, progress_every=1000))]
+fn read_pgn_lenient(
+    py: Python<'_>,
+    path: &PyAny,
+    progress: Option<PyObject>,
+    progress_every: usize,
+) -> PyResult<(Vec<PgnRecord>, Vec<ReplayError>)> {
+    let source = PgnSource::extract(path, true)?;
+    run_cancellable(py, |cancel| {
+        let mut reader = LenientReader::new(pgn::PgnReader::new(source.open()?));
+        let mut reporter = progress_reporter(progress, progress_every);
+        let mut games = Vec::new();
+        while let Some(game) = reader.next() {
+            check_cancelled(cancel)?;
+            let g = game?;
+            games.push((g.headers, g.moves, g.result));
+            let is_failed = reader
+                .errors()
+                .last()
+                .is_some_and(|e| e.game + 1 == games.len());
+            reporter.record(is_failed)?;
+        }
+        reporter.finish()?;
+        let errors = reader
+            .into_errors()
+            .into_iter()
+            .map(|e| (e.game, e.ply, e.san, e.reason))
+            .collect();
+        Ok((games, errors))
+    })
+}
+
+/// Replay every game of a PGN string (or bytes, pathlib.Path or file object) and report it as a dict with the keys "headers", "moves",
+/// "fens", "status", "declared" and "anomalies", where anomalies are dicts with the keys "kind",
+/// "ply" and "message", e.g. {"kind": "illegal_move", "ply": 3, "message": "illegal move: Ke3"}.
+#[pyfunction]
+fn report_pgn<'py>(py: Python<'py>, pgn: &PyAny) -> PyResult<Vec<&'py PyDict>> {
+    let pgn = PgnSource::extract(pgn, false)?.read_to_string()?;
+    let mut reports = Vec::new();
+    for report in fency_core::utils::report::report_pgn(&pgn).map_err(PyValueError::new_err)? {
+        reports.push(report_to_dict(py, report)?);
+    }
+    Ok(reports)
+}
+
+type BucketSummary = (
+    Option<u16>,
+    usize,
+    f64,
+    f64,
+    usize,
+    usize,
+    usize,
+    usize,
+    Vec<(String, usize)>,
+);
+
+/// Aggregate the games of a (possibly compressed) PGN file or other source, see read_pgn, by the
+/// average rating of both players
+/// without replaying them. Returns one row per bucket as (bucket, games, average_plies,
+/// capture_rate, white_wins, draws, black_wins, undecided, top_openings), where the bucket is the
+/// lower bound of the rating range (None for unrated games) and openings are (ECO, games).
+#[pyfunction]
+#[pyo3(signature = (path, bucket_width=200, n_openings=5))]
+fn elo_stats(path: &PyAny, bucket_width: u16, n_openings: usize) -> PyResult<Vec<BucketSummary>> {
+    let mut stats = EloStats::new(bucket_width);
+    for game in pgn::PgnReader::new(PgnSource::extract(path, true)?.open()?) {
+        stats.add(&game?);
+    }
+    Ok(stats
+        .buckets
+        .iter()
+        .map(|(&bucket, s)| {
+            let openings = s
+                .top_openings(n_openings)
+                .into_iter()
+                .map(|(eco, n)| (eco.to_string(), n))
+                .collect();
+            (
+                bucket,
+                s.games,
+                s.average_plies(),
+                s.capture_rate(),
+                s.white_wins,
+                s.draws,
+                s.black_wins,
+                s.undecided,
+                openings,
+            )
+        })
+        .collect())
+}
+
+type OpeningRow = (String, usize, usize, usize, usize, usize, Option<f64>);
+
+/// Count the games of a (possibly compressed) PGN file or other source, see read_pgn, per opening, i.e. the table of an opening
+/// explorer. Openings are ECO codes ("?" if missing), or the first plies in SAN like "e4 c5 Nf3"
+/// if plies is given. Only games within the rating band of both players and with one of the
+/// TimeControl tags are counted, if given. Returns (opening, games, white_wins, draws, black_wins,
+/// undecided, white_score), the most popular first, where white_score is the percentage of points
+/// of white in the decided games.
+#[pyfunction]
+#[pyo3(signature = (path, plies=None, min_elo=None, max_elo=None, time_controls=None))]
+fn opening_report(
+    path: &PyAny,
+    plies: Option<usize>,
+    min_elo: Option<u16>,
+    max_elo: Option<u16>,
+    time_controls: Option<Vec<String>>,
+) -> PyResult<Vec<OpeningRow>> {
+    let key = plies.map_or(OpeningKey::Eco, OpeningKey::Plies);
+    let filter = GameFilter {
+        min_elo,
+        max_elo,
+        time_controls: time_controls.unwrap_or_default(),
+        ..GameFilter::default()
+    };
+    let source = PgnSource::extract(path, true)?.open()?;
+    let report = explorer::opening_report(source, key, filter).map_err(PyValueError::new_err)?;
+    Ok(report
+        .rows()
+        .into_iter()
+        .map(|(opening, s)| {
+            (
+                opening.to_string(),
+                s.games,
+                s.white_wins,
+                s.draws,
+                s.black_wins,
+                s.undecided,
+                s.white_score(),
+            )
+        })
+        .collect())
+}
+
+type BlunderRecord = (usize, usize, String, String, String, String, String, i32);
+
+/// Find the moves of the games in a PGN string (or bytes, pathlib.Path or file object) after which the "[%eval]" comments drop by at least
+/// the threshold (in centipawns) for the moving side, where evals are capped at plus/minus cap.
+/// Returns (game, ply, fen_before, san, uci, eval_before, eval_after, loss), where evals are given
+/// as in the comments, e.g. "0.35" or "#-2".
+#[pyfunction]
+#[pyo3(signature = (pgn, threshold=200, cap=1000))]
+fn find_blunders(pgn: &PyAny, threshold: i32, cap: i32) -> PyResult<Vec<BlunderRecord>> {
+    let pgn = PgnSource::extract(pgn, false)?.read_to_string()?;
+    let criteria = BlunderCriteria { threshold, cap };
+    Ok(blunders::blunders_in_pgn(&pgn, &criteria)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|(g, b)| {
+            let (before, after) = (b.eval_before.to_string(), b.eval_after.to_string());
+            (g, b.ply, b.fen, b.san, b.uci, before, after, b.loss)
+        })
+        .collect())
+}
+
+type MaterialBlunderRecord = (usize, String, String, String, String, i32);
+
+/// Find the moves in SAN that hang material worth at least the threshold (in centipawns), i.e.
+/// the opponent wins it by a capture afterwards, e.g. for games without evals. Returns (ply,
+/// fen_before, san, uci, refutation, loss), where the refutation is the capture in UCI notation.
+#[pyfunction]
+#[pyo3(signature = (moves, threshold=200, evaluator=None))]
+fn find_material_blunders(
+    moves: Vec<&str>,
+    threshold: i32,
+    evaluator: Option<PyMaterialEvaluator>,
+) -> PyResult<Vec<MaterialBlunderRecord>> {
+    let evaluator = evaluator.map(|e| e.evaluator).unwrap_or_default();
+    Ok(
+        blunders::find_material_blunders(&moves, &evaluator, threshold)
+            .map_err(PyValueError::new_err)?
+            .into_iter()
+            .map(|b| (b.ply, b.fen, b.san, b.uci, b.refutation, b.loss))
+            .collect(),
+    )
+}
+
+type TimeUsage = (usize, Option<u32>, Option<u32>);
+
+/// The clock annotations of every game of a PGN string (or bytes, pathlib.Path or file object) as (ply, clock, time_spent) in seconds per
+/// ply, where the time spent accounts for the increments of the TimeControl tag, e.g. "300+3" or
+/// "40/5400:1800". Values are None for plies without (consistent) clocks.
+#[pyfunction]
+fn time_usage(pgn: &PyAny) -> PyResult<Vec<Vec<TimeUsage>>> {
+    pgn::PgnReader::new(PgnSource::extract(pgn, false)?.open()?)
+        .map(|game| {
+            let game = game?;
+            let time_spent = game.time_spent();
+            Ok((0..game.moves.len())
+                .map(|i| {
+                    let clock = game.clocks.get(i).copied().flatten();
+                    (i + 1, clock, time_spent.get(i).copied().flatten())
+                })
+                .collect())
+        })
+        .collect()
+}
+
+type ClockState = (usize, String, Option<u32>, bool, bool);
+
+/// The clocks of every game of a PGN string (or bytes, pathlib.Path or file object) as (ply, color, clock, is_interpolated, is_flag_fall)
+/// per ply, where missing clocks are interpolated between the known clocks of the same side and
+/// the initial time of the TimeControl tag. Clocks after the last known one of a side are None.
+#[pyfunction]
+fn clock_readings(pgn: &PyAny) -> PyResult<Vec<Vec<ClockState>>> {
+    pgn::PgnReader::new(PgnSource::extract(pgn, false)?.open()?)
+        .map(|game| {
+            Ok(game?
+                .clock_readings()
+                .into_iter()
+                .map(|r| {
+                    let color = r.color.to_string();
+                    (r.ply, color, r.seconds, r.is_interpolated, r.is_flag_fall)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+type EngineAnalysis = (Option<String>, Option<String>, Option<u32>);
+
+/// Analyse the initial position and the position after every move with the UCI engine at the
+/// given path, searching each position to the given depth. Returns (best_move, eval, depth) per
+/// position, where the best move is in UCI notation and the eval is from the point of view of
+/// white, e.g. "0.35" or "#-2".
+#[pyfunction]
+#[pyo3(signature = (engine_path, moves, depth=12, options=Vec::new()))]
+fn analyse_game(
+    engine_path: &str,
+    moves: Vec<&str>,
+    depth: u32,
+    options: Vec<(String, String)>,
+) -> PyResult<Vec<EngineAnalysis>> {
+    let config = EngineConfig {
+        path: engine_path.to_string(),
+        options,
+        limit: SearchLimit::Depth(depth),
+        ..EngineConfig::default()
+    };
+    let mut engine = Engine::spawn(&config)?;
+    Ok(engine
+        .analyse_game(&moves)?
+        .into_iter()
+        .map(|a| (a.best_move, a.eval.map(|e| e.to_string()), a.depth))
+        .collect())
+}
+
+type EndgameRecord = (usize, usize, String, String, i8, Option<i32>);
+
+/// Label every position of a (possibly compressed) PGN file or other source, see read_pgn, that is
+/// covered by the Syzygy tables in the given directories, i.e. with few pieces and no castling rights. Returns (game, ply, fen,
+/// wdl, score, dtz), where wdl is e.g. "cursed_win" and score is 1, 0 or -1 from the point of view
+/// of the side to move. dtz is None if the DTZ tables are missing.
+#[pyfunction]
+fn label_endgames(path: &PyAny, tablebase_dirs: Vec<&str>) -> PyResult<Vec<EndgameRecord>> {
+    let tablebase = Tablebase::open(&tablebase_dirs).map_err(PyValueError::new_err)?;
+    let games = pgn::PgnReader::new(PgnSource::extract(path, true)?.open()?);
+    Ok(tablebase::label_endgames(games, &tablebase)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|l| {
+            (
+                l.game,
+                l.ply,
+                l.fen,
+                l.wdl.to_string(),
+                l.wdl.score(),
+                l.dtz,
+            )
+        })
+        .collect())
+}
+
+/// All positions of a (possibly compressed) PGN file or other source, see read_pgn, with the given
+/// material signature, e.g. "KRPvKR" in any order and case. Returns (game, ply, fen) per position.
+#[pyfunction]
+fn positions_of_class(path: &PyAny, class: &str) -> PyResult<Vec<(usize, usize, String)>> {
+    let games = pgn::PgnReader::new(PgnSource::extract(path, true)?.open()?);
+    Ok(endgame::positions_of_class(games, class)
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|p| (p.game, p.ply, p.fen))
+        .collect())
+}
+
+type PuzzleMoveRecord = (String, String, String, bool, bool, bool, bool, bool);
+
+/// Replay a puzzle given as in the Lichess puzzle database, i.e. the FEN before the opponent's
+/// move and the UCI moves of the opponent's move and the solution. Returns (uci, san, fen,
+/// is_solution, check, checkmate, capture, promotion) per move, where the FEN is after the move.
+#[pyfunction]
+fn validate_puzzle(fen: &str, moves: Vec<&str>) -> PyResult<Vec<PuzzleMoveRecord>> {
+    let puzzle = Puzzle {
+        fen: fen.to_string(),
+        moves: moves.iter().map(|mv| mv.to_string()).collect(),
+        ..Puzzle::default()
+    };
+    Ok(puzzle
+        .validate()
+        .map_err(PyValueError::new_err)?
+        .into_iter()
+        .map(|mv| {
+            let flags = (mv.is_check, mv.is_checkmate, mv.is_capture, mv.is_promotion);
+            (
+                mv.uci,
+                mv.san,
+                mv.fen,
+                mv.is_solution,
+                flags.0,
+                flags.1,
+                flags.2,
+                flags.3,
+            )
+        })
+        .collect())
+}
+
+type PuzzleRecord = (
+    String,
+    Option<u16>,
+    Vec<String>,
+    Option<Vec<String>>,
+    Option<String>,
+);
+
+/// Validate all puzzles of a (possibly compressed) Lichess puzzle CSV. Returns (id, rating,
+/// themes, solution_sans, error) per puzzle, where either the SAN of the solver's moves or the
+/// reason why the solution cannot be replayed is given.
+#[pyfunction]
+fn validate_puzzles(path: &str) -> PyResult<Vec<PuzzleRecord>> {
+    PuzzleReader::new(compression::open(path)?)
+        .map(|puzzle| {
+            let puzzle = puzzle?;
+            let (sans, error) = match puzzle.solution_sans() {
+                Ok(sans) => (Some(sans), None),
+                Err(e) => (None, Some(e)),
+            };
+            Ok((puzzle.id, puzzle.rating, puzzle.themes, sans, error))
+        })
+        .collect()
+}
+
+fn report_to_dict<'py>(py: Python<'py>, report: GameReport) -> PyResult<&'py PyDict> {
+    let anomalies = report
+        .anomalies
+        .iter()
+        .map(|a| {
+            let anomaly = PyDict::new(py);
+            anomaly.set_item("kind", a.kind())?;
+            anomaly.set_item("ply", a.ply())?;
+            anomaly.set_item("message", a.to_string())?;
+            Ok(anomaly)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let headers = PyDict::new(py);
+    for (key, value) in report.headers {
+        headers.set_item(key, value)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("headers", headers)?;
+    dict.set_item("moves", report.moves)?;
+    dict.set_item("fens", report.fens)?;
+    dict.set_item("status", report.status.to_string())?;
+    dict.set_item("declared", report.declared.map(|r| r.to_string()))?;
+    dict.set_item("anomalies", anomalies)?;
+    Ok(dict)
+}
+
+fn record_to_dict(py: Python<'_>, record: FenRecord) -> PyResult<&PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("ply", record.ply)?;
+    dict.set_item("san", record.san)?;
+    dict.set_item("color", record.color.to_string())?;
+    dict.set_item("fen", record.fen)?;
+    if let Some(uci) = record.uci {
+        dict.set_item("uci", uci)?;
+    }
+    if let Some(flags) = record.flags {
+        let flag_dict = PyDict::new(py);
+        flag_dict.set_item("is_check", flags.is_check)?;
+        flag_dict.set_item("is_checkmate", flags.is_checkmate)?;
+        flag_dict.set_item("is_capture", flags.is_capture)?;
+        flag_dict.set_item("is_promotion", flags.is_promotion)?;
+        flag_dict.set_item("is_castling", flags.is_castling)?;
+        dict.set_item("flags", flag_dict)?;
+    }
+    if let Some(hash) = record.hash {
+        dict.set_item("hash", hash)?;
+    }
+    if let Some(king_safety) = record.king_safety {
+        let safeties = king_safety
+            .into_iter()
+            .map(|safety| king_safety_to_dict(py, safety))
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("king_safety", safeties)?;
+    }
+    if let Some(phase) = record.phase {
+        dict.set_item("phase", phase.to_string())?;
+    }
+    if let Some(move_label) = record.move_label {
+        dict.set_item("move_label", move_label)?;
+    }
+    Ok(dict)
+}
+
+fn king_safety_to_dict(py: Python<'_>, safety: KingSafety) -> PyResult<&PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("color", safety.color.to_string())?;
+    dict.set_item("square", safety.square.to_string())?;
+    dict.set_item("pawn_shield", safety.pawn_shield)?;
+    dict.set_item("open_files", safety.open_files)?;
+    dict.set_item("half_open_files", safety.half_open_files)?;
+    dict.set_item("attackers", safety.attackers)?;
+    dict.set_item("attack_weight", safety.attack_weight)?;
+    dict.set_item("castled", safety.castled.map(|side| side.to_string()))?;
+    Ok(dict)
+}
+
+/// Games and results as (games, white wins, draws, black wins).
+type Stats = (u32, u32, u32, u32);
+
+fn stats_tuple(stats: tree::Stats) -> Stats {
+    (stats.games, stats.white_wins, stats.draws, stats.black_wins)
+}
+
+fn game_from_fen(fen: &str) -> PyResult<Game> {
+    Game::from_str(fen).map_err(value_error)
+}
+
+/// Raise an error of the stable API as ValueError with its message.
+fn value_error(error: Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// What fentasize_records derives per ply, e.g. FentasizeOptions(include_uci=True, reduced_fen=True).
+#[pyclass(name = "FentasizeOptions")]
+#[derive(Clone)]
+struct PyFentasizeOptions {
+    options: FentasizeOptions,
+}
+
+#[pymethods]
+impl PyFentasizeOptions {
+    #[new]
+    #[pyo3(signature = (
+        include_uci=false,
+        include_flags=false,
+        include_hash=false,
+        include_king_safety=false,
+        include_phase=false,
+        phase_scheme=None,
+        include_move_label=false,
+        reduced_fen=false,
+        start_fen=None,
+        variant="standard",
+        strict=true,
+        max_plies=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        include_uci: bool,
+        include_flags: bool,
+        include_hash: bool,
+        include_king_safety: bool,
+        include_phase: bool,
+        phase_scheme: Option<PyPhaseScheme>,
+        include_move_label: bool,
+        reduced_fen: bool,
+        start_fen: Option<String>,
+        variant: &str,
+        strict: bool,
+        max_plies: Option<usize>,
+    ) -> Self {
+        let options = FentasizeOptions {
+            include_uci,
+            include_flags,
+            include_hash,
+            include_king_safety,
+            include_phase,
+            phase_scheme: phase_scheme.map_or_else(PhaseScheme::default, |s| s.scheme),
+            include_move_label,
+            reduced_fen,
+            start_fen,
+            variant: variant.to_string(),
+            strict,
+            max_plies,
+        };
+        PyFentasizeOptions { options }
+    }
+
+    #[getter]
+    fn include_uci(&self) -> bool {
+        self.options.include_uci
+    }
+
+    #[getter]
+    fn include_flags(&self) -> bool {
+        self.options.include_flags
+    }
+
+    #[getter]
+    fn include_hash(&self) -> bool {
+        self.options.include_hash
+    }
+
+    #[getter]
+    fn include_king_safety(&self) -> bool {
+        self.options.include_king_safety
+    }
+
+    #[getter]
+    fn include_phase(&self) -> bool {
+        self.options.include_phase
+    }
+
+    #[getter]
+    fn phase_scheme(&self) -> PyPhaseScheme {
+        PyPhaseScheme {
+            scheme: self.options.phase_scheme,
+        }
+    }
+
+    #[getter]
+    fn include_move_label(&self) -> bool {
+        self.options.include_move_label
+    }
+
+    #[getter]
+    fn reduced_fen(&self) -> bool {
+        self.options.reduced_fen
+    }
+
+    #[getter]
+    fn start_fen(&self) -> Option<String> {
+        self.options.start_fen.clone()
+    }
+
+    #[getter]
+    fn variant(&self) -> String {
+        self.options.variant.clone()
+    }
+
+    #[getter]
+    fn strict(&self) -> bool {
+        self.options.strict
+    }
+
+    #[getter]
+    fn max_plies(&self) -> Option<usize> {
+        self.options.max_plies
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.options)
+    }
+}
+
+/// Weights of the non-pawn material and thresholds to classify the phase of a game, e.g.
+/// PhaseScheme(endgame_material=8). The default weights sum up to 24 in the initial position.
+#[pyclass(name = "PhaseScheme")]
+#[derive(Clone)]
+struct PyPhaseScheme {
+    scheme: PhaseScheme,
+}
+
+#[pymethods]
+impl PyPhaseScheme {
+    #[new]
+    #[pyo3(signature = (
+        knight=1,
+        bishop=1,
+        rook=2,
+        queen=4,
+        opening_moves=10,
+        opening_material=20,
+        endgame_material=6
+    ))]
+    fn new(
+        knight: u16,
+        bishop: u16,
+        rook: u16,
+        queen: u16,
+        opening_moves: u16,
+        opening_material: u16,
+        endgame_material: u16,
+    ) -> Self {
+        let scheme = PhaseScheme {
+            knight,
+            bishop,
+            rook,
+            queen,
+            opening_moves,
+            opening_material,
+            endgame_material,
+        };
+        PyPhaseScheme { scheme }
+    }
+
+    /// Sum of the weights of all pieces in the position of the game.
+    fn material(&self, game: &PyGame) -> u16 {
+        self.scheme.material(&game.game)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.scheme)
+    }
+}
+
+/// Values of the pieces in centipawns, which score material, exchanges and material blunders, e.g.
+/// MaterialEvaluator(bishop=330). The defaults are the conventional 1, 3, 3, 5 and 9 pawns.
+#[pyclass(name = "MaterialEvaluator")]
+#[derive(Clone)]
+struct PyMaterialEvaluator {
+    evaluator: MaterialEvaluator,
+}
+
+#[pymethods]
+impl PyMaterialEvaluator {
+    #[new]
+    #[pyo3(signature = (pawn=100, knight=300, bishop=300, rook=500, queen=900))]
+    fn new(pawn: i32, knight: i32, bishop: i32, rook: i32, queen: i32) -> Self {
+        let evaluator = MaterialEvaluator {
+            pawn,
+            knight,
+            bishop,
+            rook,
+            queen,
+        };
+        PyMaterialEvaluator { evaluator }
+    }
+
+    /// Material of white minus the material of black in the position of the game.
+    fn evaluate(&self, game: &PyGame) -> i32 {
+        self.evaluator.evaluate(&game.game)
+    }
+
+    /// The pieces that either side has in excess as (white, black, score), e.g. ("R", "BP", 100).
+    fn imbalance(&self, game: &PyGame) -> (String, String, i32) {
+        let imbalance = self.evaluator.imbalance(&game.game);
+        let letters = |pieces: &[Piece]| pieces.iter().map(|p| p.to_char(Color::W)).collect();
+        (
+            letters(&imbalance.white),
+            letters(&imbalance.black),
+            imbalance.score,
+        )
+    }
+
+    /// Static exchange evaluation of a move in UCI notation: the material won by the side to move
+    /// if both sides recapture on the target square with their least valuable figure.
+    fn see(&self, game: &PyGame, uci: &str) -> PyResult<i32> {
+        self.evaluator
+            .see(&game.game, uci)
+            .map_err(PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.evaluator)
+    }
+}
+
+/// Opening tree over many games, which can be queried by FEN and saved to disk.
+#[pyclass(name = "PositionTree")]
+struct PyPositionTree {
+    tree: PositionTree,
+}
+
+#[pymethods]
+impl PyPositionTree {
+    #[new]
+    #[pyo3(signature = (max_plies=None))]
+    fn new(max_plies: Option<usize>) -> Self {
+        let tree = match max_plies {
+            None => PositionTree::new(),
+            Some(n) => PositionTree::with_max_plies(n),
+        };
+        PyPositionTree { tree }
+    }
+
+    /// Add the moves of a game in SAN with its result, e.g. "1-0".
+    #[pyo3(signature = (moves, result=None))]
+    fn add_game(&mut self, moves: Vec<&str>, result: Option<&str>) -> PyResult<()> {
+        let result = result
+            .map(outcome::GameResult::from_str)
+            .transpose()
+            .map_err(value_error)?;
+        self.tree
+            .add_game(&moves, result)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Add all games of a PGN string (or bytes, pathlib.Path or file object) and return their
+    /// number.
+    fn add_pgn(&mut self, pgn: &PyAny) -> PyResult<usize> {
+        let games = pgn::parse_pgn(&PgnSource::extract(pgn, false)?.read_to_string()?);
+        for game in &games {
+            self.tree
+                .add_pgn_game(game)
+                .map_err(PyValueError::new_err)?;
+        }
+        Ok(games.len())
+    }
+
+    fn stats(&self, fen: &str) -> PyResult<Option<Stats>> {
+        Ok(self.tree.stats(&game_from_fen(fen)?).map(stats_tuple))
+    }
+
+    /// Moves played from the position as (san, uci, stats), the most popular first.
+    fn children(&self, fen: &str) -> PyResult<Vec<(String, String, Stats)>> {
+        Ok(self
+            .tree
+            .children(&game_from_fen(fen)?)
+            .into_iter()
+            .map(|c| (c.san, c.uci, stats_tuple(c.stats)))
+            .collect())
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        Ok(self.tree.save(path)?)
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        let tree = PositionTree::load(path).map_err(PyValueError::new_err)?;
+        Ok(PyPositionTree { tree })
+    }
+
+    fn __len__(&self) -> usize {
+        self.tree.len()
+    }
+}
+
+/// Count how often each figure occupies each square over all games, bucketed by ply. Returns the
+/// counts with shape (n_buckets, 12, 64) and the number of positions per bucket. Figures are
+/// ordered PNBRQK (white) then pnbrqk (black), squares from a8 to h1.
+#[pyfunction]
+#[pyo3(signature = (games, bucket_size=10, n_buckets=10))]
+fn piece_heatmaps<'py>(
+    py: Python<'py>,
+    games: Vec<Vec<&str>>,
+    bucket_size: usize,
+    n_buckets: usize,
+) -> PyResult<(&'py PyArray3<u32>, &'py PyArray1<u32>)> {
+    if bucket_size == 0 || n_buckets == 0 {
+        return Err(PyValueError::new_err("buckets must not be empty"));
+    }
+    let mut heatmaps = Occupancy::new(bucket_size, n_buckets);
+    for moves in &games {
+        heatmaps.add_game(moves).map_err(PyValueError::new_err)?;
+    }
+    let counts = PyArray1::from_slice(py, heatmaps.counts()).reshape([
+        n_buckets,
+        occupancy::N_FIGURES,
+        occupancy::N_SQUARES,
+    ])?;
+
+    Ok((counts, PyArray1::from_slice(py, heatmaps.positions())))
+}
+
+/// A legal move within a position. As `from` is a keyword in Python, the squares are available as
+/// `from_square` and `to_square`.
+#[pyclass(name = "Move", frozen)]
+struct PyMove {
+    mv: Move,
+}
+
+#[pymethods]
+impl PyMove {
+    #[staticmethod]
+    fn from_san(fen: &str, san: &str) -> PyResult<Self> {
+        let mv = Move::from_san(&game_from_fen(fen)?, san).map_err(value_error)?;
+        Ok(PyMove { mv })
+    }
+
+    #[staticmethod]
+    fn from_uci(fen: &str, uci: &str) -> PyResult<Self> {
+        let mv = Move::from_uci(&game_from_fen(fen)?, uci).map_err(value_error)?;
+        Ok(PyMove { mv })
+    }
+
+    /// Piece letter of the moving figure, uppercase for white, e.g. "N" or "p".
+    #[getter]
+    fn piece(&self) -> String {
+        self.mv.piece.to_char(self.mv.color).to_string()
+    }
+
+    #[getter(from_square)]
+    fn source_square(&self) -> String {
+        self.mv.from.to_string()
+    }
+
+    #[getter(to_square)]
+    fn target_square(&self) -> String {
+        self.mv.to.to_string()
+    }
+
+    #[getter]
+    fn capture(&self) -> bool {
+        self.mv.is_capture
+    }
+
+    /// Piece letter of the promotion, e.g. "Q", if the move is a promotion.
+    #[getter]
+    fn promotion(&self) -> Option<String> {
+        self.mv
+            .promotion
+            .map(|p| p.to_char(self.mv.color).to_string())
+    }
+
+    #[getter]
+    fn check(&self) -> bool {
+        self.mv.is_check
+    }
+
+    #[getter]
+    fn checkmate(&self) -> bool {
+        self.mv.is_checkmate
+    }
+
+    #[getter]
+    fn castling(&self) -> bool {
+        self.mv.is_castling
+    }
+
+    #[getter]
+    fn san(&self) -> String {
+        self.mv.san.clone()
+    }
+
+    #[getter]
+    fn uci(&self) -> String {
+        self.mv.uci.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.mv.san.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Move({}, {})", self.mv.san, self.mv.uci)
+    }
+}
+
+/// A square of the board. The index follows the FEN order, i.e. 0 is a8 and 63 is h1.
+#[pyclass(name = "Square", frozen)]
+#[derive(Clone, Copy)]
+struct PySquare {
+    coord: Coord,
+}
+
+#[pymethods]
+impl PySquare {
+    #[new]
+    fn new(name: &str) -> PyResult<Self> {
+        if !Coord::is_valid(name) {
+            return Err(PyValueError::new_err(format!("invalid square: {}", name)));
+        }
+        Ok(PySquare {
+            coord: Coord::from(name),
+        })
+    }
+
+    #[staticmethod]
+    fn from_index(index: i8) -> PyResult<Self> {
+        if !(0..64).contains(&index) {
+            return Err(PyValueError::new_err(format!("invalid index: {}", index)));
+        }
+        Ok(PySquare {
+            coord: Coord::from_idx(index),
+        })
+    }
+
+    #[getter]
+    fn index(&self) -> i8 {
+        self.coord.idx
+    }
+
+    #[getter]
+    fn file(&self) -> char {
+        self.coord.file
+    }
+
+    #[getter]
+    fn rank(&self) -> char {
+        self.coord.rank
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.coord.to_string()
+    }
+
+    /// Number of king steps to the other square.
+    fn distance(&self, other: &Self) -> i8 {
+        self.coord.distance(&other.coord)
+    }
+
+    fn same_diagonal(&self, other: &Self) -> bool {
+        self.coord.same_diagonal(&other.coord)
+    }
+
+    /// File and rank deltas of a single step towards the other square as (dx, dy), or None if it
+    /// is neither on the same file, rank nor diagonal.
+    fn direction(&self, other: &Self) -> Option<(i8, i8)> {
+        self.coord.direction(&other.coord)
+    }
+
+    /// The squares strictly between both squares on their common ray.
+    fn between(&self, other: &Self) -> Vec<Self> {
+        self.coord
+            .between(&other.coord)
+            .into_iter()
+            .map(|coord| PySquare { coord })
+            .collect()
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.coord.idx.cmp(&other.coord.idx))
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.coord.idx as u64
+    }
+
+    fn __str__(&self) -> String {
+        self.coord.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Square(\"{}\")", self.coord)
+    }
+}
+
+#[pyclass(name = "Piece")]
+#[derive(Clone, Copy, PartialEq)]
+enum PyPiece {
+    #[pyo3(name = "PAWN")]
+    Pawn,
+    #[pyo3(name = "KNIGHT")]
+    Knight,
+    #[pyo3(name = "BISHOP")]
+    Bishop,
+    #[pyo3(name = "ROOK")]
+    Rook,
+    #[pyo3(name = "QUEEN")]
+    Queen,
+    #[pyo3(name = "KING")]
+    King,
+}
+
+#[pymethods]
+impl PyPiece {
+    /// Piece of a FEN or SAN letter, e.g. 'n' or 'N' for a knight.
+    #[staticmethod]
+    fn from_char(c: char) -> PyResult<Self> {
+        if !"pnbrqkPNBRQK".contains(c) {
+            return Err(PyValueError::new_err(format!("invalid piece: {}", c)));
+        }
+        Ok(PyPiece::from(Piece::from(c)))
+    }
+
+    /// Letter of the piece, uppercase for white and lowercase for black.
+    #[pyo3(name = "to_char", signature = (color=PyColor::White))]
+    fn letter(&self, color: PyColor) -> char {
+        Piece::from(*self).to_char(Color::from(color))
+    }
+}
+
+impl From<Piece> for PyPiece {
+    fn from(piece: Piece) -> Self {
+        match piece {
+            Piece::P => PyPiece::Pawn,
+            Piece::N => PyPiece::Knight,
+            Piece::B => PyPiece::Bishop,
+            Piece::R => PyPiece::Rook,
+            Piece::Q => PyPiece::Queen,
+            Piece::K => PyPiece::King,
+        }
+    }
+}
+
+impl From<PyPiece> for Piece {
+    fn from(piece: PyPiece) -> Self {
+        match piece {
+            PyPiece::Pawn => Piece::P,
+            PyPiece::Knight => Piece::N,
+            PyPiece::Bishop => Piece::B,
+            PyPiece::Rook => Piece::R,
+            PyPiece::Queen => Piece::Q,
+            PyPiece::King => Piece::K,
+        }
+    }
+}
+
+#[pyclass(name = "Color")]
+#[derive(Clone, Copy, PartialEq)]
+enum PyColor {
+    #[pyo3(name = "WHITE")]
+    White,
+    #[pyo3(name = "BLACK")]
+    Black,
+}
+
+impl From<Color> for PyColor {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::W => PyColor::White,
+            Color::B => PyColor::Black,
+        }
+    }
+}
+
+impl From<PyColor> for Color {
+    fn from(color: PyColor) -> Self {
+        match color {
+            PyColor::White => Color::W,
+            PyColor::Black => Color::B,
+        }
+    }
+}
+
+/// Iterator over the positions of a replay, which pulls the moves one by one.
+#[pyclass]
+struct PositionIterator {
+    game: Game,
+    moves: Py<PyIterator>,
+    ply: usize,
+}
+
+#[pymethods]
+impl PositionIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyPosition>> {
+        for mv in self.moves.as_ref(py) {
+            let mv: &str = mv?.extract()?;
+            if outcome::is_result_token(mv) {
+                continue;
+            }
+            self.ply += 1;
+            self.game
+                .play_move(mv)
+                .map_err(at_ply(self.ply))
+                .map_err(PyValueError::new_err)?;
+            return Ok(Some(PyPosition {
+                game: self.game.clone(),
+                ply: self.ply,
+                san: mv.to_string(),
+            }));
+        }
+        Ok(None)
+    }
+}
+
+/// The position after a ply, of which the FEN is only derived on request.
+#[pyclass(name = "Position", frozen)]
+struct PyPosition {
+    game: Game,
+    ply: usize,
+    san: String,
+}
+
+#[pymethods]
+impl PyPosition {
+    #[getter]
+    fn ply(&self) -> usize {
+        self.ply
+    }
+
+    #[getter]
+    fn san(&self) -> String {
+        self.san.clone()
+    }
+
+    #[getter]
+    fn uci(&self) -> String {
+        self.game.uci.clone()
+    }
+
+    #[getter]
+    fn turn(&self) -> PyColor {
+        PyColor::from(self.game.color)
+    }
+
+    #[getter]
+    fn fen(&self) -> String {
+        self.game.clone().to_fen()
+    }
+
+    fn to_game(&self) -> PyGame {
+        PyGame {
+            game: self.game.clone(),
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.game.to_unicode()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Position(ply={}, san={})", self.ply, self.san)
+    }
+}
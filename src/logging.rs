@@ -0,0 +1,219 @@
+//! Forwarding of the tracing events of fency-core to the `logging` module of Python, e.g. to find
+//! the game and ply at which a long batch stalls.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Name of the Python logger that receives the events.
+const LOGGER: &str = "fency_pgn";
+
+/// Levels by their names, from the least to the most verbose.
+const LEVELS: [(&str, LevelFilter); 6] = [
+    ("off", LevelFilter::OFF),
+    ("error", LevelFilter::ERROR),
+    ("warn", LevelFilter::WARN),
+    ("info", LevelFilter::INFO),
+    ("debug", LevelFilter::DEBUG),
+    ("trace", LevelFilter::TRACE),
+];
+
+/// Index into `LEVELS` of the most verbose level that is forwarded.
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Spans entered by the current thread, the innermost last.
+    static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+fn max_level() -> LevelFilter {
+    LEVELS[MAX_LEVEL.load(Ordering::Relaxed)].1
+}
+
+/// The level of the Python `logging` module, where TRACE is below DEBUG.
+fn python_level(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 40,
+        Level::WARN => 30,
+        Level::INFO => 20,
+        Level::DEBUG => 10,
+        Level::TRACE => 5,
+    }
+}
+
+/// Writes fields as "key=value", where the message is kept apart.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    pairs: String,
+}
+
+impl Fields {
+    fn push(&mut self, field: &Field, value: std::fmt::Arguments) {
+        let text = if field.name() == "message" {
+            &mut self.message
+        } else {
+            if !self.pairs.is_empty() {
+                self.pairs.push(' ');
+            }
+            write!(self.pairs, "{}=", field.name()).unwrap();
+            &mut self.pairs
+        };
+        text.write_fmt(value).unwrap();
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.push(field, format_args!("{:?}", value));
+    }
+}
+
+struct SpanData {
+    name: &'static str,
+    fields: Fields,
+    refs: usize,
+}
+
+/// Subscriber that formats an event with its spans, e.g.
+/// "replay_game{game=12}:ply{ply=40 san=Nf3}: disambiguated stage=by_pins", and logs it.
+#[derive(Default)]
+struct PyLogger {
+    spans: Mutex<HashMap<u64, SpanData>>,
+    next_id: AtomicU64,
+}
+
+impl Subscriber for PyLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        max_level() >= *metadata.level()
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(max_level())
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let data = SpanData {
+            name: span.metadata().name(),
+            fields,
+            refs: 1,
+        };
+        self.spans.lock().unwrap().insert(id, data);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut data.fields);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut text = String::new();
+        {
+            let spans = self.spans.lock().unwrap();
+            ENTERED.with(|entered| {
+                for data in entered.borrow().iter().filter_map(|id| spans.get(id)) {
+                    write!(text, "{}{{{}}}:", data.name, data.fields.pairs).unwrap();
+                }
+            });
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&fields.message);
+        if !fields.pairs.is_empty() {
+            write!(text, " {}", fields.pairs).unwrap();
+        }
+
+        let level = python_level(event.metadata().level());
+        Python::with_gil(|py| {
+            let logged = py
+                .import("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (LOGGER,)))
+                .and_then(|logger| logger.call_method1("log", (level, text)));
+            // A failing handler must not abort the replay.
+            if let Err(e) = logged {
+                e.print(py);
+            }
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(i) = entered.iter().rposition(|&id| id == span.into_u64()) {
+                entered.remove(i);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            data.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let Some(data) = spans.get_mut(&span.into_u64()) else {
+            return false;
+        };
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+        spans.remove(&span.into_u64());
+        true
+    }
+}
+
+/// Forward the diagnostics of parsing and replaying games to the Python logger "fency_pgn",
+/// from the given level on: "error", "warn", "info", "debug" (every parsed game) or "trace"
+/// (every ply and disambiguation stage). "off" stops forwarding. Lines are prefixed by the game
+/// and ply they belong to. Configure the handlers of the logger as for any other, e.g. by
+/// `logging.basicConfig(level=5)` to see trace lines.
+#[pyfunction]
+#[pyo3(signature = (level="debug"))]
+pub fn enable_logging(level: &str) -> PyResult<()> {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+    let idx = LEVELS
+        .iter()
+        .position(|(name, _)| name.eq_ignore_ascii_case(level))
+        .ok_or_else(|| PyValueError::new_err(format!("unknown log level: {}", level)))?;
+    let is_installed = *INSTALLED
+        .get_or_init(|| tracing::subscriber::set_global_default(PyLogger::default()).is_ok());
+    if !is_installed {
+        return Err(PyValueError::new_err(
+            "another tracing subscriber is installed already",
+        ));
+    }
+    MAX_LEVEL.store(idx, Ordering::Relaxed);
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! PGN input from Python, given as content, as path or as open file object, such that every entry
+//! point accepts whatever the caller's IO code happens to hold.
+
+use fency_core::utils::compression;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::PathBuf;
+
+/// Number of bytes or characters requested per read from a Python file object.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// PGN as given by Python. Content is parsed from memory, while files and file objects are
+/// streamed. Compressed input is decompressed in any case.
+pub enum PgnSource {
+    Content(Vec<u8>),
+    Path(PathBuf),
+    File(PyObject),
+}
+
+impl PgnSource {
+    /// Take bytes as content, an os.PathLike like pathlib.Path as path and an object with a read
+    /// method as file object, in text or binary mode. A str is a path if `str_is_path` and content
+    /// otherwise, which keeps the meaning of arguments that only accepted str before.
+    pub fn extract(obj: &PyAny, str_is_path: bool) -> PyResult<Self> {
+        if let Ok(s) = obj.downcast::<PyString>() {
+            let s = s.to_str()?;
+            return Ok(if str_is_path {
+                PgnSource::Path(PathBuf::from(s))
+            } else {
+                PgnSource::Content(s.as_bytes().to_vec())
+            });
+        }
+        if let Ok(bytes) = obj.downcast::<PyBytes>() {
+            return Ok(PgnSource::Content(bytes.as_bytes().to_vec()));
+        }
+        if obj.hasattr("read")? {
+            return Ok(PgnSource::File(obj.into()));
+        }
+        obj.extract().map(PgnSource::Path).map_err(|_| {
+            let type_name = obj.get_type().name().unwrap_or("?");
+            PyTypeError::new_err(format!(
+                "expected PGN as str, bytes, path or file object, got {}",
+                type_name
+            ))
+        })
+    }
+
+    /// Open the source for streaming into a `PgnReader`.
+    pub fn open(self) -> io::Result<Box<dyn BufRead>> {
+        match self {
+            PgnSource::Content(content) => compression::decompress(Cursor::new(content)),
+            PgnSource::Path(path) => compression::open(path),
+            PgnSource::File(file) => compression::decompress(BufReader::with_capacity(
+                CHUNK_SIZE,
+                PyFileReader {
+                    file,
+                    pending: Cursor::default(),
+                },
+            )),
+        }
+    }
+
+    /// Read the whole source, e.g. for functions that parse a PGN string.
+    pub fn read_to_string(self) -> io::Result<String> {
+        let mut pgn = String::new();
+        self.open()?.read_to_string(&mut pgn)?;
+        Ok(pgn)
+    }
+}
+
+/// Reads a Python file object chunk by chunk, where text is encoded as UTF-8. The GIL is only
+/// taken per chunk, thus the file can be read by a worker thread of `run_cancellable`.
+struct PyFileReader {
+    file: PyObject,
+
+    /// The rest of the last chunk, which did not fit into the buffer of the caller.
+    pending: Cursor<Vec<u8>>,
+}
+
+impl PyFileReader {
+    fn read_chunk(&self) -> PyResult<Vec<u8>> {
+        Python::with_gil(|py| {
+            let chunk = self.file.call_method1(py, "read", (CHUNK_SIZE,))?;
+            let chunk = chunk.as_ref(py);
+            if let Ok(text) = chunk.downcast::<PyString>() {
+                return Ok(text.to_str()?.as_bytes().to_vec());
+            }
+            Ok(chunk.downcast::<PyBytes>()?.as_bytes().to_vec())
+        })
+    }
+}
+
+impl Read for PyFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.position() as usize == self.pending.get_ref().len() {
+            self.pending = Cursor::new(self.read_chunk().map_err(io::Error::other)?);
+        }
+        self.pending.read(buf)
+    }
+}
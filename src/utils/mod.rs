@@ -1,7 +0,0 @@
-mod castling;
-mod color;
-mod coord;
-mod draw;
-mod figure;
-pub mod game;
-mod piece;